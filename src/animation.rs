@@ -0,0 +1,122 @@
+//! Animated GIFs of solves, showing the values appear one move at a time.
+
+use crate::{
+    raster::{self, RasterOptions, PALETTE},
+    replay::Replay,
+};
+use gif::{Encoder, Frame, Repeat};
+use std::{fmt::Display, io::Write};
+
+/// Timing and sizes of an animation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnimationOptions {
+    /// Time each move is shown, in hundredths of a second.
+    pub delay: u16,
+    /// Time the finished grid is shown before the animation starts over, in hundredths of a
+    /// second.
+    pub hold: u16,
+    /// Sizes of the frames.
+    pub raster: RasterOptions,
+}
+
+/// Error type for [`write_gif`].
+#[derive(Debug)]
+pub enum AnimationError {
+    /// Encoding or writing the GIF failed.
+    Gif(gif::EncodingError),
+    /// The frames do not fit the 65535 pixels a GIF allows.
+    TooLarge(usize),
+}
+
+impl From<gif::EncodingError> for AnimationError {
+    fn from(err: gif::EncodingError) -> Self {
+        AnimationError::Gif(err)
+    }
+}
+
+impl Display for AnimationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnimationError::Gif(err) => write!(f, "{}", err),
+            AnimationError::TooLarge(width) => {
+                write!(f, "frames {} pixels wide do not fit in a GIF", width)
+            }
+        }
+    }
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            delay: 10,
+            hold: 300,
+            raster: RasterOptions::default(),
+        }
+    }
+}
+
+/// Writes the replay as an endlessly repeating GIF: the puzzle, then one frame per move with
+/// the square of the move highlighted, and finally the grid without highlight.
+///
+/// # Arguments
+///
+/// * `replay` - The moves to animate, e.g. from [`Replay::record_solve`].
+/// * `options` - Timing and sizes of the animation.
+/// * `writer` - The destination of the GIF.
+pub fn write_gif<W: Write>(
+    replay: &Replay,
+    options: &AnimationOptions,
+    writer: W,
+) -> Result<(), AnimationError> {
+    let puzzle = replay.puzzle();
+    let moves = replay.moves();
+    let mut board = puzzle.clone();
+    let first = raster::render(&board, puzzle, None, &options.raster);
+    let size = u16::try_from(first.width).map_err(|_| AnimationError::TooLarge(first.width))?;
+
+    let mut encoder = Encoder::new(writer, size, size, &PALETTE)?;
+    encoder.set_repeat(Repeat::Infinite)?;
+    let mut write = |pixels: Vec<u8>, delay: u16| {
+        let mut frame = Frame::from_indexed_pixels(size, size, pixels, None);
+        frame.delay = delay;
+        encoder.write_frame(&frame)
+    };
+    write(first.pixels, options.delay)?;
+    for step in moves {
+        board.set_unchecked(step.cell.row, step.cell.column, step.value);
+        let raster = raster::render(&board, puzzle, Some(&step.cell), &options.raster);
+        write(raster.pixels, options.delay)?;
+    }
+    write(
+        raster::render(&board, puzzle, None, &options.raster).pixels,
+        options.hold,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_gif, AnimationError, AnimationOptions};
+    use crate::{corpus, format::parse_line, replay::Replay};
+
+    #[test]
+    fn test_write_gif() {
+        let puzzle = parse_line(corpus::EASY.puzzle, 1).unwrap();
+        let replay = Replay::record_solve(puzzle).unwrap();
+        let mut gif = Vec::new();
+        write_gif(&replay, &AnimationOptions::default(), &mut gif).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+        assert_eq!(Some(&0x3b), gif.last());
+
+        let mut decoder = gif::DecodeOptions::new().read_info(gif.as_slice()).unwrap();
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(replay.moves().len() + 2, frames);
+        assert_eq!(
+            "frames 70000 pixels wide do not fit in a GIF",
+            AnimationError::TooLarge(70000).to_string()
+        );
+    }
+}
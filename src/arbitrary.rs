@@ -0,0 +1,196 @@
+//! Random sudokus for property testing, as `proptest::arbitrary::Arbitrary` with the
+//! `proptest` feature and `quickcheck::Arbitrary` with the `quickcheck` feature.
+//!
+//! [`Sudoku`] generates valid partial grids: no two squares in the same row, column or block
+//! have the same value, but the grid does not need to have a solution. [`SolvablePuzzle`]
+//! generates puzzles together with a solution.
+
+use crate::{
+    solver::Solver,
+    sudoku::{Coordinates, Grid, Sudoku},
+};
+
+/// A puzzle that is known to have a solution.
+#[derive(Debug, Clone)]
+pub struct SolvablePuzzle {
+    /// The givens.
+    pub puzzle: Sudoku,
+    /// A solution of the puzzle. Puzzles with many blanks can have others, so compare a
+    /// solver's answer against the givens rather than against this grid.
+    pub solution: Sudoku,
+}
+
+impl SolvablePuzzle {
+    /// Generates a solved grid from the seed and removes squares from it.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Seed for generating the solution.
+    /// * `blanks` - Whether to remove each square, in reading order.
+    pub fn new(seed: u64, blanks: &[bool]) -> Self {
+        let solution =
+            Solver::generate_seeded(0, seed).expect("an empty grid always has a solution");
+        let mut squares = solution.squares;
+        for (square, blank) in squares.iter_mut().zip(blanks) {
+            if *blank {
+                *square = 0;
+            }
+        }
+        SolvablePuzzle {
+            puzzle: Sudoku::new_from_state(squares),
+            solution,
+        }
+    }
+}
+
+/// Places the values one at a time on an empty grid, skipping those that would conflict with
+/// a value placed earlier.
+///
+/// # Arguments
+///
+/// * `placements` - Pairs of square index ([0,81)) and value ([1,9]).
+fn partial_grid(placements: &[(usize, u8)]) -> Sudoku {
+    let mut squares: Grid = [0; 9 * 9];
+    for (i, value) in placements {
+        let cell = Coordinates {
+            row: i / 9,
+            column: i % 9,
+        };
+        let conflict = cell
+            .peers()
+            .any(|peer| squares[peer.row * 9 + peer.column] == *value);
+        if squares[*i] == 0 && !conflict {
+            squares[*i] = *value;
+        }
+    }
+    Sudoku::new_from_state(squares)
+}
+
+#[cfg(feature = "proptest")]
+mod proptest_impls {
+    use super::{partial_grid, SolvablePuzzle};
+    use crate::sudoku::Sudoku;
+    use proptest::{
+        arbitrary::{any, Arbitrary},
+        collection::vec,
+        strategy::{BoxedStrategy, Strategy},
+    };
+
+    impl Arbitrary for Sudoku {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            vec((0..9 * 9usize, 1..=9u8), 0..=9 * 9)
+                .prop_map(|placements| partial_grid(&placements))
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for SolvablePuzzle {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            (any::<u64>(), vec(any::<bool>(), 9 * 9))
+                .prop_map(|(seed, blanks)| SolvablePuzzle::new(seed, &blanks))
+                .boxed()
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impls {
+    use super::{partial_grid, SolvablePuzzle};
+    use crate::sudoku::Sudoku;
+    use quickcheck::{Arbitrary, Gen};
+
+    /// Every grid with one of the values removed.
+    fn without_one(sudoku: &Sudoku) -> Box<dyn Iterator<Item = Sudoku>> {
+        let squares = sudoku.squares;
+        Box::new((0..9 * 9).filter(move |i| squares[*i] != 0).map(move |i| {
+            let mut smaller = squares;
+            smaller[i] = 0;
+            Sudoku::new_from_state(smaller)
+        }))
+    }
+
+    impl Arbitrary for Sudoku {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let count = usize::arbitrary(g) % (9 * 9 + 1);
+            let placements: Vec<(usize, u8)> = (0..count)
+                .map(|_| (usize::arbitrary(g) % (9 * 9), u8::arbitrary(g) % 9 + 1))
+                .collect();
+            partial_grid(&placements)
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            without_one(self)
+        }
+    }
+
+    impl Arbitrary for SolvablePuzzle {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let blanks: Vec<bool> = (0..9 * 9).map(|_| bool::arbitrary(g)).collect();
+            SolvablePuzzle::new(u64::arbitrary(g), &blanks)
+        }
+
+        /// Removing givens keeps the solution valid.
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            let solution = self.solution.clone();
+            Box::new(without_one(&self.puzzle).map(move |puzzle| SolvablePuzzle {
+                puzzle,
+                solution: solution.clone(),
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{partial_grid, SolvablePuzzle};
+    use crate::{solver::Solver, sudoku::Sudoku};
+
+    /// Checks the properties every generated value must have.
+    fn check(sudoku: &Sudoku, solvable: &SolvablePuzzle) -> bool {
+        Sudoku::try_new_from_state(sudoku.squares).is_ok()
+            && solvable.solution.set_count == 81
+            && (0..81).all(|i| {
+                solvable.puzzle.squares[i] == 0
+                    || solvable.puzzle.squares[i] == solvable.solution.squares[i]
+            })
+            && Solver::new(solvable.puzzle.clone()).count_solutions(1) == 1
+    }
+
+    #[test]
+    fn test_partial_grid() {
+        let sudoku = partial_grid(&[(0, 5), (1, 5), (9, 5), (80, 5), (0, 3)]);
+        assert_eq!(2, sudoku.set_count);
+        assert_eq!(5, sudoku.squares[0]);
+        assert_eq!(5, sudoku.squares[80]);
+
+        let solvable = SolvablePuzzle::new(7, &[true; 81]);
+        assert_eq!(0, solvable.puzzle.set_count);
+        assert!(check(&sudoku, &solvable));
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #![proptest_config(proptest::test_runner::Config::with_cases(16))]
+        #[test]
+        fn test_proptest(sudoku: Sudoku, solvable: SolvablePuzzle) {
+            proptest::prop_assert!(check(&sudoku, &solvable));
+        }
+    }
+
+    #[cfg(feature = "quickcheck")]
+    #[test]
+    fn test_quickcheck() {
+        fn property(sudoku: Sudoku, solvable: SolvablePuzzle) -> bool {
+            check(&sudoku, &solvable)
+        }
+        quickcheck::QuickCheck::new()
+            .tests(16)
+            .quickcheck(property as fn(Sudoku, SolvablePuzzle) -> bool);
+    }
+}
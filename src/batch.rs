@@ -0,0 +1,915 @@
+use crate::{
+    format::{
+        csv::{CsvReader, CsvRecord, CsvWriter},
+        parse_line, write_line, FormatError,
+    },
+    solver::{GenerationOptions, RatingScale, Solver, SolverBackend, SolverBuilder, SolverError},
+    sudoku::{Grid, Sudoku},
+    technique::Technique,
+    transform,
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+    io::{self, BufRead, Write},
+    thread,
+    time::{Duration, Instant},
+};
+#[cfg(feature = "mmap")]
+use std::{fs::OpenOptions, path::Path};
+
+/// Counts of the puzzles processed by [`solve_stream`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StreamSummary {
+    /// Puzzles solved.
+    pub solved: usize,
+    /// Lines that could not be parsed or solved.
+    pub failed: usize,
+}
+
+/// Summary statistics of a puzzle collection, made by [`analyze`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CollectionReport {
+    /// Number of puzzles in the collection.
+    pub puzzles: usize,
+    /// Number of puzzles that repeat an earlier one exactly.
+    pub duplicates: usize,
+    /// Number of puzzles that could not be rated because they have no solution.
+    pub unsolvable: usize,
+    /// Number of puzzles with each number of clues.
+    pub clues: BTreeMap<u8, usize>,
+    /// Number of puzzles with each difficulty score, rounded down to a whole number.
+    pub scores: BTreeMap<u32, usize>,
+    /// Number of puzzles needing each technique as their hardest one.
+    pub techniques: BTreeMap<&'static str, usize>,
+}
+
+/// Writes the report as a few indented tables, one line per value.
+impl Display for CollectionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Puzzles: {} ({} duplicates, {} unsolvable)",
+            self.puzzles, self.duplicates, self.unsolvable
+        )?;
+        writeln!(f, "Clues:")?;
+        for (clues, count) in &self.clues {
+            writeln!(f, "  {}: {}", clues, count)?;
+        }
+        writeln!(f, "Difficulty:")?;
+        for (score, count) in &self.scores {
+            writeln!(f, "  {}-{}: {}", score, score + 1, count)?;
+        }
+        writeln!(f, "Hardest technique:")?;
+        for (technique, count) in &self.techniques {
+            match Technique::find(technique) {
+                Some(known) => writeln!(f, "  {} ({}): {}", technique, known.hodoku_code, count)?,
+                None => writeln!(f, "  {}: {}", technique, count)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Results of solving the same puzzles with two backends, made by [`compare`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendComparison {
+    /// The backends compared.
+    pub backends: [SolverBackend; 2],
+    /// Number of puzzles.
+    pub puzzles: usize,
+    /// Total time each backend took.
+    pub times: [Duration; 2],
+    /// Number of puzzles each backend failed to solve.
+    pub failures: [usize; 2],
+    /// Indices of the puzzles both backends solved, but differently.
+    pub disagreements: Vec<usize>,
+}
+
+/// Writes the totals of both backends, the ratio of their times and the puzzles they disagree
+/// on, numbered from 1.
+impl Display for BackendComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Puzzles: {} ({} disagreements)",
+            self.puzzles,
+            self.disagreements.len()
+        )?;
+        for i in 0..2 {
+            let millis = self.times[i].as_secs_f64() * 1000.0;
+            writeln!(
+                f,
+                "  {:?}: {:.3} ms, {:.3} ms per puzzle, {} failed",
+                self.backends[i],
+                millis,
+                millis / self.puzzles.max(1) as f64,
+                self.failures[i]
+            )?;
+        }
+        if !self.times[0].is_zero() {
+            writeln!(
+                f,
+                "  {:?} took {:.2}x the time of {:?}",
+                self.backends[1],
+                self.times[1].as_secs_f64() / self.times[0].as_secs_f64(),
+                self.backends[0]
+            )?;
+        }
+        for i in &self.disagreements {
+            writeln!(f, "Disagreement on puzzle {}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// How many times the median solve time a puzzle must take to be reported as an outlier by
+/// [`verify`].
+pub const OUTLIER_FACTOR: u32 = 10;
+
+/// Results of checking the solver against a corpus of puzzles with known solutions, made by
+/// [`verify`]. Puzzles are numbered from 1 in the order of the file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VerifyReport {
+    /// Number of puzzles.
+    pub puzzles: usize,
+    /// Puzzles solved differently than the corpus says.
+    pub mismatches: Vec<usize>,
+    /// Puzzles the solver failed to solve.
+    pub failures: Vec<usize>,
+    /// Number of puzzles without a solution in the corpus, which are only solved.
+    pub unchecked: usize,
+    /// Total time of the solves.
+    pub total: Duration,
+    /// Median time of the solves.
+    pub median: Duration,
+    /// Puzzles that took more than [`OUTLIER_FACTOR`] times the median, with their times.
+    pub outliers: Vec<(usize, Duration)>,
+}
+
+/// Writes the counts and times, followed by a line for every puzzle that needs a look.
+impl Display for VerifyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Puzzles: {} ({} mismatches, {} failed, {} without solution)",
+            self.puzzles,
+            self.mismatches.len(),
+            self.failures.len(),
+            self.unchecked
+        )?;
+        writeln!(
+            f,
+            "Time: {:.3} ms, median {:.3} ms",
+            self.total.as_secs_f64() * 1000.0,
+            self.median.as_secs_f64() * 1000.0
+        )?;
+        for i in &self.mismatches {
+            writeln!(f, "Mismatch on puzzle {}", i)?;
+        }
+        for i in &self.failures {
+            writeln!(f, "Failed to solve puzzle {}", i)?;
+        }
+        for (i, time) in &self.outliers {
+            writeln!(
+                f,
+                "Slow puzzle {}: {:.3} ms",
+                i,
+                time.as_secs_f64() * 1000.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Scores achieved by generated puzzles, grouped by the difficulty they were generated with,
+/// for checking how well the difficulty setting predicts the rating.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RatingHistogram {
+    /// For every requested difficulty, the number of puzzles with each score rounded down.
+    pub counts: BTreeMap<u8, BTreeMap<u32, usize>>,
+    /// For every requested difficulty, the sum of the scores.
+    totals: BTreeMap<u8, f64>,
+}
+
+impl RatingHistogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Self {
+        RatingHistogram::default()
+    }
+
+    /// Records the score of one generated puzzle.
+    ///
+    /// # Arguments
+    ///
+    /// * `requested` - The difficulty the puzzle was generated with.
+    /// * `score` - The [`DifficultyReport`](crate::solver::DifficultyReport) score it got.
+    pub fn record(&mut self, requested: u8, score: f64) {
+        *self
+            .counts
+            .entry(requested)
+            .or_default()
+            .entry(score as u32)
+            .or_default() += 1;
+        *self.totals.entry(requested).or_default() += score;
+    }
+
+    /// Returns the mean score of the puzzles generated with the difficulty, if there are any.
+    ///
+    /// # Arguments
+    ///
+    /// * `requested` - The difficulty the puzzles were generated with.
+    pub fn mean(&self, requested: u8) -> Option<f64> {
+        let count: usize = self.counts.get(&requested)?.values().sum();
+        Some(self.totals[&requested] / count as f64)
+    }
+}
+
+/// Draws a bar of '#' per score for every requested difficulty.
+impl Display for RatingHistogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (requested, scores) in &self.counts {
+            writeln!(
+                f,
+                "Difficulty {} ({} puzzles, mean score {:.2}):",
+                requested,
+                scores.values().sum::<usize>(),
+                self.mean(*requested).unwrap_or_default()
+            )?;
+            for (score, count) in scores {
+                writeln!(
+                    f,
+                    "  {:>2}-{:<2} {} {}",
+                    score,
+                    score + 1,
+                    "#".repeat(*count),
+                    count
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Solves a puzzle with the fastest backend and returns the solution.
+pub(crate) fn solve_dlx(puzzle: Sudoku) -> Result<Sudoku, String> {
+    let mut solver = SolverBuilder::new()
+        .backend(SolverBackend::Dlx)
+        .build(puzzle);
+    solver.solve().map_err(|err| err.to_string())?;
+    Ok(solver.sudoku().clone())
+}
+
+/// Solves puzzles read one 81 character line at a time and writes one line per puzzle: the
+/// solution, or `error: ` followed by the reason. Blank lines are skipped. Only one line is
+/// held in memory at a time, so inputs of any size can be processed. Puzzles are solved with
+/// [`SolverBackend::Dlx`], the fastest backend.
+///
+/// # Arguments
+///
+/// * `reader` - Source of the puzzles.
+/// * `writer` - Destination of the solutions. Wrap it in a `BufWriter` for large inputs.
+pub fn solve_stream<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+) -> io::Result<StreamSummary> {
+    let mut summary = StreamSummary::default();
+    let mut line = String::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let solved = parse_line(text, line_number)
+            .map_err(|err| err.to_string())
+            .and_then(solve_dlx)
+            .map(|solution| write_line(&solution));
+        match solved {
+            Ok(solution) => {
+                summary.solved += 1;
+                writeln!(writer, "{}", solution)?;
+            }
+            Err(message) => {
+                summary.failed += 1;
+                writeln!(writer, "error: {}", message)?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(summary)
+}
+
+/// Number of puzzles per thread read by [`rate_stream`] before rating them.
+const RATE_BATCH: usize = 64;
+
+/// Returns the number of threads to use for a requested count, where 0 means one per core.
+pub(crate) fn thread_count(threads: usize) -> usize {
+    match threads {
+        0 => thread::available_parallelism().map_or(1, |count| count.get()),
+        _ => threads,
+    }
+}
+
+/// Applies the function to every item on the given number of threads, keeping the order.
+pub(crate) fn parallel_map<T: Sync, U: Send, F: Fn(&T) -> U + Sync>(
+    items: &[T],
+    threads: usize,
+    f: F,
+) -> Vec<U> {
+    let per_thread = items.len().div_ceil(threads.max(1)).max(1);
+    let f = &f;
+    thread::scope(|scope| {
+        let workers: Vec<_> = items
+            .chunks(per_thread)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<U>>()))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("rating thread panicked"))
+            .collect()
+    })
+}
+
+/// Rates puzzles read one 81 character line at a time and writes them as CSV with
+/// [`CsvWriter`], with the [`DifficultyReport`](crate::solver::DifficultyReport) rating on the
+/// given scale. Rating is much slower than solving, so the puzzles are read in batches and every
+/// batch is split between the threads; the output keeps the input order. Blank lines are
+/// skipped, and lines that cannot be parsed or rated are left out and counted as failed.
+///
+/// # Arguments
+///
+/// * `reader` - Source of the puzzles.
+/// * `writer` - Destination of the CSV text.
+/// * `threads` - Number of threads to use, or 0 for one per available core.
+/// * `scale` - The scale of the ratings.
+pub fn rate_stream<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    threads: usize,
+    scale: RatingScale,
+) -> Result<StreamSummary, FormatError> {
+    let threads = thread_count(threads);
+    let mut summary = StreamSummary::default();
+    let mut csv = CsvWriter::new(writer)?;
+    let mut lines = reader.lines().enumerate();
+    let mut batch = Vec::new();
+    loop {
+        batch.clear();
+        for (i, line) in lines.by_ref() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match parse_line(line.trim(), i + 1) {
+                Ok(puzzle) => batch.push(puzzle),
+                Err(_) => summary.failed += 1,
+            }
+            if batch.len() == RATE_BATCH * threads {
+                break;
+            }
+        }
+        if batch.is_empty() {
+            break;
+        }
+        let reports = parallel_map(&batch, threads, |puzzle| {
+            Solver::new(puzzle.clone()).difficulty_report()
+        });
+        for (puzzle, report) in batch.drain(..).zip(reports) {
+            match report {
+                Ok(report) => {
+                    summary.solved += 1;
+                    csv.write(&CsvRecord {
+                        rating: Some(report.rating(scale) as f32),
+                        ..CsvRecord::new(puzzle)
+                    })?;
+                }
+                Err(_) => summary.failed += 1,
+            }
+        }
+    }
+    csv.into_inner().flush()?;
+    Ok(summary)
+}
+
+/// Generates distinct puzzles with [`Solver::generate_with_options`]. Every puzzle is reduced
+/// to its [canonical form](crate::transform::canonical), and puzzles equivalent to an earlier
+/// one under symmetry or relabeling are rejected and generated again; at the `count`th
+/// rejection the batch fails with [`SolverError::GenerationFailed`] giving the number of tries,
+/// accepted or not. Try `n` starts from the seed plus
+/// `n` times the attempt limit, so no two tries share an attempt and the batch is reproducible.
+/// With a histogram every puzzle is also rated and recorded under the requested difficulty,
+/// which costs more time than generating.
+///
+/// # Arguments
+///
+/// * `count` - Number of puzzles to generate.
+/// * `options` - The requirements every puzzle must meet.
+/// * `seed` - Seed for the first puzzle.
+/// * `histogram` - Where to record the scores of the puzzles, if anywhere.
+pub fn generate_batch(
+    count: usize,
+    options: &GenerationOptions,
+    seed: u64,
+    mut histogram: Option<&mut RatingHistogram>,
+) -> Result<Vec<Sudoku>, SolverError> {
+    let mut retval = Vec::with_capacity(count);
+    let mut seen: HashSet<Grid> = HashSet::new();
+    let mut tries = 0;
+    let mut rejections = 0;
+    while retval.len() < count {
+        let puzzle_seed = seed.wrapping_add(tries as u64 * options.max_attempts as u64);
+        tries += 1;
+        let puzzle = Solver::generate_with_options(options, puzzle_seed)?;
+        if !seen.insert(transform::canonical(&puzzle).squares) {
+            rejections += 1;
+            if rejections == count {
+                return Err(SolverError::GenerationFailed {
+                    attempts: tries as u32,
+                });
+            }
+            continue;
+        }
+        if let Some(histogram) = histogram.as_deref_mut() {
+            let report = Solver::new(puzzle.clone()).difficulty_report()?;
+            histogram.record(options.difficulty, report.score);
+        }
+        retval.push(puzzle);
+    }
+    Ok(retval)
+}
+
+/// Rates every puzzle of a collection and summarizes the clue counts, difficulties, hardest
+/// techniques and duplicates, as a curator would check before publishing a puzzle pack.
+///
+/// # Arguments
+///
+/// * `puzzles` - The collection.
+/// * `threads` - Number of threads to rate on, or 0 for one per available core.
+pub fn analyze(puzzles: &[Sudoku], threads: usize) -> CollectionReport {
+    let mut report = CollectionReport {
+        puzzles: puzzles.len(),
+        ..CollectionReport::default()
+    };
+    let mut seen: HashSet<Grid> = HashSet::new();
+    for puzzle in puzzles {
+        if !seen.insert(puzzle.squares) {
+            report.duplicates += 1;
+        }
+        *report.clues.entry(puzzle.set_count).or_default() += 1;
+    }
+    let ratings = parallel_map(puzzles, thread_count(threads), |puzzle| {
+        Solver::new(puzzle.clone()).difficulty_report()
+    });
+    for rating in ratings {
+        match rating {
+            Ok(rating) => {
+                *report.scores.entry(rating.score as u32).or_default() += 1;
+                if let Some(technique) = rating.hardest_technique {
+                    *report.techniques.entry(technique).or_default() += 1;
+                }
+            }
+            Err(_) => report.unsolvable += 1,
+        }
+    }
+    report
+}
+
+/// Solves every puzzle of a corpus and checks the solutions against those in the corpus,
+/// timing every solve to find the puzzles that take much longer than the rest. Puzzles whose
+/// givens break a rule count as failed without a solve.
+///
+/// # Arguments
+///
+/// * `reader` - The corpus as CSV, read by [`CsvReader`], with a puzzle and usually a solution
+///   column.
+pub fn verify<R: BufRead>(reader: R) -> Result<VerifyReport, FormatError> {
+    let mut retval = VerifyReport::default();
+    let mut times = Vec::new();
+    for (i, record) in CsvReader::new(reader)?.enumerate() {
+        let record = record?;
+        let number = i + 1;
+        let mut solver = Solver::new(record.puzzle);
+        let started = Instant::now();
+        let solved = solver.find_conflicts().is_empty() && solver.solve().is_ok();
+        let time = started.elapsed();
+        retval.puzzles += 1;
+        retval.total += time;
+        times.push((number, time));
+        match (solved, record.solution) {
+            (false, _) => retval.failures.push(number),
+            (true, Some(solution)) if solution.squares != solver.sudoku().squares => {
+                retval.mismatches.push(number)
+            }
+            (true, Some(_)) => {}
+            (true, None) => retval.unchecked += 1,
+        }
+    }
+    let mut sorted: Vec<Duration> = times.iter().map(|(_, time)| *time).collect();
+    sorted.sort_unstable();
+    retval.median = sorted.get(sorted.len() / 2).copied().unwrap_or_default();
+    let limit = retval.median * OUTLIER_FACTOR;
+    retval.outliers = times
+        .into_iter()
+        .filter(|(_, time)| !limit.is_zero() && *time > limit)
+        .collect();
+    Ok(retval)
+}
+
+/// Solves every puzzle with both backends, one after the other on the current thread so that
+/// the times are comparable, and checks that the solutions agree. Puzzles with several
+/// solutions may be solved differently by correct backends, so they are best left out.
+///
+/// # Arguments
+///
+/// * `puzzles` - The puzzles to solve.
+/// * `backends` - The backends to compare.
+pub fn compare(puzzles: &[Sudoku], backends: [SolverBackend; 2]) -> BackendComparison {
+    let mut retval = BackendComparison {
+        backends,
+        puzzles: puzzles.len(),
+        times: [Duration::ZERO; 2],
+        failures: [0; 2],
+        disagreements: Vec::new(),
+    };
+    for (i, puzzle) in puzzles.iter().enumerate() {
+        let mut solutions = [None, None];
+        for (j, backend) in backends.into_iter().enumerate() {
+            let mut solver = SolverBuilder::new().backend(backend).build(puzzle.clone());
+            let started = Instant::now();
+            let solved = solver.solve();
+            retval.times[j] += started.elapsed();
+            match solved {
+                Ok(_) => solutions[j] = Some(solver.sudoku().squares),
+                Err(_) => retval.failures[j] += 1,
+            }
+        }
+        if let [Some(first), Some(second)] = solutions {
+            if first != second {
+                retval.disagreements.push(i);
+            }
+        }
+    }
+    retval
+}
+
+/// Solves a buffer of fixed-width records in place: every record is an 81 character puzzle
+/// followed by `\n` or `\r\n`, and solved puzzles are overwritten with their solutions.
+/// Records that cannot be parsed or solved are left as they are and counted as failed. The
+/// records are split evenly between the threads.
+///
+/// # Arguments
+///
+/// * `data` - The records. The newline after the last one may be missing.
+/// * `threads` - Number of threads to use, or 0 for one per available core.
+pub fn solve_in_place(data: &mut [u8], threads: usize) -> Result<StreamSummary, FormatError> {
+    let record_len = match data.iter().position(|byte| *byte == b'\n') {
+        Some(81) => 82,
+        Some(82) if data[81] == b'\r' => 83,
+        Some(_) => return Err(FormatError::BadLineLength(1)),
+        None if data.len() == 81 => 82,
+        None => return Err(FormatError::BadLineLength(1)),
+    };
+    let records = (data.len() + 1) / record_len;
+    if data.len() + 1 < record_len * records || data.len() > record_len * records {
+        return Err(FormatError::BadLineLength(records + 1));
+    }
+    for (i, record) in data.chunks(record_len).enumerate() {
+        let terminated = record.len() == record_len - 1 || record[record_len - 1] == b'\n';
+        if !terminated || record[..81].contains(&b'\n') {
+            return Err(FormatError::BadLineLength(i + 1));
+        }
+    }
+
+    let per_thread = records.div_ceil(thread_count(threads)).max(1);
+    let summaries: Vec<StreamSummary> = thread::scope(|scope| {
+        let workers: Vec<_> = data
+            .chunks_mut(per_thread * record_len)
+            .enumerate()
+            .map(|(n, chunk)| {
+                scope.spawn(move || {
+                    let mut summary = StreamSummary::default();
+                    for (i, record) in chunk.chunks_mut(record_len).enumerate() {
+                        let text = String::from_utf8_lossy(&record[..81]).to_string();
+                        let line_number = n * per_thread + i + 1;
+                        match parse_line(&text, line_number)
+                            .map_err(|err| err.to_string())
+                            .and_then(solve_dlx)
+                        {
+                            Ok(solution) => {
+                                record[..81].copy_from_slice(write_line(&solution).as_bytes());
+                                summary.solved += 1;
+                            }
+                            Err(_) => summary.failed += 1,
+                        }
+                    }
+                    summary
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap_or_default())
+            .collect()
+    });
+    Ok(StreamSummary {
+        solved: summaries.iter().map(|summary| summary.solved).sum(),
+        failed: summaries.iter().map(|summary| summary.failed).sum(),
+    })
+}
+
+/// Memory-maps a file of fixed-width records and solves it in place with [`solve_in_place`],
+/// so that the file never has to be read into memory or written out again.
+///
+/// # Arguments
+///
+/// * `path` - The file of puzzles, overwritten with the solutions.
+/// * `threads` - Number of threads to use, or 0 for one per available core.
+#[cfg(feature = "mmap")]
+pub fn solve_file_in_place<P: AsRef<Path>>(
+    path: P,
+    threads: usize,
+) -> Result<StreamSummary, FormatError> {
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Ok(StreamSummary::default());
+    }
+    // Safety: the map is only valid while no other process truncates the file, which is the
+    // caller's responsibility as with any file opened for writing.
+    let mut map = unsafe { memmap2::MmapMut::map_mut(&file)? };
+    let summary = solve_in_place(&mut map, threads)?;
+    map.flush()?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        analyze, compare, generate_batch, rate_stream, solve_in_place, solve_stream, verify,
+        RatingHistogram, StreamSummary,
+    };
+    use crate::{
+        corpus,
+        format::{parse_line, FormatError},
+        solver::{GenerationOptions, RatingScale, Solver, SolverBackend, SolverError},
+    };
+
+    #[test]
+    fn test_solve_stream() {
+        let input =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..
+
+123
+2...8.3...6..7..84.3.5..2.9...1.54.8.........4.27.6...3.1..7.4.72..4..6...4.1...3
+";
+        let mut output = Vec::new();
+        let summary = solve_stream(input.as_bytes(), &mut output).unwrap();
+        assert_eq!(
+            StreamSummary {
+                solved: 2,
+                failed: 1
+            },
+            summary
+        );
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            vec![
+                "483921657967345821251876493548132976729564138136798245372689514814253769695417382",
+                "error: line 3 does not contain nine squares",
+                "245981376169273584837564219976125438513498627482736951391657842728349165654812793",
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn test_solve_in_place() {
+        let easy =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+        let solution =
+            "483921657967345821251876493548132976729564138136798245372689514814253769695417382";
+        let unsolvable = format!("11{}", &easy[2..]);
+        let mut data = format!("{}\n{}\n{}\n{}", easy, unsolvable, easy, easy).into_bytes();
+        let summary = solve_in_place(&mut data, 2).unwrap();
+        assert_eq!(
+            StreamSummary {
+                solved: 3,
+                failed: 1
+            },
+            summary
+        );
+        assert_eq!(
+            format!("{}\n{}\n{}\n{}", solution, unsolvable, solution, solution),
+            String::from_utf8(data).unwrap()
+        );
+
+        let mut crlf = format!("{}\r\n{}\r\n", easy, easy).into_bytes();
+        assert_eq!(2, solve_in_place(&mut crlf, 0).unwrap().solved);
+        assert_eq!(
+            format!("{}\r\n{}\r\n", solution, solution).into_bytes(),
+            crlf
+        );
+
+        let mut short = format!("{}\n{}\n", easy, &easy[1..]).into_bytes();
+        assert_eq!(
+            Err(FormatError::BadLineLength(2)),
+            solve_in_place(&mut short, 1)
+        );
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_solve_file_in_place() {
+        let path = std::env::temp_dir().join(format!("sudoku_mmap_{}.txt", std::process::id()));
+        std::fs::write(
+            &path,
+            "2...8.3...6..7..84.3.5..2.9...1.54.8.........4.27.6...3.1..7.4.72..4..6...4.1...3\n",
+        )
+        .unwrap();
+        let summary = super::solve_file_in_place(&path, 0).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(1, summary.solved);
+        assert_eq!(
+            "245981376169273584837564219976125438513498627482736951391657842728349165654812793\n",
+            written
+        );
+    }
+
+    #[test]
+    fn test_rate_stream() {
+        let input =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..
+bad
+2...8.3...6..7..84.3.5..2.9...1.54.8.........4.27.6...3.1..7.4.72..4..6...4.1...3
+";
+        let mut output = Vec::new();
+        let summary = rate_stream(input.as_bytes(), &mut output, 2, RatingScale::Score).unwrap();
+        assert_eq!(
+            StreamSummary {
+                solved: 2,
+                failed: 1
+            },
+            summary
+        );
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(3, lines.len());
+        assert_eq!("puzzle,solution,rating,clues", lines[0]);
+        for (line, puzzle) in lines[1..]
+            .iter()
+            .zip([input.lines().next().unwrap(), input.lines().nth(2).unwrap()])
+        {
+            let report = Solver::new(parse_line(puzzle, 1).unwrap())
+                .difficulty_report()
+                .unwrap();
+            assert_eq!(
+                format!("{},,{},{}", puzzle, report.score as f32, report.clues),
+                *line
+            );
+        }
+
+        let mut output = Vec::new();
+        rate_stream(
+            input.as_bytes(),
+            &mut output,
+            1,
+            RatingScale::SudokuExplainer,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.lines().nth(1).unwrap().ends_with(",,2.3,32"));
+    }
+
+    #[test]
+    fn test_analyze() {
+        let easy = parse_line(
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+            1,
+        )
+        .unwrap();
+        let medium = parse_line(
+            "2...8.3...6..7..84.3.5..2.9...1.54.8.........4.27.6...3.1..7.4.72..4..6...4.1...3",
+            1,
+        )
+        .unwrap();
+        let mut unsolvable = easy.clone();
+        unsolvable.set(0, 0, 3).unwrap();
+
+        let report = analyze(&[easy.clone(), medium, easy, unsolvable], 2);
+        assert_eq!(4, report.puzzles);
+        assert_eq!(1, report.duplicates);
+        assert_eq!(1, report.unsolvable);
+        assert_eq!(
+            vec![(&30, &1), (&32, &2), (&33, &1)],
+            report.clues.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![(&1, &3)], report.scores.iter().collect::<Vec<_>>());
+        assert_eq!(Some(&2), report.techniques.get("Naked Single"));
+        assert_eq!(Some(&1), report.techniques.get("Hidden Single"));
+        assert!(report
+            .to_string()
+            .starts_with("Puzzles: 4 (1 duplicates, 1 unsolvable)\nClues:\n  30: 1\n"));
+        assert!(report.to_string().ends_with("  Naked Single (ns): 2\n"));
+    }
+
+    #[test]
+    fn test_generate_batch() {
+        let options = GenerationOptions {
+            difficulty: 40,
+            ..GenerationOptions::default()
+        };
+        let mut histogram = RatingHistogram::new();
+        let puzzles = generate_batch(3, &options, 11, Some(&mut histogram)).unwrap();
+        assert_eq!(3, puzzles.len());
+        assert_eq!(
+            puzzles[2].squares,
+            Solver::generate_with_options(&options, 11 + 2 * 100)
+                .unwrap()
+                .squares
+        );
+        assert_eq!(3, histogram.counts[&40].values().sum::<usize>());
+        assert!(histogram.mean(40).unwrap() >= 1.0);
+        assert_eq!(None, histogram.mean(70));
+
+        let empty = GenerationOptions {
+            difficulty: 100,
+            ..GenerationOptions::default()
+        };
+        // Every empty grid is the same, so after the first one each try is rejected, and the
+        // batch fails at the `count`th rejection.
+        assert_eq!(1, generate_batch(1, &empty, 0, None).unwrap().len());
+        assert!(matches!(
+            generate_batch(2, &empty, 0, None),
+            Err(SolverError::GenerationFailed { attempts: 3 })
+        ));
+        assert!(matches!(
+            generate_batch(5, &empty, 0, None),
+            Err(SolverError::GenerationFailed { attempts: 6 })
+        ));
+
+        let mut histogram = RatingHistogram::new();
+        histogram.record(70, 1.5);
+        histogram.record(70, 3.5);
+        histogram.record(70, 1.0);
+        assert_eq!(
+            "Difficulty 70 (3 puzzles, mean score 2.00):\n   1-2  ## 2\n   3-4  # 1\n",
+            histogram.to_string()
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        let puzzles = vec![
+            parse_line(corpus::EASY.puzzle, 1).unwrap(),
+            parse_line(corpus::MEDIUM.puzzle, 1).unwrap(),
+        ];
+        let comparison = compare(&puzzles, [SolverBackend::Backtracking, SolverBackend::Dlx]);
+        assert_eq!(2, comparison.puzzles);
+        assert_eq!([0, 0], comparison.failures);
+        assert!(comparison.disagreements.is_empty());
+        let report = comparison.to_string();
+        assert!(report.starts_with("Puzzles: 2 (0 disagreements)\n  Backtracking: "));
+        assert!(report.contains("\n  Dlx: "));
+
+        // Logical strategies are stuck on the empty grid, a failure rather than a disagreement.
+        let comparison = compare(
+            &[crate::sudoku::Sudoku::new_empty()],
+            [SolverBackend::Backtracking, SolverBackend::Logic],
+        );
+        assert_eq!([0, 1], comparison.failures);
+    }
+
+    #[test]
+    fn test_verify() {
+        let csv = format!(
+            "puzzle,solution\n{},{}\n{},{}\n{},\n11{},\n",
+            corpus::EASY.puzzle,
+            corpus::EASY.solution,
+            corpus::MEDIUM.puzzle,
+            corpus::EASY.solution,
+            corpus::MEDIUM.puzzle,
+            &corpus::EASY.puzzle[2..]
+        );
+        let report = verify(csv.as_bytes()).unwrap();
+        assert_eq!(4, report.puzzles);
+        assert_eq!(vec![2], report.mismatches);
+        assert_eq!(vec![4], report.failures);
+        assert_eq!(1, report.unchecked);
+        assert!(report.median <= report.total);
+        assert!(report
+            .to_string()
+            .starts_with("Puzzles: 4 (1 mismatches, 1 failed, 1 without solution)\n"));
+
+        assert!(matches!(
+            verify("solution\n".as_bytes()),
+            Err(FormatError::MissingColumn(_))
+        ));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{
+    solver::{DifficultyReport, GenerationOptions, Solver, SolverError},
+    sudoku::{Grid, Sudoku},
+    transform,
+};
+use std::{
+    collections::HashSet,
+    io::{self, Write},
+};
+
+/// Width of an A4 page in points.
+const PAGE_WIDTH: f32 = 595.0;
+
+/// Height of an A4 page in points.
+const PAGE_HEIGHT: f32 = 842.0;
+
+/// Puzzles are printed two per page, one above the other.
+const PUZZLE_SIZE: f32 = 324.0;
+
+/// Solutions are printed six per page in two columns.
+const SOLUTION_SIZE: f32 = 216.0;
+
+/// One puzzle of a book with its solution and how hard it is.
+#[derive(Debug, Clone)]
+pub struct BookEntry {
+    /// The puzzle as printed.
+    pub puzzle: Sudoku,
+    /// The solution printed at the back of the book.
+    pub solution: Sudoku,
+    /// The difficulty of the puzzle; its score is printed above it.
+    pub report: DifficultyReport,
+}
+
+/// Generates puzzles for a book, cycling through the given difficulties. Every puzzle has a
+/// unique solution (see [`Solver::generate_unique`]), and puzzles equivalent to an earlier one
+/// under symmetry or relabeling (see [`transform::canonical`]) are replaced, so every puzzle in
+/// the book is different.
+///
+/// # Arguments
+///
+/// * `count` - Number of puzzles to generate.
+/// * `difficulties` - The percentages of the squares to reset, used in turn. An empty slice
+///   uses 70.
+pub fn generate(count: usize, difficulties: &[u8]) -> Result<Vec<BookEntry>, SolverError> {
+    let mut retval = Vec::new();
+    let mut seen: HashSet<Grid> = HashSet::new();
+    for i in 0..count {
+        let difficulty = match difficulties {
+            [] => 70,
+            _ => difficulties[i % difficulties.len()],
+        };
+        let mut puzzle = Solver::generate_unique(difficulty)?;
+        for attempt in 1.. {
+            if seen.insert(transform::canonical(&puzzle).squares) {
+                break;
+            }
+            if attempt == GenerationOptions::default().max_attempts {
+                return Err(SolverError::GenerationFailed { attempts: attempt });
+            }
+            puzzle = Solver::generate_unique(difficulty)?;
+        }
+        let mut solver = Solver::new(puzzle.clone());
+        let report = solver.difficulty_report()?;
+        solver.solve()?;
+        retval.push(BookEntry {
+            puzzle,
+            solution: solver.sudoku().clone(),
+            report,
+        });
+    }
+    Ok(retval)
+}
+
+/// Escapes text for use inside a PDF string literal.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('(', "\\(")
+        .replace(')', "\\)")
+}
+
+/// Draws a sudoku grid as PDF content stream operators.
+///
+/// # Arguments
+///
+/// * `content` - The content stream to append to.
+/// * `sudoku` - The values to print.
+/// * `left` - X coordinate of the left edge of the grid.
+/// * `top` - Y coordinate of the top edge of the grid.
+/// * `size` - Width and height of the grid.
+fn draw_grid(content: &mut String, sudoku: &Sudoku, left: f32, top: f32, size: f32) {
+    let cell = size / 9.0;
+    for i in 0..=9 {
+        let width = if i % 3 == 0 { 2.0 } else { 0.5 };
+        let offset = i as f32 * cell;
+        content.push_str(&format!(
+            "{} w {:.2} {:.2} m {:.2} {:.2} l S {:.2} {:.2} m {:.2} {:.2} l S\n",
+            width,
+            left + offset,
+            top,
+            left + offset,
+            top - size,
+            left,
+            top - offset,
+            left + size,
+            top - offset
+        ));
+    }
+
+    let font_size = cell * 0.6;
+    content.push_str(&format!("BT /F1 {:.2} Tf\n", font_size));
+    for (i, value) in sudoku.squares.iter().enumerate() {
+        if *value == 0 {
+            continue;
+        }
+        let x = left + (i % 9) as f32 * cell + (cell - font_size * 0.556) / 2.0;
+        let y = top - (i / 9 + 1) as f32 * cell + (cell - font_size * 0.7) / 2.0;
+        content.push_str(&format!("1 0 0 1 {:.2} {:.2} Tm ({}) Tj\n", x, y, value));
+    }
+    content.push_str("ET\n");
+}
+
+/// Draws a line of text as PDF content stream operators.
+fn draw_text(content: &mut String, text: &str, x: f32, y: f32, size: f32) {
+    content.push_str(&format!(
+        "BT /F1 {:.2} Tf 1 0 0 1 {:.2} {:.2} Tm ({}) Tj ET\n",
+        size,
+        x,
+        y,
+        escape(text)
+    ));
+}
+
+/// Lays out the pages of the book: puzzles two per page, then solutions six per page.
+fn layout(title: &str, entries: &[BookEntry]) -> Vec<String> {
+    let mut pages = Vec::new();
+
+    let puzzle_left = (PAGE_WIDTH - PUZZLE_SIZE) / 2.0;
+    for (page, chunk) in entries.chunks(2).enumerate() {
+        let mut content = String::new();
+        if page == 0 {
+            draw_text(&mut content, title, puzzle_left, PAGE_HEIGHT - 40.0, 18.0);
+        }
+        for (j, entry) in chunk.iter().enumerate() {
+            let number = page * 2 + j + 1;
+            let top = PAGE_HEIGHT - 80.0 - j as f32 * (PUZZLE_SIZE + 50.0);
+            draw_text(
+                &mut content,
+                &format!("Puzzle {} (difficulty {:.1})", number, entry.report.score),
+                puzzle_left,
+                top + 10.0,
+                12.0,
+            );
+            draw_grid(&mut content, &entry.puzzle, puzzle_left, top, PUZZLE_SIZE);
+        }
+        pages.push(content);
+    }
+
+    let margin = (PAGE_WIDTH - 2.0 * SOLUTION_SIZE) / 3.0;
+    for (page, chunk) in entries.chunks(6).enumerate() {
+        let mut content = String::new();
+        if page == 0 {
+            draw_text(&mut content, "Solutions", margin, PAGE_HEIGHT - 40.0, 18.0);
+        }
+        for (j, entry) in chunk.iter().enumerate() {
+            let number = page * 6 + j + 1;
+            let left = margin + (j % 2) as f32 * (SOLUTION_SIZE + margin);
+            let top = PAGE_HEIGHT - 80.0 - (j / 2) as f32 * (SOLUTION_SIZE + 40.0);
+            draw_text(
+                &mut content,
+                &format!("Solution {}", number),
+                left,
+                top + 8.0,
+                10.0,
+            );
+            draw_grid(&mut content, &entry.solution, left, top, SOLUTION_SIZE);
+        }
+        pages.push(content);
+    }
+
+    pages
+}
+
+/// Writes the book as a PDF document: the puzzles up front and their solutions at the back.
+///
+/// # Arguments
+///
+/// * `title` - Title printed on the first page.
+/// * `entries` - The puzzles of the book.
+/// * `writer` - Destination of the PDF data.
+pub fn write_pdf<W: Write>(title: &str, entries: &[BookEntry], mut writer: W) -> io::Result<()> {
+    let pages = layout(title, entries);
+
+    // Objects 1-3 are the catalog, the page tree and the font, followed by
+    // a page object and a content stream object for every page.
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 4 + i * 2).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids
+                .iter()
+                .map(|id| format!("{} 0 R", id))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+    for (id, content) in page_ids.iter().zip(&pages) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            id + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+
+    let mut output = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(output.len());
+        output.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+    let xref = output.len();
+    output.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        output.extend(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    output.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+
+    writer.write_all(&output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, write_pdf};
+    use crate::solver::Solver;
+
+    #[test]
+    fn test_generate() {
+        let entries = generate(3, &[30, 60]).unwrap();
+        assert_eq!(3, entries.len());
+        assert!(entries
+            .iter()
+            .all(|e| e.report.clues == e.puzzle.set_count && e.report.score >= 1.0));
+        assert!(entries.iter().all(|e| e.solution.set_count == 81));
+    }
+
+    #[test]
+    fn test_generate_unique() {
+        for entry in generate(8, &[40, 55, 70]).unwrap() {
+            assert_eq!(1, Solver::new(entry.puzzle).count_solutions(2));
+        }
+    }
+
+    #[test]
+    fn test_write_pdf() {
+        let entries = generate(7, &[50]).unwrap();
+        let mut output = Vec::new();
+        write_pdf("My (first) book", &entries, &mut output).unwrap();
+        let text = String::from_utf8_lossy(&output);
+
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        // Four puzzle pages and two solution pages.
+        assert!(text.contains("/Count 6 >>"));
+        assert!(text.contains("(My \\(first\\) book) Tj"));
+
+        let xref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(text[xref..].starts_with("xref\n"));
+    }
+}
@@ -0,0 +1,266 @@
+//! Fitting the difficulty score to how long real players take, so that scores track the
+//! difficulty a particular audience perceives. After calibration, the score of a puzzle is the
+//! number of minutes the audience is expected to need for it.
+
+use crate::{
+    format::{parse_line, FormatError},
+    solver::{RatingWeights, Solver, SolverError},
+    sudoku::Sudoku,
+};
+use std::io::BufRead;
+
+/// Header names accepted for the solve time column, in seconds.
+const TIME_COLUMNS: [&str; 3] = ["seconds", "time", "solve_time"];
+
+/// How strongly weights that the solve times cannot determine are kept at their defaults,
+/// e.g. the guess weight when no puzzle needs a guess. Small enough not to matter otherwise.
+const RIDGE: f64 = 1e-9;
+
+/// A puzzle and the time a player took to solve it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveTime {
+    /// The puzzle.
+    pub puzzle: Sudoku,
+    /// Time it took, in seconds.
+    pub seconds: f64,
+}
+
+/// Error type for [`calibrate`] and [`calibrate_csv`].
+#[derive(Debug)]
+pub enum CalibrationError {
+    /// The solve times could not be read.
+    Format(FormatError),
+    /// A puzzle could not be rated because it has no solution.
+    Solver(SolverError),
+    /// There were no solve times to fit.
+    NoSolveTimes,
+}
+
+impl From<FormatError> for CalibrationError {
+    fn from(err: FormatError) -> Self {
+        CalibrationError::Format(err)
+    }
+}
+
+impl From<SolverError> for CalibrationError {
+    fn from(err: SolverError) -> Self {
+        CalibrationError::Solver(err)
+    }
+}
+
+/// Reads solve times from CSV with a `puzzle` column of 81 character lines and a `seconds`
+/// (or `time`) column. Other columns are ignored.
+///
+/// # Arguments
+///
+/// * `reader` - The source of the CSV text.
+pub fn read_solve_times<R: BufRead>(reader: R) -> Result<Vec<SolveTime>, FormatError> {
+    let mut lines = reader.lines();
+    let header: Vec<String> = match lines.next() {
+        Some(line) => line?
+            .split(',')
+            .map(|field| field.trim().to_lowercase())
+            .collect(),
+        None => Vec::new(),
+    };
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|field| names.contains(&field.as_str()))
+            .ok_or_else(|| FormatError::MissingColumn(names[0].to_string()))
+    };
+    let (puzzle_column, time_column) = (column(&["puzzle"])?, column(&TIME_COLUMNS)?);
+    let mut retval = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = i + 2;
+        let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+        let (Some(puzzle), Some(seconds)) = (fields.get(puzzle_column), fields.get(time_column))
+        else {
+            return Err(FormatError::BadLineLength(line_number));
+        };
+        retval.push(SolveTime {
+            puzzle: parse_line(puzzle, line_number)?,
+            seconds: seconds
+                .parse()
+                .map_err(|_| FormatError::BadNumber(line_number, seconds.to_string()))?,
+        });
+    }
+    Ok(retval)
+}
+
+/// Fits the rating weights by least squares, so that the scores of the puzzles come as close
+/// as possible to their solve times in minutes. Weights the times cannot determine keep their
+/// default values.
+///
+/// # Arguments
+///
+/// * `times` - The puzzles and how long they took.
+pub fn calibrate(times: &[SolveTime]) -> Result<RatingWeights, CalibrationError> {
+    if times.is_empty() {
+        return Err(CalibrationError::NoSolveTimes);
+    }
+    let defaults = RatingWeights::default();
+    let prior = [0.0, defaults.guesses, defaults.branching];
+    // Normal equations of the features 1, log2(1 + guesses) and branching factor - 1.
+    let mut matrix = [[0.0; 3]; 3];
+    let mut vector = [0.0; 3];
+    for time in times {
+        let report = Solver::new(time.puzzle.clone()).difficulty_report()?;
+        let features = [
+            1.0,
+            (1.0 + report.guesses as f64).log2(),
+            report.branching_factor - 1.0,
+        ];
+        for i in 0..3 {
+            for j in 0..3 {
+                matrix[i][j] += features[i] * features[j];
+            }
+            vector[i] += features[i] * time.seconds / 60.0;
+        }
+    }
+    let ridge = RIDGE * times.len() as f64;
+    for i in 1..3 {
+        matrix[i][i] += ridge;
+        vector[i] += ridge * prior[i];
+    }
+    let [base, guesses, branching] = solve_linear(matrix, vector);
+    Ok(RatingWeights {
+        base,
+        guesses,
+        branching,
+    })
+}
+
+/// Reads solve times with [`read_solve_times`] and fits the rating weights to them with
+/// [`calibrate`].
+///
+/// # Arguments
+///
+/// * `reader` - The source of the CSV text.
+pub fn calibrate_csv<R: BufRead>(reader: R) -> Result<RatingWeights, CalibrationError> {
+    calibrate(&read_solve_times(reader)?)
+}
+
+/// Solves a system of three linear equations by Gaussian elimination with partial pivoting.
+/// The matrix must not be singular.
+///
+/// # Arguments
+///
+/// * `matrix` - Coefficients of the equations, one row per equation.
+/// * `vector` - Right-hand sides of the equations.
+fn solve_linear(mut matrix: [[f64; 3]; 3], mut vector: [f64; 3]) -> [f64; 3] {
+    for column in 0..3 {
+        let pivot = (column..3)
+            .max_by(|a, b| {
+                matrix[*a][column]
+                    .abs()
+                    .total_cmp(&matrix[*b][column].abs())
+            })
+            .expect("rows left");
+        matrix.swap(column, pivot);
+        vector.swap(column, pivot);
+        let (pivot_row, pivot_value) = (matrix[column], vector[column]);
+        for row in column + 1..3 {
+            let factor = matrix[row][column] / pivot_row[column];
+            for (value, pivot) in matrix[row].iter_mut().zip(pivot_row).skip(column) {
+                *value -= factor * pivot;
+            }
+            vector[row] -= factor * pivot_value;
+        }
+    }
+    let mut retval = [0.0; 3];
+    for row in (0..3).rev() {
+        let rest: f64 = (row + 1..3).map(|k| matrix[row][k] * retval[k]).sum();
+        retval[row] = (vector[row] - rest) / matrix[row][row];
+    }
+    retval
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{calibrate, calibrate_csv, read_solve_times, CalibrationError, SolveTime};
+    use crate::{
+        corpus,
+        format::{parse_line, FormatError},
+        solver::{RatingWeights, Solver, SolverBuilder},
+    };
+
+    #[test]
+    fn test_calibrate() {
+        let target = RatingWeights {
+            base: 4.0,
+            guesses: 2.5,
+            branching: 3.0,
+        };
+        let times: Vec<SolveTime> = [
+            corpus::EASY,
+            corpus::MEDIUM,
+            corpus::MINIMAL,
+            corpus::INKALA,
+        ]
+        .iter()
+        .map(|known| {
+            let puzzle = parse_line(known.puzzle, 1).unwrap();
+            let report = Solver::new(puzzle.clone()).difficulty_report().unwrap();
+            SolveTime {
+                puzzle,
+                seconds: 60.0 * target.score(report.guesses, report.branching_factor),
+            }
+        })
+        .collect();
+        let weights = calibrate(&times).unwrap();
+        assert!((weights.base - target.base).abs() < 1e-3, "{:?}", weights);
+        assert!(
+            (weights.guesses - target.guesses).abs() < 1e-3,
+            "{:?}",
+            weights
+        );
+        assert!(
+            (weights.branching - target.branching).abs() < 1e-3,
+            "{:?}",
+            weights
+        );
+
+        let report = SolverBuilder::new()
+            .rating_weights(weights)
+            .build(times[1].puzzle.clone())
+            .difficulty_report()
+            .unwrap();
+        assert!((report.score - times[1].seconds / 60.0).abs() < 1e-3);
+
+        // Without guesses the guess weight keeps its default.
+        let weights = calibrate(&times[..1]).unwrap();
+        assert!((weights.guesses - RatingWeights::default().guesses).abs() < 1e-6);
+        assert!(matches!(
+            calibrate(&[]),
+            Err(CalibrationError::NoSolveTimes)
+        ));
+    }
+
+    #[test]
+    fn test_read_solve_times() {
+        let csv = format!(
+            "player,Puzzle,Seconds\nanna,{},300\n\nbo,{},90.5\n",
+            corpus::EASY.puzzle,
+            corpus::MEDIUM.puzzle
+        );
+        let times = read_solve_times(csv.as_bytes()).unwrap();
+        assert_eq!(2, times.len());
+        assert_eq!(90.5, times[1].seconds);
+        assert!(calibrate_csv(csv.as_bytes()).is_ok());
+
+        assert_eq!(
+            Err(FormatError::MissingColumn("seconds".to_string())),
+            read_solve_times("puzzle\n".as_bytes())
+        );
+        let csv = format!("puzzle,seconds\n{},slow\n", corpus::EASY.puzzle);
+        assert_eq!(
+            Err(FormatError::BadNumber(2, "slow".to_string())),
+            read_solve_times(csv.as_bytes())
+        );
+    }
+}
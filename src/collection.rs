@@ -0,0 +1,306 @@
+use crate::{metadata::PuzzleMetadata, sudoku::Sudoku, transform};
+use rand::{seq::index, Rng};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+/// Filters for [`PuzzleCollection::filter`], the in-memory counterpart of the database's
+/// `PuzzleQuery`. Every field left as `None` matches all puzzles.
+#[derive(Debug, Default, Clone)]
+pub struct CollectionQuery {
+    /// Lowest accepted rating. Puzzles without a rating do not match.
+    pub min_rating: Option<f32>,
+    /// Highest accepted rating. Puzzles without a rating do not match.
+    pub max_rating: Option<f32>,
+    /// Lowest accepted number of givens.
+    pub min_clues: Option<u8>,
+    /// Highest accepted number of givens.
+    pub max_clues: Option<u8>,
+    /// Technique the puzzle must be tagged with.
+    pub technique: Option<String>,
+}
+
+/// Puzzles with their metadata, in order.
+#[derive(Debug, Default, Clone)]
+pub struct PuzzleCollection {
+    entries: Vec<(Sudoku, PuzzleMetadata)>,
+}
+
+impl CollectionQuery {
+    /// Returns whether the puzzle matches every filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `sudoku` - The puzzle.
+    /// * `metadata` - What is known about it.
+    fn matches(&self, sudoku: &Sudoku, metadata: &PuzzleMetadata) -> bool {
+        let rating_matches = |bound: Option<f32>, accept: fn(f32, f32) -> bool| {
+            bound.is_none_or(|bound| metadata.rating.is_some_and(|rating| accept(rating, bound)))
+        };
+        rating_matches(self.min_rating, |rating, min| rating >= min)
+            && rating_matches(self.max_rating, |rating, max| rating <= max)
+            && self.min_clues.is_none_or(|min| sudoku.set_count >= min)
+            && self.max_clues.is_none_or(|max| sudoku.set_count <= max)
+            && self
+                .technique
+                .as_ref()
+                .is_none_or(|technique| metadata.tags.contains(technique))
+    }
+}
+
+impl PuzzleCollection {
+    /// Creates an empty collection.
+    pub fn new() -> Self {
+        PuzzleCollection::default()
+    }
+
+    /// Adds a puzzle to the end of the collection.
+    ///
+    /// # Arguments
+    ///
+    /// * `sudoku` - The puzzle.
+    /// * `metadata` - What is known about it.
+    pub fn push(&mut self, sudoku: Sudoku, metadata: PuzzleMetadata) {
+        self.entries.push((sudoku, metadata));
+    }
+
+    /// Returns the number of puzzles.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the collection has no puzzles.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the puzzles with their metadata, in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, (Sudoku, PuzzleMetadata)> {
+        self.entries.iter()
+    }
+
+    /// Returns a collection of the puzzles matching the query, in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The filters to apply.
+    pub fn filter(&self, query: &CollectionQuery) -> PuzzleCollection {
+        self.iter()
+            .filter(|(sudoku, metadata)| query.matches(sudoku, metadata))
+            .cloned()
+            .collect()
+    }
+
+    /// Sorts the puzzles from the easiest to the hardest rating, puzzles without a rating last.
+    /// The order of equal ratings is kept.
+    pub fn sort_by_rating(&mut self) {
+        self.entries
+            .sort_by(|(_, a), (_, b)| match (a.rating, b.rating) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            });
+    }
+
+    /// Sorts the puzzles from the fewest to the most givens. The order of equal counts is kept.
+    pub fn sort_by_clues(&mut self) {
+        self.entries.sort_by_key(|(sudoku, _)| sudoku.set_count);
+    }
+
+    /// Returns a collection of randomly chosen puzzles, in their original order. Returns every
+    /// puzzle if the collection has no more than the count.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of puzzles to choose.
+    /// * `rng` - Random number generator to choose with.
+    pub fn sample<R: Rng + ?Sized>(&self, count: usize, rng: &mut R) -> PuzzleCollection {
+        let mut chosen = index::sample(rng, self.len(), count.min(self.len())).into_vec();
+        chosen.sort_unstable();
+        chosen
+            .into_iter()
+            .map(|i| self.entries[i].clone())
+            .collect()
+    }
+
+    /// Moves every puzzle of the other collection to the end of this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The collection to add.
+    pub fn merge(&mut self, other: PuzzleCollection) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Removes puzzles with the same grid as an earlier puzzle and returns how many were
+    /// removed. Fields of the removed metadata that the kept puzzle lacks are copied to it, and
+    /// their tags are added to its tags.
+    pub fn dedup(&mut self) -> usize {
+        self.dedup_by_key(|sudoku| sudoku.squares)
+    }
+
+    /// Like [`PuzzleCollection::dedup`], but also removes puzzles that are only a relabeling,
+    /// reflection or reordering of an earlier puzzle, see [`transform::canonical`].
+    pub fn dedup_equivalent(&mut self) -> usize {
+        self.dedup_by_key(|sudoku| transform::canonical(sudoku).squares)
+    }
+
+    /// Removes puzzles whose key equals the key of an earlier puzzle, merging their metadata
+    /// into the kept one. Returns the number of puzzles removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to compare puzzles by.
+    fn dedup_by_key<K: Eq + std::hash::Hash, F: Fn(&Sudoku) -> K>(&mut self, key: F) -> usize {
+        let before = self.len();
+        let mut kept: Vec<(Sudoku, PuzzleMetadata)> = Vec::with_capacity(before);
+        let mut positions: HashMap<K, usize> = HashMap::new();
+        for (sudoku, metadata) in self.entries.drain(..) {
+            match positions.entry(key(&sudoku)) {
+                Entry::Occupied(entry) => merge_metadata(&mut kept[*entry.get()].1, metadata),
+                Entry::Vacant(entry) => {
+                    entry.insert(kept.len());
+                    kept.push((sudoku, metadata));
+                }
+            }
+        }
+        self.entries = kept;
+        before - self.len()
+    }
+}
+
+/// Fills the fields the kept metadata lacks from the other metadata and adds its new tags and
+/// extra pairs.
+///
+/// # Arguments
+///
+/// * `kept` - The metadata to complete.
+/// * `other` - The metadata of a removed duplicate.
+fn merge_metadata(kept: &mut PuzzleMetadata, other: PuzzleMetadata) {
+    kept.id = kept.id.take().or(other.id);
+    kept.source = kept.source.take().or(other.source);
+    kept.author = kept.author.take().or(other.author);
+    kept.created = kept.created.or(other.created);
+    kept.rating = kept.rating.or(other.rating);
+    let tags: HashSet<String> = kept.tags.iter().cloned().collect();
+    kept.tags
+        .extend(other.tags.into_iter().filter(|tag| !tags.contains(tag)));
+    for pair in other.extra {
+        if !kept.extra.contains(&pair) {
+            kept.extra.push(pair);
+        }
+    }
+}
+
+impl From<Vec<Sudoku>> for PuzzleCollection {
+    fn from(puzzles: Vec<Sudoku>) -> Self {
+        puzzles
+            .into_iter()
+            .map(|sudoku| (sudoku, PuzzleMetadata::default()))
+            .collect()
+    }
+}
+
+impl FromIterator<(Sudoku, PuzzleMetadata)> for PuzzleCollection {
+    fn from_iter<I: IntoIterator<Item = (Sudoku, PuzzleMetadata)>>(iter: I) -> Self {
+        PuzzleCollection {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for PuzzleCollection {
+    type Item = (Sudoku, PuzzleMetadata);
+    type IntoIter = std::vec::IntoIter<(Sudoku, PuzzleMetadata)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CollectionQuery, PuzzleCollection};
+    use crate::{
+        corpus::{EASY, MEDIUM},
+        format::parse_line,
+        metadata::PuzzleMetadata,
+        sudoku::Sudoku,
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn entry(puzzle: &str, rating: Option<f32>, tag: &str) -> (Sudoku, PuzzleMetadata) {
+        let metadata = PuzzleMetadata {
+            rating,
+            tags: vec![tag.to_string()],
+            ..PuzzleMetadata::default()
+        };
+        (parse_line(puzzle, 1).unwrap(), metadata)
+    }
+
+    fn collection() -> PuzzleCollection {
+        [
+            entry(MEDIUM.puzzle, Some(3.0), "Guess"),
+            entry(EASY.puzzle, Some(1.0), "Naked Single"),
+            entry(EASY.puzzle, None, "Naked Single"),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_filter_and_sort() {
+        let collection = collection();
+        let hard = collection.filter(&CollectionQuery {
+            min_rating: Some(2.0),
+            ..CollectionQuery::default()
+        });
+        assert_eq!(1, hard.len());
+        let singles = collection.filter(&CollectionQuery {
+            technique: Some("Naked Single".to_string()),
+            min_clues: Some(31),
+            ..CollectionQuery::default()
+        });
+        assert_eq!(2, singles.len());
+        assert!(collection
+            .filter(&CollectionQuery {
+                min_clues: Some(33),
+                ..CollectionQuery::default()
+            })
+            .is_empty());
+
+        let mut sorted = collection.clone();
+        sorted.sort_by_rating();
+        let ratings: Vec<Option<f32>> = sorted.iter().map(|(_, m)| m.rating).collect();
+        assert_eq!(vec![Some(1.0), Some(3.0), None], ratings);
+        sorted.sort_by_clues();
+        assert_eq!(30, sorted.iter().next().unwrap().0.set_count);
+    }
+
+    #[test]
+    fn test_sample_merge_dedup() {
+        let mut collection = collection();
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        assert_eq!(2, collection.sample(2, &mut rng).len());
+        assert_eq!(3, collection.sample(10, &mut rng).len());
+
+        collection.merge(PuzzleCollection::from(vec![
+            parse_line(MEDIUM.puzzle, 1).unwrap()
+        ]));
+        assert_eq!(4, collection.len());
+        assert_eq!(2, collection.dedup());
+        assert_eq!(2, collection.len());
+        let (_, medium) = collection.iter().next().unwrap();
+        assert_eq!(vec!["Guess".to_string()], medium.tags);
+
+        let easy = parse_line(EASY.puzzle, 1).unwrap();
+        let mut transposed = [0; 81];
+        for (i, square) in transposed.iter_mut().enumerate() {
+            *square = easy.squares[i % 9 * 9 + i / 9];
+        }
+        collection.push(
+            Sudoku::new_from_state(transposed),
+            PuzzleMetadata::default(),
+        );
+        assert_eq!(0, collection.dedup());
+        assert_eq!(1, collection.dedup_equivalent());
+    }
+}
@@ -0,0 +1,316 @@
+use crate::{
+    coords,
+    sudoku::{houses_of, Coordinates, Grid, House},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+};
+
+/// Type alias for a set of candidate values (1-9) for a given square.
+pub type CandidateSet = HashSet<u8>;
+
+/// Type alias for the candidate values of every square of the 9*9 grid.
+pub type CandidateGrid = [CandidateSet; 9 * 9];
+
+/// A rule restricting the values a square may contain. The classic rules (row, column and block)
+/// are built-in constraints, variants are added by implementing this trait. Constraints are
+/// shared between the threads of [`SolverBackend::Parallel`](crate::solver::SolverBackend).
+pub trait Constraint: Debug + Sync {
+    /// Returns the values this constraint rules out for the given square.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid` - The current state of the sudoku.
+    /// * `cell` - Coordinates of the square to check. Always within bounds.
+    fn eliminate(&self, grid: &Grid, cell: &Coordinates) -> CandidateSet;
+
+    /// Returns the squares whose values this constraint compares against the given square.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square to check. Always within bounds.
+    fn peers(&self, cell: &Coordinates) -> Vec<Coordinates>;
+
+    /// Removes the values this constraint rules out from the candidates of every empty square.
+    /// The default calls [`Constraint::eliminate`] for each of them; constraints that can do it
+    /// in a single pass over the grid should override this.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid` - The current state of the sudoku.
+    /// * `candidates` - The candidates of every square, narrowed down in place.
+    fn eliminate_all(&self, grid: &Grid, candidates: &mut CandidateGrid) {
+        for (i, square) in candidates.iter_mut().enumerate() {
+            if grid[i] == 0 {
+                for value in self.eliminate(grid, &Coordinates::from((i / 9, i % 9))) {
+                    square.remove(&value);
+                }
+            }
+        }
+    }
+}
+
+/// Collects the non-zero values of the given squares.
+fn values_of(grid: &Grid, squares: Vec<Coordinates>) -> CandidateSet {
+    squares
+        .into_iter()
+        .map(|square| grid[coords!(square.row, square.column)])
+        .filter(|value| *value != 0)
+        .collect()
+}
+
+/// Removes from every empty square the values already set in its house, collecting the values
+/// of all nine houses in one pass.
+///
+/// # Arguments
+///
+/// * `grid` - The current state of the sudoku.
+/// * `candidates` - The candidates of every square, narrowed down in place.
+/// * `house_of` - Index ([0,9)) of the house a square index belongs to.
+fn eliminate_by_house(grid: &Grid, candidates: &mut CandidateGrid, house_of: fn(usize) -> usize) {
+    let mut used: [CandidateSet; 9] = Default::default();
+    for (i, value) in grid.iter().enumerate() {
+        if *value != 0 {
+            used[house_of(i)].insert(*value);
+        }
+    }
+    for (i, square) in candidates.iter_mut().enumerate() {
+        if grid[i] == 0 {
+            square.retain(|value| !used[house_of(i)].contains(value));
+        }
+    }
+}
+
+/// Every value may appear only once per row.
+#[derive(Debug)]
+pub struct RowConstraint;
+
+/// Every value may appear only once per column.
+#[derive(Debug)]
+pub struct ColumnConstraint;
+
+/// Every value may appear only once per 3*3 block.
+#[derive(Debug)]
+pub struct BlockConstraint;
+
+impl Constraint for RowConstraint {
+    fn eliminate(&self, grid: &Grid, cell: &Coordinates) -> CandidateSet {
+        values_of(grid, self.peers(cell))
+    }
+
+    fn eliminate_all(&self, grid: &Grid, candidates: &mut CandidateGrid) {
+        eliminate_by_house(grid, candidates, |i| i / 9);
+    }
+
+    fn peers(&self, cell: &Coordinates) -> Vec<Coordinates> {
+        House::Row(cell.row)
+            .cells()
+            .filter(|square| square != cell)
+            .collect()
+    }
+}
+
+impl Constraint for ColumnConstraint {
+    fn eliminate(&self, grid: &Grid, cell: &Coordinates) -> CandidateSet {
+        values_of(grid, self.peers(cell))
+    }
+
+    fn eliminate_all(&self, grid: &Grid, candidates: &mut CandidateGrid) {
+        eliminate_by_house(grid, candidates, |i| i % 9);
+    }
+
+    fn peers(&self, cell: &Coordinates) -> Vec<Coordinates> {
+        House::Column(cell.column)
+            .cells()
+            .filter(|square| square != cell)
+            .collect()
+    }
+}
+
+impl Constraint for BlockConstraint {
+    fn eliminate(&self, grid: &Grid, cell: &Coordinates) -> CandidateSet {
+        values_of(grid, self.peers(cell))
+    }
+
+    fn eliminate_all(&self, grid: &Grid, candidates: &mut CandidateGrid) {
+        eliminate_by_house(grid, candidates, |i| i / 27 * 3 + i % 9 / 3);
+    }
+
+    fn peers(&self, cell: &Coordinates) -> Vec<Coordinates> {
+        houses_of(cell)[2]
+            .cells()
+            .filter(|square| square != cell)
+            .collect()
+    }
+}
+
+/// Values ruled out for particular squares by knowledge the solver does not have, e.g. a
+/// variant rule this crate does not model or a deduction made by a player. Added to a solver
+/// with [`Solver::add_constraint`](crate::solver::Solver::add_constraint), the eliminations are
+/// respected by [`Solver::get_possible`](crate::solver::Solver::get_possible) and the search.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PinnedEliminations {
+    eliminated: HashMap<Coordinates, CandidateSet>,
+}
+
+impl PinnedEliminations {
+    /// Creates a constraint without eliminations.
+    pub fn new() -> Self {
+        PinnedEliminations::default()
+    }
+
+    /// Rules out a value for a square.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square.
+    /// * `value` - The value ([1,9]) the square cannot have.
+    pub fn pin(&mut self, cell: &Coordinates, value: u8) {
+        self.eliminated
+            .entry(cell.clone())
+            .or_default()
+            .insert(value);
+    }
+
+    /// Allows a value ruled out with [`PinnedEliminations::pin`] again.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square.
+    /// * `value` - The value to allow.
+    pub fn unpin(&mut self, cell: &Coordinates, value: u8) {
+        if let Some(values) = self.eliminated.get_mut(cell) {
+            values.remove(&value);
+        }
+    }
+
+    /// Returns whether the value is ruled out for the square.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square.
+    /// * `value` - The value to check.
+    pub fn is_pinned(&self, cell: &Coordinates, value: u8) -> bool {
+        self.eliminated
+            .get(cell)
+            .is_some_and(|values| values.contains(&value))
+    }
+}
+
+impl Constraint for PinnedEliminations {
+    fn eliminate(&self, _grid: &Grid, cell: &Coordinates) -> CandidateSet {
+        self.eliminated.get(cell).cloned().unwrap_or_default()
+    }
+
+    fn peers(&self, _cell: &Coordinates) -> Vec<Coordinates> {
+        Vec::new()
+    }
+}
+
+/// Returns the three constraints of a classic sudoku: rows, columns and blocks.
+pub fn standard() -> Vec<Box<dyn Constraint>> {
+    vec![
+        Box::new(RowConstraint),
+        Box::new(ColumnConstraint),
+        Box::new(BlockConstraint),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        standard, BlockConstraint, CandidateGrid, CandidateSet, ColumnConstraint, Constraint,
+        PinnedEliminations, RowConstraint,
+    };
+    use crate::{
+        corpus,
+        format::parse_line,
+        solver::Solver,
+        sudoku::{Coordinates, Sudoku},
+    };
+
+    #[test]
+    fn test_eliminate() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set(0, 8, 1).unwrap();
+        sudoku.set(8, 0, 2).unwrap();
+        sudoku.set(2, 2, 3).unwrap();
+        let cell = Coordinates::from((0, 0));
+
+        assert_eq!(
+            CandidateSet::from([1]),
+            RowConstraint.eliminate(&sudoku.squares, &cell)
+        );
+        assert_eq!(
+            CandidateSet::from([2]),
+            ColumnConstraint.eliminate(&sudoku.squares, &cell)
+        );
+        assert_eq!(
+            CandidateSet::from([3]),
+            BlockConstraint.eliminate(&sudoku.squares, &cell)
+        );
+    }
+
+    #[test]
+    fn test_eliminate_all() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set(0, 8, 1).unwrap();
+        sudoku.set(8, 0, 2).unwrap();
+        sudoku.set(2, 2, 3).unwrap();
+        sudoku.set(4, 4, 4).unwrap();
+
+        for constraint in standard() {
+            let mut candidates: CandidateGrid =
+                std::array::from_fn(|_| CandidateSet::from([1, 2, 3, 4]));
+            constraint.eliminate_all(&sudoku.squares, &mut candidates);
+            for (i, square) in candidates.iter().enumerate() {
+                let cell = Coordinates::from((i / 9, i % 9));
+                if sudoku.squares[i] == 0 {
+                    let eliminated = constraint.eliminate(&sudoku.squares, &cell);
+                    assert!(eliminated.iter().all(|value| !square.contains(value)));
+                    assert_eq!(4, square.len() + eliminated.len());
+                } else {
+                    assert_eq!(4, square.len());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_peers() {
+        let cell = Coordinates::from((4, 4));
+        assert_eq!(8, RowConstraint.peers(&cell).len());
+        assert_eq!(8, ColumnConstraint.peers(&cell).len());
+        assert_eq!(8, BlockConstraint.peers(&cell).len());
+        assert!(!BlockConstraint.peers(&cell).contains(&cell));
+    }
+
+    #[test]
+    fn test_pinned_eliminations() {
+        let puzzle = parse_line(corpus::MEDIUM.puzzle, 1).unwrap();
+        let solution = parse_line(corpus::MEDIUM.solution, 1).unwrap();
+        let i = (0..9 * 9).find(|i| puzzle.squares[*i] == 0).unwrap();
+        let cell = Coordinates::from((i / 9, i % 9));
+        let mut pinned = PinnedEliminations::new();
+        pinned.pin(&cell, 1);
+        pinned.pin(&cell, 2);
+        pinned.unpin(&cell, 2);
+        assert!(pinned.is_pinned(&cell, 1));
+        assert!(!pinned.is_pinned(&cell, 2));
+
+        let mut solver = Solver::new(puzzle.clone());
+        solver.add_constraint(Box::new(pinned));
+        assert!(!solver
+            .get_possible(cell.row, cell.column)
+            .unwrap()
+            .contains(&1));
+
+        // Ruling out the value of the only solution leaves the puzzle without one.
+        let mut pinned = PinnedEliminations::new();
+        pinned.pin(&cell, solution.squares[i]);
+        let mut solver = Solver::new(puzzle);
+        solver.add_constraint(Box::new(pinned));
+        assert_eq!(0, solver.count_solutions(2));
+    }
+}
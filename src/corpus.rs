@@ -0,0 +1,169 @@
+//! Puzzles with known answers, shared by the tests of this crate and available to other
+//! crates with the `test-fixtures` feature. Grids are written as 81 characters with '.' for
+//! blanks, as read by [`parse_line`](crate::format::parse_line).
+
+/// A valid puzzle with its unique solution and rating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnownPuzzle {
+    /// Where the puzzle comes from.
+    pub name: &'static str,
+    /// The givens.
+    pub puzzle: &'static str,
+    /// The only solution.
+    pub solution: &'static str,
+    /// Hardest technique the solver needs, see
+    /// [`SolveStats::hardest_technique`](crate::solver::SolveStats::hardest_technique).
+    pub hardest_technique: &'static str,
+    /// Guesses needed by the reproducible search of
+    /// [`Solver::rate_remaining`](crate::solver::Solver::rate_remaining).
+    pub guesses: u32,
+}
+
+/// What is wrong with an [`InvalidPuzzle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    /// Two givens in the same row, column or block have the same value.
+    Conflict,
+    /// The givens do not conflict, but cannot be completed.
+    NoSolution,
+    /// The givens allow more than one solution.
+    MultipleSolutions,
+}
+
+/// A grid that is not a proper puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidPuzzle {
+    /// Short description of the grid.
+    pub name: &'static str,
+    /// The givens.
+    pub puzzle: &'static str,
+    /// What is wrong with it.
+    pub problem: Problem,
+}
+
+/// A puzzle solvable with naked singles alone.
+pub const EASY: KnownPuzzle = KnownPuzzle {
+    name: "Project Euler 96, grid 01",
+    puzzle: "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+    solution: "483921657967345821251876493548132976729564138136798245372689514814253769695417382",
+    hardest_technique: "Naked Single",
+    guesses: 0,
+};
+
+/// A puzzle needing hidden singles, or a few guesses without them.
+pub const MEDIUM: KnownPuzzle = KnownPuzzle {
+    name: "Norvig, easy50 grid 02",
+    puzzle: "2...8.3...6..7..84.3.5..2.9...1.54.8.........4.27.6...3.1..7.4.72..4..6...4.1...3",
+    solution: "245981376169273584837564219976125438513498627482736951391657842728349165654812793",
+    hardest_technique: "Hidden Single",
+    guesses: 0,
+};
+
+/// A 17-clue puzzle, the minimum number of clues for a unique solution.
+pub const MINIMAL: KnownPuzzle = KnownPuzzle {
+    name: "Norvig, hard1",
+    puzzle: "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......",
+    solution: "417369825632158947958724316825437169791586432346912758289643571573291684164875293",
+    hardest_technique: "Locked Candidates",
+    guesses: 0,
+};
+
+/// Arto Inkala's puzzle, published as the world's hardest sudoku.
+pub const INKALA: KnownPuzzle = KnownPuzzle {
+    name: "Arto Inkala, 2012",
+    puzzle: "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+    solution: "812753649943682175675491283154237896369845721287169534521974368438526917796318452",
+    hardest_technique: "Guess",
+    guesses: 48,
+};
+
+/// Every known puzzle, from easiest to hardest.
+pub const PUZZLES: [KnownPuzzle; 4] = [EASY, MEDIUM, MINIMAL, INKALA];
+
+/// Solved grids that satisfy every rule.
+pub const SOLVED_GRIDS: [&str; 3] = [
+    "123456789456789123789123456214365897365897214897214365531642978642978531978531642",
+    EASY.solution,
+    INKALA.solution,
+];
+
+/// Grids that are not proper puzzles, one for every kind of problem.
+pub const INVALID: [InvalidPuzzle; 3] = [
+    InvalidPuzzle {
+        name: "two 3s in the first row",
+        puzzle: "3.3.2.6..9....5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+        problem: Problem::Conflict,
+    },
+    InvalidPuzzle {
+        name: "no place for 1 in the first row",
+        puzzle: "23...............1....1......1...................................................",
+        problem: Problem::NoSolution,
+    },
+    InvalidPuzzle {
+        name: "solved grid with a unique rectangle removed",
+        puzzle: "4.3921.579.7345.21251876493548132976729564138136798245372689514814253769695417382",
+        problem: Problem::MultipleSolutions,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{Problem, INVALID, PUZZLES, SOLVED_GRIDS};
+    use crate::{
+        format::{parse_line, write_line},
+        solver::{Solver, SolverError},
+        sudoku::Sudoku,
+    };
+
+    #[test]
+    fn test_puzzles() {
+        for known in PUZZLES {
+            let puzzle = parse_line(known.puzzle, 1).unwrap();
+            let solver = Solver::new(puzzle.clone());
+            let solution = solver.unique_solution().unwrap();
+            assert_eq!(known.solution, write_line(&solution), "{}", known.name);
+            if known.guesses < 200 {
+                let stats = solver.rate_remaining(&puzzle).unwrap();
+                assert_eq!(
+                    Some(known.hardest_technique),
+                    stats.hardest_technique(),
+                    "{}",
+                    known.name
+                );
+                assert_eq!(known.guesses, stats.guesses, "{}", known.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_solved_grids() {
+        for grid in SOLVED_GRIDS {
+            let sudoku = parse_line(grid, 1).unwrap();
+            assert_eq!(81, sudoku.set_count);
+            assert!(Sudoku::try_new_from_state(sudoku.squares).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        for invalid in INVALID {
+            let puzzle = parse_line(invalid.puzzle, 1).unwrap();
+            let conflict = Sudoku::try_new_from_state(puzzle.squares).is_err();
+            assert_eq!(
+                invalid.problem == Problem::Conflict,
+                conflict,
+                "{}",
+                invalid.name
+            );
+            let result = Solver::new(puzzle).unique_solution();
+            match invalid.problem {
+                Problem::Conflict | Problem::NoSolution => {
+                    assert!(matches!(result, Err(SolverError::Unsolvable { .. })))
+                }
+                Problem::MultipleSolutions => {
+                    assert!(matches!(result, Err(SolverError::MultipleSolutions)))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use crate::{
+    format::{csv::CsvRecord, parse_line, write_line, FormatError},
+    metadata::PuzzleMetadata,
+    sudoku::Sudoku,
+};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use std::path::Path;
+
+/// Schema of the puzzle database. Metadata other than the rating is stored as the key/value
+/// pairs of [`PuzzleMetadata::to_pairs`].
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS puzzles (
+    id INTEGER PRIMARY KEY,
+    puzzle TEXT NOT NULL UNIQUE,
+    solution TEXT,
+    rating REAL,
+    clues INTEGER NOT NULL,
+    technique TEXT
+);
+CREATE INDEX IF NOT EXISTS puzzles_rating ON puzzles (rating);
+CREATE INDEX IF NOT EXISTS puzzles_clues ON puzzles (clues);
+CREATE TABLE IF NOT EXISTS metadata (
+    puzzle_id INTEGER NOT NULL REFERENCES puzzles (id) ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL
+);
+";
+
+/// A puzzle collection stored in an SQLite database.
+#[derive(Debug)]
+pub struct PuzzleDb {
+    connection: Connection,
+}
+
+/// A puzzle with everything the database knows about it.
+#[derive(Debug, Clone)]
+pub struct PuzzleRecord {
+    /// The puzzle to solve.
+    pub puzzle: Sudoku,
+    /// The solution of the puzzle, if known.
+    pub solution: Option<Sudoku>,
+    /// The hardest technique needed to solve the puzzle, if known.
+    pub technique: Option<String>,
+    /// Source, author, rating and the like. The rating is the one queries filter on.
+    pub metadata: PuzzleMetadata,
+}
+
+/// Filters for [`PuzzleDb::query`]. Every field left as `None` matches all puzzles.
+#[derive(Debug, Default, Clone)]
+pub struct PuzzleQuery {
+    /// Lowest accepted rating.
+    pub min_rating: Option<f32>,
+    /// Highest accepted rating.
+    pub max_rating: Option<f32>,
+    /// Lowest accepted number of givens.
+    pub min_clues: Option<u8>,
+    /// Highest accepted number of givens.
+    pub max_clues: Option<u8>,
+    /// Required hardest technique.
+    pub technique: Option<String>,
+    /// Maximum number of results.
+    pub limit: Option<usize>,
+}
+
+/// Error type for database operations.
+#[derive(Debug)]
+pub enum DbError {
+    /// The SQLite library reported an error.
+    Sqlite(rusqlite::Error),
+    /// A grid stored in the database could not be parsed.
+    Format(FormatError),
+}
+
+impl From<rusqlite::Error> for DbError {
+    fn from(err: rusqlite::Error) -> Self {
+        DbError::Sqlite(err)
+    }
+}
+
+impl From<FormatError> for DbError {
+    fn from(err: FormatError) -> Self {
+        DbError::Format(err)
+    }
+}
+
+impl From<CsvRecord> for PuzzleRecord {
+    fn from(record: CsvRecord) -> Self {
+        PuzzleRecord {
+            puzzle: record.puzzle,
+            solution: record.solution,
+            technique: None,
+            metadata: PuzzleMetadata {
+                rating: record.rating,
+                ..PuzzleMetadata::default()
+            },
+        }
+    }
+}
+
+impl PuzzleDb {
+    /// Opens (or creates) the database file at the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Location of the SQLite file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, DbError> {
+        PuzzleDb::init(Connection::open(path)?)
+    }
+
+    /// Opens a temporary database that lives only in memory.
+    pub fn open_in_memory() -> Result<Self, DbError> {
+        PuzzleDb::init(Connection::open_in_memory()?)
+    }
+
+    /// Creates the tables if they do not exist yet.
+    fn init(connection: Connection) -> Result<Self, DbError> {
+        connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(PuzzleDb { connection })
+    }
+
+    /// Stores a puzzle and returns its id. A puzzle that is already stored is updated instead:
+    /// fields that are not known keep their stored values, and metadata other than the rating
+    /// replaces the stored metadata unless there is none.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The puzzle to store.
+    pub fn insert(&mut self, record: &PuzzleRecord) -> Result<i64, DbError> {
+        let transaction = self.connection.transaction()?;
+        let puzzle = write_line(&record.puzzle);
+        transaction.execute(
+            "INSERT INTO puzzles (puzzle, solution, rating, clues, technique)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (puzzle) DO UPDATE SET
+                solution = COALESCE(excluded.solution, solution),
+                rating = COALESCE(excluded.rating, rating),
+                technique = COALESCE(excluded.technique, technique)",
+            params![
+                puzzle,
+                record.solution.as_ref().map(write_line),
+                record.metadata.rating,
+                record.puzzle.set_count,
+                record.technique,
+            ],
+        )?;
+        let id: i64 = transaction.query_row(
+            "SELECT id FROM puzzles WHERE puzzle = ?1",
+            params![puzzle],
+            |row| row.get(0),
+        )?;
+        let pairs: Vec<(String, String)> = record
+            .metadata
+            .to_pairs()
+            .into_iter()
+            .filter(|(key, _)| key != "rating")
+            .collect();
+        if !pairs.is_empty() {
+            transaction.execute("DELETE FROM metadata WHERE puzzle_id = ?1", params![id])?;
+        }
+        for (key, value) in pairs {
+            transaction.execute(
+                "INSERT INTO metadata (puzzle_id, key, value) VALUES (?1, ?2, ?3)",
+                params![id, key, value],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(id)
+    }
+
+    /// Fetches a puzzle by its id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id returned by [`PuzzleDb::insert`].
+    pub fn get(&self, id: i64) -> Result<Option<PuzzleRecord>, DbError> {
+        let row = self
+            .connection
+            .query_row(
+                "SELECT puzzle, solution, rating, technique FROM puzzles WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, Option<f32>>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                },
+            )
+            .optional()?;
+        match row {
+            Some(row) => Ok(Some(self.to_record(id, row)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the (id, puzzle) pairs matching every filter of the query, easiest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The filters to apply.
+    pub fn query(&self, query: &PuzzleQuery) -> Result<Vec<(i64, PuzzleRecord)>, DbError> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+        if let Some(min_rating) = query.min_rating {
+            conditions.push("rating >= ?");
+            values.push((min_rating as f64).into());
+        }
+        if let Some(max_rating) = query.max_rating {
+            conditions.push("rating <= ?");
+            values.push((max_rating as f64).into());
+        }
+        if let Some(min_clues) = query.min_clues {
+            conditions.push("clues >= ?");
+            values.push((min_clues as i64).into());
+        }
+        if let Some(max_clues) = query.max_clues {
+            conditions.push("clues <= ?");
+            values.push((max_clues as i64).into());
+        }
+        if let Some(technique) = &query.technique {
+            conditions.push("technique = ?");
+            values.push(technique.clone().into());
+        }
+
+        let mut sql = "SELECT id, puzzle, solution, rating, technique FROM puzzles".to_string();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY rating, id");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let mut statement = self.connection.prepare(&sql)?;
+        let rows = statement
+            .query_map(params_from_iter(values), |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    (
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, Option<f32>>(3)?,
+                        row.get::<_, Option<String>>(4)?,
+                    ),
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(id, row)| Ok((id, self.to_record(id, row)?)))
+            .collect()
+    }
+
+    /// Returns the number of stored puzzles.
+    pub fn count(&self) -> Result<usize, DbError> {
+        let count: i64 = self
+            .connection
+            .query_row("SELECT COUNT(*) FROM puzzles", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Builds a record from the columns of the puzzles table and the metadata table.
+    fn to_record(
+        &self,
+        id: i64,
+        (puzzle, solution, rating, technique): (
+            String,
+            Option<String>,
+            Option<f32>,
+            Option<String>,
+        ),
+    ) -> Result<PuzzleRecord, DbError> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT key, value FROM metadata WHERE puzzle_id = ?1 ORDER BY rowid")?;
+        let pairs = statement
+            .query_map(params![id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let metadata = PuzzleMetadata {
+            rating,
+            ..PuzzleMetadata::from_pairs(pairs)
+        };
+        Ok(PuzzleRecord {
+            puzzle: parse_line(&puzzle, 1)?,
+            solution: match solution {
+                Some(solution) => Some(parse_line(&solution, 1)?),
+                None => None,
+            },
+            technique,
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PuzzleDb, PuzzleQuery, PuzzleRecord};
+    use crate::{format::parse_line, metadata::PuzzleMetadata};
+
+    const PUZZLES: [&str; 2] = [
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+        "2...8.3...6..7..84.3.5..2.9...1.54.8.........4.27.6...3.1..7.4.72..4..6...4.1...3",
+    ];
+
+    fn record(i: usize, rating: f32, technique: &str) -> PuzzleRecord {
+        PuzzleRecord {
+            puzzle: parse_line(PUZZLES[i], 1).unwrap(),
+            solution: None,
+            technique: Some(technique.to_string()),
+            metadata: PuzzleMetadata {
+                source: Some("test".to_string()),
+                rating: Some(rating),
+                tags: vec![technique.to_string()],
+                ..PuzzleMetadata::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_insert_and_get() {
+        let mut db = PuzzleDb::open_in_memory().unwrap();
+        let id = db.insert(&record(0, 1.0, "Naked Single")).unwrap();
+        let stored = db.get(id).unwrap().unwrap();
+        assert_eq!(PUZZLES[0], crate::format::write_line(&stored.puzzle));
+        assert_eq!(record(0, 1.0, "Naked Single").metadata, stored.metadata);
+        assert!(db.get(id + 1).unwrap().is_none());
+
+        assert_eq!(id, db.insert(&record(0, 2.0, "Guess")).unwrap());
+        assert_eq!(1, db.count().unwrap());
+        let updated = db.get(id).unwrap().unwrap();
+        assert_eq!(record(0, 2.0, "Guess").metadata, updated.metadata);
+    }
+
+    #[test]
+    fn test_query() {
+        let mut db = PuzzleDb::open_in_memory().unwrap();
+        db.insert(&record(0, 1.0, "Naked Single")).unwrap();
+        db.insert(&record(1, 4.5, "Guess")).unwrap();
+
+        let all = db.query(&PuzzleQuery::default()).unwrap();
+        assert_eq!(2, all.len());
+
+        let hard = db
+            .query(&PuzzleQuery {
+                min_rating: Some(2.0),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(1, hard.len());
+        assert_eq!(Some(4.5), hard[0].1.metadata.rating);
+
+        let by_technique = db
+            .query(&PuzzleQuery {
+                technique: Some("Naked Single".to_string()),
+                max_clues: Some(32),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(1, by_technique.len());
+    }
+}
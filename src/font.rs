@@ -0,0 +1,30 @@
+/// Width of a glyph in the bitmap font.
+pub(crate) const GLYPH_WIDTH: usize = 5;
+
+/// Height of a glyph in the bitmap font.
+pub(crate) const GLYPH_HEIGHT: usize = 7;
+
+/// 5*7 bitmap glyphs of the digits 0-9. Each row is a bit mask with the leftmost pixel in bit 4.
+pub(crate) const DIGITS: [[u8; GLYPH_HEIGHT]; 10] = [
+    [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+    [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+    [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+    [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+    [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+    [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+    [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+    [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+    [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+];
+
+/// Returns whether the pixel of the digit glyph at the given position is set.
+///
+/// # Arguments
+///
+/// * `digit` - The digit (0-9).
+/// * `x` - Column of the pixel ([0,5)).
+/// * `y` - Row of the pixel ([0,7)).
+pub(crate) fn pixel(digit: u8, x: usize, y: usize) -> bool {
+    DIGITS[digit as usize][y] & (1 << (GLYPH_WIDTH - 1 - x)) != 0
+}
@@ -0,0 +1,263 @@
+use crate::sudoku::{Grid, Sudoku};
+use std::{fmt::Display, io, str::FromStr};
+
+pub mod binary;
+pub mod csv;
+pub mod pretty;
+pub mod qqwing;
+pub mod sdk;
+pub mod ss;
+
+/// Error type for malformed puzzle files.
+#[derive(Debug, PartialEq)]
+pub enum FormatError {
+    /// A character that is not a value or a blank marker, with the line number it was found on.
+    BadCharacter(usize, char),
+    /// A grid line that does not contain exactly nine squares, with its line number.
+    BadLineLength(usize),
+    /// A grid that does not contain exactly nine rows, with the number of rows found.
+    BadRowCount(usize),
+    /// A section header the format does not recognize.
+    UnknownSection(String),
+    /// A column the format requires is missing from the header.
+    MissingColumn(String),
+    /// A field that should contain a number, with its line number.
+    BadNumber(usize, String),
+    /// Encoded data that does not have the expected number of bytes.
+    BadByteCount(usize),
+    /// An encoded square value > 9.
+    BadValue(u8),
+    /// Reading or writing the underlying stream failed.
+    Io(io::ErrorKind),
+}
+
+/// Error type for [`Sudoku::from_str`], pointing at the character that broke parsing.
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// Index of the character in the input, counted in characters rather than bytes.
+    pub index: usize,
+    /// The character found there, or `None` if the input ended before 81 squares.
+    pub found: Option<char>,
+}
+
+impl From<io::Error> for FormatError {
+    fn from(err: io::Error) -> Self {
+        FormatError::Io(err.kind())
+    }
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormatError::BadCharacter(line, found) => {
+                write!(f, "unexpected character '{}' on line {}", found, line)
+            }
+            FormatError::BadLineLength(line) => {
+                write!(f, "line {} does not contain nine squares", line)
+            }
+            FormatError::BadRowCount(rows) => write!(f, "expected nine rows, found {}", rows),
+            FormatError::UnknownSection(section) => write!(f, "unknown section [{}]", section),
+            FormatError::MissingColumn(column) => write!(f, "missing column '{}'", column),
+            FormatError::BadNumber(line, field) => {
+                write!(f, "'{}' on line {} is not a number", field, line)
+            }
+            FormatError::BadByteCount(count) => {
+                write!(f, "expected {} bytes, found {}", binary::ENCODED_LEN, count)
+            }
+            FormatError::BadValue(value) => write!(f, "bad square value {}", value),
+            FormatError::Io(kind) => write!(f, "i/o error: {}", kind),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.found {
+            Some(found) => write!(f, "unexpected '{}' at character {}", found, self.index + 1),
+            None => write!(
+                f,
+                "input ended after {} characters, before 81 squares",
+                self.index
+            ),
+        }
+    }
+}
+
+/// Reads the 81 squares in reading order with '.' or '0' for blanks. Whitespace, line breaks
+/// and the `|`, `-` and `+` of drawn grids are skipped, so grids copied from websites or
+/// printed with `{:#}` parse as they are. The set squares become the givens of the sudoku.
+impl FromStr for Sudoku {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut grid: Grid = [0; 9 * 9];
+        let mut count = 0;
+        for (index, character) in s.chars().enumerate() {
+            let value = match character {
+                '.' | '0' => 0,
+                '1'..='9' => character as u8 - b'0',
+                '|' | '-' | '+' => continue,
+                _ if character.is_whitespace() => continue,
+                _ => {
+                    return Err(ParseError {
+                        index,
+                        found: Some(character),
+                    })
+                }
+            };
+            if count == 9 * 9 {
+                return Err(ParseError {
+                    index,
+                    found: Some(character),
+                });
+            }
+            grid[count] = value;
+            count += 1;
+        }
+        if count != 9 * 9 {
+            return Err(ParseError {
+                index: s.chars().count(),
+                found: None,
+            });
+        }
+        Ok(Sudoku::new_from_state(grid))
+    }
+}
+
+/// Parses one row of nine squares, where blanks are written as '.' or '0'.
+///
+/// # Arguments
+///
+/// * `line` - The text of the row.
+/// * `line_number` - Line number used in error messages.
+pub(crate) fn parse_row(line: &str, line_number: usize) -> Result<[u8; 9], FormatError> {
+    let mut retval = [0u8; 9];
+    let mut count = 0;
+    for character in line.chars() {
+        let value = match character {
+            '.' | '0' => 0,
+            '1'..='9' => character as u8 - b'0',
+            _ => return Err(FormatError::BadCharacter(line_number, character)),
+        };
+        if count == 9 {
+            return Err(FormatError::BadLineLength(line_number));
+        }
+        retval[count] = value;
+        count += 1;
+    }
+    if count != 9 {
+        return Err(FormatError::BadLineLength(line_number));
+    }
+    Ok(retval)
+}
+
+/// Parses a whole grid written on one line as 81 squares, where blanks are written as '.' or '0'.
+///
+/// # Arguments
+///
+/// * `line` - The text of the grid.
+/// * `line_number` - Line number used in error messages.
+pub fn parse_line(line: &str, line_number: usize) -> Result<Sudoku, FormatError> {
+    let characters: Vec<char> = line.chars().collect();
+    if characters.len() != 9 * 9 {
+        return Err(FormatError::BadLineLength(line_number));
+    }
+    let rows = characters
+        .chunks(9)
+        .map(|row| parse_row(&row.iter().collect::<String>(), line_number))
+        .collect::<Result<Vec<_>, _>>()?;
+    from_rows(&rows)
+}
+
+/// Writes the whole grid on one line with '.' for blanks.
+///
+/// # Arguments
+///
+/// * `sudoku` - The sudoku to write.
+pub fn write_line(sudoku: &Sudoku) -> String {
+    sudoku
+        .squares
+        .iter()
+        .map(|value| match value {
+            0 => '.',
+            _ => (b'0' + value) as char,
+        })
+        .collect()
+}
+
+/// Builds a sudoku from nine parsed rows, with the set squares as its givens.
+///
+/// # Arguments
+///
+/// * `rows` - The parsed rows, top to bottom.
+pub(crate) fn from_rows(rows: &[[u8; 9]]) -> Result<Sudoku, FormatError> {
+    if rows.len() != 9 {
+        return Err(FormatError::BadRowCount(rows.len()));
+    }
+    let mut grid: Grid = [0; 9 * 9];
+    for (row, values) in rows.iter().enumerate() {
+        grid[row * 9..row * 9 + 9].copy_from_slice(values);
+    }
+    Ok(Sudoku::new_from_state(grid))
+}
+
+/// Writes one row of the grid with '.' for blanks.
+///
+/// # Arguments
+///
+/// * `f` - The formatter to write to.
+/// * `row` - The values of the row.
+pub(crate) fn write_row(f: &mut std::fmt::Formatter<'_>, row: &[u8]) -> std::fmt::Result {
+    for value in row {
+        if *value == 0 {
+            write!(f, ".")?;
+        } else {
+            write!(f, "{}", value)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseError;
+    use crate::sudoku::Sudoku;
+
+    #[test]
+    fn test_from_str() {
+        let line =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+        let sudoku: Sudoku = line.parse().unwrap();
+        assert_eq!(32, sudoku.set_count);
+        let framed: Sudoku = format!("{:#}", sudoku).parse().unwrap();
+        assert_eq!(sudoku.squares, framed.squares);
+        let spaced: Sudoku = format!(" {}\r\n", line.replace('.', "0 ")).parse().unwrap();
+        assert_eq!(sudoku.squares, spaced.squares);
+
+        assert_eq!(
+            Err(ParseError {
+                index: 4,
+                found: Some('x')
+            }),
+            "..3 x".parse::<Sudoku>().map(|sudoku| sudoku.squares)
+        );
+        assert_eq!(
+            Err(ParseError {
+                index: 81,
+                found: None
+            }),
+            format!("{}|", &line[..80])
+                .parse::<Sudoku>()
+                .map(|sudoku| sudoku.squares)
+        );
+        let error = format!("{} 7", line).parse::<Sudoku>().unwrap_err();
+        assert_eq!(
+            ParseError {
+                index: 82,
+                found: Some('7')
+            },
+            error
+        );
+        assert_eq!("unexpected '7' at character 83", error.to_string());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{
+    format::FormatError,
+    sudoku::{Grid, Sudoku},
+};
+
+/// Number of bytes in the packed encoding: 81 squares of 4 bits each, rounded up.
+pub const ENCODED_LEN: usize = (9 * 9usize).div_ceil(2);
+
+/// URL-safe base64 alphabet used by the text form.
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Packs the grid into 41 bytes, two squares per byte with the first square in the high nibble.
+///
+/// # Arguments
+///
+/// * `sudoku` - The sudoku to encode.
+pub fn encode(sudoku: &Sudoku) -> [u8; ENCODED_LEN] {
+    let mut retval = [0u8; ENCODED_LEN];
+    for (i, value) in sudoku.squares.iter().enumerate() {
+        if i % 2 == 0 {
+            retval[i / 2] |= value << 4;
+        } else {
+            retval[i / 2] |= value & 0x0f;
+        }
+    }
+    retval
+}
+
+/// Unpacks a grid produced by [`encode`].
+///
+/// # Arguments
+///
+/// * `bytes` - The 41 bytes of the packed grid.
+pub fn decode(bytes: &[u8]) -> Result<Sudoku, FormatError> {
+    if bytes.len() != ENCODED_LEN {
+        return Err(FormatError::BadByteCount(bytes.len()));
+    }
+    let mut grid: Grid = [0; 9 * 9];
+    for (i, square) in grid.iter_mut().enumerate() {
+        let value = if i % 2 == 0 {
+            bytes[i / 2] >> 4
+        } else {
+            bytes[i / 2] & 0x0f
+        };
+        if value > 9 {
+            return Err(FormatError::BadValue(value));
+        }
+        *square = value;
+    }
+    Ok(Sudoku::new_from_state(grid))
+}
+
+/// Encodes the grid as unpadded URL-safe base64 text (55 characters), suitable for URLs and QR codes.
+///
+/// # Arguments
+///
+/// * `sudoku` - The sudoku to encode.
+pub fn encode_base64(sudoku: &Sudoku) -> String {
+    let mut retval = String::new();
+    for chunk in encode(sudoku).chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            retval.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    retval
+}
+
+/// Decodes a grid produced by [`encode_base64`]. Trailing '=' padding is accepted.
+///
+/// # Arguments
+///
+/// * `text` - The base64 text of the packed grid.
+pub fn decode_base64(text: &str) -> Result<Sudoku, FormatError> {
+    let mut bytes = Vec::new();
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for character in text.trim().trim_end_matches('=').chars() {
+        let index = ALPHABET
+            .iter()
+            .position(|c| *c as char == character)
+            .ok_or(FormatError::BadCharacter(1, character))?;
+        bits = bits << 6 | index as u32;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count & 0xff) as u8);
+        }
+    }
+    decode(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, decode_base64, encode, encode_base64, ENCODED_LEN};
+    use crate::sudoku::Sudoku;
+    use crate::{format::FormatError, solver::Solver};
+
+    #[test]
+    fn test_round_trip() {
+        let sudoku = Solver::generate(50).unwrap();
+        let bytes = encode(&sudoku);
+        assert_eq!(41, ENCODED_LEN);
+        assert_eq!(sudoku.squares, decode(&bytes).unwrap().squares);
+
+        let text = encode_base64(&sudoku);
+        assert_eq!(55, text.len());
+        assert_eq!(sudoku.squares, decode_base64(&text).unwrap().squares);
+    }
+
+    #[test]
+    fn test_layout() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set(0, 0, 1).unwrap();
+        sudoku.set(0, 1, 2).unwrap();
+        sudoku.set(8, 8, 9).unwrap();
+        let bytes = encode(&sudoku);
+        assert_eq!(0x12, bytes[0]);
+        assert_eq!(0x90, bytes[40]);
+    }
+
+    #[test]
+    fn test_bad_input() {
+        assert_eq!(
+            FormatError::BadByteCount(3),
+            decode(&[0, 0, 0]).unwrap_err()
+        );
+        assert_eq!(
+            FormatError::BadValue(15),
+            decode(&[0xf0; ENCODED_LEN]).unwrap_err()
+        );
+        assert_eq!(
+            FormatError::BadCharacter(1, '*'),
+            decode_base64("AA*").unwrap_err()
+        );
+    }
+}
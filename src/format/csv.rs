@@ -0,0 +1,280 @@
+use crate::{
+    format::{parse_line, write_line, FormatError},
+    sudoku::Sudoku,
+};
+use std::io::{BufRead, Lines, Write};
+
+/// Header names accepted for the puzzle column.
+const PUZZLE_COLUMNS: [&str; 3] = ["puzzle", "quizzes", "quiz"];
+
+/// Header names accepted for the solution column.
+const SOLUTION_COLUMNS: [&str; 2] = ["solution", "solutions"];
+
+/// Header names accepted for the rating column.
+const RATING_COLUMNS: [&str; 2] = ["rating", "difficulty"];
+
+/// Header names accepted for the clue count column.
+const CLUES_COLUMNS: [&str; 2] = ["clues", "clue_count"];
+
+/// One row of a puzzle CSV file.
+#[derive(Debug)]
+pub struct CsvRecord {
+    /// The puzzle to solve.
+    pub puzzle: Sudoku,
+    /// The solution of the puzzle, if the file contains one.
+    pub solution: Option<Sudoku>,
+    /// The difficulty rating of the puzzle, if the file contains one.
+    pub rating: Option<f32>,
+    /// The number of givens in the puzzle, if the file contains one.
+    pub clues: Option<u8>,
+}
+
+/// Column positions of the known fields in the file.
+#[derive(Debug)]
+struct Columns {
+    puzzle: usize,
+    solution: Option<usize>,
+    rating: Option<usize>,
+    clues: Option<usize>,
+}
+
+/// Reads puzzle CSV files row by row. The columns are looked up from the header, so files with
+/// `puzzle,solution`, `quizzes,solutions` or `id,puzzle,solution,clues,difficulty` headers (as in
+/// the common Kaggle datasets) are all understood. Blanks may be written as '.' or '0'.
+#[derive(Debug)]
+pub struct CsvReader<R: BufRead> {
+    lines: Lines<R>,
+    columns: Columns,
+    line_number: usize,
+}
+
+/// Writes puzzle CSV files with a `puzzle,solution,rating,clues` header.
+#[derive(Debug)]
+pub struct CsvWriter<W: Write> {
+    writer: W,
+}
+
+impl CsvRecord {
+    /// Creates a record for a puzzle without a known solution or rating.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The puzzle of the record.
+    pub fn new(puzzle: Sudoku) -> Self {
+        let clues = puzzle.set_count;
+        CsvRecord {
+            puzzle,
+            solution: None,
+            rating: None,
+            clues: Some(clues),
+        }
+    }
+}
+
+/// Finds the position of the first header matching any of the given names.
+fn find_column(header: &[String], names: &[&str]) -> Option<usize> {
+    header
+        .iter()
+        .position(|field| names.contains(&field.as_str()))
+}
+
+impl<R: BufRead> CsvReader<R> {
+    /// Creates a reader and consumes the header line.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The source of the CSV text.
+    pub fn new(reader: R) -> Result<Self, FormatError> {
+        let mut lines = reader.lines();
+        let header: Vec<String> = match lines.next() {
+            Some(line) => line?
+                .split(',')
+                .map(|field| field.trim().to_lowercase())
+                .collect(),
+            None => Vec::new(),
+        };
+        let columns = Columns {
+            puzzle: find_column(&header, &PUZZLE_COLUMNS)
+                .ok_or_else(|| FormatError::MissingColumn(PUZZLE_COLUMNS[0].to_string()))?,
+            solution: find_column(&header, &SOLUTION_COLUMNS),
+            rating: find_column(&header, &RATING_COLUMNS),
+            clues: find_column(&header, &CLUES_COLUMNS),
+        };
+        Ok(CsvReader {
+            lines,
+            columns,
+            line_number: 1,
+        })
+    }
+
+    /// Parses one data line into a record.
+    fn parse_record(&self, line: &str) -> Result<CsvRecord, FormatError> {
+        let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|i| fields.get(i).copied())
+                .filter(|field| !field.is_empty())
+        };
+
+        let puzzle =
+            field(Some(self.columns.puzzle)).ok_or(FormatError::BadLineLength(self.line_number))?;
+        let puzzle = parse_line(puzzle, self.line_number)?;
+        let solution = match field(self.columns.solution) {
+            Some(solution) => Some(parse_line(solution, self.line_number)?),
+            None => None,
+        };
+        let rating = match field(self.columns.rating) {
+            Some(rating) => Some(
+                rating
+                    .parse()
+                    .map_err(|_| FormatError::BadNumber(self.line_number, rating.to_string()))?,
+            ),
+            None => None,
+        };
+        let clues = match field(self.columns.clues) {
+            Some(clues) => Some(
+                clues
+                    .parse()
+                    .map_err(|_| FormatError::BadNumber(self.line_number, clues.to_string()))?,
+            ),
+            None => None,
+        };
+
+        Ok(CsvRecord {
+            puzzle,
+            solution,
+            rating,
+            clues,
+        })
+    }
+}
+
+impl<R: BufRead> Iterator for CsvReader<R> {
+    type Item = Result<CsvRecord, FormatError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into())),
+            };
+            self.line_number += 1;
+            if !line.trim().is_empty() {
+                return Some(self.parse_record(&line));
+            }
+        }
+    }
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Creates a writer and writes the header line.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the CSV text.
+    pub fn new(mut writer: W) -> Result<Self, FormatError> {
+        writeln!(writer, "puzzle,solution,rating,clues")?;
+        Ok(CsvWriter { writer })
+    }
+
+    /// Writes one record. Missing fields are left empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The record to write.
+    pub fn write(&mut self, record: &CsvRecord) -> Result<(), FormatError> {
+        let solution = record.solution.as_ref().map(write_line).unwrap_or_default();
+        let rating = record.rating.map(|r| r.to_string()).unwrap_or_default();
+        let clues = record.clues.map(|c| c.to_string()).unwrap_or_default();
+        writeln!(
+            self.writer,
+            "{},{},{},{}",
+            write_line(&record.puzzle),
+            solution,
+            rating,
+            clues
+        )?;
+        Ok(())
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CsvReader, CsvRecord, CsvWriter};
+    use crate::format::FormatError;
+
+    const PUZZLE: &str =
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+    const SOLUTION: &str =
+        "483921657967345821251876493548132976729564138136798245372689514814253769695417382";
+
+    #[test]
+    fn test_read_kaggle_layout() {
+        let input = format!(
+            "id,puzzle,solution,clues,difficulty\n1,{},{},32,2.5\n\n",
+            PUZZLE, SOLUTION
+        );
+        let records: Vec<CsvRecord> = CsvReader::new(input.as_bytes())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(1, records.len());
+        assert_eq!(Some(2.5), records[0].rating);
+        assert_eq!(Some(32), records[0].clues);
+        assert_eq!(32, records[0].puzzle.set_count);
+        assert_eq!(81, records[0].solution.as_ref().unwrap().set_count);
+    }
+
+    #[test]
+    fn test_read_zero_blanks() {
+        let input = format!(
+            "quizzes,solutions\n{},{}\n",
+            PUZZLE.replace('.', "0"),
+            SOLUTION
+        );
+        let record = CsvReader::new(input.as_bytes())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(32, record.puzzle.set_count);
+        assert_eq!(None, record.rating);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input = format!("puzzle,solution\n{},{}\n", PUZZLE, SOLUTION);
+        let mut record = CsvReader::new(input.as_bytes())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        record.rating = Some(3.0);
+
+        let mut writer = CsvWriter::new(Vec::new()).unwrap();
+        writer.write(&record).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(
+            format!("puzzle,solution,rating,clues\n{},{},3,\n", PUZZLE, SOLUTION),
+            output
+        );
+    }
+
+    #[test]
+    fn test_bad_input() {
+        assert_eq!(
+            FormatError::MissingColumn("puzzle".to_string()),
+            CsvReader::new("a,b\n".as_bytes()).unwrap_err()
+        );
+        let mut reader = CsvReader::new("puzzle,rating\n123,x\n".as_bytes()).unwrap();
+        assert_eq!(
+            FormatError::BadLineLength(2),
+            reader.next().unwrap().unwrap_err()
+        );
+    }
+}
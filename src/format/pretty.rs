@@ -0,0 +1,103 @@
+use crate::{
+    format::{from_rows, FormatError},
+    sudoku::Sudoku,
+};
+
+/// Parses a grid written by [`Sudoku::format_with`] without coordinates, such as the output of
+/// `{:#}`. Every square takes two characters, the value and a space or '*', and blocks are
+/// separated by `| `. Lines containing '-' are rules between the bands. Blanks may be written as any character the options allow, including a
+/// space, so squares are read by position; rows whose trailing spaces were trimmed are padded
+/// with blanks.
+///
+/// # Arguments
+///
+/// * `input` - The text of the grid.
+pub fn parse(input: &str) -> Result<Sudoku, FormatError> {
+    let lines: Vec<&str> = input.lines().collect();
+    // Rows of blanks can be all spaces, so only empty lines around the grid are skipped.
+    let first = lines
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .unwrap_or(lines.len());
+    let last = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(first, |last| last + 1);
+    let mut rows = Vec::new();
+    for (i, line) in lines.iter().enumerate().take(last).skip(first) {
+        let line_number = i + 1;
+        if line.contains('-') {
+            continue;
+        }
+        let characters: Vec<char> = line.chars().collect();
+        let at = |position: usize| characters.get(position).copied().unwrap_or(' ');
+        let mut position = 0;
+        let mut row = [0u8; 9];
+        for value in row.iter_mut() {
+            if at(position) == '|' {
+                position += 2;
+            }
+            *value = match at(position) {
+                character @ '1'..='9' => character as u8 - b'0',
+                '.' | '0' | ' ' | '_' => 0,
+                character => return Err(FormatError::BadCharacter(line_number, character)),
+            };
+            position += 2;
+        }
+        rows.push(row);
+    }
+    from_rows(&rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::{
+        format::{parse_line, FormatError},
+        sudoku::{Coordinates, DisplayOptions},
+    };
+
+    #[test]
+    fn test_parse_formatted() {
+        let sudoku = parse_line(
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+            1,
+        )
+        .unwrap();
+        assert_eq!(
+            sudoku.squares,
+            parse(&format!("{:#}", sudoku)).unwrap().squares
+        );
+
+        let plain = sudoku.format_with(&DisplayOptions::default());
+        let trimmed: String = plain
+            .lines()
+            .map(|line| line.trim_end().to_string() + "\n")
+            .collect();
+        assert_eq!(sudoku.squares, parse(&plain).unwrap().squares);
+        assert_eq!(sudoku.squares, parse(&trimmed).unwrap().squares);
+
+        let bare = DisplayOptions {
+            blank: '_',
+            separators: false,
+            highlight: vec![Coordinates { row: 0, column: 2 }],
+            ..DisplayOptions::default()
+        };
+        assert_eq!(
+            sudoku.squares,
+            parse(&sudoku.format_with(&bare)).unwrap().squares
+        );
+    }
+
+    #[test]
+    fn test_bad_input() {
+        assert_eq!(
+            FormatError::BadCharacter(1, 'x'),
+            parse("x . . | . . . | . . .").unwrap_err()
+        );
+        assert_eq!(
+            FormatError::BadRowCount(1),
+            parse(". . . | . . . | . . .").unwrap_err()
+        );
+    }
+}
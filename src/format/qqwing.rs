@@ -0,0 +1,120 @@
+use crate::{format::write_line, solver::SolveStats, sudoku::Sudoku};
+use std::fmt::Display;
+
+/// Header line of the QQWing `--csv` output.
+pub const CSV_HEADER: &str = "Puzzle,Solution,Givens,Singles,Hidden Singles,Naked Pairs,Hidden Pairs,Pointing Pairs/Triples,Box/Line Intersections,Guesses,Backtracks,Difficulty";
+
+/// The difficulty levels QQWing reports.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum QqwingDifficulty {
+    /// Solvable with singles only.
+    Simple,
+    /// Requires hidden singles.
+    Easy,
+    /// Requires pairs or intersections.
+    Intermediate,
+    /// Requires guessing.
+    Expert,
+}
+
+/// A puzzle with the statistics of solving it, formatted the way QQWing prints them so scripts
+/// parsing QQWing output keep working. Techniques the solver does not use are reported as zero.
+#[derive(Debug)]
+pub struct QqwingReport<'a> {
+    /// The puzzle as given.
+    pub puzzle: &'a Sudoku,
+    /// The solved grid.
+    pub solution: &'a Sudoku,
+    /// The statistics of the solve.
+    pub stats: &'a SolveStats,
+}
+
+impl From<&SolveStats> for QqwingDifficulty {
+    fn from(stats: &SolveStats) -> Self {
+        if stats.guesses > 0 {
+            QqwingDifficulty::Expert
+        } else {
+            QqwingDifficulty::Simple
+        }
+    }
+}
+
+impl Display for QqwingDifficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QqwingDifficulty::Simple => write!(f, "Simple"),
+            QqwingDifficulty::Easy => write!(f, "Easy"),
+            QqwingDifficulty::Intermediate => write!(f, "Intermediate"),
+            QqwingDifficulty::Expert => write!(f, "Expert"),
+        }
+    }
+}
+
+impl QqwingReport<'_> {
+    /// Returns the report as one row of QQWing `--csv` output, matching [`CSV_HEADER`].
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},0,0,0,0,0,{},{},{}",
+            write_line(self.puzzle),
+            write_line(self.solution),
+            self.puzzle.set_count,
+            self.stats.singles,
+            self.stats.guesses,
+            self.stats.backtracks,
+            QqwingDifficulty::from(self.stats)
+        )
+    }
+}
+
+/// Writes the puzzle on one line followed by the statistics block of `qqwing --one-line --stats`.
+impl Display for QqwingReport<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", write_line(self.puzzle))?;
+        writeln!(f, "Number of Givens: {}", self.puzzle.set_count)?;
+        writeln!(f, "Number of Singles: {}", self.stats.singles)?;
+        writeln!(f, "Number of Hidden Singles: 0")?;
+        writeln!(f, "Number of Naked Pairs: 0")?;
+        writeln!(f, "Number of Hidden Pairs: 0")?;
+        writeln!(f, "Number of Pointing Pairs/Triples: 0")?;
+        writeln!(f, "Number of Box/Line Intersections: 0")?;
+        writeln!(f, "Number of Guesses: {}", self.stats.guesses)?;
+        writeln!(f, "Number of Backtracks: {}", self.stats.backtracks)?;
+        write!(f, "Difficulty: {}", QqwingDifficulty::from(self.stats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QqwingDifficulty, QqwingReport, CSV_HEADER};
+    use crate::solver::{SolveStats, Solver};
+
+    #[test]
+    fn test_difficulty() {
+        let mut stats = SolveStats::default();
+        assert_eq!(QqwingDifficulty::Simple, QqwingDifficulty::from(&stats));
+        stats.guesses = 1;
+        assert_eq!(QqwingDifficulty::Expert, QqwingDifficulty::from(&stats));
+    }
+
+    #[test]
+    fn test_report() {
+        let puzzle = Solver::generate(50).unwrap();
+        let mut solver = Solver::new(puzzle.clone());
+        solver.solve().unwrap();
+        let report = QqwingReport {
+            puzzle: &puzzle,
+            solution: solver.sudoku(),
+            stats: solver.stats(),
+        };
+
+        let text = report.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(11, lines.len());
+        assert_eq!(81, lines[0].len());
+        assert_eq!(format!("Number of Givens: {}", puzzle.set_count), lines[1]);
+        assert!(lines[10].starts_with("Difficulty: "));
+
+        let fields = report.to_csv().split(',').count();
+        assert_eq!(CSV_HEADER.split(',').count(), fields);
+    }
+}
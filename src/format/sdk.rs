@@ -0,0 +1,326 @@
+use crate::{
+    constraint::{CandidateGrid, CandidateSet},
+    format::{from_rows, parse_row, write_row, FormatError},
+    metadata::PuzzleMetadata,
+    sudoku::Sudoku,
+};
+use std::{fmt::Display, str::FromStr};
+
+/// A puzzle in the SadMan Sudoku `.sdk` format: the givens, optionally the player's current state
+/// and pencil marks, and any `#X` metadata lines (e.g. `#A` author, `#D` description, `#L` level).
+///
+/// A plain file is nine rows of digits with '.' for blanks. The extended form splits the file into
+/// `[Puzzle]`, `[State]` and `[PencilMarks]` sections, where each pencil mark row lists the
+/// candidates of its nine squares separated by spaces ('.' for none).
+#[derive(Debug)]
+pub struct SdkPuzzle {
+    /// The original clues of the puzzle.
+    pub givens: Sudoku,
+    /// The givens plus every value the player has entered so far.
+    pub state: Option<Sudoku>,
+    /// The candidates the player has noted for every square.
+    pub pencil_marks: Option<CandidateGrid>,
+    /// Metadata lines as (tag, value) pairs, in file order.
+    pub metadata: Vec<(char, String)>,
+}
+
+/// The sections of an extended `.sdk` file.
+enum Section {
+    Puzzle,
+    State,
+    PencilMarks,
+}
+
+impl From<Sudoku> for SdkPuzzle {
+    fn from(givens: Sudoku) -> Self {
+        SdkPuzzle {
+            givens,
+            state: None,
+            pencil_marks: None,
+            metadata: Vec::new(),
+        }
+    }
+}
+
+impl SdkPuzzle {
+    /// Returns the metadata lines as [`PuzzleMetadata`]: `#A` is the author, `#S` the source
+    /// and `#B` the date of publication. Other lines are kept as extra pairs keyed by their tag.
+    pub fn puzzle_metadata(&self) -> PuzzleMetadata {
+        PuzzleMetadata::from_pairs(self.metadata.iter().map(|(tag, value)| {
+            let key = match tag {
+                'A' => "author".to_string(),
+                'S' => "source".to_string(),
+                'B' => "created".to_string(),
+                _ => tag.to_string(),
+            };
+            (key, value.clone())
+        }))
+    }
+
+    /// Replaces the metadata lines with the [`PuzzleMetadata`], the reverse of
+    /// [`SdkPuzzle::puzzle_metadata`]. The id, rating, tags and extra pairs whose key is not a
+    /// single character have no place in the format and are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - The metadata to write.
+    pub fn set_puzzle_metadata(&mut self, metadata: &PuzzleMetadata) {
+        self.metadata = metadata
+            .to_pairs()
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let tag = match key.as_str() {
+                    "author" => 'A',
+                    "source" => 'S',
+                    "created" => 'B',
+                    _ => {
+                        let mut characters = key.chars();
+                        match (characters.next(), characters.next()) {
+                            (Some(tag), None) => tag,
+                            _ => return None,
+                        }
+                    }
+                };
+                Some((tag, value))
+            })
+            .collect();
+    }
+}
+
+/// Parses one row of pencil marks.
+///
+/// # Arguments
+///
+/// * `line` - The text of the row.
+/// * `line_number` - Line number used in error messages.
+fn parse_pencil_row(line: &str, line_number: usize) -> Result<Vec<CandidateSet>, FormatError> {
+    let mut retval = Vec::new();
+    for token in line.split_whitespace() {
+        let mut candidates = CandidateSet::new();
+        for character in token.chars() {
+            match character {
+                '.' | '0' => {}
+                '1'..='9' => {
+                    candidates.insert(character as u8 - b'0');
+                }
+                _ => return Err(FormatError::BadCharacter(line_number, character)),
+            }
+        }
+        retval.push(candidates);
+    }
+    if retval.len() != 9 {
+        return Err(FormatError::BadLineLength(line_number));
+    }
+    Ok(retval)
+}
+
+impl FromStr for SdkPuzzle {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut section = Section::Puzzle;
+        let mut puzzle_rows = Vec::new();
+        let mut state_rows = Vec::new();
+        let mut pencil_rows: Vec<CandidateSet> = Vec::new();
+        let mut pencil_row_count = 0;
+        let mut metadata = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(tag_line) = line.strip_prefix('#') {
+                let mut characters = tag_line.chars();
+                if let Some(tag) = characters.next() {
+                    metadata.push((tag, characters.as_str().trim().to_string()));
+                }
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = match name {
+                    "Puzzle" => Section::Puzzle,
+                    "State" => Section::State,
+                    "PencilMarks" => Section::PencilMarks,
+                    _ => return Err(FormatError::UnknownSection(name.to_string())),
+                };
+                continue;
+            }
+            match section {
+                Section::Puzzle => puzzle_rows.push(parse_row(line, line_number)?),
+                Section::State => state_rows.push(parse_row(line, line_number)?),
+                Section::PencilMarks => {
+                    pencil_rows.extend(parse_pencil_row(line, line_number)?);
+                    pencil_row_count += 1;
+                }
+            }
+        }
+
+        let givens = from_rows(&puzzle_rows)?;
+        let state = if state_rows.is_empty() {
+            None
+        } else {
+            // Only the squares of the puzzle are givens, not the player's values.
+            let mut state = from_rows(&state_rows)?;
+            state.lock_givens_of(&givens);
+            Some(state)
+        };
+        let pencil_marks = if pencil_rows.is_empty() {
+            None
+        } else if pencil_row_count != 9 {
+            return Err(FormatError::BadRowCount(pencil_row_count));
+        } else {
+            let mut marks = pencil_rows.into_iter();
+            Some(std::array::from_fn(|_| marks.next().unwrap_or_default()))
+        };
+
+        Ok(SdkPuzzle {
+            givens,
+            state,
+            pencil_marks,
+            metadata,
+        })
+    }
+}
+
+impl Display for SdkPuzzle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (tag, value) in &self.metadata {
+            writeln!(f, "#{}{}", tag, value)?;
+        }
+        let extended = self.state.is_some() || self.pencil_marks.is_some();
+        if extended {
+            writeln!(f, "[Puzzle]")?;
+        }
+        for row in self.givens.squares.chunks(9) {
+            write_row(f, row)?;
+            writeln!(f)?;
+        }
+        if let Some(state) = &self.state {
+            writeln!(f, "[State]")?;
+            for row in state.squares.chunks(9) {
+                write_row(f, row)?;
+                writeln!(f)?;
+            }
+        }
+        if let Some(pencil_marks) = &self.pencil_marks {
+            writeln!(f, "[PencilMarks]")?;
+            for row in pencil_marks.chunks(9) {
+                let tokens: Vec<String> = row
+                    .iter()
+                    .map(|candidates| {
+                        if candidates.is_empty() {
+                            return ".".to_string();
+                        }
+                        let mut values: Vec<u8> = candidates.iter().copied().collect();
+                        values.sort_unstable();
+                        values.iter().map(|value| value.to_string()).collect()
+                    })
+                    .collect();
+                writeln!(f, "{}", tokens.join(" "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SdkPuzzle;
+    use crate::{
+        constraint::{CandidateGrid, CandidateSet},
+        format::FormatError,
+        metadata::PuzzleMetadata,
+        sudoku::Sudoku,
+    };
+
+    const PLAIN: &str = "#AJohn Doe
+#DA test puzzle
+2..6.8...
+58...97..
+....4....
+37....5..
+6.......4
+..8....13
+....2....
+..98...36
+...3.6..9
+";
+
+    #[test]
+    fn test_parse_plain() {
+        let puzzle: SdkPuzzle = PLAIN.parse().unwrap();
+        assert_eq!(
+            [2, 0, 0, 6, 0, 8, 0, 0, 0],
+            puzzle.givens.get_row(0).unwrap()
+        );
+        assert_eq!(24, puzzle.givens.set_count);
+        assert_eq!(('A', "John Doe".to_string()), puzzle.metadata[0]);
+        assert_eq!(('D', "A test puzzle".to_string()), puzzle.metadata[1]);
+        assert!(puzzle.state.is_none());
+        assert_eq!(PLAIN, puzzle.to_string());
+    }
+
+    #[test]
+    fn test_puzzle_metadata() {
+        let mut puzzle: SdkPuzzle = PLAIN.parse().unwrap();
+        let mut metadata = puzzle.puzzle_metadata();
+        assert_eq!(Some("John Doe".to_string()), metadata.author);
+        assert_eq!(
+            vec![("D".to_string(), "A test puzzle".to_string())],
+            metadata.extra
+        );
+
+        metadata.created = chrono::NaiveDate::from_ymd_opt(2005, 4, 1);
+        metadata.rating = Some(3.0);
+        puzzle.set_puzzle_metadata(&metadata);
+        assert!(puzzle
+            .to_string()
+            .starts_with("#AJohn Doe\n#B2005-04-01\n#DA test puzzle\n"));
+        assert_eq!(
+            PuzzleMetadata {
+                rating: None,
+                ..metadata
+            },
+            puzzle.puzzle_metadata()
+        );
+    }
+
+    #[test]
+    fn test_extended_round_trip() {
+        let mut puzzle: SdkPuzzle = PLAIN.parse().unwrap();
+        let mut state = puzzle.givens.squares;
+        state[1] = 1;
+        puzzle.state = Some(Sudoku::new_from_state(state));
+        let mut marks: CandidateGrid = std::array::from_fn(|_| CandidateSet::new());
+        marks[2] = CandidateSet::from([3, 4]);
+        puzzle.pencil_marks = Some(marks);
+
+        let text = puzzle.to_string();
+        let parsed: SdkPuzzle = text.parse().unwrap();
+        assert_eq!(puzzle.givens.squares, parsed.givens.squares);
+        let state = parsed.state.unwrap();
+        assert_eq!(1, state.squares[1]);
+        assert!(state.is_given(0, 0).unwrap());
+        assert!(!state.is_given(0, 1).unwrap());
+        assert_eq!(CandidateSet::from([3, 4]), parsed.pencil_marks.unwrap()[2]);
+        assert!(text.contains("[PencilMarks]\n. . 34 . . . . . .\n"));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        assert_eq!(
+            FormatError::BadCharacter(1, 'x'),
+            "2..6.8..x".parse::<SdkPuzzle>().unwrap_err()
+        );
+        assert_eq!(
+            FormatError::BadRowCount(1),
+            "2..6.8...".parse::<SdkPuzzle>().unwrap_err()
+        );
+        assert_eq!(
+            FormatError::UnknownSection("Foo".to_string()),
+            "[Foo]".parse::<SdkPuzzle>().unwrap_err()
+        );
+    }
+}
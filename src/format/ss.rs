@@ -0,0 +1,116 @@
+use crate::{
+    format::{from_rows, parse_row, FormatError},
+    sudoku::Sudoku,
+};
+
+/// Top and bottom border of the Simple Sudoku layout.
+const BORDER: &str = "*-----------*";
+
+/// Separator between bands of the Simple Sudoku layout.
+const SEPARATOR: &str = "|---+---+---|";
+
+/// Parses a puzzle in the Simple Sudoku `.ss` layout: nine rows with '.' for blanks, where `|`
+/// box separators and border lines such as `*-----------*` or `|---+---+---|` are ignored.
+///
+/// # Arguments
+///
+/// * `input` - The text of the puzzle.
+pub fn parse(input: &str) -> Result<Sudoku, FormatError> {
+    let mut rows = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let squares: String = line
+            .chars()
+            .filter(|character| *character != '|' && !character.is_whitespace())
+            .collect();
+        if squares.chars().all(|character| "-+*=".contains(character)) {
+            continue;
+        }
+        rows.push(parse_row(&squares, i + 1)?);
+    }
+    from_rows(&rows)
+}
+
+/// Writes the sudoku in the Simple Sudoku `.ss` layout, including the borders.
+///
+/// # Arguments
+///
+/// * `sudoku` - The sudoku to write.
+pub fn write(sudoku: &Sudoku) -> String {
+    let mut retval = String::new();
+    retval.push_str(BORDER);
+    retval.push('\n');
+    for (i, row) in sudoku.squares.chunks(9).enumerate() {
+        if i != 0 && i % 3 == 0 {
+            retval.push_str(SEPARATOR);
+            retval.push('\n');
+        }
+        for (j, value) in row.iter().enumerate() {
+            if j % 3 == 0 {
+                retval.push('|');
+            }
+            if *value == 0 {
+                retval.push('.');
+            } else {
+                retval.push((b'0' + value) as char);
+            }
+        }
+        retval.push_str("|\n");
+    }
+    retval.push_str(BORDER);
+    retval.push('\n');
+    retval
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, write};
+    use crate::format::FormatError;
+
+    const FRAMED: &str = "*-----------*
+|.2.|...|...|
+|...|6..|..3|
+|.74|.8.|...|
+|---+---+---|
+|...|..3|..2|
+|.8.|.4.|.1.|
+|6..|5..|...|
+|---+---+---|
+|...|.1.|78.|
+|5..|..9|...|
+|...|...|.4.|
+*-----------*
+";
+
+    #[test]
+    fn test_parse_framed() {
+        let sudoku = parse(FRAMED).unwrap();
+        assert_eq!([0, 2, 0, 0, 0, 0, 0, 0, 0], sudoku.get_row(0).unwrap());
+        assert_eq!([0, 0, 0, 0, 0, 0, 0, 4, 0], sudoku.get_row(8).unwrap());
+        assert_eq!(FRAMED, write(&sudoku));
+    }
+
+    #[test]
+    fn test_parse_unframed() {
+        let unframed = FRAMED
+            .lines()
+            .filter(|line| !line.starts_with('*') && !line.contains('+'))
+            .map(|line| line.trim_matches('|').replace('|', " | ") + "\n")
+            .collect::<String>();
+        assert_eq!(
+            parse(FRAMED).unwrap().squares,
+            parse(&unframed).unwrap().squares
+        );
+    }
+
+    #[test]
+    fn test_bad_input() {
+        assert_eq!(
+            FormatError::BadCharacter(2, 'x'),
+            parse("*-----------*\n|.2.|...|..x|").unwrap_err()
+        );
+        assert_eq!(
+            FormatError::BadRowCount(0),
+            parse(super::BORDER).unwrap_err()
+        );
+    }
+}
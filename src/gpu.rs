@@ -0,0 +1,381 @@
+//! Experimental batch solving on the GPU with a `wgpu` compute shader, for collections of
+//! millions of puzzles. Every puzzle is searched by its own shader invocation with a depth-first
+//! search over bit masks and a step limit; puzzles that hit the limit are solved on the CPU
+//! afterwards. Only the standard rules are supported.
+
+use crate::{
+    batch::{parallel_map, solve_dlx, thread_count},
+    sudoku::{Grid, Sudoku},
+};
+use std::sync::mpsc;
+use wgpu::util::DeviceExt;
+
+/// Number of search steps an invocation takes before leaving the puzzle to the CPU. Keeps
+/// single dispatches short enough for drivers that reset long-running shaders.
+pub const DEFAULT_MAX_STEPS: u32 = 20_000;
+
+/// Number of puzzles per dispatch, kept below the default storage buffer size limit.
+const CHUNK: usize = 65_536;
+
+/// Invocations per workgroup, matching `@workgroup_size` in the shader.
+const WORKGROUP_SIZE: usize = 64;
+
+/// Status the shader writes for a solved puzzle.
+const SOLVED: u32 = 0;
+
+/// Status the shader writes for a puzzle without solutions.
+const UNSOLVABLE: u32 = 1;
+
+/// Searches every puzzle of the grids buffer and replaces it with its solution. The status
+/// buffer gets 0 for solved, 1 for unsolvable and 2 for puzzles that hit the step limit.
+const SHADER: &str = r#"
+struct Params {
+    count: u32,
+    max_steps: u32,
+}
+
+@group(0) @binding(0) var<storage, read_write> grids: array<u32>;
+@group(0) @binding(1) var<storage, read_write> status: array<u32>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let puzzle = id.x;
+    if (puzzle >= params.count) {
+        return;
+    }
+    let base = puzzle * 81u;
+    var grid: array<u32, 81>;
+    var rows: array<u32, 9>;
+    var columns: array<u32, 9>;
+    var blocks: array<u32, 9>;
+    for (var i = 0u; i < 81u; i++) {
+        let value = grids[base + i];
+        grid[i] = value;
+        if (value != 0u) {
+            let bit = 1u << value;
+            let row = i / 9u;
+            let column = i % 9u;
+            let block = row / 3u * 3u + column / 3u;
+            if (((rows[row] | columns[column] | blocks[block]) & bit) != 0u) {
+                status[puzzle] = 1u;
+                return;
+            }
+            rows[row] |= bit;
+            columns[column] |= bit;
+            blocks[block] |= bit;
+        }
+    }
+
+    // Squares guessed so far, in order, to undo when a square runs out of values.
+    var stack: array<u32, 81>;
+    var depth = 0u;
+    for (var step = 0u; step < params.max_steps; step++) {
+        var cell = 81u;
+        var fewest = 10u;
+        for (var i = 0u; i < 81u; i++) {
+            if (grid[i] == 0u) {
+                let row = i / 9u;
+                let column = i % 9u;
+                let block = row / 3u * 3u + column / 3u;
+                let count = countOneBits(~(rows[row] | columns[column] | blocks[block]) & 0x3feu);
+                if (count < fewest) {
+                    cell = i;
+                    fewest = count;
+                }
+            }
+        }
+        if (cell == 81u) {
+            for (var i = 0u; i < 81u; i++) {
+                grids[base + i] = grid[i];
+            }
+            status[puzzle] = 0u;
+            return;
+        }
+
+        // Try the values of the square from `lowest` up, undoing guesses while none is left.
+        var lowest = 1u;
+        loop {
+            let row = cell / 9u;
+            let column = cell % 9u;
+            let block = row / 3u * 3u + column / 3u;
+            let free = ~(rows[row] | columns[column] | blocks[block]) & 0x3feu;
+            let allowed = free & ~((1u << lowest) - 1u);
+            if (allowed != 0u) {
+                let value = firstTrailingBit(allowed);
+                let bit = 1u << value;
+                grid[cell] = value;
+                rows[row] |= bit;
+                columns[column] |= bit;
+                blocks[block] |= bit;
+                stack[depth] = cell;
+                depth++;
+                break;
+            }
+            if (depth == 0u) {
+                status[puzzle] = 1u;
+                return;
+            }
+            depth--;
+            cell = stack[depth];
+            let previous = grid[cell];
+            let previous_bit = ~(1u << previous);
+            let previous_row = cell / 9u;
+            let previous_column = cell % 9u;
+            let previous_block = previous_row / 3u * 3u + previous_column / 3u;
+            rows[previous_row] &= previous_bit;
+            columns[previous_column] &= previous_bit;
+            blocks[previous_block] &= previous_bit;
+            grid[cell] = 0u;
+            lowest = previous + 1u;
+        }
+    }
+    status[puzzle] = 2u;
+}
+"#;
+
+/// Error type for setting up and running the GPU.
+#[derive(Debug)]
+pub enum GpuError {
+    /// No graphics adapter supporting compute shaders was found.
+    NoAdapter,
+    /// The adapter refused to create a device.
+    RequestDevice(wgpu::RequestDeviceError),
+    /// The results could not be read back from the GPU.
+    BufferAsync(wgpu::BufferAsyncError),
+}
+
+impl From<wgpu::RequestDeviceError> for GpuError {
+    fn from(err: wgpu::RequestDeviceError) -> Self {
+        GpuError::RequestDevice(err)
+    }
+}
+
+impl From<wgpu::BufferAsyncError> for GpuError {
+    fn from(err: wgpu::BufferAsyncError) -> Self {
+        GpuError::BufferAsync(err)
+    }
+}
+
+/// A GPU device with the solving shader compiled, reusable for any number of batches.
+#[derive(Debug)]
+pub struct GpuSolver {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    max_steps: u32,
+}
+
+impl GpuSolver {
+    /// Opens the default adapter and compiles the shader, with [`DEFAULT_MAX_STEPS`].
+    pub fn new() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+                .ok_or(GpuError::NoAdapter)?;
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("sudoku solver"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("sudoku solver"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+        Ok(GpuSolver {
+            device,
+            queue,
+            pipeline,
+            max_steps: DEFAULT_MAX_STEPS,
+        })
+    }
+
+    /// Sets the number of search steps per puzzle before it is left to the CPU.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_steps` - Steps per puzzle; every step fills one square.
+    pub fn max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Solves the puzzles, returning the solution or the reason of the failure for each one in
+    /// order. Puzzles the GPU gives up on are solved with
+    /// [`SolverBackend::Dlx`](crate::solver::SolverBackend::Dlx) on the CPU.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzles` - The puzzles to solve.
+    /// * `threads` - Number of CPU threads for the stragglers, or 0 for one per available core.
+    pub fn solve_batch(
+        &self,
+        puzzles: &[Sudoku],
+        threads: usize,
+    ) -> Result<Vec<Result<Sudoku, String>>, GpuError> {
+        let mut retval = Vec::with_capacity(puzzles.len());
+        let mut stragglers = Vec::new();
+        for chunk in puzzles.chunks(CHUNK) {
+            for (grid, status) in self.dispatch(chunk)? {
+                retval.push(match status {
+                    SOLVED => Ok(Sudoku::new_from_state(grid)),
+                    UNSOLVABLE => Err("the sudoku has no solution".to_string()),
+                    _ => {
+                        stragglers.push(retval.len());
+                        Err(String::new())
+                    }
+                });
+            }
+        }
+
+        let unsolved: Vec<Sudoku> = stragglers.iter().map(|i| puzzles[*i].clone()).collect();
+        let solved = parallel_map(&unsolved, thread_count(threads), |puzzle| {
+            solve_dlx(puzzle.clone())
+        });
+        for (i, result) in stragglers.into_iter().zip(solved) {
+            retval[i] = result;
+        }
+        Ok(retval)
+    }
+
+    /// Runs the shader over one chunk of puzzles and reads back every grid with its status.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzles` - At most [`CHUNK`] puzzles.
+    fn dispatch(&self, puzzles: &[Sudoku]) -> Result<Vec<(Grid, u32)>, GpuError> {
+        if puzzles.is_empty() {
+            return Ok(Vec::new());
+        }
+        let grid_bytes: Vec<u8> = puzzles
+            .iter()
+            .flat_map(|puzzle| puzzle.squares)
+            .flat_map(|value| (value as u32).to_le_bytes())
+            .collect();
+        let status_size = (puzzles.len() * 4) as u64;
+        let params: Vec<u8> = [puzzles.len() as u32, self.max_steps]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+
+        let storage = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC;
+        let grids = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("grids"),
+                contents: &grid_bytes,
+                usage: storage,
+            });
+        let status = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("status"),
+            size: status_size,
+            usage: storage,
+            mapped_at_creation: false,
+        });
+        let uniform = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: &params,
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: grid_bytes.len() as u64 + status_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sudoku solver"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: grids.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: status.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(puzzles.len().div_ceil(WORKGROUP_SIZE) as u32, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&grids, 0, &readback, 0, grid_bytes.len() as u64);
+        encoder.copy_buffer_to_buffer(&status, 0, &readback, grid_bytes.len() as u64, status_size);
+        self.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = mpsc::channel();
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv().unwrap_or(Err(wgpu::BufferAsyncError))?;
+
+        let bytes = slice.get_mapped_range();
+        let words: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect();
+        let (grid_words, status_words) = words.split_at(puzzles.len() * 9 * 9);
+        let retval = grid_words
+            .chunks_exact(9 * 9)
+            .zip(status_words)
+            .map(|(values, status)| {
+                let grid: Grid = std::array::from_fn(|i| values[i] as u8);
+                (grid, *status)
+            })
+            .collect();
+        drop(bytes);
+        readback.unmap();
+        Ok(retval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GpuSolver;
+    use crate::{
+        corpus,
+        format::{parse_line, write_line},
+        sudoku::Sudoku,
+    };
+
+    #[test]
+    fn test_solve_batch() {
+        // Machines without a GPU, like most CI runners, have nothing to test.
+        let Ok(solver) = GpuSolver::new() else {
+            return;
+        };
+        let mut puzzles: Vec<Sudoku> = corpus::PUZZLES
+            .iter()
+            .map(|known| parse_line(known.puzzle, 1).unwrap())
+            .collect();
+        puzzles.push(parse_line(corpus::INVALID[0].puzzle, 1).unwrap());
+
+        // The step limit leaves the two hardest puzzles to the CPU.
+        let results = solver.max_steps(100).solve_batch(&puzzles, 2).unwrap();
+        for (known, result) in corpus::PUZZLES.iter().zip(&results) {
+            assert_eq!(known.solution, write_line(result.as_ref().unwrap()));
+        }
+        assert!(results[corpus::PUZZLES.len()].is_err());
+    }
+}
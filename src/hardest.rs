@@ -0,0 +1,136 @@
+//! Famous hard puzzles, for benchmarks and for exercising worst-case behavior. Grids are written
+//! as 81 characters with '.' for blanks, as read by [`parse_line`].
+//!
+//! Only a small selection is included. The top1465 list is not bundled: its grids would
+//! outweigh the rest of the crate, so read it from a file with the [`batch`](crate::batch)
+//! functions, e.g. [`batch::rate_stream`](crate::batch::rate_stream).
+
+use crate::{format::parse_line, sudoku::Sudoku};
+
+/// A famous hard puzzle with its unique solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HardPuzzle {
+    /// The name the puzzle is known by.
+    pub name: &'static str,
+    /// The givens.
+    pub puzzle: &'static str,
+    /// The only solution.
+    pub solution: &'static str,
+}
+
+impl HardPuzzle {
+    /// Returns the givens as a sudoku.
+    pub fn sudoku(&self) -> Sudoku {
+        parse_line(self.puzzle, 1).expect("the built-in puzzles are valid")
+    }
+}
+
+/// Arto Inkala's "AI Escargot" (2006).
+pub const AI_ESCARGOT: HardPuzzle = HardPuzzle {
+    name: "AI Escargot",
+    puzzle: "1....7.9..3..2...8..96..5....53..9...1..8...26....4...3......1..4......7..7...3..",
+    solution: "162857493534129678789643521475312986913586742628794135356478219241935867897261354",
+};
+
+/// "Easter Monster" from the Sudoku Players' Forum (2007).
+pub const EASTER_MONSTER: HardPuzzle = HardPuzzle {
+    name: "Easter Monster",
+    puzzle: "1.......2.9.4...5...6...7...5.9.3.......7.......85..4.7.....6...3...9.8...2.....1",
+    solution: "174385962293467158586192734451923876928674315367851249719548623635219487842736591",
+};
+
+/// Arto Inkala's puzzle published as the world's hardest sudoku (2012).
+pub const INKALA_2012: HardPuzzle = HardPuzzle {
+    name: "Inkala 2012",
+    puzzle: "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+    solution: "812753649943682175675491283154237896369845721287169534521974368438526917796318452",
+};
+
+/// "Platinum Blonde", long at the top of the Sudoku Explainer ratings.
+pub const PLATINUM_BLONDE: HardPuzzle = HardPuzzle {
+    name: "Platinum Blonde",
+    puzzle: ".......12........3..23..4....18....5.6..7.8.......9.....85.....9...4.5..47...6...",
+    solution: "839465712146782953752391486391824675564173829287659341628537194913248567475916238",
+};
+
+/// A 17-clue puzzle built to make naive backtracking in reading order as slow as possible,
+/// from the Wikipedia article on sudoku solving algorithms.
+pub const BRUTE_FORCE_RESISTANT: HardPuzzle = HardPuzzle {
+    name: "Brute-force resistant",
+    puzzle: "..............3.85..1.2.......5.7.....4...1...9.......5......73..2.1........4...9",
+    solution: "987654321246173985351928746128537694634892157795461832519286473472319568863745219",
+};
+
+/// The first five 17-clue puzzles of the top95 list.
+pub const TOP95: [HardPuzzle; 5] = [
+    HardPuzzle {
+        name: "top95 #1",
+        puzzle: "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......",
+        solution:
+            "417369825632158947958724316825437169791586432346912758289643571573291684164875293",
+    },
+    HardPuzzle {
+        name: "top95 #2",
+        puzzle: "52...6.........7.13...........4..8..6......5...........418.........3..2...87.....",
+        solution:
+            "527316489896542731314987562172453896689271354453698217941825673765134928238769145",
+    },
+    HardPuzzle {
+        name: "top95 #3",
+        puzzle: "6.....8.3.4.7.................5.4.7.3..2.....1.6.......2.....5.....8.6......1....",
+        solution:
+            "617459823248736915539128467982564371374291586156873294823647159791385642465912738",
+    },
+    HardPuzzle {
+        name: "top95 #4",
+        puzzle: "48.3............71.2.......7.5....6....2..8.............1.76...3.....4......5....",
+        solution:
+            "487312695593684271126597384735849162914265837268731549851476923379128456642953718",
+    },
+    HardPuzzle {
+        name: "top95 #5",
+        puzzle: "....14....3....2...7..........9...3.6.1.............8.2.....1.4....5.6.....7.8...",
+        solution:
+            "962314857134587269578296413847962531651873942329145786285639174793451628416728395",
+    },
+];
+
+/// Every puzzle of the module: the named ones, then the top95 selection.
+pub fn all() -> impl Iterator<Item = &'static HardPuzzle> {
+    const NAMED: [HardPuzzle; 5] = [
+        AI_ESCARGOT,
+        EASTER_MONSTER,
+        INKALA_2012,
+        PLATINUM_BLONDE,
+        BRUTE_FORCE_RESISTANT,
+    ];
+    NAMED.iter().chain(TOP95.iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::all;
+    use crate::{
+        format::write_line,
+        solver::{SolverBackend, SolverBuilder},
+    };
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_all() {
+        assert_eq!(10, all().count());
+        assert_eq!(
+            10,
+            all().map(|hard| hard.name).collect::<HashSet<_>>().len()
+        );
+        for hard in all() {
+            let sudoku = hard.sudoku();
+            assert!(sudoku.set_count <= 23, "{}", hard.name);
+            let mut solver = SolverBuilder::new()
+                .backend(SolverBackend::Dlx)
+                .build(sudoku);
+            solver.solve().unwrap();
+            assert_eq!(hard.solution, write_line(solver.sudoku()), "{}", hard.name);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Hints for players who are stuck, built on the deductions of the solver. A hint can be given
+//! in three tiers, each telling more than the last, so that players can ask for just enough
+//! help to find the next step themselves.
+
+use crate::{
+    solver::{self, Solver, Strategy},
+    sudoku::{houses_of, Coordinates, House},
+};
+
+/// How much a hint gives away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HintLevel {
+    /// Point at the house where the next step can be found.
+    Region,
+    /// Name the technique that finds the next step, and where.
+    Technique,
+    /// Reveal the square and its value.
+    Value,
+}
+
+/// The next step a player can take without guessing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    /// The square that can be filled.
+    pub cell: Coordinates,
+    /// The value it must have.
+    pub value: u8,
+    /// Name of the technique that finds it, as in
+    /// [`SolveStats::hardest_technique`](crate::solver::SolveStats::hardest_technique).
+    pub technique: &'static str,
+    /// The house to look at.
+    pub house: House,
+}
+
+impl HintLevel {
+    /// Returns the next level, or `None` after [`HintLevel::Value`].
+    pub fn next(self) -> Option<HintLevel> {
+        match self {
+            HintLevel::Region => Some(HintLevel::Technique),
+            HintLevel::Technique => Some(HintLevel::Value),
+            HintLevel::Value => None,
+        }
+    }
+}
+
+impl Hint {
+    /// Finds the next step in the grid of the solver: the first square in reading order with
+    /// only one possible value, or else the first value that fits only one square of a row,
+    /// column or block, or else the way out of a bivalue universal grave if the solver may use
+    /// [`Strategy::BivalueUniversalGrave`]. Returns `None` if there is no such step, either
+    /// because the grid is full, breaks a rule or needs a guess.
+    ///
+    /// # Arguments
+    ///
+    /// * `solver` - The solver holding the grid, with its constraints.
+    pub fn find(solver: &Solver) -> Option<Hint> {
+        if !solver.find_conflicts().is_empty() {
+            return None;
+        }
+        let candidates = solver.all_candidates();
+        let naked_single = (0..9 * 9)
+            .filter(|i| solver.sudoku().squares[*i] == 0)
+            .find(|i| candidates[*i].len() == 1)
+            .map(|i| {
+                let cell = Coordinates::from((i / 9, i % 9));
+                Hint {
+                    value: *candidates[i].iter().next().expect("one candidate"),
+                    technique: "Naked Single",
+                    house: houses_of(&cell)[2],
+                    cell,
+                }
+            });
+        naked_single
+            .or_else(|| {
+                solver::hidden_single(&solver.sudoku().squares, &candidates).map(
+                    |(cell, value, house)| Hint {
+                        cell,
+                        value,
+                        technique: "Hidden Single",
+                        house,
+                    },
+                )
+            })
+            .or_else(|| {
+                if !solver
+                    .strategies()
+                    .contains(&Strategy::BivalueUniversalGrave)
+                {
+                    return None;
+                }
+                solver::bivalue_universal_grave(&solver.sudoku().squares, &candidates).map(
+                    |(cell, value)| Hint {
+                        value,
+                        technique: "Bivalue Universal Grave",
+                        house: House::Row(cell.row),
+                        cell,
+                    },
+                )
+            })
+    }
+
+    /// Describes the hint up to the given level, e.g. `Look at block 5.`, `Look for a naked
+    /// single in block 5.` or `r4c6 is 7 (naked single).`.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - How much to give away.
+    pub fn describe(&self, level: HintLevel) -> String {
+        let technique = self.technique.to_lowercase();
+        match level {
+            HintLevel::Region => format!("Look at {}.", self.house),
+            HintLevel::Technique => format!("Look for a {} in {}.", technique, self.house),
+            HintLevel::Value => format!("{} is {} ({}).", self.cell, self.value, technique),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hint, HintLevel};
+    use crate::{
+        corpus,
+        format::parse_line,
+        solver::Solver,
+        sudoku::{Coordinates, House},
+    };
+
+    #[test]
+    fn test_hint() {
+        let mut sudoku = parse_line(corpus::EASY.puzzle, 1).unwrap();
+        let hint = Hint::find(&Solver::new(sudoku.clone())).unwrap();
+        let solution = parse_line(corpus::EASY.solution, 1).unwrap();
+        assert_eq!(
+            solution.squares[hint.cell.row * 9 + hint.cell.column],
+            hint.value
+        );
+        assert_eq!("Naked Single", hint.technique);
+
+        let hint = Hint {
+            cell: Coordinates::from((3, 5)),
+            value: 7,
+            technique: "Naked Single",
+            house: House::Block(4),
+        };
+        assert_eq!("Look at block 5.", hint.describe(HintLevel::Region));
+        assert_eq!(
+            "Look for a naked single in block 5.",
+            hint.describe(HintLevel::Technique)
+        );
+        assert_eq!("r4c6 is 7 (naked single).", hint.describe(HintLevel::Value));
+        assert_eq!(Some(HintLevel::Value), HintLevel::Technique.next());
+        assert_eq!(None, HintLevel::Value.next());
+
+        // r1c3 already holds a 3.
+        sudoku.set_unchecked(0, 0, 3);
+        assert_eq!(None, Hint::find(&Solver::new(sudoku)));
+        assert_eq!(None, Hint::find(&Solver::new(solution)));
+    }
+}
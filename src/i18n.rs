@@ -0,0 +1,669 @@
+//! Translations of the explanations the solver gives, see
+//! [`SolveStep::describe_in`](crate::observer::SolveStep::describe_in). Every sentence goes
+//! through [`Locale::format`], so adding a language means adding one arm per [`Message`].
+
+use crate::{
+    notation::Move,
+    sudoku::{Coordinates, House},
+};
+use std::fmt::Display;
+
+/// Language of the explanations.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    English,
+    German,
+    Finnish,
+}
+
+/// A sentence explaining one step of a solve, before it is put into words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message<'a> {
+    /// The square has only one possible value left.
+    NakedSingle { cell: &'a Coordinates, value: u8 },
+    /// The square is the only one in the house that can hold the value.
+    HiddenSingle {
+        cell: &'a Coordinates,
+        value: u8,
+        house: House,
+    },
+    /// Any other value would leave every empty square with two possible values.
+    BivalueUniversalGrave { cell: &'a Coordinates, value: u8 },
+    /// The value was set in another square of the same house.
+    SeenInHouse {
+        cell: &'a Coordinates,
+        value: u8,
+        cause: &'a Coordinates,
+        house: House,
+    },
+    /// The value can only go where `base` overlaps `cover`, and the square is in `cover` only.
+    LockedCandidates {
+        cell: &'a Coordinates,
+        value: u8,
+        base: House,
+        cover: House,
+    },
+    /// Without ruling the value out, the corners could be left with only the two values, which
+    /// could then be swapped for a second solution.
+    UniqueRectangle {
+        cell: &'a Coordinates,
+        value: u8,
+        corners: &'a [Coordinates; 4],
+        values: [u8; 2],
+    },
+    /// One of the two almost locked sets must hold the value, as only one of them can hold the
+    /// restricted common, and the square sees the value in both.
+    AlsXz {
+        cell: &'a Coordinates,
+        value: u8,
+        sets: &'a [Vec<Coordinates>; 2],
+        restricted_common: u8,
+    },
+    /// Setting the value in the square forces the placements of the chain, which lead to a
+    /// contradiction.
+    ForcingChain {
+        cell: &'a Coordinates,
+        value: u8,
+        chain: &'a [Move],
+    },
+    /// The value was set in a square a constraint other than the houses keeps apart from it.
+    SeenByConstraint {
+        cell: &'a Coordinates,
+        value: u8,
+        cause: &'a Coordinates,
+    },
+    /// The square was guessed, out of its ascending candidates.
+    Guess {
+        cell: &'a Coordinates,
+        value: u8,
+        candidates: &'a [u8],
+    },
+    /// The guessed value was taken back out of the square.
+    Backtrack { cell: &'a Coordinates, value: u8 },
+}
+
+impl Locale {
+    /// Every supported locale.
+    pub const ALL: [Locale; 3] = [Locale::English, Locale::German, Locale::Finnish];
+
+    /// Returns the ISO 639-1 code of the language, e.g. `fi`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::German => "de",
+            Locale::Finnish => "fi",
+        }
+    }
+
+    /// Returns the locale for an ISO 639-1 code, ignoring case and any region such as in
+    /// `en-GB`. Returns `None` for unsupported languages.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The language code.
+    pub fn from_code(code: &str) -> Option<Locale> {
+        let language = code.split(['-', '_']).next().unwrap_or_default();
+        Locale::ALL
+            .into_iter()
+            .find(|locale| locale.code().eq_ignore_ascii_case(language))
+    }
+
+    /// Returns the name of a row, column or block with its 1-based number, e.g. `row 4`.
+    ///
+    /// # Arguments
+    ///
+    /// * `house` - The house to name.
+    pub fn house(self, house: House) -> String {
+        let (kind, number) = match house {
+            House::Row(row) => (0, row),
+            House::Column(column) => (1, column),
+            House::Block(block) => (2, block),
+        };
+        let names = match self {
+            Locale::English => ["row", "column", "block"],
+            Locale::German => ["Zeile", "Spalte", "Block"],
+            Locale::Finnish => ["rivi", "sarake", "laatikko"],
+        };
+        format!("{} {}", names[kind], number + 1)
+    }
+
+    /// Returns the name of a technique as reported by
+    /// [`SolveStats::hardest_technique`](crate::solver::SolveStats::hardest_technique).
+    /// Names without a translation are returned as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The English name of the technique.
+    pub fn technique(self, name: &str) -> &str {
+        match (self, name) {
+            (Locale::German, "Guess") => "Raten",
+            (Locale::Finnish, "Naked Single") => "Ainoa vaihtoehto",
+            (Locale::Finnish, "Hidden Single") => "Ainoa paikka",
+            (Locale::Finnish, "Locked Candidates") => "Lukitut ehdokkaat",
+            (Locale::Finnish, "Unique Rectangle") => "Ainutkertainen suorakulmio",
+            (Locale::Finnish, "Bivalue Universal Grave") => "Kaksoisvaihtoehtojen umpikuja",
+            (Locale::Finnish, "ALS-XZ") => "Lähes lukitut joukot",
+            (Locale::Finnish, "Forcing Chain") => "Pakotettu ketju",
+            (Locale::Finnish, "Guess") => "Arvaus",
+            _ => name,
+        }
+    }
+
+    /// Joins alternative values into a list, e.g. `1, 7 or 9`.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values to list.
+    fn list(self, values: &[u8]) -> String {
+        let or = match self {
+            Locale::English => "or",
+            Locale::German => "oder",
+            Locale::Finnish => "tai",
+        };
+        join(values, or)
+    }
+
+    /// Joins items that go together into a list, e.g. `r1c1, r1c5 and r4c1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The items to list.
+    fn all<T: Display>(self, items: &[T]) -> String {
+        let and = match self {
+            Locale::English => "and",
+            Locale::German => "und",
+            Locale::Finnish => "ja",
+        };
+        join(items, and)
+    }
+
+    /// Puts a message into words.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to write.
+    pub fn format(self, message: &Message) -> String {
+        match (self, message) {
+            (Locale::English, Message::NakedSingle { cell, value }) => format!(
+                "{} has only one possible value left, {} (naked single).",
+                cell, value
+            ),
+            (Locale::German, Message::NakedSingle { cell, value }) => format!(
+                "{} hat nur noch einen möglichen Wert, {} (Naked Single).",
+                cell, value
+            ),
+            (Locale::Finnish, Message::NakedSingle { cell, value }) => {
+                format!("{} voi enää olla vain {} (ainoa vaihtoehto).", cell, value)
+            }
+            (Locale::English, Message::HiddenSingle { cell, value, house }) => format!(
+                "{} is the only square in {} that can be {} (hidden single).",
+                cell,
+                self.house(*house),
+                value
+            ),
+            (Locale::German, Message::HiddenSingle { cell, value, house }) => format!(
+                "{} ist das einzige Feld in {}, das {} sein kann (Hidden Single).",
+                cell,
+                self.house(*house),
+                value
+            ),
+            (Locale::Finnish, Message::HiddenSingle { cell, value, house }) => format!(
+                "{} on ainoa kohta, johon {} sopii ({}, ainoa paikka).",
+                cell,
+                value,
+                self.house(*house)
+            ),
+            (Locale::English, Message::BivalueUniversalGrave { cell, value }) => format!(
+                "{} must be {}, as otherwise every empty square would have two possible values, \
+                 which would allow a second solution (BUG+1).",
+                cell, value
+            ),
+            (Locale::German, Message::BivalueUniversalGrave { cell, value }) => format!(
+                "{} muss {} sein, da sonst jedes leere Feld zwei mögliche Werte hätte, was eine \
+                 zweite Lösung zuließe (BUG+1).",
+                cell, value
+            ),
+            (Locale::Finnish, Message::BivalueUniversalGrave { cell, value }) => format!(
+                "{} on {}, koska muuten jokaisessa tyhjässä ruudussa olisi kaksi vaihtoehtoa \
+                 ja ratkaisuja voisi olla kaksi (BUG+1).",
+                cell, value
+            ),
+            (
+                Locale::English,
+                Message::SeenInHouse {
+                    cell,
+                    value,
+                    cause,
+                    house,
+                },
+            ) => format!(
+                "{} cannot be {}, as {} in {} is {}.",
+                cell,
+                value,
+                cause,
+                self.house(*house),
+                value
+            ),
+            (
+                Locale::German,
+                Message::SeenInHouse {
+                    cell,
+                    value,
+                    cause,
+                    house,
+                },
+            ) => format!(
+                "{} kann nicht {} sein, da {} in {} schon {} ist.",
+                cell,
+                value,
+                cause,
+                self.house(*house),
+                value
+            ),
+            (
+                Locale::Finnish,
+                Message::SeenInHouse {
+                    cell,
+                    value,
+                    cause,
+                    house,
+                },
+            ) => format!(
+                "{} ei voi olla {}, koska {} on jo {} ({}).",
+                cell,
+                value,
+                cause,
+                value,
+                self.house(*house)
+            ),
+            (
+                Locale::English,
+                Message::LockedCandidates {
+                    cell,
+                    value,
+                    base,
+                    cover,
+                },
+            ) => format!(
+                "{} cannot be {}, as the {} of {} must be in {} (locked candidates).",
+                cell,
+                value,
+                value,
+                self.house(*base),
+                self.house(*cover)
+            ),
+            (
+                Locale::German,
+                Message::LockedCandidates {
+                    cell,
+                    value,
+                    base,
+                    cover,
+                },
+            ) => format!(
+                "{} kann nicht {} sein, da die {} von {} in {} liegen muss (Locked Candidates).",
+                cell,
+                value,
+                value,
+                self.house(*base),
+                self.house(*cover)
+            ),
+            (
+                Locale::Finnish,
+                Message::LockedCandidates {
+                    cell,
+                    value,
+                    base,
+                    cover,
+                },
+            ) => format!(
+                "{} ei voi olla {}, koska {} sopii vain yhteisiin kohtiin ({}, {}; lukitut ehdokkaat).",
+                cell,
+                value,
+                value,
+                self.house(*base),
+                self.house(*cover)
+            ),
+            (
+                Locale::English,
+                Message::UniqueRectangle {
+                    cell,
+                    value,
+                    corners,
+                    values,
+                },
+            ) => format!(
+                "{} cannot be {}, as otherwise {} could only be {} and {}, which could be \
+                 swapped for a second solution (unique rectangle).",
+                cell,
+                value,
+                self.all(*corners),
+                values[0],
+                values[1]
+            ),
+            (
+                Locale::German,
+                Message::UniqueRectangle {
+                    cell,
+                    value,
+                    corners,
+                    values,
+                },
+            ) => format!(
+                "{} kann nicht {} sein, da sonst {} nur {} oder {} sein könnten, die sich für \
+                 eine zweite Lösung tauschen ließen (Unique Rectangle).",
+                cell,
+                value,
+                self.all(*corners),
+                values[0],
+                values[1]
+            ),
+            (
+                Locale::Finnish,
+                Message::UniqueRectangle {
+                    cell,
+                    value,
+                    corners,
+                    values,
+                },
+            ) => format!(
+                "{} ei voi olla {}, koska muuten {} voisivat olla vain {} tai {}, ja niiden \
+                 vaihtaminen antaisi toisen ratkaisun (ainutkertainen suorakulmio).",
+                cell,
+                value,
+                self.all(*corners),
+                values[0],
+                values[1]
+            ),
+            (
+                Locale::English,
+                Message::AlsXz {
+                    cell,
+                    value,
+                    sets,
+                    restricted_common,
+                },
+            ) => format!(
+                "{} cannot be {}, as {} or {} must hold a {}: they are almost locked sets and \
+                 only one of them can hold their common {} (ALS-XZ).",
+                cell,
+                value,
+                self.all(&sets[0]),
+                self.all(&sets[1]),
+                value,
+                restricted_common
+            ),
+            (
+                Locale::German,
+                Message::AlsXz {
+                    cell,
+                    value,
+                    sets,
+                    restricted_common,
+                },
+            ) => format!(
+                "{} kann nicht {} sein, da {} oder {} eine {} enthalten müssen: Es sind fast \
+                 gesperrte Mengen, von denen nur eine ihre gemeinsame {} enthalten kann (ALS-XZ).",
+                cell,
+                value,
+                self.all(&sets[0]),
+                self.all(&sets[1]),
+                value,
+                restricted_common
+            ),
+            (
+                Locale::Finnish,
+                Message::AlsXz {
+                    cell,
+                    value,
+                    sets,
+                    restricted_common,
+                },
+            ) => format!(
+                "{} ei voi olla {}, koska {} tai {} sisältää luvun {}: ne ovat lähes lukittuja \
+                 joukkoja, joista vain toinen voi sisältää yhteisen luvun {} (lähes lukitut \
+                 joukot).",
+                cell,
+                value,
+                self.all(&sets[0]),
+                self.all(&sets[1]),
+                value,
+                restricted_common
+            ),
+            (Locale::English, Message::ForcingChain { cell, value, chain }) => match chain {
+                [] => format!(
+                    "{} cannot be {}, as {}={} leads straight to a contradiction (forcing chain).",
+                    cell, value, cell, value
+                ),
+                _ => format!(
+                    "{} cannot be {}, as {}={} forces {}, a contradiction (forcing chain).",
+                    cell,
+                    value,
+                    cell,
+                    value,
+                    self.all(chain)
+                ),
+            },
+            (Locale::German, Message::ForcingChain { cell, value, chain }) => match chain {
+                [] => format!(
+                    "{} kann nicht {} sein, da {}={} sofort zu einem Widerspruch führt \
+                     (Forcing Chain).",
+                    cell, value, cell, value
+                ),
+                _ => format!(
+                    "{} kann nicht {} sein, da {}={} zu {} und damit zu einem Widerspruch führt \
+                     (Forcing Chain).",
+                    cell,
+                    value,
+                    cell,
+                    value,
+                    self.all(chain)
+                ),
+            },
+            (Locale::Finnish, Message::ForcingChain { cell, value, chain }) => match chain {
+                [] => format!(
+                    "{} ei voi olla {}, koska {}={} johtaa suoraan ristiriitaan (pakotettu ketju).",
+                    cell, value, cell, value
+                ),
+                _ => format!(
+                    "{} ei voi olla {}, koska {}={} pakottaa {} ja johtaa ristiriitaan \
+                     (pakotettu ketju).",
+                    cell,
+                    value,
+                    cell,
+                    value,
+                    self.all(chain)
+                ),
+            },
+            (Locale::English, Message::SeenByConstraint { cell, value, cause }) => format!(
+                "{} cannot be {}, as {} is {} and a constraint keeps them apart.",
+                cell, value, cause, value
+            ),
+            (Locale::German, Message::SeenByConstraint { cell, value, cause }) => format!(
+                "{} kann nicht {} sein, da {} schon {} ist und eine Regel beide trennt.",
+                cell, value, cause, value
+            ),
+            (Locale::Finnish, Message::SeenByConstraint { cell, value, cause }) => format!(
+                "{} ei voi olla {}, koska {} on jo {} eikä sääntö salli samaa numeroa.",
+                cell, value, cause, value
+            ),
+            (
+                Locale::English,
+                Message::Guess {
+                    cell,
+                    value,
+                    candidates,
+                },
+            ) => format!(
+                "{} could be {}; guess {}.",
+                cell,
+                self.list(candidates),
+                value
+            ),
+            (
+                Locale::German,
+                Message::Guess {
+                    cell,
+                    value,
+                    candidates,
+                },
+            ) => format!(
+                "Für {} kommen {} in Frage; rate {}.",
+                cell,
+                self.list(candidates),
+                value
+            ),
+            (
+                Locale::Finnish,
+                Message::Guess {
+                    cell,
+                    value,
+                    candidates,
+                },
+            ) => format!(
+                "{} voi olla {}; arvataan {}.",
+                cell,
+                self.list(candidates),
+                value
+            ),
+            (Locale::English, Message::Backtrack { cell, value }) => format!(
+                "Clear the {} from {}, the guesses led to a contradiction.",
+                value, cell
+            ),
+            (Locale::German, Message::Backtrack { cell, value }) => format!(
+                "Entferne die {} aus {}, die Vermutungen führten zu einem Widerspruch.",
+                value, cell
+            ),
+            (Locale::Finnish, Message::Backtrack { cell, value }) => format!(
+                "Poista {} kohdasta {}, arvaukset johtivat ristiriitaan.",
+                value, cell
+            ),
+        }
+    }
+}
+
+/// Joins items into a list with the given word before the last one, e.g. `1, 7 or 9`.
+///
+/// # Arguments
+///
+/// * `items` - The items to list.
+/// * `last` - The word before the last item.
+fn join<T: Display>(items: &[T], last: &str) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.to_string(),
+        [rest @ .., final_item] => {
+            let rest: Vec<String> = rest.iter().map(T::to_string).collect();
+            format!("{} {} {}", rest.join(", "), last, final_item)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Locale, Message};
+    use crate::sudoku::{Coordinates, House};
+
+    #[test]
+    fn test_format() {
+        let cell = Coordinates::from((3, 5));
+        let cause = Coordinates::from((3, 1));
+        let message = Message::SeenInHouse {
+            cell: &cell,
+            value: 7,
+            cause: &cause,
+            house: House::Row(3),
+        };
+        assert_eq!(
+            "r4c6 cannot be 7, as r4c2 in row 4 is 7.",
+            Locale::English.format(&message)
+        );
+        assert_eq!(
+            "r4c6 kann nicht 7 sein, da r4c2 in Zeile 4 schon 7 ist.",
+            Locale::German.format(&message)
+        );
+        assert_eq!(
+            "r4c6 ei voi olla 7, koska r4c2 on jo 7 (rivi 4).",
+            Locale::Finnish.format(&message)
+        );
+        let guess = Message::Guess {
+            cell: &cell,
+            value: 7,
+            candidates: &[1, 7, 9],
+        };
+        assert_eq!(
+            "r4c6 voi olla 1, 7 tai 9; arvataan 7.",
+            Locale::Finnish.format(&guess)
+        );
+        let hidden_single = Message::HiddenSingle {
+            cell: &cell,
+            value: 7,
+            house: House::Block(4),
+        };
+        assert_eq!(
+            "r4c6 is the only square in block 5 that can be 7 (hidden single).",
+            Locale::English.format(&hidden_single)
+        );
+        assert_eq!(
+            "r4c6 on ainoa kohta, johon 7 sopii (laatikko 5, ainoa paikka).",
+            Locale::Finnish.format(&hidden_single)
+        );
+        let locked_candidates = Message::LockedCandidates {
+            cell: &cell,
+            value: 7,
+            base: House::Block(3),
+            cover: House::Row(3),
+        };
+        assert_eq!(
+            "r4c6 cannot be 7, as the 7 of block 4 must be in row 4 (locked candidates).",
+            Locale::English.format(&locked_candidates)
+        );
+        let corners = [(0, 0), (0, 3), (1, 0), (1, 3)].map(Coordinates::from);
+        let unique_rectangle = Message::UniqueRectangle {
+            cell: &cell,
+            value: 5,
+            corners: &corners,
+            values: [2, 7],
+        };
+        assert_eq!(
+            "r4c6 cannot be 5, as otherwise r1c1, r1c4, r2c1 and r2c4 could only be 2 and 7, \
+             which could be swapped for a second solution (unique rectangle).",
+            Locale::English.format(&unique_rectangle)
+        );
+        let chain = ["r4c1=3".parse().unwrap(), "r5c1=8".parse().unwrap()];
+        let forcing_chain = Message::ForcingChain {
+            cell: &cell,
+            value: 7,
+            chain: &chain,
+        };
+        assert_eq!(
+            "r4c6 cannot be 7, as r4c6=7 forces r4c1=3 and r5c1=8, a contradiction (forcing \
+             chain).",
+            Locale::English.format(&forcing_chain)
+        );
+        assert_eq!(
+            "r4c6 cannot be 7, as r4c6=7 leads straight to a contradiction (forcing chain).",
+            Locale::English.format(&Message::ForcingChain {
+                cell: &cell,
+                value: 7,
+                chain: &[],
+            })
+        );
+
+        assert_eq!("Arvaus", Locale::Finnish.technique("Guess"));
+        assert_eq!("Naked Single", Locale::German.technique("Naked Single"));
+        assert_eq!("X-Wing", Locale::Finnish.technique("X-Wing"));
+    }
+
+    #[test]
+    fn test_from_code() {
+        assert_eq!(Some(Locale::English), Locale::from_code("en-GB"));
+        assert_eq!(Some(Locale::Finnish), Locale::from_code("FI"));
+        assert_eq!(Some(Locale::German), Locale::from_code("de_AT"));
+        assert_eq!(None, Locale::from_code("sv"));
+        for locale in Locale::ALL {
+            assert_eq!(Some(locale), Locale::from_code(locale.code()));
+        }
+    }
+}
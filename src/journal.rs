@@ -0,0 +1,375 @@
+//! A journal of every change made to a grid: who changed which square, from what to what and
+//! when. The journal can undo its changes, rebuild the grid at any point, be saved as text for
+//! auditing, and replay entries from elsewhere to keep two copies of a grid in step.
+
+use crate::{
+    format::{parse_line, write_line, FormatError},
+    notation::parse_cell,
+    replay::{Actor, Replay},
+    solver::SolverError,
+    sudoku::{Coordinates, Sudoku, SudokuError},
+};
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+/// One change of a square.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// Time since the journal was started, to the millisecond.
+    pub elapsed: Duration,
+    /// Who made the change.
+    pub actor: Actor,
+    /// The square.
+    pub cell: Coordinates,
+    /// The value before the change, 0 if the square was empty.
+    pub previous: u8,
+    /// The value after the change, 0 if the square was cleared.
+    pub value: u8,
+}
+
+/// A grid and the changes made to it since it was the puzzle, in order.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    puzzle: Sudoku,
+    sudoku: Sudoku,
+    entries: Vec<JournalEntry>,
+    started: Instant,
+}
+
+/// Error type for [`Journal::set`] and [`Journal::apply`].
+#[derive(Debug, PartialEq)]
+pub enum JournalError {
+    /// The square or value is out of bounds.
+    Sudoku(SudokuError),
+    /// The entry was made on a grid where the square held a different value, e.g. because
+    /// another change to it was made first.
+    Mismatch {
+        cell: Coordinates,
+        expected: u8,
+        found: u8,
+    },
+}
+
+impl From<SudokuError> for JournalError {
+    fn from(err: SudokuError) -> Self {
+        JournalError::Sudoku(err)
+    }
+}
+
+impl Display for JournalEntry {
+    /// Writes the entry as `<milliseconds> r<row>c<column> <previous> <value> <actor>`, e.g.
+    /// `1500 r4c6 0 7 player`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.elapsed.as_millis(),
+            self.cell,
+            self.previous,
+            self.value,
+            self.actor.name()
+        )
+    }
+}
+
+impl Journal {
+    /// Starts a journal of changes to the puzzle, starting its clock now.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The grid before any change.
+    pub fn new(puzzle: Sudoku) -> Self {
+        Journal {
+            sudoku: puzzle.clone(),
+            puzzle,
+            entries: Vec::new(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Builds the journal of a recorded solve, e.g. from [`Replay::record_solve`] for auditing
+    /// the solver.
+    ///
+    /// # Arguments
+    ///
+    /// * `replay` - The recorded moves.
+    pub fn from_replay(replay: &Replay) -> Self {
+        let mut retval = Journal::new(replay.puzzle().clone());
+        for step in replay.moves() {
+            let previous = retval.sudoku.get_unchecked(step.cell.row, step.cell.column);
+            retval.push(JournalEntry {
+                elapsed: step.elapsed,
+                actor: step.actor,
+                cell: step.cell.clone(),
+                previous,
+                value: step.value,
+            });
+        }
+        retval
+    }
+
+    /// Solves the puzzle and journals every change the solver makes, see
+    /// [`Replay::record_solve`].
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The puzzle to solve.
+    pub fn record_solve(puzzle: Sudoku) -> Result<Self, SolverError> {
+        Ok(Journal::from_replay(&Replay::record_solve(puzzle)?))
+    }
+
+    /// Get the grid before any change.
+    pub fn puzzle(&self) -> &Sudoku {
+        &self.puzzle
+    }
+
+    /// Get the grid after every change.
+    pub fn sudoku(&self) -> &Sudoku {
+        &self.sudoku
+    }
+
+    /// Get the changes in the order they were made.
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// Sets a square and journals the change with the time since the journal was started.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - The square.
+    /// * `value` - The value to set ([0,9]), 0 to clear the square.
+    /// * `actor` - Who makes the change.
+    pub fn set(&mut self, cell: &Coordinates, value: u8, actor: Actor) -> Result<(), JournalError> {
+        let previous = self.value_at(cell)?;
+        self.sudoku.set(cell.row, cell.column, value)?;
+        self.entries.push(JournalEntry {
+            elapsed: Duration::from_millis(self.started.elapsed().as_millis() as u64),
+            actor,
+            cell: cell.clone(),
+            previous,
+            value,
+        });
+        Ok(())
+    }
+
+    /// Makes a change journaled elsewhere, e.g. by another player on their copy of the grid.
+    /// Fails without changing anything if the square no longer holds the value the change was
+    /// made on.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The change.
+    pub fn apply(&mut self, entry: JournalEntry) -> Result<(), JournalError> {
+        let found = self.value_at(&entry.cell)?;
+        if found != entry.previous {
+            return Err(JournalError::Mismatch {
+                cell: entry.cell,
+                expected: entry.previous,
+                found,
+            });
+        }
+        self.sudoku
+            .set(entry.cell.row, entry.cell.column, entry.value)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Takes back the last change, returning it, or `None` if there are no changes.
+    pub fn undo(&mut self) -> Option<JournalEntry> {
+        let entry = self.entries.pop()?;
+        self.sudoku
+            .set_unchecked(entry.cell.row, entry.cell.column, entry.previous);
+        Some(entry)
+    }
+
+    /// Returns the grid after the given number of changes, the puzzle itself for 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - Number of changes to make; more than there are makes all of them.
+    pub fn board_at(&self, entries: usize) -> Sudoku {
+        let mut retval = self.puzzle.clone();
+        for entry in self.entries.iter().take(entries) {
+            retval.set_unchecked(entry.cell.row, entry.cell.column, entry.value);
+        }
+        retval
+    }
+
+    /// Writes the journal as text: a `puzzle <81 characters>` line followed by one line per
+    /// change, see [`JournalEntry`]'s `Display`.
+    pub fn to_text(&self) -> String {
+        let mut retval = format!("puzzle {}\n", write_line(&self.puzzle));
+        for entry in &self.entries {
+            retval.push_str(&format!("{}\n", entry));
+        }
+        retval
+    }
+
+    /// Reads a journal written by [`Journal::to_text`], checking that every change was made on
+    /// the grid the changes before it left. The clock of the journal continues from the last
+    /// change.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The journal.
+    pub fn from_text(text: &str) -> Result<Self, FormatError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        let puzzle = match lines
+            .next()
+            .map(|(i, line)| (i, line.strip_prefix("puzzle ")))
+        {
+            Some((line_number, Some(puzzle))) => parse_line(puzzle.trim(), line_number)?,
+            _ => return Err(FormatError::MissingColumn("puzzle".to_string())),
+        };
+        let mut retval = Journal::new(puzzle);
+        for (line_number, line) in lines {
+            let entry = parse_entry(line, line_number)?;
+            if retval
+                .sudoku
+                .get_unchecked(entry.cell.row, entry.cell.column)
+                != entry.previous
+            {
+                return Err(FormatError::BadNumber(
+                    line_number,
+                    entry.previous.to_string(),
+                ));
+            }
+            retval.push(entry);
+        }
+        if let Some(last) = retval.entries.last() {
+            retval.started = Instant::now()
+                .checked_sub(last.elapsed)
+                .unwrap_or(retval.started);
+        }
+        Ok(retval)
+    }
+
+    /// Returns the value of a square of the grid, checking the coordinates.
+    fn value_at(&self, cell: &Coordinates) -> Result<u8, SudokuError> {
+        self.sudoku.is_set(cell.row, cell.column)?;
+        Ok(self.sudoku.get_unchecked(cell.row, cell.column))
+    }
+
+    /// Makes a change known to be valid and journals it.
+    fn push(&mut self, entry: JournalEntry) {
+        self.sudoku
+            .set_unchecked(entry.cell.row, entry.cell.column, entry.value);
+        self.entries.push(entry);
+    }
+}
+
+/// Parses a change written by [`JournalEntry`]'s `Display`.
+///
+/// # Arguments
+///
+/// * `line` - The change.
+/// * `line_number` - Line number of the change, for errors.
+fn parse_entry(line: &str, line_number: usize) -> Result<JournalEntry, FormatError> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() != 5 {
+        return Err(FormatError::BadLineLength(line_number));
+    }
+    let bad_number = |field: &str| FormatError::BadNumber(line_number, field.to_string());
+    let millis: u64 = fields[0].parse().map_err(|_| bad_number(fields[0]))?;
+    let cell = parse_cell(fields[1]).ok_or_else(|| bad_number(fields[1]))?;
+    let value = |field: &str| match field.parse::<u8>() {
+        Ok(value) if value <= 9 => Ok(value),
+        Ok(value) => Err(FormatError::BadValue(value)),
+        Err(_) => Err(bad_number(field)),
+    };
+    Ok(JournalEntry {
+        elapsed: Duration::from_millis(millis),
+        actor: Actor::from_name(fields[4])
+            .ok_or_else(|| FormatError::UnknownSection(fields[4].to_string()))?,
+        cell,
+        previous: value(fields[2])?,
+        value: value(fields[3])?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Journal, JournalEntry, JournalError};
+    use crate::{
+        corpus,
+        format::{parse_line, FormatError},
+        replay::Actor,
+        sudoku::{Coordinates, SudokuError},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_journal() {
+        let puzzle = parse_line(corpus::EASY.puzzle, 1).unwrap();
+        let r1c1 = Coordinates::from((0, 0));
+        let mut journal = Journal::new(puzzle.clone());
+        journal.set(&r1c1, 5, Actor::Player).unwrap();
+        journal.set(&r1c1, 4, Actor::Player).unwrap();
+        assert_eq!(5, journal.entries()[1].previous);
+        assert_eq!(
+            Err(JournalError::Sudoku(SudokuError::BadValue(10))),
+            journal.set(&r1c1, 10, Actor::Player)
+        );
+        assert_eq!(2, journal.entries().len());
+        assert_eq!(5, journal.board_at(1).squares[0]);
+
+        let mut copy = Journal::from_text(&journal.to_text()).unwrap();
+        assert_eq!(journal.entries(), copy.entries());
+        assert_eq!(journal.sudoku(), copy.sudoku());
+
+        let entry = JournalEntry {
+            elapsed: Duration::from_secs(2),
+            actor: Actor::Solver,
+            cell: r1c1.clone(),
+            previous: 4,
+            value: 0,
+        };
+        journal.apply(entry.clone()).unwrap();
+        assert_eq!(puzzle, *journal.sudoku());
+        assert_eq!(
+            Err(JournalError::Mismatch {
+                cell: r1c1.clone(),
+                expected: 4,
+                found: 0
+            }),
+            journal.apply(entry.clone())
+        );
+        assert_eq!(Some(entry), journal.undo());
+        assert_eq!(4, journal.sudoku().squares[0]);
+        copy.undo();
+        copy.undo();
+        assert_eq!(None, copy.undo());
+        assert_eq!(puzzle, *copy.sudoku());
+
+        let text = format!("puzzle {}\n100 r1c1 3 4 player\n", corpus::EASY.puzzle);
+        assert_eq!(
+            Err(FormatError::BadNumber(2, "3".to_string())),
+            Journal::from_text(&text).map(|journal| journal.entries().len())
+        );
+    }
+
+    #[test]
+    fn test_record_solve() {
+        let puzzle = parse_line(corpus::MEDIUM.puzzle, 1).unwrap();
+        let mut journal = Journal::record_solve(puzzle.clone()).unwrap();
+        assert_eq!(
+            parse_line(corpus::MEDIUM.solution, 1).unwrap(),
+            *journal.sudoku()
+        );
+        assert!(journal
+            .entries()
+            .iter()
+            .all(|entry| entry.actor == Actor::Solver));
+        let copy = Journal::from_text(&journal.to_text()).unwrap();
+        assert_eq!(journal.entries().len(), copy.entries().len());
+        while journal.undo().is_some() {}
+        assert_eq!(puzzle, *journal.sudoku());
+    }
+}
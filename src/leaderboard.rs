@@ -0,0 +1,243 @@
+//! Scores of finished games, kept in a small CSV file next to where the game is played so that
+//! players can see how they are improving.
+
+use crate::{
+    format::{parse_line, write_line, FormatError},
+    sudoku::Sudoku,
+};
+use chrono::NaiveDate;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+/// File the command line keeps the leaderboard in when no other is given.
+pub const DEFAULT_PATH: &str = "leaderboard.csv";
+
+/// Points taken off the score for every hint.
+pub const HINT_PENALTY: u32 = 300;
+
+/// Points taken off the score for every mistake.
+pub const MISTAKE_PENALTY: u32 = 200;
+
+/// A finished game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    /// The puzzle as it was given.
+    pub puzzle: Sudoku,
+    /// Day the game was finished.
+    pub date: Option<NaiveDate>,
+    /// Time it took to solve, in whole seconds.
+    pub seconds: u64,
+    /// Number of hints used.
+    pub hints: u32,
+    /// Number of mistakes made.
+    pub mistakes: u32,
+}
+
+/// Finished games from the highest score to the lowest.
+#[derive(Debug, Default, Clone)]
+pub struct Leaderboard {
+    records: Vec<GameRecord>,
+}
+
+impl GameRecord {
+    /// Returns the score of the game: 100 points for every square the puzzle left empty, less
+    /// a point per second, [`HINT_PENALTY`] per hint and [`MISTAKE_PENALTY`] per mistake, but
+    /// never below 0.
+    pub fn score(&self) -> u32 {
+        let empty = 9 * 9 - self.puzzle.set_count as u64;
+        let penalty = self
+            .seconds
+            .saturating_add(HINT_PENALTY as u64 * self.hints as u64)
+            .saturating_add(MISTAKE_PENALTY as u64 * self.mistakes as u64);
+        (empty * 100).saturating_sub(penalty) as u32
+    }
+}
+
+impl Leaderboard {
+    /// Creates an empty leaderboard.
+    pub fn new() -> Self {
+        Leaderboard::default()
+    }
+
+    /// Reads a leaderboard written by [`Leaderboard::write`].
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The source of the CSV text.
+    pub fn read<R: BufRead>(reader: R) -> Result<Self, FormatError> {
+        let mut retval = Leaderboard::new();
+        for (i, line) in reader.lines().enumerate().skip(1) {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let line_number = i + 1;
+            let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+            if fields.len() != 5 {
+                return Err(FormatError::BadLineLength(line_number));
+            }
+            let number = |field: &str| {
+                field
+                    .parse()
+                    .map_err(|_| FormatError::BadNumber(line_number, field.to_string()))
+            };
+            let count = |field: &str| {
+                u32::try_from(number(field)?)
+                    .map_err(|_| FormatError::BadNumber(line_number, field.to_string()))
+            };
+            retval.add(GameRecord {
+                date: fields[0].parse().ok(),
+                puzzle: parse_line(fields[1], line_number)?,
+                seconds: number(fields[2])?,
+                hints: count(fields[3])?,
+                mistakes: count(fields[4])?,
+            });
+        }
+        Ok(retval)
+    }
+
+    /// Reads the leaderboard from a file. A file that does not exist yet is an empty
+    /// leaderboard.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FormatError> {
+        match File::open(path) {
+            Ok(file) => Leaderboard::read(BufReader::new(file)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Leaderboard::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes the leaderboard as CSV with the columns date, puzzle, seconds, hints and
+    /// mistakes.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the CSV text.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), FormatError> {
+        writeln!(writer, "date,puzzle,seconds,hints,mistakes")?;
+        for record in &self.records {
+            let date = record.date.map(|date| date.to_string()).unwrap_or_default();
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                date,
+                write_line(&record.puzzle),
+                record.seconds,
+                record.hints,
+                record.mistakes
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the leaderboard to a file, replacing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), FormatError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Adds a game and returns its 1-based rank. Games with equal scores rank in the order
+    /// they were added.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The finished game.
+    pub fn add(&mut self, record: GameRecord) -> usize {
+        let score = record.score();
+        let rank = self.records.partition_point(|other| other.score() >= score);
+        self.records.insert(rank, record);
+        rank + 1
+    }
+
+    /// Returns the number of games.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns whether no game has been added.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns at most `count` games with the highest scores, best first.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of games to return.
+    pub fn top(&self, count: usize) -> &[GameRecord] {
+        &self.records[..count.min(self.records.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GameRecord, Leaderboard};
+    use crate::{
+        corpus,
+        format::{parse_line, FormatError},
+    };
+    use chrono::NaiveDate;
+
+    fn record(seconds: u64, hints: u32, mistakes: u32) -> GameRecord {
+        GameRecord {
+            puzzle: parse_line(corpus::EASY.puzzle, 1).unwrap(),
+            date: NaiveDate::from_ymd_opt(2024, 5, 1),
+            seconds,
+            hints,
+            mistakes,
+        }
+    }
+
+    #[test]
+    fn test_score_and_rank() {
+        // EASY leaves 49 squares empty.
+        assert_eq!(4900 - 600 - 300 - 400, record(600, 1, 2).score());
+        assert_eq!(0, record(10_000, 0, 0).score());
+        assert_eq!(0, record(u64::MAX, u32::MAX, u32::MAX).score());
+
+        let mut leaderboard = Leaderboard::new();
+        assert_eq!(1, leaderboard.add(record(600, 0, 0)));
+        assert_eq!(1, leaderboard.add(record(300, 0, 0)));
+        assert_eq!(3, leaderboard.add(record(300, 1, 1)));
+        assert_eq!(4, leaderboard.add(record(600, 0, 1)));
+        let seconds: Vec<u64> = leaderboard.top(3).iter().map(|r| r.seconds).collect();
+        assert_eq!(vec![300, 600, 300], seconds);
+        assert_eq!(4, leaderboard.top(10).len());
+    }
+
+    #[test]
+    fn test_read_write() {
+        let mut leaderboard = Leaderboard::new();
+        leaderboard.add(record(600, 1, 2));
+        leaderboard.add(GameRecord {
+            date: None,
+            ..record(60, 0, 0)
+        });
+        let mut written = Vec::new();
+        leaderboard.write(&mut written).unwrap();
+        let read = Leaderboard::read(written.as_slice()).unwrap();
+        assert_eq!(leaderboard.top(2), read.top(2));
+
+        assert!(Leaderboard::read("date,puzzle\n,x".as_bytes()).is_err());
+        let text = format!(
+            "date,puzzle,seconds,hints,mistakes\n,{},60,4294967296,0\n",
+            corpus::EASY.puzzle
+        );
+        assert_eq!(
+            Err(FormatError::BadNumber(2, "4294967296".to_string())),
+            Leaderboard::read(text.as_bytes()).map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(feature = "gif")]
+pub mod animation;
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+pub mod arbitrary;
+pub mod batch;
+pub mod book;
+pub mod calibration;
+pub mod collection;
+pub mod constraint;
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod corpus;
+#[cfg(feature = "db")]
+pub mod db;
+mod font;
+pub mod format;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod hardest;
+pub mod hint;
+pub mod i18n;
+pub mod journal;
+pub mod leaderboard;
+pub mod metadata;
+pub mod notation;
+pub mod observer;
+#[cfg(feature = "ocr")]
+pub mod ocr;
+pub mod perturb;
+pub mod play;
+pub mod raster;
+pub mod replay;
+pub mod sat;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod solver;
+pub mod sudoku;
+pub mod technique;
+pub mod transform;
@@ -1,27 +1,1293 @@
-use solver::Solver;
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use indicatif::{ProgressBar, ProgressStyle};
+use rand::Rng;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, Instant},
+};
+#[cfg(feature = "clipboard")]
+use sudoku_solver::format::ParseError;
+#[cfg(feature = "ocr")]
+use sudoku_solver::ocr;
+#[cfg(feature = "server")]
+use sudoku_solver::server;
+#[cfg(feature = "gif")]
+use sudoku_solver::{
+    animation::{self, AnimationOptions},
+    raster::RasterOptions,
+};
+use sudoku_solver::{
+    batch::{self, RatingHistogram},
+    book, coords,
+    format::{
+        self,
+        qqwing::{self, QqwingReport},
+    },
+    hint::{Hint, HintLevel},
+    leaderboard::{self, Leaderboard},
+    observer::Tracer,
+    play::{self, Action, Editor, Entry, Game, Playback, Watcher},
+    replay::Replay,
+    solver::{GenerationOptions, RatingScale, Solver, SolverBackend, SolverError},
+    sudoku::{Coordinates, Sudoku},
+    technique::Technique,
+    transform,
+};
+#[cfg(feature = "db")]
+use sudoku_solver::{
+    db::{PuzzleDb, PuzzleQuery, PuzzleRecord},
+    format::csv::{CsvReader, CsvRecord, CsvWriter},
+};
 
-mod solver;
-mod sudoku;
+/// Sudoku generator and solver.
+///
+/// Every command exits with status 2 if its input cannot be read or parsed, its output
+/// cannot be written or its puzzles cannot be generated.
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
 
-pub fn main() {
+#[derive(Subcommand)]
+enum Command {
+    /// Generate new puzzles and print them with their solutions.
+    Generate {
+        /// Number of puzzles to generate.
+        #[arg(short, long, default_value_t = 1)]
+        count: usize,
+        /// Probability (0-100) for each square to be blank.
+        #[arg(short, long, default_value_t = 70)]
+        difficulty: u8,
+        /// Only generate puzzles with exactly one solution.
+        #[arg(short, long)]
+        unique: bool,
+        /// Rate the puzzles and print a histogram of their scores on standard error.
+        #[arg(long)]
+        histogram: bool,
+        /// Make the solutions by shuffling a fixed solved grid instead of solving an empty one.
+        /// Much faster, but every solution is equivalent to the others.
+        #[arg(long)]
+        fast: bool,
+        /// How to print the results.
+        #[arg(short, long, value_enum, default_value_t = Output::Pretty)]
+        output: Output,
+    },
+    /// Solve puzzles read from standard input: 81 character lines, or grids in the `.sdk`, `.ss`
+    /// or pretty formats separated by blank lines.
+    Solve {
+        /// How to print the results.
+        #[arg(short, long, value_enum, default_value_t = Output::Pretty)]
+        output: Output,
+        /// Read 81 character lines one at a time and print only one solution line per puzzle,
+        /// for inputs too large to hold in memory.
+        #[arg(long, conflicts_with = "output")]
+        stream: bool,
+        /// Redraw the grid after every step of the solver, to watch it work.
+        #[arg(long, conflicts_with = "stream")]
+        watch: bool,
+        /// Time to wait after each step when watching, in milliseconds.
+        #[arg(long, default_value_t = 100, requires = "watch")]
+        delay: u64,
+        /// Print the time taken, the propagation passes and the guesses of every puzzle on
+        /// standard error.
+        #[arg(long, conflicts_with = "stream")]
+        time: bool,
+        /// Trace every step of the solver on standard error; twice to also trace eliminations
+        /// and print the grid after every step.
+        #[arg(short, long, action = ArgAction::Count, conflicts_with = "stream")]
+        verbose: u8,
+        /// Solve the puzzle on the clipboard instead of reading standard input.
+        #[cfg(feature = "clipboard")]
+        #[arg(long)]
+        from_clipboard: bool,
+        /// Copy the last solution to the clipboard as an 81 character line.
+        #[cfg(feature = "clipboard")]
+        #[arg(long)]
+        to_clipboard: bool,
+    },
+    /// Overwrite every puzzle in a file of 81 character lines with its solution, solving the
+    /// lines on several threads. Lines that cannot be solved are left as they are.
+    #[cfg(feature = "mmap")]
+    SolveFile {
+        /// The file of puzzles, one per line.
+        file: PathBuf,
+        /// Number of threads to use; 0 uses one per core.
+        #[arg(short, long, default_value_t = 0)]
+        jobs: usize,
+    },
+    /// Rate the puzzles of a file of 81 character lines in parallel and print them as CSV with
+    /// their difficulty scores.
+    Rate {
+        /// The file of puzzles, one per line.
+        #[arg(short, long)]
+        input: PathBuf,
+        /// Number of threads to use; 0 uses one per core.
+        #[arg(short, long, default_value_t = 0)]
+        jobs: usize,
+        /// Scale of the ratings.
+        #[arg(short, long, value_enum, default_value_t = Scale::Score)]
+        scale: Scale,
+    },
+    /// Print summary statistics of a file of 81 character lines: clue counts, difficulties,
+    /// hardest techniques and duplicates.
+    Analyze {
+        /// The file of puzzles, one per line.
+        #[arg(short, long)]
+        input: PathBuf,
+        /// Number of threads to use; 0 uses one per core.
+        #[arg(short, long, default_value_t = 0)]
+        jobs: usize,
+    },
+    /// Solve the puzzles of a CSV corpus with puzzle and solution columns, and report the
+    /// puzzles solved differently or not at all and those much slower than the rest. Exits with
+    /// status 1 if any puzzle was solved differently or not at all.
+    Verify {
+        /// The corpus file.
+        input: PathBuf,
+    },
+    /// Solve the puzzles of a file of 81 character lines with two backends, check that their
+    /// solutions agree and compare their times. Exits with status 1 on any disagreement.
+    Compare {
+        /// The file of puzzles, one per line.
+        #[arg(short, long)]
+        input: PathBuf,
+        /// The backend to compare against.
+        #[arg(value_enum, default_value_t = Backend::Backtracking)]
+        first: Backend,
+        /// The backend to compare.
+        #[arg(value_enum, default_value_t = Backend::Dlx)]
+        second: Backend,
+    },
+    /// Check a completed or partial grid against the rules, and optionally against the solution
+    /// of the puzzle it was solved from. Exits with status 1 if anything is wrong.
+    Check {
+        /// The grid to check, as 81 characters with '.' or '0' for blanks or in any other
+        /// format the solve command reads.
+        grid: String,
+        /// The original puzzle; the grid must agree with its solution and keep its givens.
+        #[arg(short, long)]
+        puzzle: Option<String>,
+    },
+    /// Play a puzzle in the terminal. Squares breaking a rule are highlighted, and the
+    /// candidates of the selected square can be shown with the c key.
+    Play {
+        /// The puzzle to play, in any format the solve command reads. A new puzzle with a
+        /// unique solution is generated if none is given.
+        puzzle: Option<String>,
+        /// Probability (0-100) for each square of a generated puzzle to be blank.
+        #[arg(short, long, default_value_t = 50)]
+        difficulty: u8,
+        /// File of the leaderboard the solved game is added to.
+        #[arg(long, default_value = leaderboard::DEFAULT_PATH)]
+        leaderboard: PathBuf,
+        /// File to save the game to when quitting. If it exists, the game saved in it is
+        /// resumed instead of starting a new one.
+        #[arg(long)]
+        session: Option<PathBuf>,
+        /// End the game after this many wrong values, which are checked against the solution
+        /// and not entered.
+        #[arg(long)]
+        max_mistakes: Option<u32>,
+        /// File to write the replay of the game to when it ends, for the replay command.
+        #[arg(long)]
+        replay: Option<PathBuf>,
+    },
+    /// Step through a replay written by the play command, or record the solver solving a
+    /// puzzle and step through that.
+    Replay {
+        /// The replay file.
+        file: PathBuf,
+        /// Solve this puzzle, in any format the solve command reads, and write the replay to
+        /// the file before showing it.
+        #[arg(long)]
+        solve: Option<String>,
+    },
+    /// Print the best games of the leaderboard with their scores.
+    Leaderboard {
+        /// File of the leaderboard.
+        #[arg(default_value = leaderboard::DEFAULT_PATH)]
+        file: PathBuf,
+        /// Number of games to print.
+        #[arg(short, long, default_value_t = 10)]
+        count: usize,
+    },
+    /// Enter a puzzle square by square, e.g. to transcribe one from print, while it is checked
+    /// against the rules and for a unique solution. Saving prints it as an 81 character line.
+    Edit {
+        /// Grid to start from, in any format the solve command reads.
+        puzzle: Option<String>,
+        /// Append the saved puzzle to this file instead of printing it.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Type a puzzle as 81 characters in reading order, with a preview of the grid, and then
+    /// solve or play it.
+    Type,
+    /// Print a hint for the next step of a puzzle, giving away as much as the level asks for.
+    Hint {
+        /// The grid, in any format the solve command reads.
+        grid: String,
+        /// How much the hint gives away.
+        #[arg(short, long, value_enum, default_value_t = HintTier::Region)]
+        level: HintTier,
+    },
+    /// Generate a printable PDF book with puzzles up front and solutions at the back.
+    Book {
+        /// Number of puzzles in the book.
+        #[arg(short, long, default_value_t = 12)]
+        count: usize,
+        /// Percentages of the squares to reset (0-100) to cycle through; may be given multiple
+        /// times.
+        #[arg(short, long, default_values_t = [40, 55, 70])]
+        difficulty: Vec<u8>,
+        /// Title printed on the first page.
+        #[arg(short, long, default_value = "Sudoku")]
+        title: String,
+        /// Path of the PDF file to write.
+        output: PathBuf,
+    },
+    /// Manage an SQLite puzzle database.
+    #[cfg(feature = "db")]
+    Db {
+        /// Path of the database file.
+        database: PathBuf,
+        #[command(subcommand)]
+        command: DbCommand,
+    },
+    /// Solve a puzzle and write an animated GIF of the values appearing one by one.
+    #[cfg(feature = "gif")]
+    Gif {
+        /// The puzzle, in any format the solve command reads.
+        puzzle: String,
+        /// Path of the GIF to write.
+        output: PathBuf,
+        /// Time each move is shown, in hundredths of a second.
+        #[arg(long, default_value_t = 10)]
+        delay: u16,
+        /// Width and height of a square in pixels.
+        #[arg(long, default_value_t = 40)]
+        cell: usize,
+    },
+    /// Recognize a puzzle in a photo or screenshot and solve it.
+    #[cfg(feature = "ocr")]
+    Scan {
+        /// The PNG or JPEG image containing the grid.
+        image: PathBuf,
+        /// How to print the results.
+        #[arg(short, long, value_enum, default_value_t = Output::Pretty)]
+        output: Output,
+    },
+    /// Run a WebSocket server that streams the steps of solving the puzzles clients send.
+    #[cfg(feature = "server")]
+    Serve {
+        /// Address to listen on.
+        #[arg(default_value = "127.0.0.1:8080")]
+        address: String,
+    },
+}
+
+#[cfg(feature = "db")]
+#[derive(Subcommand)]
+enum DbCommand {
+    /// Import puzzles from a CSV file, solving the ones without a solution.
+    Import {
+        /// The CSV file to import.
+        file: PathBuf,
+        /// Source recorded in the metadata of every imported puzzle.
+        #[arg(long)]
+        source: Option<String>,
+    },
+    /// Print the puzzles matching the filters as CSV.
+    Query {
+        /// Lowest accepted rating.
+        #[arg(long)]
+        min_rating: Option<f32>,
+        /// Highest accepted rating.
+        #[arg(long)]
+        max_rating: Option<f32>,
+        /// Lowest accepted number of givens.
+        #[arg(long)]
+        min_clues: Option<u8>,
+        /// Highest accepted number of givens.
+        #[arg(long)]
+        max_clues: Option<u8>,
+        /// Required hardest technique.
+        #[arg(long)]
+        technique: Option<String>,
+        /// Maximum number of results.
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+}
+
+/// Output formats of the generate and solve commands.
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Output {
+    /// Framed grids of the puzzle and the solution.
+    Pretty,
+    /// The puzzle on one line followed by statistics, like `qqwing --one-line --stats`.
+    Qqwing,
+    /// One CSV row per puzzle, like `qqwing --csv`.
+    QqwingCsv,
+}
+
+/// How the solve command solves and prints puzzles.
+#[derive(Clone, Copy)]
+struct SolveOptions {
+    /// Format of the results.
+    output: Output,
+    /// Draw every step of the solver, waiting this long after each.
+    watch: Option<Duration>,
+    /// Report the time and work of every solve.
+    time: bool,
+    /// 1 to trace the steps of every solve, 2 to trace them in detail.
+    verbosity: u8,
+}
+
+/// Rating scales of the rate command, see [`RatingScale`].
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Scale {
+    /// The score of this crate.
+    Score,
+    /// An approximation of the Sudoku Explainer scale, 1.0 to 11.9.
+    Se,
+}
+
+/// Backends of the compare command, see [`SolverBackend`].
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Backend {
+    /// Singles, random guesses and backtracking.
+    Backtracking,
+    /// Exact cover search with dancing links.
+    Dlx,
+    /// Logical strategies only.
+    Logic,
+    /// Logical strategies, then exact cover search.
+    Hybrid,
+    /// The built-in SAT solver.
+    Sat,
+    /// Simulated annealing.
+    Annealing,
+    /// Logical strategies, then a search on several threads.
+    Parallel,
+}
+
+/// Tiers of the hint command, see [`HintLevel`].
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum HintTier {
+    /// The house to look at.
+    Region,
+    /// The technique to use, and where.
+    Technique,
+    /// The square and its value.
+    Value,
+}
+
+impl From<Output> for SolveOptions {
+    fn from(output: Output) -> Self {
+        SolveOptions {
+            output,
+            watch: None,
+            time: false,
+            verbosity: 0,
+        }
+    }
+}
+
+impl From<Scale> for RatingScale {
+    fn from(scale: Scale) -> Self {
+        match scale {
+            Scale::Score => RatingScale::Score,
+            Scale::Se => RatingScale::SudokuExplainer,
+        }
+    }
+}
+
+impl From<Backend> for SolverBackend {
+    fn from(backend: Backend) -> Self {
+        match backend {
+            Backend::Backtracking => SolverBackend::Backtracking,
+            Backend::Dlx => SolverBackend::Dlx,
+            Backend::Logic => SolverBackend::Logic,
+            Backend::Hybrid => SolverBackend::Hybrid,
+            Backend::Sat => SolverBackend::Sat,
+            Backend::Annealing => SolverBackend::Annealing,
+            Backend::Parallel => SolverBackend::Parallel,
+        }
+    }
+}
+
+impl From<HintTier> for HintLevel {
+    fn from(tier: HintTier) -> Self {
+        match tier {
+            HintTier::Region => HintLevel::Region,
+            HintTier::Technique => HintLevel::Technique,
+            HintTier::Value => HintLevel::Value,
+        }
+    }
+}
+
+/// Solves the puzzle and prints it in the requested format. Returns the solution. When
+/// watching, every step of the solver is drawn on the terminal first; when tracing, the steps
+/// are written to standard error, and when timing, the time and work of the solve follow.
+fn print_solved(puzzle: Sudoku, options: &SolveOptions) -> Option<Sudoku> {
+    let mut solver = Solver::new(puzzle.clone());
+    if let Some(delay) = options.watch {
+        solver.add_observer(Box::new(Watcher::new(puzzle.clone(), delay)));
+    }
+    if options.verbosity > 0 {
+        let detailed = options.verbosity > 1;
+        let tracer = Tracer::new(io::stderr(), puzzle.clone(), detailed);
+        solver.add_observer(Box::new(tracer));
+    }
+    let started = Instant::now();
+    let solved = solver.solve();
+    let elapsed = started.elapsed();
+    if let Err(err) = solved {
+        eprintln!("Error solving sudoku: {}", err);
+        return None;
+    }
+    let report = QqwingReport {
+        puzzle: &puzzle,
+        solution: solver.sudoku(),
+        stats: solver.stats(),
+    };
+    match options.output {
+        Output::Pretty => println!("Puzzle:\n{:#}\nSolution:\n{:#}\n", puzzle, solver),
+        Output::Qqwing => println!("{}\n", report),
+        Output::QqwingCsv => println!("{}", report.to_csv()),
+    }
+    if options.time {
+        eprintln!(
+            "Solved in {:.3} ms: {} propagation passes, {} guesses",
+            elapsed.as_secs_f64() * 1000.0,
+            solver.stats().propagations,
+            solver.stats().guesses
+        );
+    }
+    Some(solver.sudoku().clone())
+}
+
+/// Solves the puzzles read from standard input and prints them. Returns the last solution and
+/// whether all of the input could be read and parsed. Puzzles are separated by blank lines,
+/// except that 81 character lines are puzzles of their own; see [`Sudoku::parse_any`] for the
+/// formats recognized.
+fn solve_stdin(options: &SolveOptions) -> (Option<Sudoku>, bool) {
+    let mut solution = None;
+    let mut read = true;
+    let mut block: Vec<(usize, String)> = Vec::new();
+    let mut lines = io::stdin().lock().lines().enumerate();
+    loop {
+        let line = match lines.next() {
+            Some((i, Ok(line))) => Some((i, line)),
+            Some((_, Err(err))) => {
+                eprintln!("Error reading input: {}", err);
+                read = false;
+                None
+            }
+            None => None,
+        };
+        match line {
+            Some((i, line)) if !line.trim().is_empty() => {
+                block.push((i, line));
+                continue;
+            }
+            _ if block.is_empty() => {}
+            _ if block.iter().all(|(_, line)| line.trim().len() == 9 * 9) => {
+                for (i, line) in block.drain(..) {
+                    match format::parse_line(line.trim(), i + 1) {
+                        Ok(puzzle) => solution = print_solved(puzzle, options).or(solution),
+                        Err(err) => {
+                            eprintln!("Error parsing sudoku: {}", err);
+                            read = false;
+                        }
+                    }
+                }
+            }
+            _ => {
+                let text: String = block.drain(..).map(|(_, line)| line + "\n").collect();
+                match Sudoku::parse_any(&text) {
+                    Ok(puzzle) => solution = print_solved(puzzle, options).or(solution),
+                    Err(err) => {
+                        eprintln!("Error parsing sudoku: {}", err);
+                        read = false;
+                    }
+                }
+            }
+        }
+        if line.is_none() {
+            return (solution, read);
+        }
+    }
+}
+
+/// Reads a file of 81 character lines, skipping blank lines and reporting the ones that cannot
+/// be parsed on standard error.
+fn read_puzzles(path: &PathBuf) -> io::Result<Vec<Sudoku>> {
+    let mut puzzles = Vec::new();
+    for (i, line) in io::BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match format::parse_line(line.trim(), i + 1) {
+            Ok(puzzle) => puzzles.push(puzzle),
+            Err(err) => eprintln!("Skipping puzzle: {}", err),
+        }
+    }
+    Ok(puzzles)
+}
+
+/// Solves the lines of standard input with [`batch::solve_stream`] and reports the totals on
+/// standard error.
+fn stream_stdin() {
+    let stdout = io::stdout();
+    match batch::solve_stream(io::stdin().lock(), BufWriter::new(stdout.lock())) {
+        Ok(summary) => eprintln!("Solved {}, failed {}", summary.solved, summary.failed),
+        Err(err) => {
+            eprintln!("Error solving stream: {}", err);
+            process::exit(2);
+        }
+    }
+}
+
+/// Reads a puzzle from the clipboard in any format [`Sudoku::parse_any`] recognizes. Failing
+/// that, whitespace and grid decorations are skipped as by [`Sudoku::from_str`], and the error
+/// points at the first character that is neither.
+#[cfg(feature = "clipboard")]
+fn read_clipboard() -> Result<Sudoku, String> {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|err| err.to_string())?;
+    Sudoku::parse_any(&text).or_else(|_| text.parse().map_err(|err: ParseError| err.to_string()))
+}
+
+/// Puts the text on the clipboard. On Linux the clipboard is emptied when its owner exits, so
+/// this blocks until another program takes over the clipboard.
+#[cfg(feature = "clipboard")]
+fn write_clipboard(text: String) -> Result<(), arboard::Error> {
+    let mut clipboard = arboard::Clipboard::new()?;
+    #[cfg(target_os = "linux")]
+    {
+        use arboard::SetExtLinux;
+        eprintln!("Solution copied to the clipboard, waiting for it to be pasted over...");
+        clipboard.set().wait().text(text)
+    }
+    #[cfg(not(target_os = "linux"))]
+    clipboard.set_text(text)
+}
+
+/// Solves the puzzle on the clipboard, or else the puzzles on standard input, and copies the
+/// last solution to the clipboard if asked to.
+///
+/// # Arguments
+///
+/// * `options` - How to solve and print the puzzles.
+/// * `from_clipboard` - Whether to solve the puzzle on the clipboard.
+/// * `to_clipboard` - Whether to copy the solution to the clipboard.
+#[cfg(feature = "clipboard")]
+fn solve_clipboard(options: &SolveOptions, from_clipboard: bool, to_clipboard: bool) {
+    let (solution, read) = if from_clipboard {
+        match read_clipboard() {
+            Ok(puzzle) => (print_solved(puzzle, options), true),
+            Err(err) => {
+                eprintln!("Error reading clipboard: {}", err);
+                process::exit(2);
+            }
+        }
+    } else {
+        solve_stdin(options)
+    };
+    if let (true, Some(solution)) = (to_clipboard, solution) {
+        if let Err(err) = write_clipboard(format::write_line(&solution)) {
+            eprintln!("Error writing clipboard: {}", err);
+            process::exit(2);
+        }
+    }
+    if !read {
+        process::exit(2);
+    }
+}
+
+/// Runs a database subcommand.
+#[cfg(feature = "db")]
+fn run_db(database: PathBuf, command: DbCommand) -> Result<(), String> {
+    let mut db = PuzzleDb::open(database).map_err(|err| format!("{:?}", err))?;
+    match command {
+        DbCommand::Import { file, source } => {
+            let file = File::open(file).map_err(|err| err.to_string())?;
+            let reader = CsvReader::new(BufReader::new(file)).map_err(|err| err.to_string())?;
+            let mut count = 0;
+            for record in reader {
+                let mut record = PuzzleRecord::from(record.map_err(|err| err.to_string())?);
+                if record.solution.is_none() {
+                    let mut solver = Solver::new(record.puzzle.clone());
+                    solver.solve().map_err(|err| err.to_string())?;
+                    record.solution = Some(solver.sudoku().clone());
+                    record.technique = solver.stats().hardest_technique().map(String::from);
+                }
+                if source.is_some() {
+                    record.metadata.source = source.clone();
+                }
+                db.insert(&record).map_err(|err| format!("{:?}", err))?;
+                count += 1;
+            }
+            println!("Imported {} puzzles", count);
+        }
+        DbCommand::Query {
+            min_rating,
+            max_rating,
+            min_clues,
+            max_clues,
+            technique,
+            limit,
+        } => {
+            let query = PuzzleQuery {
+                min_rating,
+                max_rating,
+                min_clues,
+                max_clues,
+                technique,
+                limit,
+            };
+            let mut writer = CsvWriter::new(io::stdout().lock()).map_err(|err| err.to_string())?;
+            for (_, record) in db.query(&query).map_err(|err| format!("{:?}", err))? {
+                let clues = record.puzzle.set_count;
+                writer
+                    .write(&CsvRecord {
+                        puzzle: record.puzzle,
+                        solution: record.solution,
+                        rating: record.metadata.rating,
+                        clues: Some(clues),
+                    })
+                    .map_err(|err| err.to_string())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks the grid and prints every problem found. Returns whether the grid is correct.
+fn check(grid: &str, puzzle: Option<&str>) -> Result<bool, String> {
+    let grid = Sudoku::parse_any(grid).map_err(|err| err.to_string())?;
+    let mut correct = true;
+
+    for (first, second) in Solver::new(grid.clone()).find_conflicts() {
+        correct = false;
+        println!(
+            "Conflict: {} and {} both contain {}",
+            first,
+            second,
+            grid.squares[coords!(first.row, first.column)]
+        );
+    }
+
+    if let Some(puzzle) = puzzle {
+        let puzzle = Sudoku::parse_any(puzzle).map_err(|err| err.to_string())?;
+        let solution = Solver::new(puzzle.clone())
+            .unique_solution()
+            .map_err(|err| err.to_string())?;
+        for i in 0..9 * 9 {
+            let cell = Coordinates::from((i / 9, i % 9));
+            if puzzle.squares[i] != 0 && grid.squares[i] != puzzle.squares[i] {
+                correct = false;
+                println!("Changed given: {} should be {}", cell, puzzle.squares[i]);
+            } else if grid.squares[i] != 0 && grid.squares[i] != solution.squares[i] {
+                correct = false;
+                println!("Wrong value: {} is not {}", cell, grid.squares[i]);
+            }
+        }
+    }
+
+    let empty = 9 * 9 - grid.set_count as usize;
+    match (correct, empty) {
+        (true, 0) => println!("The grid is complete and correct."),
+        (true, _) => println!("No mistakes found, {} squares still empty.", empty),
+        (false, _) => {}
+    }
+    Ok(correct)
+}
+
+/// Generates a puzzle while showing a progress bar on standard error.
+fn generate_with_bar(difficulty: u8, unique: bool) -> Result<Sudoku, SolverError> {
+    let bar = ProgressBar::new(9 * 9);
+    bar.set_style(
+        ProgressStyle::with_template("{spinner} [{bar:40}] {pos}/{len} squares {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> "),
+    );
+    let result =
+        Solver::generate_with_progress(difficulty, unique, rand::thread_rng().gen(), |progress| {
+            bar.set_position(progress.attempts as u64);
+            bar.set_message(format!(
+                "({} removed, {} uniqueness checks)",
+                progress.clues_removed, progress.uniqueness_checks
+            ));
+        });
+    bar.finish_and_clear();
+    result
+}
+
+/// Plays the game in the terminal and adds it to the leaderboard if it was solved. A game that
+/// is quit is saved to the session file, if any, and the file is removed once it is solved.
+/// The replay of the game is written to the replay file, if any, however the game ends.
+fn play_and_record(
+    mut game: Game,
+    leaderboard: &Path,
+    session: Option<&Path>,
+    replay: Option<&Path>,
+) {
+    let result = play::play(&mut game);
+    let mut failed = false;
+    if let Some(replay) = replay {
+        if let Err(err) = fs::write(replay, game.replay().to_text()) {
+            eprintln!("Error saving {}: {}", replay.display(), err);
+            failed = true;
+        }
+    }
+    match result {
+        Ok(true) => {
+            if let Some(session) = session {
+                let _ = fs::remove_file(session);
+            }
+            let record = game.record();
+            let score = record.score();
+            let rank = Leaderboard::load(leaderboard).and_then(|mut board| {
+                let rank = board.add(record);
+                board.save(leaderboard).map(|_| rank)
+            });
+            match rank {
+                Ok(rank) => println!("Well done! Score {}, rank {}.", score, rank),
+                Err(err) => {
+                    eprintln!("Error saving {}: {}", leaderboard.display(), err);
+                    failed = true;
+                }
+            }
+        }
+        Ok(false) if game.is_lost() => {
+            println!("Game over after {} mistakes.", game.mistakes());
+            if let Some(session) = session {
+                let _ = fs::remove_file(session);
+            }
+        }
+        Ok(false) => {
+            if let Some(session) = session {
+                match fs::write(session, game.to_session()) {
+                    Ok(_) => println!("Game saved to {}.", session.display()),
+                    Err(err) => {
+                        eprintln!("Error saving {}: {}", session.display(), err);
+                        failed = true;
+                    }
+                }
+            }
+        }
+        Err(err) => {
+            eprintln!("Terminal error: {}", err);
+            failed = true;
+        }
+    }
+    if failed {
+        process::exit(2);
+    }
+}
+
+/// Generates and solves puzzles until solving fails.
+fn run_demo() {
     let mut run = true;
     while run {
         let sudoku = match Solver::generate(70) {
             Ok(sudoku_) => sudoku_,
             Err(err) => {
-                println!("Error generating sudoku: {:?}", err);
+                println!("Error generating sudoku: {}", err);
                 return;
             }
         };
-        println!("New sudoku:\n{}", sudoku);
+        println!("New sudoku:\n{:#}", sudoku);
 
         let mut solver = Solver::new(sudoku);
         match solver.solve() {
-            Ok(_) => println!("Solution:\n{}", solver),
+            Ok(_) => println!("Solution:\n{:#}", solver),
             Err(err) => {
-                println!("Error solving sudoku: {:?}", err);
+                println!("Error solving sudoku: {}", err);
                 run = false;
             }
         }
     }
 }
+
+pub fn main() {
+    let cli = Cli::parse();
+    let command = match cli.command {
+        Some(command) => command,
+        None => return run_demo(),
+    };
+
+    match command {
+        Command::Generate {
+            count,
+            difficulty,
+            unique,
+            histogram,
+            fast,
+            output,
+        } => {
+            if output == Output::QqwingCsv {
+                println!("{}", qqwing::CSV_HEADER);
+            }
+            let mut ratings = RatingHistogram::new();
+            let mut failed = false;
+            for _ in 0..count {
+                let generated = if fast {
+                    let options = GenerationOptions {
+                        difficulty,
+                        unique,
+                        seed_grid: Some(transform::SEED_GRID),
+                        ..GenerationOptions::default()
+                    };
+                    Solver::generate_with_options(&options, rand::thread_rng().gen())
+                } else {
+                    generate_with_bar(difficulty, unique)
+                };
+                let rated = generated.and_then(|puzzle| {
+                    if histogram {
+                        let report = Solver::new(puzzle.clone()).difficulty_report()?;
+                        ratings.record(difficulty, report.score);
+                    }
+                    Ok(puzzle)
+                });
+                match rated {
+                    Ok(puzzle) => {
+                        print_solved(puzzle, &output.into());
+                    }
+                    Err(err) => {
+                        eprintln!("Error generating sudoku: {}", err);
+                        failed = true;
+                        break;
+                    }
+                }
+            }
+            if histogram {
+                eprint!("{}", ratings);
+            }
+            if failed {
+                process::exit(2);
+            }
+        }
+        Command::Solve {
+            output,
+            stream,
+            watch,
+            delay,
+            time,
+            verbose,
+            #[cfg(feature = "clipboard")]
+            from_clipboard,
+            #[cfg(feature = "clipboard")]
+            to_clipboard,
+        } => {
+            let options = SolveOptions {
+                output,
+                watch: watch.then(|| Duration::from_millis(delay)),
+                time,
+                verbosity: verbose,
+            };
+            if stream {
+                return stream_stdin();
+            }
+            if output == Output::QqwingCsv {
+                println!("{}", qqwing::CSV_HEADER);
+            }
+            #[cfg(feature = "clipboard")]
+            if from_clipboard || to_clipboard {
+                return solve_clipboard(&options, from_clipboard, to_clipboard);
+            }
+            if !solve_stdin(&options).1 {
+                process::exit(2);
+            }
+        }
+        #[cfg(feature = "mmap")]
+        Command::SolveFile { file, jobs } => match batch::solve_file_in_place(&file, jobs) {
+            Ok(summary) => println!("Solved {}, failed {}", summary.solved, summary.failed),
+            Err(err) => {
+                eprintln!("Error solving {}: {}", file.display(), err);
+                process::exit(2);
+            }
+        },
+        Command::Rate { input, jobs, scale } => {
+            let rated = File::open(&input)
+                .map_err(|err| err.into())
+                .and_then(|file| {
+                    let stdout = io::stdout();
+                    batch::rate_stream(
+                        io::BufReader::new(file),
+                        BufWriter::new(stdout.lock()),
+                        jobs,
+                        scale.into(),
+                    )
+                });
+            match rated {
+                Ok(summary) => eprintln!("Rated {}, failed {}", summary.solved, summary.failed),
+                Err(err) => {
+                    eprintln!("Error rating {}: {}", input.display(), err);
+                    process::exit(2);
+                }
+            }
+        }
+        Command::Analyze { input, jobs } => match read_puzzles(&input) {
+            Ok(puzzles) => print!("{}", batch::analyze(&puzzles, jobs)),
+            Err(err) => {
+                eprintln!("Error reading {}: {}", input.display(), err);
+                process::exit(2);
+            }
+        },
+        Command::Verify { input } => {
+            let verified = File::open(&input)
+                .map_err(|err| err.into())
+                .and_then(|file| batch::verify(BufReader::new(file)));
+            match verified {
+                Ok(report) => {
+                    print!("{}", report);
+                    if !report.mismatches.is_empty() || !report.failures.is_empty() {
+                        process::exit(1);
+                    }
+                }
+                Err(err) => {
+                    eprintln!("Error verifying {}: {}", input.display(), err);
+                    process::exit(2);
+                }
+            }
+        }
+        Command::Compare {
+            input,
+            first,
+            second,
+        } => match read_puzzles(&input) {
+            Ok(puzzles) => {
+                let comparison = batch::compare(&puzzles, [first.into(), second.into()]);
+                print!("{}", comparison);
+                if !comparison.disagreements.is_empty() {
+                    process::exit(1);
+                }
+            }
+            Err(err) => {
+                eprintln!("Error reading {}: {}", input.display(), err);
+                process::exit(2);
+            }
+        },
+        Command::Check { grid, puzzle } => match check(&grid, puzzle.as_deref()) {
+            Ok(true) => {}
+            Ok(false) => process::exit(1),
+            Err(err) => {
+                eprintln!("Error checking sudoku: {}", err);
+                process::exit(2);
+            }
+        },
+        Command::Play {
+            puzzle,
+            difficulty,
+            leaderboard,
+            session,
+            max_mistakes,
+            replay,
+        } => {
+            let saved = session
+                .as_ref()
+                .and_then(|session| fs::read_to_string(session).ok());
+            let game = match (saved, puzzle) {
+                (Some(saved), _) => Game::from_session(&saved).map_err(|err| err.to_string()),
+                (None, Some(puzzle)) => Sudoku::parse_any(&puzzle)
+                    .map(Game::new)
+                    .map_err(|err| err.to_string()),
+                (None, None) => generate_with_bar(difficulty, true)
+                    .map(Game::new)
+                    .map_err(|err| err.to_string()),
+            };
+            match game {
+                Ok(mut game) => {
+                    if max_mistakes.is_some() {
+                        game.set_max_mistakes(max_mistakes);
+                    }
+                    play_and_record(game, &leaderboard, session.as_deref(), replay.as_deref())
+                }
+                Err(err) => {
+                    eprintln!("Error reading sudoku: {}", err);
+                    process::exit(2);
+                }
+            }
+        }
+        Command::Replay { file, solve } => {
+            if let Some(puzzle) = solve {
+                let recorded = Sudoku::parse_any(&puzzle)
+                    .map_err(|err| err.to_string())
+                    .and_then(|puzzle| Replay::record_solve(puzzle).map_err(|err| err.to_string()))
+                    .and_then(|replay| {
+                        fs::write(&file, replay.to_text()).map_err(|err| err.to_string())
+                    });
+                if let Err(err) = recorded {
+                    eprintln!("Error recording replay: {}", err);
+                    process::exit(2);
+                }
+            }
+            let replay = fs::read_to_string(&file)
+                .map_err(|err| err.to_string())
+                .and_then(|text| Replay::from_text(&text).map_err(|err| err.to_string()));
+            match replay {
+                Ok(replay) => {
+                    if let Err(err) = play::watch(&mut Playback::new(replay)) {
+                        eprintln!("Terminal error: {}", err);
+                        process::exit(2);
+                    }
+                }
+                Err(err) => {
+                    eprintln!("Error reading {}: {}", file.display(), err);
+                    process::exit(2);
+                }
+            }
+        }
+        Command::Leaderboard { file, count } => match Leaderboard::load(&file) {
+            Ok(leaderboard) => {
+                println!("Rank  Score  Time   Hints  Mistakes  Date        Puzzle");
+                for (i, record) in leaderboard.top(count).iter().enumerate() {
+                    let date = record.date.map(|date| date.to_string()).unwrap_or_default();
+                    println!(
+                        "{:>4}  {:>5}  {:>2}:{:02}  {:>5}  {:>8}  {:<10}  {}",
+                        i + 1,
+                        record.score(),
+                        record.seconds / 60,
+                        record.seconds % 60,
+                        record.hints,
+                        record.mistakes,
+                        date,
+                        format::write_line(&record.puzzle)
+                    );
+                }
+            }
+            Err(err) => {
+                eprintln!("Error reading {}: {}", file.display(), err);
+                process::exit(2);
+            }
+        },
+        Command::Edit { puzzle, output } => {
+            let grid = match puzzle.as_deref().map(Sudoku::parse_any) {
+                Some(Ok(grid)) => grid,
+                Some(Err(err)) => {
+                    eprintln!("Error reading sudoku: {}", err);
+                    process::exit(2);
+                }
+                None => Sudoku::new_empty(),
+            };
+            let mut editor = Editor::new(grid);
+            match play::edit(&mut editor) {
+                Ok(Action::Save) => {
+                    let line = format::write_line(editor.grid());
+                    let written = match &output {
+                        Some(path) => OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .open(path)
+                            .and_then(|mut file| writeln!(file, "{}", line)),
+                        None => {
+                            println!("{}", line);
+                            Ok(())
+                        }
+                    };
+                    if let Err(err) = written {
+                        eprintln!("Error saving sudoku: {}", err);
+                        process::exit(2);
+                    }
+                }
+                Ok(Action::Solve) => {
+                    print_solved(editor.grid().clone(), &Output::Pretty.into());
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    eprintln!("Terminal error: {}", err);
+                    process::exit(2);
+                }
+            }
+        }
+        Command::Type => {
+            let mut entry = Entry::new();
+            let action = match play::type_in(&mut entry) {
+                Ok(action) => action,
+                Err(err) => {
+                    eprintln!("Terminal error: {}", err);
+                    process::exit(2);
+                }
+            };
+            match (action, entry.sudoku()) {
+                (Action::Solve, Some(puzzle)) => {
+                    print_solved(puzzle, &Output::Pretty.into());
+                }
+                (Action::Play, Some(puzzle)) => play_and_record(
+                    Game::new(puzzle),
+                    Path::new(leaderboard::DEFAULT_PATH),
+                    None,
+                    None,
+                ),
+                _ => {}
+            }
+        }
+        Command::Hint { grid, level } => {
+            match Sudoku::parse_any(&grid) {
+                Ok(grid) => match Hint::find(&Solver::new(grid)) {
+                    Some(hint) => {
+                        let level = HintLevel::from(level);
+                        println!("{}", hint.describe(level));
+                        if level >= HintLevel::Technique {
+                            if let Some(technique) = Technique::find(hint.technique) {
+                                println!("See {}", technique.sudokuwiki_url());
+                            }
+                        }
+                    }
+                    None => {
+                        println!("No logical step found; the grid is full, breaks a rule or needs a guess.");
+                        process::exit(1);
+                    }
+                },
+                Err(err) => {
+                    eprintln!("Error reading sudoku: {}", err);
+                    process::exit(2);
+                }
+            }
+        }
+        Command::Book {
+            count,
+            difficulty,
+            title,
+            output,
+        } => {
+            let entries = match book::generate(count, &difficulty) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    eprintln!("Error generating sudoku: {}", err);
+                    process::exit(2);
+                }
+            };
+            let written = File::create(&output)
+                .and_then(|file| book::write_pdf(&title, &entries, BufWriter::new(file)));
+            match written {
+                Ok(_) => println!("Wrote {} puzzles to {}", count, output.display()),
+                Err(err) => {
+                    eprintln!("Error writing {}: {}", output.display(), err);
+                    process::exit(2);
+                }
+            }
+        }
+        #[cfg(feature = "db")]
+        Command::Db { database, command } => {
+            if let Err(err) = run_db(database, command) {
+                eprintln!("Database error: {}", err);
+                process::exit(2);
+            }
+        }
+        #[cfg(feature = "gif")]
+        Command::Gif {
+            puzzle,
+            output,
+            delay,
+            cell,
+        } => {
+            let replay = match Sudoku::parse_any(&puzzle)
+                .map_err(|err| err.to_string())
+                .and_then(|puzzle| Replay::record_solve(puzzle).map_err(|err| err.to_string()))
+            {
+                Ok(replay) => replay,
+                Err(err) => {
+                    eprintln!("Error solving puzzle: {}", err);
+                    process::exit(2);
+                }
+            };
+            let options = AnimationOptions {
+                delay,
+                raster: RasterOptions {
+                    cell: cell.max(12),
+                    scale: (cell.max(12) - 4) / 8,
+                    ..RasterOptions::default()
+                },
+                ..AnimationOptions::default()
+            };
+            let written = File::create(&output)
+                .map_err(|err| err.to_string())
+                .and_then(|file| {
+                    animation::write_gif(&replay, &options, BufWriter::new(file))
+                        .map_err(|err| err.to_string())
+                });
+            match written {
+                Ok(_) => println!(
+                    "Wrote {} moves to {}",
+                    replay.moves().len(),
+                    output.display()
+                ),
+                Err(err) => {
+                    eprintln!("Error writing {}: {}", output.display(), err);
+                    process::exit(2);
+                }
+            }
+        }
+        #[cfg(feature = "ocr")]
+        Command::Scan { image, output } => match ocr::recognize_file(&image) {
+            Ok(puzzle) => {
+                if output == Output::QqwingCsv {
+                    println!("{}", qqwing::CSV_HEADER);
+                }
+                print_solved(puzzle, &output.into());
+            }
+            Err(err) => {
+                eprintln!("Error reading {}: {:?}", image.display(), err);
+                process::exit(2);
+            }
+        },
+        #[cfg(feature = "server")]
+        Command::Serve { address } => {
+            println!("Listening on ws://{}", address);
+            if let Err(err) = server::serve(&address) {
+                eprintln!("Server error: {}", err);
+                process::exit(2);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cli, Command};
+    use clap::{CommandFactory, Parser};
+
+    #[test]
+    fn test_solve_arguments() {
+        Cli::command().debug_assert();
+        let cli = Cli::try_parse_from(["sudoku_solver", "solve", "--time"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Solve {
+                time: true,
+                stream: false,
+                ..
+            })
+        ));
+
+        let clipboard = Cli::try_parse_from([
+            "sudoku_solver",
+            "solve",
+            "--from-clipboard",
+            "--to-clipboard",
+        ]);
+        #[cfg(feature = "clipboard")]
+        assert!(matches!(
+            clipboard.unwrap().command,
+            Some(Command::Solve {
+                from_clipboard: true,
+                to_clipboard: true,
+                ..
+            })
+        ));
+        #[cfg(not(feature = "clipboard"))]
+        assert!(clipboard.is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use chrono::NaiveDate;
+
+/// What is known about a puzzle besides its grid: where it comes from, who made it and how hard
+/// it is. Carried alongside the [`Sudoku`](crate::sudoku::Sudoku) by generation, the database
+/// and the file formats that have room for it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PuzzleMetadata {
+    /// Identifier of the puzzle in its source, e.g. the row id of a CSV collection.
+    pub id: Option<String>,
+    /// Collection, website or program the puzzle comes from.
+    pub source: Option<String>,
+    /// Person who made the puzzle.
+    pub author: Option<String>,
+    /// Day the puzzle was made or published.
+    pub created: Option<NaiveDate>,
+    /// Difficulty rating on the scale of the source.
+    pub rating: Option<f32>,
+    /// Free-form labels such as "symmetric" or "X-Wing".
+    pub tags: Vec<String>,
+    /// Other (key, value) pairs, kept so that nothing read from a source is lost.
+    pub extra: Vec<(String, String)>,
+}
+
+impl PuzzleMetadata {
+    /// Returns the metadata as (key, value) pairs with the keys `id`, `source`, `author`,
+    /// `created` (as YYYY-MM-DD), `rating` and one `tag` per tag, followed by the extra pairs.
+    /// Fields that are not set are left out.
+    pub fn to_pairs(&self) -> Vec<(String, String)> {
+        let mut retval = Vec::new();
+        let mut push = |key: &str, value: String| retval.push((key.to_string(), value));
+        if let Some(id) = &self.id {
+            push("id", id.clone());
+        }
+        if let Some(source) = &self.source {
+            push("source", source.clone());
+        }
+        if let Some(author) = &self.author {
+            push("author", author.clone());
+        }
+        if let Some(created) = &self.created {
+            push("created", created.to_string());
+        }
+        if let Some(rating) = &self.rating {
+            push("rating", rating.to_string());
+        }
+        for tag in &self.tags {
+            push("tag", tag.clone());
+        }
+        retval.extend(self.extra.iter().cloned());
+        retval
+    }
+
+    /// Builds metadata from (key, value) pairs as written by [`PuzzleMetadata::to_pairs`]. Pairs
+    /// with other keys, and dates and ratings that cannot be parsed, are kept in
+    /// [`PuzzleMetadata::extra`]. Later pairs replace earlier ones with the same key, except
+    /// for tags.
+    ///
+    /// # Arguments
+    ///
+    /// * `pairs` - The (key, value) pairs.
+    pub fn from_pairs<I: IntoIterator<Item = (String, String)>>(pairs: I) -> Self {
+        let mut retval = PuzzleMetadata::default();
+        for (key, value) in pairs {
+            match key.as_str() {
+                "id" => retval.id = Some(value),
+                "source" => retval.source = Some(value),
+                "author" => retval.author = Some(value),
+                "created" => match value.parse::<NaiveDate>() {
+                    Ok(created) => retval.created = Some(created),
+                    Err(_) => retval.extra.push((key, value)),
+                },
+                "rating" => match value.parse() {
+                    Ok(rating) => retval.rating = Some(rating),
+                    Err(_) => retval.extra.push((key, value)),
+                },
+                "tag" => retval.tags.push(value),
+                _ => retval.extra.push((key, value)),
+            }
+        }
+        retval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PuzzleMetadata;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_pairs() {
+        let metadata = PuzzleMetadata {
+            id: Some("17".to_string()),
+            source: Some("Kaggle".to_string()),
+            author: None,
+            created: NaiveDate::from_ymd_opt(2024, 5, 1),
+            rating: Some(2.5),
+            tags: vec!["symmetric".to_string(), "X-Wing".to_string()],
+            extra: vec![("level".to_string(), "Hard".to_string())],
+        };
+        let pairs = metadata.to_pairs();
+        assert_eq!(
+            vec![
+                ("id", "17"),
+                ("source", "Kaggle"),
+                ("created", "2024-05-01"),
+                ("rating", "2.5"),
+                ("tag", "symmetric"),
+                ("tag", "X-Wing"),
+                ("level", "Hard"),
+            ],
+            pairs
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(metadata, PuzzleMetadata::from_pairs(pairs));
+
+        let odd = PuzzleMetadata::from_pairs([
+            ("created".to_string(), "yesterday".to_string()),
+            ("source".to_string(), "a".to_string()),
+            ("source".to_string(), "b".to_string()),
+        ]);
+        assert_eq!(None, odd.created);
+        assert_eq!(Some("b".to_string()), odd.source);
+        assert_eq!(
+            vec![("created".to_string(), "yesterday".to_string())],
+            odd.extra
+        );
+    }
+}
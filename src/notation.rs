@@ -0,0 +1,208 @@
+//! The common notation for single moves: `r4c7=3` places a 3 in row 4, column 7 and `r4c7<>5`
+//! rules out a 5 there. Rows and columns are 1-based.
+
+use crate::{
+    constraint::CandidateGrid,
+    sudoku::{Coordinates, Sudoku},
+};
+use std::{fmt::Display, str::FromStr};
+
+/// A placement or an elimination.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Move {
+    /// The value is written into the square, e.g. `r4c7=3`.
+    Place { cell: Coordinates, value: u8 },
+    /// The value is ruled out for the square, e.g. `r4c7<>5`.
+    Eliminate { cell: Coordinates, value: u8 },
+}
+
+/// Error type for reading and applying [`Move`]s.
+#[derive(Debug, PartialEq)]
+pub enum MoveError {
+    /// The text is not a move in the notation, e.g. a value outside [1,9].
+    BadNotation(String),
+    /// The square of the move is already set.
+    Occupied(Coordinates),
+    /// The value is not a candidate of the square.
+    NotCandidate(Coordinates, u8),
+}
+
+/// Writes the move in the notation, e.g. `r4c7=3` or `r4c7<>5`.
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Move::Place { cell, value } => write!(f, "{}={}", cell, value),
+            Move::Eliminate { cell, value } => write!(f, "{}<>{}", cell, value),
+        }
+    }
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveError::BadNotation(text) => write!(f, "'{}' is not a move like r4c7=3", text),
+            MoveError::Occupied(cell) => write!(f, "{} is already set", cell),
+            MoveError::NotCandidate(cell, value) => write!(f, "{} cannot be {}", cell, value),
+        }
+    }
+}
+
+/// Reads a move in the notation. Whitespace around the move and the operator is ignored, and
+/// the letters may be capitals.
+impl FromStr for Move {
+    type Err = MoveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad_notation = || MoveError::BadNotation(s.to_string());
+        let (cell, value, place) = match s.split_once("<>") {
+            Some((cell, value)) => (cell, value, false),
+            None => s
+                .split_once('=')
+                .map(|(cell, value)| (cell, value, true))
+                .ok_or_else(bad_notation)?,
+        };
+        let cell = parse_cell(cell.trim()).ok_or_else(bad_notation)?;
+        let value = value
+            .trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|value| (1..=9).contains(value))
+            .ok_or_else(bad_notation)?;
+        Ok(match place {
+            true => Move::Place { cell, value },
+            false => Move::Eliminate { cell, value },
+        })
+    }
+}
+
+impl Move {
+    /// Returns the square of the move.
+    pub fn cell(&self) -> &Coordinates {
+        match self {
+            Move::Place { cell, .. } | Move::Eliminate { cell, .. } => cell,
+        }
+    }
+
+    /// Makes the move on a grid and its candidates, e.g. from
+    /// [`Solver::all_candidates`](crate::solver::Solver::all_candidates). A placement sets the
+    /// square, clears its candidates and removes the value from the candidates of its peers; an
+    /// elimination removes the value from the candidates of the square. Nothing is changed if
+    /// the move fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `sudoku` - The grid.
+    /// * `candidates` - Possible values of every square of the grid.
+    pub fn apply(
+        &self,
+        sudoku: &mut Sudoku,
+        candidates: &mut CandidateGrid,
+    ) -> Result<(), MoveError> {
+        let (cell, value) = match self {
+            Move::Place { cell, value } | Move::Eliminate { cell, value } => (cell, *value),
+        };
+        if sudoku.get_unchecked(cell.row, cell.column) != 0 {
+            return Err(MoveError::Occupied(cell.clone()));
+        }
+        let i = cell.row * 9 + cell.column;
+        if !candidates[i].remove(&value) {
+            return Err(MoveError::NotCandidate(cell.clone(), value));
+        }
+        if let Move::Place { .. } = self {
+            sudoku.set_unchecked(cell.row, cell.column, value);
+            candidates[i].clear();
+            for peer in cell.peers() {
+                candidates[peer.row * 9 + peer.column].remove(&value);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a square in the 1-based `r1c1` notation, e.g. `r4c7`. Returns `None` if the text is
+/// not a square.
+///
+/// # Arguments
+///
+/// * `text` - The square.
+pub fn parse_cell(text: &str) -> Option<Coordinates> {
+    let text = text.to_lowercase();
+    let (row, column) = text.strip_prefix('r')?.split_once('c')?;
+    let (row, column): (usize, usize) = (row.parse().ok()?, column.parse().ok()?);
+    ((1..=9).contains(&row) && (1..=9).contains(&column))
+        .then(|| Coordinates::from((row - 1, column - 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_cell, Move, MoveError};
+    use crate::{corpus, format::parse_line, solver::Solver, sudoku::Coordinates};
+
+    #[test]
+    fn test_parse() {
+        let r4c7 = Coordinates::from((3, 6));
+        assert_eq!(Some(r4c7.clone()), parse_cell("R4C7"));
+        assert_eq!(None, parse_cell("r0c7"));
+        assert_eq!(
+            Ok(Move::Place {
+                cell: r4c7.clone(),
+                value: 3
+            }),
+            "r4c7=3".parse()
+        );
+        let elimination: Move = " r4c7 <> 5".parse().unwrap();
+        assert_eq!(
+            Move::Eliminate {
+                cell: r4c7,
+                value: 5
+            },
+            elimination
+        );
+        assert_eq!("r4c7<>5", elimination.to_string());
+        for bad in ["r4c7", "r4c7=0", "r4c10=3", "4,7=3", "r4c7<5"] {
+            assert_eq!(
+                Err(MoveError::BadNotation(bad.to_string())),
+                bad.parse::<Move>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut sudoku = parse_line(corpus::EASY.puzzle, 1).unwrap();
+        let mut candidates = Solver::new(sudoku.clone()).all_candidates();
+        let solution = parse_line(corpus::EASY.solution, 1).unwrap();
+        let (cell, value) = (0..9 * 9)
+            .find(|i| sudoku.squares[*i] == 0)
+            .map(|i| (Coordinates::from((i / 9, i % 9)), solution.squares[i]))
+            .unwrap();
+        let wrong = *candidates[cell.row * 9 + cell.column]
+            .iter()
+            .find(|candidate| **candidate != value)
+            .unwrap();
+
+        let elimination = Move::Eliminate {
+            cell: cell.clone(),
+            value: wrong,
+        };
+        elimination.apply(&mut sudoku, &mut candidates).unwrap();
+        assert_eq!(
+            Err(MoveError::NotCandidate(cell.clone(), wrong)),
+            elimination.apply(&mut sudoku, &mut candidates)
+        );
+
+        let placement = Move::Place {
+            cell: cell.clone(),
+            value,
+        };
+        placement.apply(&mut sudoku, &mut candidates).unwrap();
+        assert_eq!(value, sudoku.get_unchecked(cell.row, cell.column));
+        assert!(cell
+            .peers()
+            .all(|peer| !candidates[peer.row * 9 + peer.column].contains(&value)));
+        assert_eq!(
+            Err(MoveError::Occupied(cell.clone())),
+            placement.apply(&mut sudoku, &mut candidates)
+        );
+    }
+}
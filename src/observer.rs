@@ -0,0 +1,794 @@
+use crate::{
+    constraint::CandidateSet,
+    i18n::{Locale, Message},
+    notation::Move,
+    sudoku::{houses_of, Coordinates, House, Sudoku},
+    technique::Technique,
+};
+use std::{fmt::Debug, io::Write, sync::mpsc::Sender};
+
+/// One event of a solve, as reported to [`SolverObserver`]s.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SolveStep {
+    /// A square was set because it had only one possible value.
+    Placement { cell: Coordinates, value: u8 },
+    /// A square was set because it was the only place for the value in the house.
+    HiddenSingle {
+        cell: Coordinates,
+        value: u8,
+        house: House,
+    },
+    /// A square was set because any other value would leave a bivalue universal grave.
+    BivalueUniversalGrave { cell: Coordinates, value: u8 },
+    /// A value was ruled out for an empty square.
+    Elimination {
+        cell: Coordinates,
+        value: u8,
+        cause: EliminationCause,
+    },
+    /// A square was set by guessing between the candidates, which are in ascending order.
+    Guess {
+        cell: Coordinates,
+        value: u8,
+        candidates: Vec<u8>,
+    },
+    /// An uncertain square was reset because of a bad guess.
+    Backtrack { cell: Coordinates, value: u8 },
+}
+
+/// Why a value was ruled out for a square.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum EliminationCause {
+    /// The value was set in the given square, which shares a row, column, block or other
+    /// constraint with the square.
+    Placement(Coordinates),
+    /// The value can only go where `base` overlaps `cover`, so the squares of `cover` outside
+    /// `base` cannot hold it. One of the houses is a block, the other a row or column.
+    LockedCandidates { base: House, cover: House },
+    /// Without ruling the value out, the corners, in reading order, could be left with only
+    /// the two values, which could then be swapped for a second solution.
+    UniqueRectangle {
+        corners: [Coordinates; 4],
+        values: [u8; 2],
+    },
+    /// The value is in one of two almost locked sets, each with one possible value more than
+    /// squares, whose squares holding the restricted common all see each other, so that only
+    /// one of the sets can hold it. Every square of the sets with the value is seen by the square.
+    AlsXz {
+        sets: [Vec<Coordinates>; 2],
+        restricted_common: u8,
+    },
+    /// Setting the value in the square forces the given placements, which lead to a
+    /// contradiction.
+    ForcingChain(Vec<Move>),
+}
+
+/// Writes a numbered line describing every step of the solve, for following how the solver
+/// treats a particular puzzle. Write errors are ignored, as observers cannot stop the solver.
+pub struct Tracer<W: Write> {
+    writer: W,
+    grid: Sudoku,
+    steps: usize,
+    detailed: bool,
+}
+
+/// Hooks the solver calls while it works, e.g. for animating progress in a GUI or for
+/// instrumenting the search. Every method has an empty default implementation, so observers
+/// only implement the events they care about.
+pub trait SolverObserver: Debug {
+    /// Called after a square is set because it had only one possible value.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square.
+    /// * `value` - The value that was set.
+    fn on_placement(&mut self, _cell: &Coordinates, _value: u8) {}
+
+    /// Called after a square is set because it was the only place for the value in one of its
+    /// houses. The default treats it as any other placement and calls
+    /// [`SolverObserver::on_placement`].
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square.
+    /// * `value` - The value that was set.
+    /// * `house` - The house where no other square could hold the value.
+    fn on_hidden_single(&mut self, cell: &Coordinates, value: u8, _house: House) {
+        self.on_placement(cell, value);
+    }
+
+    /// Called after a square is set because any other value would leave every empty square
+    /// with two possible values, a bivalue universal grave. The default treats it as any other
+    /// placement and calls [`SolverObserver::on_placement`].
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square.
+    /// * `value` - The value that was set.
+    fn on_bivalue_universal_grave(&mut self, cell: &Coordinates, value: u8) {
+        self.on_placement(cell, value);
+    }
+
+    /// Called when a value is ruled out for an empty square, before the change that rules it
+    /// out is made. Following these events shows how the consequences of every placement
+    /// spread over the grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the empty square.
+    /// * `value` - The value that is no longer possible.
+    /// * `cause` - What ruled the value out.
+    fn on_elimination(&mut self, _cell: &Coordinates, _value: u8, _cause: &EliminationCause) {}
+
+    /// Called after a square is set by guessing between multiple possible values.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square.
+    /// * `value` - The guessed value.
+    /// * `candidates` - Every value that was possible for the square.
+    fn on_guess(&mut self, _cell: &Coordinates, _value: u8, _candidates: &CandidateSet) {}
+
+    /// Called after an uncertain square is reset because of a bad guess.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square that was reset.
+    /// * `value` - The value the square had.
+    fn on_backtrack(&mut self, _cell: &Coordinates, _value: u8) {}
+}
+
+impl SolveStep {
+    /// Returns the step as an English sentence for hints and solution guides, e.g.
+    /// `r4c6 cannot be 7, as r4c2 in row 4 is 7.` Squares are written in the 1-based `r1c1`
+    /// notation.
+    pub fn describe(&self) -> String {
+        self.describe_in(Locale::English)
+    }
+
+    /// Returns the step as a sentence in the given language, see [`SolveStep::describe`].
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - The language to write in.
+    pub fn describe_in(&self, locale: Locale) -> String {
+        let message = match self {
+            SolveStep::Placement { cell, value } => Message::NakedSingle {
+                cell,
+                value: *value,
+            },
+            SolveStep::HiddenSingle { cell, value, house } => Message::HiddenSingle {
+                cell,
+                value: *value,
+                house: *house,
+            },
+            SolveStep::BivalueUniversalGrave { cell, value } => Message::BivalueUniversalGrave {
+                cell,
+                value: *value,
+            },
+            SolveStep::Elimination {
+                cell,
+                value,
+                cause: EliminationCause::Placement(cause),
+            } => {
+                let shared = houses_of(cell)
+                    .into_iter()
+                    .find(|house| houses_of(cause).contains(house));
+                match shared {
+                    Some(house) => Message::SeenInHouse {
+                        cell,
+                        value: *value,
+                        cause,
+                        house,
+                    },
+                    None => Message::SeenByConstraint {
+                        cell,
+                        value: *value,
+                        cause,
+                    },
+                }
+            }
+            SolveStep::Elimination {
+                cell,
+                value,
+                cause: EliminationCause::LockedCandidates { base, cover },
+            } => Message::LockedCandidates {
+                cell,
+                value: *value,
+                base: *base,
+                cover: *cover,
+            },
+            SolveStep::Elimination {
+                cell,
+                value,
+                cause: EliminationCause::UniqueRectangle { corners, values },
+            } => Message::UniqueRectangle {
+                cell,
+                value: *value,
+                corners,
+                values: *values,
+            },
+            SolveStep::Elimination {
+                cell,
+                value,
+                cause:
+                    EliminationCause::AlsXz {
+                        sets,
+                        restricted_common,
+                    },
+            } => Message::AlsXz {
+                cell,
+                value: *value,
+                sets,
+                restricted_common: *restricted_common,
+            },
+            SolveStep::Elimination {
+                cell,
+                value,
+                cause: EliminationCause::ForcingChain(chain),
+            } => Message::ForcingChain {
+                cell,
+                value: *value,
+                chain,
+            },
+            SolveStep::Guess {
+                cell,
+                value,
+                candidates,
+            } => Message::Guess {
+                cell,
+                value: *value,
+                candidates,
+            },
+            SolveStep::Backtrack { cell, value } => Message::Backtrack {
+                cell,
+                value: *value,
+            },
+        };
+        locale.format(&message)
+    }
+
+    /// Returns the technique that finds the step, or `None` for eliminations by placements and
+    /// for backtracks, which are consequences of other steps.
+    pub fn technique(&self) -> Option<&'static Technique> {
+        match self {
+            SolveStep::Placement { .. } => Technique::find("Naked Single"),
+            SolveStep::HiddenSingle { .. } => Technique::find("Hidden Single"),
+            SolveStep::BivalueUniversalGrave { .. } => Technique::find("Bivalue Universal Grave"),
+            SolveStep::Elimination {
+                cause: EliminationCause::LockedCandidates { .. },
+                ..
+            } => Technique::find("Locked Candidates"),
+            SolveStep::Elimination {
+                cause: EliminationCause::UniqueRectangle { .. },
+                ..
+            } => Technique::find("Unique Rectangle"),
+            SolveStep::Elimination {
+                cause: EliminationCause::AlsXz { .. },
+                ..
+            } => Technique::find("ALS-XZ"),
+            SolveStep::Elimination {
+                cause: EliminationCause::ForcingChain(_),
+                ..
+            } => Technique::find("Forcing Chain"),
+            SolveStep::Guess { .. } => Technique::find("Guess"),
+            SolveStep::Elimination { .. } | SolveStep::Backtrack { .. } => None,
+        }
+    }
+
+    /// Returns the step as a move in the `r4c7=3` notation, or `None` for backtracks, which
+    /// clear a square.
+    pub fn to_move(&self) -> Option<Move> {
+        match self {
+            SolveStep::Placement { cell, value }
+            | SolveStep::HiddenSingle { cell, value, .. }
+            | SolveStep::BivalueUniversalGrave { cell, value }
+            | SolveStep::Guess { cell, value, .. } => Some(Move::Place {
+                cell: cell.clone(),
+                value: *value,
+            }),
+            SolveStep::Elimination { cell, value, .. } => Some(Move::Eliminate {
+                cell: cell.clone(),
+                value: *value,
+            }),
+            SolveStep::Backtrack { .. } => None,
+        }
+    }
+
+    /// Returns the step as a JSON object, e.g.
+    /// `{"type":"placement","row":0,"column":4,"value":7,"move":"r1c5=7","technique":"ns"}`.
+    /// Rows and columns start from 0, while the move is in the 1-based notation of
+    /// [`SolveStep::to_move`]. Placements and guesses name their technique by its HoDoKu code,
+    /// see [`SolveStep::technique`]. Eliminations name their cause, e.g.
+    /// `"cause":{"type":"placement","row":0,"column":4}` or
+    /// `"cause":{"type":"locked_candidates","base":{"block":4},"cover":{"row":3}}`. Unique
+    /// rectangles list their corners and values, e.g.
+    /// `"cause":{"type":"unique_rectangle","corners":[{"row":0,"column":0},...],"values":[2,7]}`,
+    /// ALS-XZ the squares of both sets and the restricted common, e.g.
+    /// `"cause":{"type":"als_xz","sets":[[{"row":0,"column":0},...],[...]],"restricted_common":3}`,
+    /// and forcing chains the placements forced, e.g.
+    /// `"cause":{"type":"forcing_chain","chain":["r1c2=3","r4c5=6"]}`.
+    pub fn to_json(&self) -> String {
+        let (kind, cell, value) = match self {
+            SolveStep::Placement { cell, value }
+            | SolveStep::HiddenSingle { cell, value, .. }
+            | SolveStep::BivalueUniversalGrave { cell, value } => ("placement", cell, value),
+            SolveStep::Elimination { cell, value, .. } => ("elimination", cell, value),
+            SolveStep::Guess { cell, value, .. } => ("guess", cell, value),
+            SolveStep::Backtrack { cell, value } => ("backtrack", cell, value),
+        };
+        let mut json = format!(
+            "{{\"type\":\"{}\",\"row\":{},\"column\":{},\"value\":{}",
+            kind, cell.row, cell.column, value
+        );
+        if let SolveStep::Guess { candidates, .. } = self {
+            let candidates: Vec<String> = candidates.iter().map(u8::to_string).collect();
+            json.push_str(&format!(",\"candidates\":[{}]", candidates.join(",")));
+        }
+        if let Some(step) = self.to_move() {
+            json.push_str(&format!(",\"move\":\"{}\"", step));
+        }
+        if let Some(technique) = self.technique() {
+            json.push_str(&format!(",\"technique\":\"{}\"", technique.hodoku_code));
+        }
+        if let SolveStep::Elimination { cause, .. } = self {
+            match cause {
+                EliminationCause::Placement(cause) => json.push_str(&format!(
+                    ",\"cause\":{{\"type\":\"placement\",\"row\":{},\"column\":{}}}",
+                    cause.row, cause.column
+                )),
+                EliminationCause::LockedCandidates { base, cover } => json.push_str(&format!(
+                    ",\"cause\":{{\"type\":\"locked_candidates\",\"base\":{},\"cover\":{}}}",
+                    house_json(*base),
+                    house_json(*cover)
+                )),
+                EliminationCause::UniqueRectangle { corners, values } => {
+                    let corners: Vec<String> = corners
+                        .iter()
+                        .map(|corner| {
+                            format!("{{\"row\":{},\"column\":{}}}", corner.row, corner.column)
+                        })
+                        .collect();
+                    json.push_str(&format!(
+                        ",\"cause\":{{\"type\":\"unique_rectangle\",\"corners\":[{}],\"values\":[{},{}]}}",
+                        corners.join(","),
+                        values[0],
+                        values[1]
+                    ))
+                }
+                EliminationCause::AlsXz {
+                    sets,
+                    restricted_common,
+                } => {
+                    let sets: Vec<String> = sets
+                        .iter()
+                        .map(|set| {
+                            let cells: Vec<String> = set
+                                .iter()
+                                .map(|cell| {
+                                    format!("{{\"row\":{},\"column\":{}}}", cell.row, cell.column)
+                                })
+                                .collect();
+                            format!("[{}]", cells.join(","))
+                        })
+                        .collect();
+                    json.push_str(&format!(
+                        ",\"cause\":{{\"type\":\"als_xz\",\"sets\":[{}],\"restricted_common\":{}}}",
+                        sets.join(","),
+                        restricted_common
+                    ))
+                }
+                EliminationCause::ForcingChain(chain) => {
+                    let chain: Vec<String> =
+                        chain.iter().map(|step| format!("\"{}\"", step)).collect();
+                    json.push_str(&format!(
+                        ",\"cause\":{{\"type\":\"forcing_chain\",\"chain\":[{}]}}",
+                        chain.join(",")
+                    ))
+                }
+            }
+        }
+        json.push('}');
+        json
+    }
+}
+
+/// Returns a house as a JSON object with its 0-based number, e.g. `{"block":4}`.
+///
+/// # Arguments
+///
+/// * `house` - The house.
+fn house_json(house: House) -> String {
+    let (kind, number) = match house {
+        House::Row(row) => ("row", row),
+        House::Column(column) => ("column", column),
+        House::Block(block) => ("block", block),
+    };
+    format!("{{\"{}\":{}}}", kind, number)
+}
+
+impl<W: Write> Tracer<W> {
+    /// Creates a tracer for a solve of the given puzzle.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the trace.
+    /// * `puzzle` - The puzzle the solver starts from.
+    /// * `detailed` - Whether to also write eliminations, and the grid after every square set
+    ///   or cleared.
+    pub fn new(writer: W, puzzle: Sudoku, detailed: bool) -> Self {
+        Tracer {
+            writer,
+            grid: puzzle,
+            steps: 0,
+            detailed,
+        }
+    }
+
+    /// Returns the writer, e.g. to inspect the trace written to a buffer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes the step, numbering and applying the ones that change the grid.
+    fn trace(&mut self, step: SolveStep) {
+        let change = match &step {
+            SolveStep::Placement { cell, value }
+            | SolveStep::HiddenSingle { cell, value, .. }
+            | SolveStep::BivalueUniversalGrave { cell, value }
+            | SolveStep::Guess { cell, value, .. } => Some((cell, *value)),
+            SolveStep::Backtrack { cell, .. } => Some((cell, 0)),
+            SolveStep::Elimination { .. } => None,
+        };
+        match change {
+            Some((cell, value)) => {
+                self.grid.set_unchecked(cell.row, cell.column, value);
+                self.steps += 1;
+                let _ = match step.technique() {
+                    Some(technique) => writeln!(
+                        self.writer,
+                        "{:>4}. {} [{}]",
+                        self.steps,
+                        step.describe(),
+                        technique.hodoku_code
+                    ),
+                    None => writeln!(self.writer, "{:>4}. {}", self.steps, step.describe()),
+                };
+                if self.detailed {
+                    let _ = writeln!(self.writer, "{:#}", self.grid);
+                }
+            }
+            None if self.detailed => {
+                let _ = writeln!(self.writer, "      {}", step.describe());
+            }
+            None => {}
+        }
+    }
+}
+
+impl<W: Write> Debug for Tracer<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tracer")
+            .field("grid", &self.grid)
+            .field("steps", &self.steps)
+            .field("detailed", &self.detailed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W: Write> SolverObserver for Tracer<W> {
+    fn on_placement(&mut self, cell: &Coordinates, value: u8) {
+        self.trace(SolveStep::Placement {
+            cell: cell.clone(),
+            value,
+        });
+    }
+
+    fn on_hidden_single(&mut self, cell: &Coordinates, value: u8, house: House) {
+        self.trace(SolveStep::HiddenSingle {
+            cell: cell.clone(),
+            value,
+            house,
+        });
+    }
+
+    fn on_bivalue_universal_grave(&mut self, cell: &Coordinates, value: u8) {
+        self.trace(SolveStep::BivalueUniversalGrave {
+            cell: cell.clone(),
+            value,
+        });
+    }
+
+    fn on_elimination(&mut self, cell: &Coordinates, value: u8, cause: &EliminationCause) {
+        self.trace(SolveStep::Elimination {
+            cell: cell.clone(),
+            value,
+            cause: cause.clone(),
+        });
+    }
+
+    fn on_guess(&mut self, cell: &Coordinates, value: u8, candidates: &CandidateSet) {
+        let mut candidates: Vec<u8> = candidates.iter().copied().collect();
+        candidates.sort();
+        self.trace(SolveStep::Guess {
+            cell: cell.clone(),
+            value,
+            candidates,
+        });
+    }
+
+    fn on_backtrack(&mut self, cell: &Coordinates, value: u8) {
+        self.trace(SolveStep::Backtrack {
+            cell: cell.clone(),
+            value,
+        });
+    }
+}
+
+/// Forwards every event as a [`SolveStep`], so that another thread can follow the solve live.
+/// Events are dropped once the receiving end hangs up.
+impl SolverObserver for Sender<SolveStep> {
+    fn on_placement(&mut self, cell: &Coordinates, value: u8) {
+        let _ = self.send(SolveStep::Placement {
+            cell: cell.clone(),
+            value,
+        });
+    }
+
+    fn on_hidden_single(&mut self, cell: &Coordinates, value: u8, house: House) {
+        let _ = self.send(SolveStep::HiddenSingle {
+            cell: cell.clone(),
+            value,
+            house,
+        });
+    }
+
+    fn on_bivalue_universal_grave(&mut self, cell: &Coordinates, value: u8) {
+        let _ = self.send(SolveStep::BivalueUniversalGrave {
+            cell: cell.clone(),
+            value,
+        });
+    }
+
+    fn on_elimination(&mut self, cell: &Coordinates, value: u8, cause: &EliminationCause) {
+        let _ = self.send(SolveStep::Elimination {
+            cell: cell.clone(),
+            value,
+            cause: cause.clone(),
+        });
+    }
+
+    fn on_guess(&mut self, cell: &Coordinates, value: u8, candidates: &CandidateSet) {
+        let mut candidates: Vec<u8> = candidates.iter().copied().collect();
+        candidates.sort();
+        let _ = self.send(SolveStep::Guess {
+            cell: cell.clone(),
+            value,
+            candidates,
+        });
+    }
+
+    fn on_backtrack(&mut self, cell: &Coordinates, value: u8) {
+        let _ = self.send(SolveStep::Backtrack {
+            cell: cell.clone(),
+            value,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EliminationCause, SolveStep, SolverObserver, Tracer};
+    use crate::i18n::Locale;
+    use crate::{
+        solver::Solver,
+        sudoku::{Coordinates, House, Sudoku},
+    };
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_to_json() {
+        let step = SolveStep::Guess {
+            cell: Coordinates::from((2, 5)),
+            value: 4,
+            candidates: vec![1, 4, 9],
+        };
+        assert_eq!(
+            "{\"type\":\"guess\",\"row\":2,\"column\":5,\"value\":4,\"candidates\":[1,4,9],\
+             \"move\":\"r3c6=4\",\"technique\":\"bf\"}",
+            step.to_json()
+        );
+        let step = SolveStep::Elimination {
+            cell: Coordinates::from((2, 5)),
+            value: 4,
+            cause: EliminationCause::Placement(Coordinates::from((2, 0))),
+        };
+        assert_eq!(
+            "{\"type\":\"elimination\",\"row\":2,\"column\":5,\"value\":4,\"move\":\"r3c6<>4\",\
+             \"cause\":{\"type\":\"placement\",\"row\":2,\"column\":0}}",
+            step.to_json()
+        );
+        assert_eq!(Some("r3c6<>4".parse().unwrap()), step.to_move());
+        let step = SolveStep::Elimination {
+            cell: Coordinates::from((2, 5)),
+            value: 4,
+            cause: EliminationCause::LockedCandidates {
+                base: House::Block(0),
+                cover: House::Row(2),
+            },
+        };
+        assert_eq!(
+            "{\"type\":\"elimination\",\"row\":2,\"column\":5,\"value\":4,\"move\":\"r3c6<>4\",\
+             \"technique\":\"lc\",\"cause\":{\"type\":\"locked_candidates\",\"base\":{\"block\":0},\
+             \"cover\":{\"row\":2}}}",
+            step.to_json()
+        );
+        let step = SolveStep::Elimination {
+            cell: Coordinates::from((2, 5)),
+            value: 4,
+            cause: EliminationCause::AlsXz {
+                sets: [
+                    vec![Coordinates::from((2, 0))],
+                    vec![Coordinates::from((0, 4)), Coordinates::from((1, 4))],
+                ],
+                restricted_common: 7,
+            },
+        };
+        assert_eq!(
+            "{\"type\":\"elimination\",\"row\":2,\"column\":5,\"value\":4,\"move\":\"r3c6<>4\",\
+             \"technique\":\"axz\",\"cause\":{\"type\":\"als_xz\",\"sets\":[[{\"row\":2,\"column\":0}],\
+             [{\"row\":0,\"column\":4},{\"row\":1,\"column\":4}]],\"restricted_common\":7}}",
+            step.to_json()
+        );
+        let step = SolveStep::Elimination {
+            cell: Coordinates::from((2, 5)),
+            value: 4,
+            cause: EliminationCause::ForcingChain(vec!["r1c6=5".parse().unwrap()]),
+        };
+        assert_eq!(
+            "{\"type\":\"elimination\",\"row\":2,\"column\":5,\"value\":4,\"move\":\"r3c6<>4\",\
+             \"technique\":\"fcc\",\"cause\":{\"type\":\"forcing_chain\",\"chain\":[\"r1c6=5\"]}}",
+            step.to_json()
+        );
+        let step = SolveStep::Backtrack {
+            cell: Coordinates::from((2, 5)),
+            value: 4,
+        };
+        assert_eq!(None, step.to_move());
+    }
+
+    #[test]
+    fn test_describe() {
+        let r4c6 = Coordinates::from((3, 5));
+        assert_eq!(
+            "r4c6 has only one possible value left, 7 (naked single).",
+            SolveStep::Placement {
+                cell: r4c6.clone(),
+                value: 7
+            }
+            .describe()
+        );
+        assert_eq!(
+            "r4c6 cannot be 7, as r4c2 in row 4 is 7.",
+            SolveStep::Elimination {
+                cell: r4c6.clone(),
+                value: 7,
+                cause: EliminationCause::Placement(Coordinates::from((3, 1))),
+            }
+            .describe()
+        );
+        assert_eq!(
+            "r4c6 cannot be 7, as r9c9 is 7 and a constraint keeps them apart.",
+            SolveStep::Elimination {
+                cell: r4c6.clone(),
+                value: 7,
+                cause: EliminationCause::Placement(Coordinates::from((8, 8))),
+            }
+            .describe()
+        );
+        assert_eq!(
+            "r4c6 could be 1, 7 or 9; guess 7.",
+            SolveStep::Guess {
+                cell: r4c6.clone(),
+                value: 7,
+                candidates: vec![1, 7, 9],
+            }
+            .describe()
+        );
+        assert_eq!(
+            "Clear the 7 from r4c6, the guesses led to a contradiction.",
+            SolveStep::Backtrack {
+                cell: r4c6.clone(),
+                value: 7
+            }
+            .describe()
+        );
+        assert_eq!(
+            "Poista 7 kohdasta r4c6, arvaukset johtivat ristiriitaan.",
+            SolveStep::Backtrack {
+                cell: r4c6,
+                value: 7
+            }
+            .describe_in(Locale::Finnish)
+        );
+    }
+
+    #[test]
+    fn test_sender_observer() {
+        let (sender, receiver) = mpsc::channel();
+        let mut solver = Solver::new(Sudoku::new_empty());
+        solver.add_observer(Box::new(sender));
+        solver.solve().unwrap();
+        drop(solver);
+
+        let steps: Vec<SolveStep> = receiver.iter().collect();
+        let placed = steps
+            .iter()
+            .filter(|step| {
+                matches!(
+                    step,
+                    SolveStep::Placement { .. }
+                        | SolveStep::HiddenSingle { .. }
+                        | SolveStep::BivalueUniversalGrave { .. }
+                        | SolveStep::Guess { .. }
+                )
+            })
+            .count();
+        assert!(placed >= 81);
+
+        // Every elimination comes right before the placement, hidden single or guess causing it.
+        for (i, step) in steps.iter().enumerate() {
+            if let SolveStep::Elimination {
+                cell,
+                value,
+                cause: EliminationCause::Placement(cause),
+            } = step
+            {
+                assert!(cell.peers().any(|peer| peer == *cause));
+                let placement = steps[i..]
+                    .iter()
+                    .find(|step| !matches!(step, SolveStep::Elimination { .. }));
+                assert!(matches!(
+                    placement,
+                    Some(SolveStep::Placement { cell, value: placed })
+                        | Some(SolveStep::HiddenSingle { cell, value: placed, .. })
+                        | Some(SolveStep::BivalueUniversalGrave { cell, value: placed })
+                        | Some(SolveStep::Guess { cell, value: placed, .. })
+                        if cell == cause && placed == value
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_tracer() {
+        let puzzle = crate::format::parse_line(crate::corpus::EASY.puzzle, 1).unwrap();
+        let r1c1 = Coordinates::from((0, 0));
+        let mut tracer = Tracer::new(Vec::new(), puzzle, false);
+        tracer.on_elimination(
+            &r1c1,
+            5,
+            &EliminationCause::Placement(Coordinates::from((0, 1))),
+        );
+        tracer.on_placement(&r1c1, 4);
+        let trace = String::from_utf8(tracer.into_inner()).unwrap();
+        assert_eq!(
+            "   1. r1c1 has only one possible value left, 4 (naked single). [ns]\n",
+            trace
+        );
+
+        let mut tracer = Tracer::new(Vec::new(), Sudoku::new_empty(), true);
+        tracer.on_elimination(
+            &r1c1,
+            5,
+            &EliminationCause::Placement(Coordinates::from((0, 1))),
+        );
+        tracer.on_guess(&r1c1, 4, &[4, 5].into());
+        tracer.on_backtrack(&r1c1, 4);
+        let trace = String::from_utf8(tracer.into_inner()).unwrap();
+        assert!(trace.starts_with("      r1c1 cannot be 5, as r1c2 in row 1 is 5.\n"));
+        assert!(trace.contains("   1. r1c1 could be 4 or 5; guess 4. [bf]\n+-------+"));
+        assert!(trace.contains("| 4 . . |"));
+        assert!(trace.contains("   2. Clear the 4 from r1c1"));
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{
+    font::{self, GLYPH_HEIGHT, GLYPH_WIDTH},
+    sudoku::{Grid, Sudoku},
+};
+use image::{DynamicImage, GrayImage};
+use std::path::Path;
+
+/// Share of each cell's width trimmed from every side before looking for a digit,
+/// so that grid lines and their anti-aliasing are not mistaken for ink.
+const CELL_MARGIN: f32 = 0.15;
+
+/// Minimum share of dark pixels in a cell for it to contain a digit.
+const MIN_INK: f32 = 0.02;
+
+/// A rectangle of pixels as (left, top, right, bottom), inclusive.
+type Bounds = (usize, usize, usize, usize);
+
+/// Error type for puzzle recognition.
+#[derive(Debug)]
+pub enum OcrError {
+    /// The image could not be read or decoded.
+    Image(image::ImageError),
+    /// No sudoku grid was found in the image.
+    GridNotFound,
+}
+
+impl From<image::ImageError> for OcrError {
+    fn from(err: image::ImageError) -> Self {
+        OcrError::Image(err)
+    }
+}
+
+/// Reads an image file and recognizes the sudoku in it. See [`recognize`].
+///
+/// # Arguments
+///
+/// * `path` - Location of the photo or screenshot.
+pub fn recognize_file<P: AsRef<Path>>(path: P) -> Result<Sudoku, OcrError> {
+    recognize(&image::open(path)?)
+}
+
+/// Recognizes the sudoku in a photo or screenshot. The grid is located as the largest
+/// connected area of dark pixels and split into 81 cells, and the digit in every cell is
+/// matched against a built-in set of digit shapes.
+///
+/// Works best with screenshots and straight, evenly lit photos where the grid fills most of
+/// the picture; skewed or curved grids are not corrected. Check the result before solving.
+///
+/// # Arguments
+///
+/// * `image` - The decoded image.
+pub fn recognize(image: &DynamicImage) -> Result<Sudoku, OcrError> {
+    let gray = image.to_luma8();
+    let threshold = otsu_threshold(&gray);
+    let width = gray.width() as usize;
+    let height = gray.height() as usize;
+    let dark: Vec<bool> = gray.pixels().map(|pixel| pixel.0[0] < threshold).collect();
+
+    let (grid_mask, (left, top, right, bottom)) =
+        largest_component(&dark, width, height).ok_or(OcrError::GridNotFound)?;
+    let grid_width = (right - left + 1) as f32;
+    let grid_height = (bottom - top + 1) as f32;
+    if grid_width < 27.0 || grid_height < 27.0 {
+        return Err(OcrError::GridNotFound);
+    }
+
+    let cell_width = grid_width / 9.0;
+    let cell_height = grid_height / 9.0;
+    let mut squares: Grid = [0; 9 * 9];
+    for (i, square) in squares.iter_mut().enumerate() {
+        let x0 = (left as f32 + ((i % 9) as f32 + CELL_MARGIN) * cell_width) as usize;
+        let x1 = (left as f32 + ((i % 9 + 1) as f32 - CELL_MARGIN) * cell_width) as usize;
+        let y0 = (top as f32 + ((i / 9) as f32 + CELL_MARGIN) * cell_height) as usize;
+        let y1 = (top as f32 + ((i / 9 + 1) as f32 - CELL_MARGIN) * cell_height) as usize;
+        let ink = |x: usize, y: usize| dark[y * width + x] && !grid_mask[y * width + x];
+        *square = classify(&ink, x0, y0, x1, y1);
+    }
+
+    Ok(Sudoku::new_from_state(squares))
+}
+
+/// Picks the gray level that best separates dark and light pixels (Otsu's method).
+fn otsu_threshold(gray: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in gray.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+    let total: u64 = histogram.iter().sum();
+    let sum: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(level, count)| level as f64 * *count as f64)
+        .sum();
+
+    let mut best = (0.0, 128u8);
+    let mut background_count = 0u64;
+    let mut background_sum = 0.0;
+    for (level, count) in histogram.iter().enumerate() {
+        background_count += count;
+        background_sum += level as f64 * *count as f64;
+        let foreground_count = total - background_count;
+        if background_count == 0 || foreground_count == 0 {
+            continue;
+        }
+        let background_mean = background_sum / background_count as f64;
+        let foreground_mean = (sum - background_sum) / foreground_count as f64;
+        let variance = background_count as f64
+            * foreground_count as f64
+            * (background_mean - foreground_mean).powi(2);
+        if variance > best.0 {
+            best = (variance, level as u8 + 1);
+        }
+    }
+    best.1
+}
+
+/// Finds the largest 4-connected area of dark pixels. Returns a mask of its pixels and its
+/// bounding box.
+fn largest_component(dark: &[bool], width: usize, height: usize) -> Option<(Vec<bool>, Bounds)> {
+    let mut label = vec![0usize; dark.len()];
+    let mut best: Option<(usize, usize, Bounds)> = None;
+    let mut next_label = 0;
+    let mut stack = Vec::new();
+
+    for start in 0..dark.len() {
+        if !dark[start] || label[start] != 0 {
+            continue;
+        }
+        next_label += 1;
+        label[start] = next_label;
+        stack.push(start);
+        let mut size = 0;
+        let mut bounds = (width, height, 0, 0);
+        while let Some(index) = stack.pop() {
+            size += 1;
+            let (x, y) = (index % width, index / width);
+            bounds = (
+                bounds.0.min(x),
+                bounds.1.min(y),
+                bounds.2.max(x),
+                bounds.3.max(y),
+            );
+            let mut neighbors = Vec::with_capacity(4);
+            if x > 0 {
+                neighbors.push(index - 1);
+            }
+            if x + 1 < width {
+                neighbors.push(index + 1);
+            }
+            if y > 0 {
+                neighbors.push(index - width);
+            }
+            if y + 1 < height {
+                neighbors.push(index + width);
+            }
+            for neighbor in neighbors {
+                if dark[neighbor] && label[neighbor] == 0 {
+                    label[neighbor] = next_label;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        if best.is_none_or(|(_, best_size, _)| size > best_size) {
+            best = Some((next_label, size, bounds));
+        }
+    }
+
+    best.map(|(best_label, _, bounds)| (label.iter().map(|l| *l == best_label).collect(), bounds))
+}
+
+/// Recognizes the digit inside the given rectangle of the image, or 0 if it is empty.
+fn classify<F: Fn(usize, usize) -> bool>(
+    ink: &F,
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+) -> u8 {
+    let mut count = 0;
+    let mut bounds = (x1, y1, x0, y0);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            if ink(x, y) {
+                count += 1;
+                bounds = (
+                    bounds.0.min(x),
+                    bounds.1.min(y),
+                    bounds.2.max(x),
+                    bounds.3.max(y),
+                );
+            }
+        }
+    }
+    let area = (x1 - x0) * (y1 - y0);
+    if area == 0 || (count as f32) < MIN_INK * area as f32 {
+        return 0;
+    }
+
+    // Widen the narrower side of the digit to the aspect ratio of the glyphs, keeping it
+    // centered, so that thin digits like 1 are not stretched over the whole glyph.
+    let (mut left, top, mut right, bottom) = (
+        bounds.0 as f32,
+        bounds.1 as f32,
+        bounds.2 as f32 + 1.0,
+        bounds.3 as f32 + 1.0,
+    );
+    let glyph_width = (bottom - top) * GLYPH_WIDTH as f32 / GLYPH_HEIGHT as f32;
+    if right - left < glyph_width {
+        let center = (left + right) / 2.0;
+        left = center - glyph_width / 2.0;
+        right = center + glyph_width / 2.0;
+    }
+
+    let mut features = [0f32; GLYPH_WIDTH * GLYPH_HEIGHT];
+    let sample_width = (right - left) / GLYPH_WIDTH as f32;
+    let sample_height = (bottom - top) / GLYPH_HEIGHT as f32;
+    for (j, feature) in features.iter_mut().enumerate() {
+        let sx0 = left + (j % GLYPH_WIDTH) as f32 * sample_width;
+        let sy0 = top + (j / GLYPH_WIDTH) as f32 * sample_height;
+        let mut inked = 0;
+        let mut total = 0;
+        for y in sy0.max(0.0) as usize..(sy0 + sample_height).ceil() as usize {
+            for x in sx0.max(0.0) as usize..(sx0 + sample_width).ceil() as usize {
+                total += 1;
+                if x >= x0 && x < x1 && y >= y0 && y < y1 && ink(x, y) {
+                    inked += 1;
+                }
+            }
+        }
+        *feature = inked as f32 / total.max(1) as f32;
+    }
+
+    (1..=9)
+        .map(|digit| {
+            let distance: f32 = features
+                .iter()
+                .enumerate()
+                .map(|(j, feature)| {
+                    let expected = font::pixel(digit, j % GLYPH_WIDTH, j / GLYPH_WIDTH) as u8;
+                    (feature - expected as f32).powi(2)
+                })
+                .sum();
+            (digit, distance)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(0, |(digit, _)| digit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{recognize, OcrError};
+    use crate::{
+        font::{self, GLYPH_HEIGHT, GLYPH_WIDTH},
+        solver::Solver,
+        sudoku::Sudoku,
+    };
+    use image::{DynamicImage, GrayImage, Luma};
+
+    /// Renders the sudoku as a clean screenshot-like image.
+    fn render(sudoku: &Sudoku, cell: u32, scale: u32) -> DynamicImage {
+        let margin = 10;
+        let size = cell * 9 + 2 * margin;
+        let mut image = GrayImage::from_pixel(size, size, Luma([255]));
+        for i in 0..=9 {
+            let width = if i % 3 == 0 { 3 } else { 1 };
+            for offset in 0..width {
+                let line = margin + i * cell + offset;
+                let line = line.min(margin + 9 * cell);
+                for along in margin..=margin + 9 * cell {
+                    image.put_pixel(line, along, Luma([0]));
+                    image.put_pixel(along, line, Luma([0]));
+                }
+            }
+        }
+        for (i, value) in sudoku.squares.iter().enumerate() {
+            if *value == 0 {
+                continue;
+            }
+            let x0 = margin + (i % 9) as u32 * cell + (cell - GLYPH_WIDTH as u32 * scale) / 2;
+            let y0 = margin + (i / 9) as u32 * cell + (cell - GLYPH_HEIGHT as u32 * scale) / 2;
+            for y in 0..GLYPH_HEIGHT as u32 * scale {
+                for x in 0..GLYPH_WIDTH as u32 * scale {
+                    if font::pixel(*value, (x / scale) as usize, (y / scale) as usize) {
+                        image.put_pixel(x0 + x, y0 + y, Luma([20]));
+                    }
+                }
+            }
+        }
+        DynamicImage::ImageLuma8(image)
+    }
+
+    #[test]
+    fn test_recognize_rendered() {
+        let sudoku = Solver::generate_seeded(40, 5).unwrap();
+        let recognized = recognize(&render(&sudoku, 40, 4)).unwrap();
+        assert_eq!(sudoku.squares, recognized.squares);
+    }
+
+    #[test]
+    fn test_blank_image() {
+        let image = DynamicImage::ImageLuma8(GrayImage::from_pixel(100, 100, Luma([255])));
+        assert!(matches!(recognize(&image), Err(OcrError::GridNotFound)));
+    }
+}
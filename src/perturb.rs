@@ -0,0 +1,206 @@
+//! Controlled damage to puzzles, for fuzz testing code that handles sudokus against the
+//! validator and solver of this crate. Every change comes back labeled with what was done and
+//! whether the result still follows the rules.
+
+use crate::{
+    solver::Solver,
+    sudoku::{Coordinates, Sudoku},
+};
+use rand::{seq::SliceRandom, Rng};
+use std::fmt::Display;
+
+/// A change made to a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Perturbation {
+    /// The givens of these squares were removed.
+    RemovedClues(Vec<Coordinates>),
+    /// The value was written into an empty square, repeating the given of `repeats`.
+    AddedConflict {
+        cell: Coordinates,
+        value: u8,
+        repeats: Coordinates,
+    },
+    /// The givens of two squares were swapped.
+    SwappedGivens(Coordinates, Coordinates),
+}
+
+/// A changed puzzle and what was changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Perturbed {
+    /// The puzzle after the change.
+    pub puzzle: Sudoku,
+    /// What was changed.
+    pub perturbation: Perturbation,
+    /// Whether no value repeats in a row, column or block after the change. Removing clues
+    /// keeps a valid puzzle valid, though it may no longer have a unique solution.
+    pub valid: bool,
+}
+
+/// Writes the change for logs and test names, e.g. `swapped r1c3 and r2c1`.
+impl Display for Perturbation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Perturbation::RemovedClues(cells) => {
+                let cells: Vec<String> = cells.iter().map(|cell| cell.to_string()).collect();
+                write!(f, "removed {}", cells.join(", "))
+            }
+            Perturbation::AddedConflict {
+                cell,
+                value,
+                repeats,
+            } => write!(f, "added {} at {}, repeating {}", value, cell, repeats),
+            Perturbation::SwappedGivens(first, second) => {
+                write!(f, "swapped {} and {}", first, second)
+            }
+        }
+    }
+}
+
+impl Perturbed {
+    /// Labels a changed puzzle, checking it against the rules. Its set squares become its
+    /// givens.
+    fn new(mut puzzle: Sudoku, perturbation: Perturbation) -> Self {
+        puzzle.lock_givens();
+        Perturbed {
+            valid: Solver::new(puzzle.clone()).find_conflicts().is_empty(),
+            puzzle,
+            perturbation,
+        }
+    }
+}
+
+/// Returns the coordinates of the givens of the puzzle in reading order.
+fn givens(puzzle: &Sudoku) -> Vec<Coordinates> {
+    (0..9 * 9)
+        .filter(|i| puzzle.squares[*i] != 0)
+        .map(|i| Coordinates::from((i / 9, i % 9)))
+        .collect()
+}
+
+/// Removes randomly chosen givens.
+///
+/// # Arguments
+///
+/// * `puzzle` - The puzzle to change.
+/// * `count` - Number of givens to remove; all of them if the puzzle has fewer.
+/// * `rng` - Random number generator to choose with.
+pub fn remove_clues<R: Rng + ?Sized>(puzzle: &Sudoku, count: usize, rng: &mut R) -> Perturbed {
+    let mut cells: Vec<Coordinates> = givens(puzzle)
+        .choose_multiple(rng, count)
+        .cloned()
+        .collect();
+    cells.sort_by_key(|cell| (cell.row, cell.column));
+    let mut retval = puzzle.clone();
+    for cell in &cells {
+        retval.set_unchecked(cell.row, cell.column, 0);
+    }
+    Perturbed::new(retval, Perturbation::RemovedClues(cells))
+}
+
+/// Writes the value of a random given into an empty square in the same row, column or block.
+/// Returns `None` if no empty square sees a given.
+///
+/// # Arguments
+///
+/// * `puzzle` - The puzzle to change.
+/// * `rng` - Random number generator to choose with.
+pub fn add_conflict<R: Rng + ?Sized>(puzzle: &Sudoku, rng: &mut R) -> Option<Perturbed> {
+    let pairs: Vec<(Coordinates, Coordinates)> = givens(puzzle)
+        .into_iter()
+        .flat_map(|given| {
+            given
+                .peers()
+                .filter(|peer| puzzle.get_unchecked(peer.row, peer.column) == 0)
+                .map(move |peer| (peer, given.clone()))
+        })
+        .collect();
+    let (cell, repeats) = pairs.choose(rng)?.clone();
+    let value = puzzle.get_unchecked(repeats.row, repeats.column);
+    let mut retval = puzzle.clone();
+    retval.set_unchecked(cell.row, cell.column, value);
+    Some(Perturbed::new(
+        retval,
+        Perturbation::AddedConflict {
+            cell,
+            value,
+            repeats,
+        },
+    ))
+}
+
+/// Swaps the values of two random givens with different values. Returns `None` if the puzzle
+/// has no such pair.
+///
+/// # Arguments
+///
+/// * `puzzle` - The puzzle to change.
+/// * `rng` - Random number generator to choose with.
+pub fn swap_givens<R: Rng + ?Sized>(puzzle: &Sudoku, rng: &mut R) -> Option<Perturbed> {
+    let givens = givens(puzzle);
+    let value = |cell: &Coordinates| puzzle.get_unchecked(cell.row, cell.column);
+    let first = givens.choose(rng)?;
+    let others: Vec<&Coordinates> = givens
+        .iter()
+        .filter(|other| value(other) != value(first))
+        .collect();
+    let second = others.choose(rng)?;
+    let mut retval = puzzle.clone();
+    retval.set_unchecked(first.row, first.column, value(second));
+    retval.set_unchecked(second.row, second.column, value(first));
+    Some(Perturbed::new(
+        retval,
+        Perturbation::SwappedGivens(first.clone(), (*second).clone()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_conflict, remove_clues, swap_givens, Perturbation};
+    use crate::{corpus, format::parse_line, sudoku::Sudoku};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_perturb() {
+        let puzzle = parse_line(corpus::EASY.puzzle, 1).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+
+        let removed = remove_clues(&puzzle, 5, &mut rng);
+        assert_eq!(32 - 5, removed.puzzle.set_count);
+        assert!(removed.valid);
+        let Perturbation::RemovedClues(cells) = &removed.perturbation else {
+            panic!("{:?}", removed.perturbation);
+        };
+        assert!(cells
+            .iter()
+            .all(|cell| puzzle.get_unchecked(cell.row, cell.column) != 0));
+        assert!(removed.perturbation.to_string().starts_with("removed r"));
+        assert_eq!(0, remove_clues(&puzzle, 100, &mut rng).puzzle.set_count);
+
+        let added = add_conflict(&puzzle, &mut rng).unwrap();
+        assert!(!added.valid);
+        let Perturbation::AddedConflict {
+            cell,
+            value,
+            repeats,
+        } = &added.perturbation
+        else {
+            panic!("{:?}", added.perturbation);
+        };
+        assert_eq!(0, puzzle.get_unchecked(cell.row, cell.column));
+        assert_eq!(*value, puzzle.get_unchecked(repeats.row, repeats.column));
+        assert!(cell.peers().any(|peer| &peer == repeats));
+        assert_eq!(None, add_conflict(&Sudoku::new_empty(), &mut rng));
+
+        let swapped = swap_givens(&puzzle, &mut rng).unwrap();
+        let Perturbation::SwappedGivens(first, second) = &swapped.perturbation else {
+            panic!("{:?}", swapped.perturbation);
+        };
+        assert_eq!(
+            puzzle.get_unchecked(first.row, first.column),
+            swapped.puzzle.get_unchecked(second.row, second.column)
+        );
+        assert_eq!(puzzle.set_count, swapped.puzzle.set_count);
+        assert_eq!(None, swap_givens(&Sudoku::new_empty(), &mut rng));
+    }
+}
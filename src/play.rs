@@ -0,0 +1,1490 @@
+//! Playing, entering and replaying puzzles in the terminal. [`Game`], [`Editor`], [`Entry`] and
+//! [`Playback`] hold their state and key bindings apart from the terminal, so that they can be
+//! tested; [`play`], [`edit`], [`type_in`] and [`watch`] run them on standard output. A
+//! [`Watcher`] draws the steps of a solver while it works.
+
+use crate::{
+    constraint::{CandidateGrid, CandidateSet},
+    format::{parse_line, write_line, FormatError},
+    hint::{Hint, HintLevel},
+    leaderboard::GameRecord,
+    observer::SolverObserver,
+    replay::{Actor, Replay},
+    solver::{DifficultyReport, Solver},
+    sudoku::{Coordinates, Sudoku},
+};
+use chrono::DateTime;
+use console::{style, Key, Term};
+use std::{
+    collections::{HashMap, HashSet},
+    io, thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// What the game wants done after a key press.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Action {
+    /// Keep reading keys.
+    Continue,
+    /// The player asked to stop.
+    Quit,
+    /// The puzzle in the editor should be saved.
+    Save,
+    /// The puzzle entered should be solved.
+    Solve,
+    /// The puzzle entered should be played.
+    Play,
+}
+
+/// What the editor knows about the puzzle entered so far.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Readout {
+    /// This many squares break the rules.
+    Conflicts(usize),
+    /// The givens cannot be completed.
+    NoSolution,
+    /// The givens can be completed in more than one way.
+    MultipleSolutions,
+    /// The puzzle is proper, and this is how hard it is.
+    Unique(DifficultyReport),
+}
+
+/// A puzzle being entered square by square.
+#[derive(Debug, Clone)]
+pub struct Editor {
+    grid: Sudoku,
+    cursor: Coordinates,
+}
+
+/// A puzzle being typed as 81 characters in reading order, for quick entry without moving
+/// around the grid.
+#[derive(Debug, Default, Clone)]
+pub struct Entry {
+    typed: Vec<u8>,
+}
+
+/// Playing time that only runs while the timer is started.
+#[derive(Debug, Default, Clone)]
+pub struct Timer {
+    elapsed: Duration,
+    started: Option<Instant>,
+}
+
+/// A puzzle being played: the givens, the player's entries and the selected square.
+#[derive(Debug, Clone)]
+pub struct Game {
+    puzzle: Sudoku,
+    board: Sudoku,
+    cursor: Coordinates,
+    show_candidates: bool,
+    pencil_marks: bool,
+    hints: u32,
+    mistakes: u32,
+    max_mistakes: Option<u32>,
+    solution: Option<Sudoku>,
+    hint_level: Option<HintLevel>,
+    message: Option<String>,
+    timer: Timer,
+    replay: Replay,
+}
+
+/// A replay being watched move by move.
+#[derive(Debug, Clone)]
+pub struct Playback {
+    replay: Replay,
+    position: usize,
+}
+
+/// Draws the grid of a solver on the terminal after every placement, guess and backtrack,
+/// pausing between steps so that they can be followed.
+#[derive(Debug)]
+pub struct Watcher {
+    puzzle: Sudoku,
+    board: Sudoku,
+    steps: usize,
+    last: Option<(Coordinates, String)>,
+    delay: Duration,
+    term: Option<Term>,
+}
+
+impl Timer {
+    /// Creates a stopped timer that has already run for the given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - Time run so far.
+    pub fn new(elapsed: Duration) -> Self {
+        Timer {
+            elapsed,
+            started: None,
+        }
+    }
+
+    /// Starts the timer, or keeps it running.
+    pub fn start(&mut self) {
+        self.started.get_or_insert_with(Instant::now);
+    }
+
+    /// Stops the timer, keeping the time run so far.
+    pub fn pause(&mut self) {
+        if let Some(started) = self.started.take() {
+            self.elapsed += started.elapsed();
+        }
+    }
+
+    /// Returns whether the timer is running.
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Returns the time the timer has run, not counting pauses.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+            + self
+                .started
+                .map_or(Duration::ZERO, |started| started.elapsed())
+    }
+}
+
+impl Game {
+    /// Starts a game with the cursor on the top left square. The solution is found up front,
+    /// so that entries can be checked against it.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The givens.
+    pub fn new(puzzle: Sudoku) -> Self {
+        let mut board = puzzle.clone();
+        board.lock_givens();
+        Game {
+            board,
+            solution: Solver::new(puzzle.clone()).unique_solution().ok(),
+            replay: Replay::new(puzzle.clone()),
+            puzzle,
+            cursor: Coordinates::from((0, 0)),
+            show_candidates: false,
+            pencil_marks: false,
+            hints: 0,
+            mistakes: 0,
+            max_mistakes: None,
+            hint_level: None,
+            message: None,
+            timer: Timer::default(),
+        }
+    }
+
+    /// Limits the number of mistakes: wrong values are not entered, and the game is lost
+    /// after the given number of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_mistakes` - Mistakes that end the game, or `None` for no limit.
+    pub fn set_max_mistakes(&mut self, max_mistakes: Option<u32>) {
+        self.max_mistakes = max_mistakes;
+    }
+
+    /// Get the limit of mistakes, if any.
+    pub fn max_mistakes(&self) -> Option<u32> {
+        self.max_mistakes
+    }
+
+    /// Get the unique solution of the puzzle, `None` if it does not have one.
+    pub fn solution(&self) -> Option<&Sudoku> {
+        self.solution.as_ref()
+    }
+
+    /// Resumes a game saved with [`Game::to_session`]. The timer is left stopped.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The saved game.
+    pub fn from_session(session: &str) -> Result<Self, FormatError> {
+        let mut fields = HashMap::new();
+        for (i, line) in session.lines().enumerate() {
+            if let Some((key, value)) = line.split_once('=') {
+                fields.insert(key.trim(), (i + 1, value.trim()));
+            }
+        }
+        let field = |key: &str| {
+            fields
+                .get(key)
+                .copied()
+                .ok_or_else(|| FormatError::MissingColumn(key.to_string()))
+        };
+        let number = |key: &str| {
+            let (line_number, value) = field(key)?;
+            value
+                .parse::<u64>()
+                .map_err(|_| FormatError::BadNumber(line_number, value.to_string()))
+        };
+        let (line_number, puzzle) = field("puzzle")?;
+        let mut game = Game::new(parse_line(puzzle, line_number)?);
+        let (line_number, board) = field("board")?;
+        let board = parse_line(board, line_number)?;
+        game.hints = number("hints")? as u32;
+        game.mistakes = number("mistakes")? as u32;
+        game.timer = Timer::new(Duration::from_secs(number("seconds")?));
+        // The moves before the save are not kept, so the replay starts with the saved board.
+        for (i, value) in board.squares.iter().enumerate() {
+            if *value != game.puzzle.squares[i] {
+                game.set(&Coordinates::from((i / 9, i % 9)), *value, Actor::Player);
+            }
+        }
+        if fields.contains_key("max_mistakes") {
+            game.max_mistakes = Some(number("max_mistakes")? as u32);
+        }
+        Ok(game)
+    }
+
+    /// Returns the game as `key=value` lines to resume it later with [`Game::from_session`]:
+    /// the puzzle, the board, the playing time in seconds, the hints and mistakes so far and
+    /// the limit of mistakes if there is one.
+    pub fn to_session(&self) -> String {
+        let mut retval = format!(
+            "puzzle={}\nboard={}\nseconds={}\nhints={}\nmistakes={}\n",
+            write_line(&self.puzzle),
+            write_line(&self.board),
+            self.timer.elapsed().as_secs(),
+            self.hints,
+            self.mistakes
+        );
+        if let Some(max_mistakes) = self.max_mistakes {
+            retval.push_str(&format!("max_mistakes={}\n", max_mistakes));
+        }
+        retval
+    }
+
+    /// Get the givens with the player's entries.
+    pub fn board(&self) -> &Sudoku {
+        &self.board
+    }
+
+    /// Get the selected square.
+    pub fn cursor(&self) -> &Coordinates {
+        &self.cursor
+    }
+
+    /// Returns whether the square holds a given, which the player cannot change.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - The square to check.
+    pub fn is_given(&self, cell: &Coordinates) -> bool {
+        self.puzzle.get_unchecked(cell.row, cell.column) != 0
+    }
+
+    /// Moves the cursor, wrapping around the edges of the grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - Rows to move down; negative moves up.
+    /// * `columns` - Columns to move right; negative moves left.
+    pub fn move_cursor(&mut self, rows: isize, columns: isize) {
+        self.cursor.row = (self.cursor.row as isize + rows).rem_euclid(9) as usize;
+        self.cursor.column = (self.cursor.column as isize + columns).rem_euclid(9) as usize;
+    }
+
+    /// Get the moves made so far.
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Get the playing time.
+    pub fn timer(&self) -> &Timer {
+        &self.timer
+    }
+
+    /// Get the playing time, to start or pause it.
+    pub fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.timer
+    }
+
+    /// Get the number of hints used.
+    pub fn hints(&self) -> u32 {
+        self.hints
+    }
+
+    /// Get the number of wrong values entered.
+    pub fn mistakes(&self) -> u32 {
+        self.mistakes
+    }
+
+    /// Returns whether the limit of mistakes has been reached.
+    pub fn is_lost(&self) -> bool {
+        self.max_mistakes.is_some_and(|max| self.mistakes >= max)
+    }
+
+    /// Enters a value in the selected square, or clears it with 0. Returns whether the board
+    /// changed; givens are left as they are, and so is everything once the game is lost.
+    /// A value other than the one in the solution counts as a mistake, or one that breaks a
+    /// rule if the puzzle has no unique solution. With a limit of mistakes, wrong values are
+    /// not entered.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to enter ([0,9]).
+    pub fn enter(&mut self, value: u8) -> bool {
+        let cell = self.cursor.clone();
+        if self.is_lost()
+            || self.is_given(&cell)
+            || self.board.get_unchecked(cell.row, cell.column) == value
+        {
+            return false;
+        }
+        let wrong = value != 0
+            && match &self.solution {
+                Some(solution) => solution.get_unchecked(cell.row, cell.column) != value,
+                None => {
+                    let mut board = self.board.clone();
+                    board.set_unchecked(cell.row, cell.column, 0);
+                    !Solver::new(board)
+                        .get_possible(cell.row, cell.column)
+                        .is_ok_and(|possible| possible.contains(&value))
+                }
+            };
+        if wrong {
+            self.mistakes += 1;
+            if self.max_mistakes.is_some() {
+                return false;
+            }
+        }
+        if !self.set(&cell, value, Actor::Player) {
+            return false;
+        }
+        self.hint_level = None;
+        self.message = None;
+        true
+    }
+
+    /// Sets a square with [`Sudoku::set`], which leaves the givens alone, and records the move
+    /// in the replay. Returns whether the square was set.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - The square.
+    /// * `value` - The value to set, 0 to clear it.
+    /// * `actor` - Who made the move.
+    fn set(&mut self, cell: &Coordinates, value: u8, actor: Actor) -> bool {
+        if self.board.set(cell.row, cell.column, value).is_err() {
+            return false;
+        }
+        self.replay.record(self.timer.elapsed(), cell, value, actor);
+        true
+    }
+
+    /// Gives the next tier of a hint about the next step and counts it: first the house to
+    /// look at, then the technique and finally the value, which is entered with the cursor
+    /// moved to it. The next hint starts over from the house. Values that do not match the
+    /// solution are pointed out first. Without a step the solver can find, the last tier fills
+    /// the selected square from the solution instead. Returns the hint shown to the player.
+    pub fn hint(&mut self) -> String {
+        let level = self
+            .hint_level
+            .and_then(HintLevel::next)
+            .unwrap_or(HintLevel::Region);
+        self.hints += 1;
+        let message = match self.hint_tier(level) {
+            Some(message) => {
+                self.hint_level = (level != HintLevel::Value).then_some(level);
+                message
+            }
+            None => {
+                self.hint_level = None;
+                "No hint for this square.".to_string()
+            }
+        };
+        self.message = Some(message.clone());
+        message
+    }
+
+    /// Returns one tier of a hint, entering the value at the last tier, or `None` if there is
+    /// nothing to hint at.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - How much to give away.
+    fn hint_tier(&mut self, level: HintLevel) -> Option<String> {
+        if let Some(solution) = &self.solution {
+            if let Some(i) = (0..9 * 9).find(|&i| {
+                self.board.squares[i] != 0 && self.board.squares[i] != solution.squares[i]
+            }) {
+                return Some(format!(
+                    "{} does not match the solution.",
+                    Coordinates::from((i / 9, i % 9))
+                ));
+            }
+        }
+        match Hint::find(&Solver::new(self.board.clone())) {
+            Some(hint) => {
+                if level == HintLevel::Value {
+                    self.set(&hint.cell, hint.value, Actor::Solver);
+                    self.cursor = hint.cell.clone();
+                }
+                Some(hint.describe(level))
+            }
+            None if level == HintLevel::Value => {
+                let cell = self.cursor.clone();
+                let value = self.solution.as_ref()?.get_unchecked(cell.row, cell.column);
+                if self.is_given(&cell) || self.board.get_unchecked(cell.row, cell.column) != 0 {
+                    return None;
+                }
+                self.set(&cell, value, Actor::Solver);
+                Some(format!("{} is {}.", cell, value))
+            }
+            None => Some("There is no single left; the next step needs a guess.".to_string()),
+        }
+    }
+
+    /// Returns the record of the game for the [`Leaderboard`](crate::leaderboard::Leaderboard),
+    /// dated today.
+    pub fn record(&self) -> GameRecord {
+        let date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|now| DateTime::from_timestamp(now.as_secs() as i64, 0))
+            .map(|now| now.date_naive());
+        GameRecord {
+            puzzle: self.puzzle.clone(),
+            date,
+            seconds: self.timer.elapsed().as_secs(),
+            hints: self.hints,
+            mistakes: self.mistakes,
+        }
+    }
+
+    /// Returns every square holding the same value as another square it shares a row, column
+    /// or block with.
+    pub fn conflicts(&self) -> HashSet<Coordinates> {
+        conflicts(&self.board)
+    }
+
+    /// Returns the values the rules still allow in the selected square, in ascending order,
+    /// or `None` if the square is filled.
+    pub fn candidates(&self) -> Option<Vec<u8>> {
+        let cell = &self.cursor;
+        if self.board.get_unchecked(cell.row, cell.column) != 0 {
+            return None;
+        }
+        let possible = Solver::new(self.board.clone())
+            .get_possible(cell.row, cell.column)
+            .ok()?;
+        let mut candidates: Vec<u8> = possible.into_iter().collect();
+        candidates.sort_unstable();
+        Some(candidates)
+    }
+
+    /// Shows or hides the candidates of the selected square below the grid.
+    pub fn toggle_candidates(&mut self) {
+        self.show_candidates = !self.show_candidates;
+    }
+
+    /// Turns the pencil marks on or off. While they are on, every empty square shows the
+    /// values the rules still allow in it, updated as values are entered and cleared.
+    pub fn toggle_pencil_marks(&mut self) {
+        self.pencil_marks = !self.pencil_marks;
+    }
+
+    /// Returns the pencil marks of every square, empty for set squares, or `None` while they
+    /// are off.
+    pub fn pencil_marks(&self) -> Option<CandidateGrid> {
+        self.pencil_marks
+            .then(|| Solver::new(self.board.clone()).all_candidates())
+    }
+
+    /// Returns whether every square is filled without breaking a rule.
+    pub fn is_solved(&self) -> bool {
+        self.board.set_count == 9 * 9 && self.conflicts().is_empty()
+    }
+
+    /// Acts on a key press: the arrow keys or hjkl move, 1-9 enter a value, 0, space,
+    /// backspace and delete clear the square, c shows or hides the candidates, a turns the
+    /// pencil marks on or off, ? gives the next tier of a hint, p or ctrl-z hide the board
+    /// and pause the timer and q or escape quit. While paused, q and escape still quit and
+    /// every other key resumes.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key pressed.
+    pub fn handle_key(&mut self, key: Key) -> Action {
+        if !self.timer.is_running() {
+            match key {
+                Key::Char('q') | Key::Escape => return Action::Quit,
+                _ => self.timer.start(),
+            }
+            return Action::Continue;
+        }
+        match key {
+            Key::ArrowUp | Key::Char('k') => self.move_cursor(-1, 0),
+            Key::ArrowDown | Key::Char('j') => self.move_cursor(1, 0),
+            Key::ArrowLeft | Key::Char('h') => self.move_cursor(0, -1),
+            Key::ArrowRight | Key::Char('l') => self.move_cursor(0, 1),
+            Key::Char(digit @ '1'..='9') => {
+                self.enter(digit as u8 - b'0');
+            }
+            Key::Char('0' | ' ') | Key::Backspace | Key::Del => {
+                self.enter(0);
+            }
+            Key::Char('c') => self.toggle_candidates(),
+            Key::Char('a') => self.toggle_pencil_marks(),
+            Key::Char('?') => {
+                self.hint();
+            }
+            // Raw terminals deliver ctrl-z as a character instead of suspending the program.
+            Key::Char('p' | '\u{1a}') => self.timer.pause(),
+            Key::Char('q') | Key::Escape => return Action::Quit,
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    /// Draws the grid with row and column numbers, followed by the time, the status and the
+    /// keys. The selected square is put in brackets and squares breaking a rule are marked with
+    /// a '*'; on terminals with colors, givens are bold and broken rules red. The board is
+    /// hidden while the timer is stopped.
+    pub fn render(&self) -> String {
+        let seconds = self.timer.elapsed().as_secs();
+        let time = format!("{}:{:02}", seconds / 60, seconds % 60);
+        if !self.timer.is_running() && !self.is_solved() && !self.is_lost() {
+            return format!("Paused at {}. Press any key to continue, q quits.\n", time);
+        }
+        let conflicts = self.conflicts();
+        let bold = |cell: &Coordinates| self.is_given(cell);
+        let mut retval = match self.pencil_marks() {
+            Some(marks) => render_pencil_grid(&self.board, &marks, &self.cursor, &conflicts, bold),
+            None => render_grid(&self.board, &self.cursor, &conflicts, bold),
+        };
+
+        retval.push_str(&format!("Time {}\n", time));
+        if !conflicts.is_empty() {
+            retval.push_str(&format!(
+                "{} squares break the rules.\n",
+                style(conflicts.len()).red()
+            ));
+        } else if self.is_solved() {
+            retval.push_str(&format!("{}\n", style("Solved!").green().bold()));
+        }
+        match self.max_mistakes {
+            Some(max) if self.is_lost() => retval.push_str(&format!(
+                "{} {} mistakes out of {}.\n",
+                style("Game over:").red().bold(),
+                self.mistakes,
+                max
+            )),
+            Some(max) => retval.push_str(&format!(
+                "Mistakes: {}/{}, hints: {}\n",
+                self.mistakes, max, self.hints
+            )),
+            None if self.mistakes > 0 || self.hints > 0 => retval.push_str(&format!(
+                "Mistakes: {}, hints: {}\n",
+                self.mistakes, self.hints
+            )),
+            None => {}
+        }
+        if let Some(message) = &self.message {
+            retval.push_str(&format!("Hint: {}\n", message));
+        }
+        if self.show_candidates {
+            match self.candidates() {
+                Some(candidates) => {
+                    let candidates: Vec<String> = candidates.iter().map(u8::to_string).collect();
+                    retval.push_str(&format!(
+                        "Candidates for {}: {}\n",
+                        self.cursor,
+                        candidates.join(" ")
+                    ));
+                }
+                None => retval.push_str(&format!("{} is filled.\n", self.cursor)),
+            }
+        }
+        retval.push_str(
+            "Arrows/hjkl move, 1-9 enter, 0 clears, c candidates, a pencil marks, ? hint, \
+             p pauses, q quits.\n",
+        );
+        retval
+    }
+}
+
+impl Playback {
+    /// Starts watching the replay from the puzzle as it was given.
+    ///
+    /// # Arguments
+    ///
+    /// * `replay` - The replay to watch.
+    pub fn new(replay: Replay) -> Self {
+        Playback {
+            replay,
+            position: 0,
+        }
+    }
+
+    /// Get the number of moves made on the board shown.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Acts on a key press: the right arrow, l, space and enter make the next move, the left
+    /// arrow, h and backspace take the last one back, home and end jump to the start and the
+    /// end and q or escape quit.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key pressed.
+    pub fn handle_key(&mut self, key: Key) -> Action {
+        let last = self.replay.moves().len();
+        match key {
+            Key::ArrowRight | Key::Char('l' | ' ') | Key::Enter => {
+                self.position = (self.position + 1).min(last)
+            }
+            Key::ArrowLeft | Key::Char('h') | Key::Backspace => {
+                self.position = self.position.saturating_sub(1)
+            }
+            Key::Home => self.position = 0,
+            Key::End => self.position = last,
+            Key::Char('q') | Key::Escape => return Action::Quit,
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    /// Draws the board after the moves made so far with the last move in brackets, followed
+    /// by the move and the keys.
+    pub fn render(&self) -> String {
+        let board = self.replay.board_at(self.position);
+        let last = match self.position {
+            0 => None,
+            position => self.replay.moves().get(position - 1),
+        };
+        let cursor = last.map_or(Coordinates::from((9, 0)), |step| step.cell.clone());
+        let puzzle = self.replay.puzzle();
+        let mut retval = render_grid(&board, &cursor, &conflicts(&board), |cell| {
+            puzzle.get_unchecked(cell.row, cell.column) != 0
+        });
+        match last {
+            Some(step) => {
+                let seconds = step.elapsed.as_secs();
+                let change = match step.value {
+                    0 => format!("clears {}", step.cell),
+                    value => format!("sets {} to {}", step.cell, value),
+                };
+                retval.push_str(&format!(
+                    "Move {}/{} at {}:{:02}: the {} {}.\n",
+                    self.position,
+                    self.replay.moves().len(),
+                    seconds / 60,
+                    seconds % 60,
+                    match step.actor {
+                        Actor::Player => "player",
+                        Actor::Solver => "solver",
+                    },
+                    change
+                ));
+            }
+            None => retval.push_str(&format!(
+                "Start, {} moves to go.\n",
+                self.replay.moves().len()
+            )),
+        }
+        retval.push_str("Right/space next, left back, home/end jump, q quits.\n");
+        retval
+    }
+}
+
+impl Watcher {
+    /// Creates a watcher that draws on standard output, and draws the puzzle.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The puzzle the solver starts from.
+    /// * `delay` - Time to wait after drawing each step.
+    pub fn new(puzzle: Sudoku, delay: Duration) -> Self {
+        let retval = Watcher {
+            board: puzzle.clone(),
+            puzzle,
+            steps: 0,
+            last: None,
+            delay,
+            term: Some(Term::stdout()),
+        };
+        retval.draw();
+        retval
+    }
+
+    /// Get the number of steps seen.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Draws the grid with the square of the last step in brackets, followed by the step.
+    pub fn render(&self) -> String {
+        let cursor = self
+            .last
+            .as_ref()
+            .map_or(Coordinates::from((9, 0)), |(cell, _)| cell.clone());
+        let mut retval = render_grid(&self.board, &cursor, &HashSet::new(), |cell| {
+            self.puzzle.get_unchecked(cell.row, cell.column) != 0
+        });
+        match &self.last {
+            Some((_, description)) => {
+                retval.push_str(&format!("Step {}: {}.\n", self.steps, description))
+            }
+            None => retval.push_str(&format!("Start, {} givens.\n", self.puzzle.set_count)),
+        }
+        retval
+    }
+
+    /// Makes a step on the board and draws it.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - The square changed.
+    /// * `value` - Its new value, 0 if it was cleared.
+    /// * `description` - What the solver did.
+    fn step(&mut self, cell: &Coordinates, value: u8, description: String) {
+        self.board.set_unchecked(cell.row, cell.column, value);
+        self.steps += 1;
+        self.last = Some((cell.clone(), description));
+        self.draw();
+    }
+
+    /// Draws the board on the terminal, if any, and waits. Errors are ignored, as observers
+    /// cannot stop the solver.
+    fn draw(&self) {
+        if let Some(term) = &self.term {
+            let _ = term
+                .clear_screen()
+                .and_then(|_| term.write_str(&self.render()));
+            thread::sleep(self.delay);
+        }
+    }
+}
+
+impl SolverObserver for Watcher {
+    fn on_placement(&mut self, cell: &Coordinates, value: u8) {
+        self.step(cell, value, format!("{} is {}", cell, value));
+    }
+
+    fn on_guess(&mut self, cell: &Coordinates, value: u8, candidates: &CandidateSet) {
+        let mut candidates: Vec<u8> = candidates.iter().copied().collect();
+        candidates.sort_unstable();
+        let candidates: Vec<String> = candidates.iter().map(|value| value.to_string()).collect();
+        self.step(
+            cell,
+            value,
+            format!(
+                "guess {} for {} out of {}",
+                value,
+                cell,
+                candidates.join(", ")
+            ),
+        );
+    }
+
+    fn on_backtrack(&mut self, cell: &Coordinates, value: u8) {
+        self.step(cell, 0, format!("take {} back from {}", value, cell));
+    }
+}
+
+impl Editor {
+    /// Starts entering a puzzle with the cursor on the top left square.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid` - The squares entered so far, usually [`Sudoku::new_empty`].
+    pub fn new(grid: Sudoku) -> Self {
+        Editor {
+            grid,
+            cursor: Coordinates::from((0, 0)),
+        }
+    }
+
+    /// Get the puzzle entered so far.
+    pub fn grid(&self) -> &Sudoku {
+        &self.grid
+    }
+
+    /// Writes a given of the puzzle, or clears the square with 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - The square.
+    /// * `value` - The given ([0,9]).
+    fn write(&mut self, cell: &Coordinates, value: u8) {
+        // The cursor never leaves the grid, so the square is always valid.
+        let _ = self.grid.set_given(cell.row, cell.column, value);
+    }
+
+    /// Get the selected square.
+    pub fn cursor(&self) -> &Coordinates {
+        &self.cursor
+    }
+
+    /// Moves the cursor to the next square in reading order, wrapping to the top left.
+    fn advance(&mut self) {
+        let i = (self.cursor.row * 9 + self.cursor.column + 1) % (9 * 9);
+        self.cursor = Coordinates::from((i / 9, i % 9));
+    }
+
+    /// Checks the puzzle entered so far: first the rules, then the number of solutions and
+    /// finally the difficulty of a proper puzzle.
+    pub fn readout(&self) -> Readout {
+        let conflicts = conflicts(&self.grid);
+        if !conflicts.is_empty() {
+            return Readout::Conflicts(conflicts.len());
+        }
+        let solver = Solver::new(self.grid.clone());
+        match solver.count_solutions(2) {
+            0 => Readout::NoSolution,
+            1 => match solver.difficulty_report() {
+                Ok(report) => Readout::Unique(report),
+                Err(_) => Readout::NoSolution,
+            },
+            _ => Readout::MultipleSolutions,
+        }
+    }
+
+    /// Acts on a key press: the arrow keys move, 1-9 enter a value and move to the next
+    /// square, 0, . and space clear the square and move on, backspace clears the previous
+    /// square, delete clears the selected one, s saves, enter solves and escape quits.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key pressed.
+    pub fn handle_key(&mut self, key: Key) -> Action {
+        let cell = self.cursor.clone();
+        match key {
+            Key::ArrowUp => self.cursor.row = (cell.row + 8) % 9,
+            Key::ArrowDown => self.cursor.row = (cell.row + 1) % 9,
+            Key::ArrowLeft => self.cursor.column = (cell.column + 8) % 9,
+            Key::ArrowRight => self.cursor.column = (cell.column + 1) % 9,
+            Key::Char(digit @ '0'..='9') => {
+                self.write(&cell, digit as u8 - b'0');
+                self.advance();
+            }
+            Key::Char('.' | ' ') => {
+                self.write(&cell, 0);
+                self.advance();
+            }
+            Key::Backspace => {
+                let i = (cell.row * 9 + cell.column + 9 * 9 - 1) % (9 * 9);
+                self.cursor = Coordinates::from((i / 9, i % 9));
+                self.write(&self.cursor.clone(), 0);
+            }
+            Key::Del => self.write(&cell, 0),
+            Key::Char('s') => return Action::Save,
+            Key::Enter => return Action::Solve,
+            Key::Escape => return Action::Quit,
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    /// Draws the grid, the readout and the keys. Squares breaking a rule are marked with a
+    /// '*'.
+    pub fn render(&self) -> String {
+        let mut retval = render_grid(&self.grid, &self.cursor, &conflicts(&self.grid), |_| false);
+        retval.push_str(&format!("{} givens. ", self.grid.set_count));
+        match self.readout() {
+            Readout::Conflicts(count) => retval.push_str(&format!(
+                "{} squares break the rules.\n",
+                style(count).red()
+            )),
+            Readout::NoSolution => retval.push_str(&format!("{}\n", style("No solution.").red())),
+            Readout::MultipleSolutions => retval.push_str("More than one solution.\n"),
+            Readout::Unique(report) => retval.push_str(&format!(
+                "{} Difficulty {:.2}, hardest technique {}.\n",
+                style("Unique solution.").green(),
+                report.score,
+                report.hardest_technique.unwrap_or("none")
+            )),
+        }
+        retval.push_str(
+            "Arrows move, 1-9 enter, 0/./space clear, backspace erases, s saves, enter solves, \
+             escape quits.\n",
+        );
+        retval
+    }
+}
+
+impl Entry {
+    /// Starts with nothing typed.
+    pub fn new() -> Self {
+        Entry::default()
+    }
+
+    /// Returns the number of squares typed so far.
+    pub fn len(&self) -> usize {
+        self.typed.len()
+    }
+
+    /// Returns whether nothing has been typed.
+    pub fn is_empty(&self) -> bool {
+        self.typed.is_empty()
+    }
+
+    /// Returns the squares typed so far with the rest left empty.
+    pub fn grid(&self) -> Sudoku {
+        let mut squares = [0; 9 * 9];
+        squares[..self.typed.len()].copy_from_slice(&self.typed);
+        Sudoku::new_from_state(squares)
+    }
+
+    /// Returns the puzzle once all 81 squares have been typed.
+    pub fn sudoku(&self) -> Option<Sudoku> {
+        (self.typed.len() == 9 * 9).then(|| self.grid())
+    }
+
+    /// Acts on a key press: 1-9 type a value, 0 and . a blank, backspace takes back the last
+    /// square and escape quits. Once the grid is complete, enter solves it and p plays it.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key pressed.
+    pub fn handle_key(&mut self, key: Key) -> Action {
+        let complete = self.typed.len() == 9 * 9;
+        match key {
+            Key::Char(digit @ '0'..='9') if !complete => self.typed.push(digit as u8 - b'0'),
+            Key::Char('.') if !complete => self.typed.push(0),
+            Key::Backspace => {
+                self.typed.pop();
+            }
+            Key::Enter if complete => return Action::Solve,
+            Key::Char('p') if complete => return Action::Play,
+            Key::Escape => return Action::Quit,
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    /// Draws a preview of the grid with the next square to type in brackets, followed by the
+    /// progress and the keys.
+    pub fn render(&self) -> String {
+        let grid = self.grid();
+        let next = match self.typed.len() {
+            i if i < 9 * 9 => Coordinates::from((i / 9, i % 9)),
+            // Past the grid, so that no square is selected.
+            _ => Coordinates::from((9, 0)),
+        };
+        let mut retval = render_grid(&grid, &next, &conflicts(&grid), |_| false);
+        if self.typed.len() < 9 * 9 {
+            retval.push_str(&format!(
+                "{}/81 squares typed, next {}.\n\
+                 Type 1-9 or 0/. for a blank, backspace corrects, escape quits.\n",
+                self.typed.len(),
+                next
+            ));
+        } else {
+            retval.push_str("All 81 squares typed.\n");
+            retval.push_str("Enter solves, p plays, backspace corrects, escape quits.\n");
+        }
+        retval
+    }
+}
+
+/// Returns every square holding the same value as another square it shares a row, column or
+/// block with.
+///
+/// # Arguments
+///
+/// * `grid` - The grid to check.
+fn conflicts(grid: &Sudoku) -> HashSet<Coordinates> {
+    grid.find_conflicts()
+        .into_iter()
+        .flat_map(|(first, second)| [first, second])
+        .collect()
+}
+
+/// Draws a grid with row and column numbers, the selected square in brackets and squares
+/// breaking a rule marked with a '*'. On terminals with colors, bold squares are bold and
+/// broken rules red.
+///
+/// # Arguments
+///
+/// * `grid` - The grid to draw.
+/// * `cursor` - The selected square.
+/// * `conflicts` - The squares breaking a rule.
+/// * `bold` - Whether a square is drawn in bold.
+fn render_grid<F: Fn(&Coordinates) -> bool>(
+    grid: &Sudoku,
+    cursor: &Coordinates,
+    conflicts: &HashSet<Coordinates>,
+    bold: F,
+) -> String {
+    let rule = "  +---------+---------+---------+\n";
+    let mut retval = String::from("    1  2  3   4  5  6   7  8  9\n");
+    for row in 0..9 {
+        if row % 3 == 0 {
+            retval.push_str(rule);
+        }
+        retval.push_str(&format!("{} |", row + 1));
+        for column in 0..9 {
+            let cell = Coordinates { row, column };
+            let value = match grid.get_unchecked(row, column) {
+                0 => '.',
+                value => (b'0' + value) as char,
+            };
+            let selected = &cell == cursor;
+            let conflict = conflicts.contains(&cell);
+            let text = format!(
+                "{}{}{}",
+                if selected { '[' } else { ' ' },
+                value,
+                match (selected, conflict) {
+                    (true, _) => ']',
+                    (false, true) => '*',
+                    (false, false) => ' ',
+                }
+            );
+            let mut styled = style(text);
+            if bold(&cell) {
+                styled = styled.bold();
+            }
+            if conflict {
+                styled = styled.red();
+            }
+            retval.push_str(&styled.to_string());
+            if column % 3 == 2 {
+                retval.push('|');
+            }
+        }
+        retval.push('\n');
+    }
+    retval.push_str(rule);
+    retval
+}
+
+/// Draws a grid like [`render_grid`], but with every square three lines high so that empty
+/// squares can show their pencil marks, each value in its own place of a 3x3 pattern.
+///
+/// # Arguments
+///
+/// * `grid` - The grid to draw.
+/// * `marks` - The pencil marks of every square.
+/// * `cursor` - The selected square.
+/// * `conflicts` - The squares breaking a rule.
+/// * `bold` - Whether a square is drawn in bold.
+fn render_pencil_grid<F: Fn(&Coordinates) -> bool>(
+    grid: &Sudoku,
+    marks: &CandidateGrid,
+    cursor: &Coordinates,
+    conflicts: &HashSet<Coordinates>,
+    bold: F,
+) -> String {
+    let rule = "  +---------------+---------------+---------------+\n";
+    let mut retval = String::from("     1    2    3      4    5    6      7    8    9\n");
+    for row in 0..9 {
+        if row % 3 == 0 {
+            retval.push_str(rule);
+        }
+        for line in 0..3 {
+            if line == 1 {
+                retval.push_str(&format!("{} |", row + 1));
+            } else {
+                retval.push_str("  |");
+            }
+            for column in 0..9 {
+                let cell = Coordinates { row, column };
+                let selected = &cell == cursor;
+                let conflict = conflicts.contains(&cell);
+                let (left, right) = match (line, selected, conflict) {
+                    (1, true, _) => ('[', ']'),
+                    (1, false, true) => (' ', '*'),
+                    _ => (' ', ' '),
+                };
+                let inner: String = match grid.get_unchecked(row, column) {
+                    0 => (1..=3)
+                        .map(|i| {
+                            let value = line * 3 + i;
+                            if marks[row * 9 + column].contains(&value) {
+                                (b'0' + value) as char
+                            } else {
+                                ' '
+                            }
+                        })
+                        .collect(),
+                    value if line == 1 => format!(" {} ", value),
+                    _ => "   ".to_string(),
+                };
+                let mut styled = style(format!("{}{}{}", left, inner, right));
+                if bold(&cell) {
+                    styled = styled.bold();
+                }
+                if conflict {
+                    styled = styled.red();
+                }
+                retval.push_str(&styled.to_string());
+                if column % 3 == 2 {
+                    retval.push('|');
+                }
+            }
+            retval.push('\n');
+        }
+    }
+    retval.push_str(rule);
+    retval
+}
+
+/// Plays the game on the terminal until it is solved, lost or the player quits. Returns
+/// whether it was solved. The timer runs while the game is played, unless the player pauses it.
+///
+/// # Arguments
+///
+/// * `game` - The game to play, left in its final state with the timer stopped.
+pub fn play(game: &mut Game) -> io::Result<bool> {
+    let term = Term::stdout();
+    term.hide_cursor()?;
+    game.timer.start();
+    let result = (|| loop {
+        term.clear_screen()?;
+        term.write_str(&game.render())?;
+        if game.is_solved() || game.is_lost() {
+            return Ok(game.is_solved());
+        }
+        if game.handle_key(term.read_key()?) == Action::Quit {
+            return Ok(false);
+        }
+    })();
+    game.timer.pause();
+    term.show_cursor()?;
+    result
+}
+
+/// Runs the editor on the terminal until the user saves, solves or quits, and returns which
+/// it was.
+///
+/// # Arguments
+///
+/// * `editor` - The editor to run, left with the puzzle entered.
+pub fn edit(editor: &mut Editor) -> io::Result<Action> {
+    let term = Term::stdout();
+    term.hide_cursor()?;
+    let result = (|| loop {
+        term.clear_screen()?;
+        term.write_str(&editor.render())?;
+        match editor.handle_key(term.read_key()?) {
+            Action::Continue => {}
+            action => return Ok(action),
+        }
+    })();
+    term.show_cursor()?;
+    result
+}
+
+/// Runs the entry wizard on the terminal until the user quits or asks to solve or play the
+/// typed puzzle, and returns which it was.
+///
+/// # Arguments
+///
+/// * `entry` - The wizard to run, left with the squares typed.
+pub fn type_in(entry: &mut Entry) -> io::Result<Action> {
+    let term = Term::stdout();
+    term.hide_cursor()?;
+    let result = (|| loop {
+        term.clear_screen()?;
+        term.write_str(&entry.render())?;
+        match entry.handle_key(term.read_key()?) {
+            Action::Continue => {}
+            action => return Ok(action),
+        }
+    })();
+    term.show_cursor()?;
+    result
+}
+
+/// Shows the replay on the terminal, a move per key press, until the user quits.
+///
+/// # Arguments
+///
+/// * `playback` - The replay to show.
+pub fn watch(playback: &mut Playback) -> io::Result<()> {
+    let term = Term::stdout();
+    term.hide_cursor()?;
+    let result = (|| loop {
+        term.clear_screen()?;
+        term.write_str(&playback.render())?;
+        if playback.handle_key(term.read_key()?) == Action::Quit {
+            return Ok(());
+        }
+    })();
+    term.show_cursor()?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, Editor, Entry, Game, Playback, Readout, Timer, Watcher};
+    use crate::{
+        corpus,
+        format::{parse_line, FormatError},
+        observer::SolverObserver,
+        replay::Actor,
+        sudoku::{Coordinates, Sudoku},
+    };
+    use console::Key;
+    use std::{collections::HashSet, time::Duration};
+
+    #[test]
+    fn test_conflicts_and_candidates() {
+        let mut game = Game::new(parse_line(corpus::EASY.puzzle, 1).unwrap());
+        game.timer_mut().start();
+        // r1c1 is empty and r1c3 holds a given 3.
+        assert_eq!(Some(vec![4, 5]), game.candidates());
+        assert!(game.conflicts().is_empty());
+
+        game.handle_key(Key::Char('3'));
+        let conflicts = game.conflicts();
+        assert!(conflicts.contains(&Coordinates::from((0, 0))));
+        assert!(conflicts.contains(&Coordinates::from((0, 2))));
+        assert_eq!(None, game.candidates());
+        game.toggle_candidates();
+        let rendered = game.render();
+        assert!(rendered.contains("1 |[3]"));
+        assert!(rendered.contains(" 3*"));
+        assert!(rendered.contains("squares break the rules"));
+        assert!(rendered.contains("r1c1 is filled."));
+        assert_eq!(1, game.mistakes());
+
+        game.handle_key(Key::Backspace);
+        assert!(game.conflicts().is_empty());
+        assert!(game.render().contains("Candidates for r1c1: 4 5"));
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut game = Game::new(parse_line(corpus::EASY.puzzle, 1).unwrap());
+        game.timer_mut().start();
+        game.handle_key(Key::ArrowLeft);
+        game.handle_key(Key::Char('k'));
+        assert_eq!(&Coordinates::from((8, 8)), game.cursor());
+        game.move_cursor(1, -6);
+        assert_eq!(&Coordinates::from((0, 2)), game.cursor());
+        assert!(game.is_given(game.cursor()));
+        assert!(!game.enter(5));
+        assert_eq!(3, game.board().squares[2]);
+        assert_eq!(Action::Quit, game.handle_key(Key::Escape));
+
+        assert_eq!(Action::Continue, game.handle_key(Key::Char('?')));
+        assert!(game.render().contains("Hint: Look at block "));
+        assert!(game.hint().starts_with("Look for a naked single in block "));
+        let revealed = game.hint();
+        let cell = game.cursor().clone();
+        let value = game.board().get_unchecked(cell.row, cell.column);
+        assert_eq!(format!("{} is {} (naked single).", cell, value), revealed);
+        assert_eq!(
+            game.solution()
+                .unwrap()
+                .get_unchecked(cell.row, cell.column),
+            value
+        );
+        assert!(game.hint().starts_with("Look at block "));
+        let record = game.record();
+        assert_eq!((0, 4, 0), (record.seconds, record.hints, record.mistakes));
+
+        game.cursor = Coordinates::from((0, 0));
+        game.enter(5);
+        assert_eq!("r1c1 does not match the solution.", game.hint());
+    }
+
+    #[test]
+    fn test_pencil_marks() {
+        let mut game = Game::new(parse_line(corpus::EASY.puzzle, 1).unwrap());
+        game.timer_mut().start();
+        assert_eq!(None, game.pencil_marks());
+        game.handle_key(Key::Char('a'));
+        let marks = game.pencil_marks().unwrap();
+        assert_eq!(HashSet::from([4, 5]), marks[0]);
+        assert!(game.render().contains("1 |[45 ] 45    3  |"));
+
+        game.handle_key(Key::Char('4'));
+        let marks = game.pencil_marks().unwrap();
+        assert!(marks[0].is_empty());
+        assert!(!marks[1].contains(&4));
+        assert!(game.render().contains("1 |[ 4 ]"));
+        game.handle_key(Key::Del);
+        assert_eq!(HashSet::from([4, 5]), game.pencil_marks().unwrap()[0]);
+    }
+
+    #[test]
+    fn test_max_mistakes() {
+        let mut game = Game::new(parse_line(corpus::EASY.puzzle, 1).unwrap());
+        game.timer_mut().start();
+        game.set_max_mistakes(Some(2));
+        assert_eq!(
+            corpus::EASY.solution,
+            crate::format::write_line(game.solution().unwrap())
+        );
+        // 5 breaks no rule in r1c1, but the solution has a 4 there.
+        assert!(!game.enter(5));
+        assert_eq!(1, game.mistakes());
+        assert_eq!(0, game.board().squares[0]);
+        assert!(game.render().contains("Mistakes: 1/2"));
+        assert!(game.enter(4));
+        assert!(!game.is_lost());
+        game.move_cursor(0, 1);
+        assert!(!game.enter(9));
+        assert!(game.is_lost());
+        assert!(!game.enter(8));
+        assert!(game.render().contains("Game over: 2 mistakes out of 2."));
+
+        let resumed = Game::from_session(&game.to_session()).unwrap();
+        assert_eq!(Some(2), resumed.max_mistakes());
+        assert!(resumed.is_lost());
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut game = Game::new(parse_line(corpus::EASY.puzzle, 1).unwrap());
+        game.timer_mut().start();
+        for key in ['5', '4', 'l', '8'] {
+            game.handle_key(Key::Char(key));
+        }
+        game.hint();
+        game.hint();
+        game.hint();
+        let replay = game.replay().clone();
+        assert_eq!(4, replay.moves().len());
+        assert_eq!(Actor::Solver, replay.moves()[3].actor);
+        assert_eq!(game.board(), &replay.board_at(4));
+
+        let mut playback = Playback::new(replay);
+        assert!(playback.render().contains("Start, 4 moves to go."));
+        playback.handle_key(Key::ArrowRight);
+        assert!(playback
+            .render()
+            .contains("Move 1/4 at 0:00: the player sets r1c1 to 5."));
+        assert!(playback.render().contains("1 |[5]"));
+        playback.handle_key(Key::End);
+        playback.handle_key(Key::Char('l'));
+        assert_eq!(4, playback.position());
+        playback.handle_key(Key::Home);
+        playback.handle_key(Key::Backspace);
+        assert_eq!(0, playback.position());
+        assert_eq!(Action::Quit, playback.handle_key(Key::Char('q')));
+
+        let resumed = Game::from_session(&game.to_session()).unwrap();
+        assert_eq!(game.board(), &resumed.replay().board_at(usize::MAX));
+    }
+
+    #[test]
+    fn test_watcher() {
+        let puzzle = parse_line(corpus::EASY.puzzle, 1).unwrap();
+        let mut watcher = Watcher {
+            board: puzzle.clone(),
+            puzzle,
+            steps: 0,
+            last: None,
+            delay: Duration::ZERO,
+            term: None,
+        };
+        assert!(watcher.render().contains("Start, 32 givens."));
+        let cell = Coordinates::from((0, 0));
+        watcher.on_guess(&cell, 5, &HashSet::from([5, 4]));
+        assert!(watcher.render().contains("1 |[5]"));
+        assert!(watcher
+            .render()
+            .contains("Step 1: guess 5 for r1c1 out of 4, 5."));
+        watcher.on_backtrack(&cell, 5);
+        watcher.on_placement(&cell, 4);
+        assert!(watcher.render().contains("Step 3: r1c1 is 4."));
+        assert_eq!(3, watcher.steps());
+    }
+
+    #[test]
+    fn test_pause_and_session() {
+        let mut game = Game::new(parse_line(corpus::EASY.puzzle, 1).unwrap());
+        game.timer_mut().start();
+        game.handle_key(Key::Char('4'));
+        game.handle_key(Key::ArrowRight);
+        game.handle_key(Key::Char('1'));
+        game.handle_key(Key::Char('p'));
+        assert!(!game.timer().is_running());
+        assert!(game.render().starts_with("Paused at 0:00."));
+        // The key that resumes does nothing else.
+        game.handle_key(Key::ArrowRight);
+        assert!(game.timer().is_running());
+        assert_eq!(&Coordinates::from((0, 1)), game.cursor());
+        assert!(game.render().contains("Time 0:00"));
+
+        game.timer = Timer::new(Duration::from_secs(125));
+        let session = game.to_session();
+        assert!(session.contains("seconds=125\n"));
+        let resumed = Game::from_session(&session).unwrap();
+        assert_eq!(game.board(), resumed.board());
+        // The player's values stay open to change, the puzzle's do not.
+        assert!(!resumed.board().is_given(0, 0).unwrap());
+        assert!(resumed.board().is_given(0, 2).unwrap());
+        assert_eq!(1, resumed.mistakes());
+        assert_eq!(125, resumed.timer().elapsed().as_secs());
+        assert!(!resumed.timer().is_running());
+        assert_eq!(
+            Err(FormatError::MissingColumn("board".to_string())),
+            Game::from_session("puzzle=..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut game = Game::new(parse_line(corpus::EASY.puzzle, 1).unwrap());
+        game.timer_mut().start();
+        for (i, value) in corpus::EASY.solution.bytes().enumerate() {
+            game.cursor = Coordinates::from((i / 9, i % 9));
+            game.handle_key(Key::Char(value as char));
+        }
+        assert!(game.is_solved());
+        assert!(game.render().contains("Solved!"));
+    }
+
+    #[test]
+    fn test_editor() {
+        let mut editor = Editor::new(Sudoku::new_empty());
+        assert_eq!(Readout::MultipleSolutions, editor.readout());
+        for key in "55".chars() {
+            editor.handle_key(Key::Char(key));
+        }
+        assert_eq!(Readout::Conflicts(2), editor.readout());
+        assert!(editor.grid().is_given(0, 1).unwrap());
+        editor.handle_key(Key::Backspace);
+        assert_eq!(&Coordinates::from((0, 1)), editor.cursor());
+        assert_eq!(1, editor.grid().set_count);
+        assert!(!editor.grid().is_given(0, 1).unwrap());
+
+        let mut editor = Editor::new(Sudoku::new_empty());
+        for key in corpus::EASY.puzzle.chars() {
+            assert_eq!(Action::Continue, editor.handle_key(Key::Char(key)));
+        }
+        assert_eq!(&Coordinates::from((0, 0)), editor.cursor());
+        assert_eq!(
+            corpus::EASY.puzzle,
+            crate::format::write_line(editor.grid())
+        );
+        match editor.readout() {
+            Readout::Unique(report) => assert_eq!(Some("Naked Single"), report.hardest_technique),
+            readout => panic!("unexpected readout {:?}", readout),
+        }
+        assert!(editor.render().contains("Unique solution."));
+        assert_eq!(Action::Save, editor.handle_key(Key::Char('s')));
+        assert_eq!(Action::Solve, editor.handle_key(Key::Enter));
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut entry = Entry::new();
+        for key in "..3.2.6.x7".chars() {
+            entry.handle_key(Key::Char(key));
+        }
+        assert_eq!(9, entry.len());
+        entry.handle_key(Key::Backspace);
+        assert_eq!(None, entry.sudoku());
+        assert!(entry.render().contains("8/81 squares typed, next r1c9."));
+        assert_eq!(Action::Continue, entry.handle_key(Key::Enter));
+
+        let mut entry = Entry::new();
+        for key in corpus::EASY.puzzle.chars() {
+            entry.handle_key(Key::Char(key));
+        }
+        entry.handle_key(Key::Char('1'));
+        assert_eq!(81, entry.len());
+        assert_eq!(parse_line(corpus::EASY.puzzle, 1).ok(), entry.sudoku());
+        assert_eq!(Action::Play, entry.handle_key(Key::Char('p')));
+        assert_eq!(Action::Solve, entry.handle_key(Key::Enter));
+    }
+}
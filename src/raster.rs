@@ -0,0 +1,154 @@
+//! Renders grids into pixels with the built-in bitmap font, for image and animation export.
+//! Pixels are indices into [`PALETTE`], so that they can be written as indexed colors.
+
+use crate::{
+    font::{self, GLYPH_HEIGHT, GLYPH_WIDTH},
+    sudoku::{Coordinates, Sudoku},
+};
+
+/// Index of the background color.
+pub const BACKGROUND: u8 = 0;
+
+/// Index of the grid lines and the givens.
+pub const INK: u8 = 1;
+
+/// Index of the values filled in after the start.
+pub const FILLED: u8 = 2;
+
+/// Index of the background of the highlighted square.
+pub const HIGHLIGHT: u8 = 3;
+
+/// RGB colors of the pixel indices: white, near black, blue and light yellow.
+pub const PALETTE: [u8; 12] = [
+    0xff, 0xff, 0xff, 0x14, 0x14, 0x14, 0x1f, 0x4e, 0xb4, 0xff, 0xec, 0x99,
+];
+
+/// Sizes of a rendered grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RasterOptions {
+    /// Width and height of a square in pixels. Must leave room for a scaled glyph.
+    pub cell: usize,
+    /// Size of a font pixel in pixels.
+    pub scale: usize,
+    /// Empty border around the grid in pixels.
+    pub margin: usize,
+}
+
+/// A rendered grid, one palette index per pixel in reading order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raster {
+    /// Width in pixels.
+    pub width: usize,
+    /// Height in pixels.
+    pub height: usize,
+    /// Palette indices of the pixels, row by row.
+    pub pixels: Vec<u8>,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        RasterOptions {
+            cell: 40,
+            scale: 4,
+            margin: 10,
+        }
+    }
+}
+
+/// Renders the grid with thick lines around the blocks. Values that are not in the puzzle are
+/// drawn in the [`FILLED`] color, so that the givens stand out.
+///
+/// # Arguments
+///
+/// * `grid` - The grid to draw.
+/// * `puzzle` - The givens of the grid.
+/// * `highlight` - A square to draw with a [`HIGHLIGHT`] background, if any.
+/// * `options` - Sizes of the image.
+pub fn render(
+    grid: &Sudoku,
+    puzzle: &Sudoku,
+    highlight: Option<&Coordinates>,
+    options: &RasterOptions,
+) -> Raster {
+    let RasterOptions {
+        cell,
+        scale,
+        margin,
+    } = *options;
+    let size = cell * 9 + 2 * margin + 2;
+    let mut raster = Raster {
+        width: size,
+        height: size,
+        pixels: vec![BACKGROUND; size * size],
+    };
+
+    if let Some(highlight) = highlight {
+        let (x0, y0) = (
+            margin + highlight.column * cell,
+            margin + highlight.row * cell,
+        );
+        for y in y0..y0 + cell {
+            raster.pixels[y * size + x0..y * size + x0 + cell].fill(HIGHLIGHT);
+        }
+    }
+    for i in 0..=9 {
+        let width = if i % 3 == 0 { 3 } else { 1 };
+        for offset in 0..width {
+            let line = margin + i * cell + offset;
+            for along in margin..=margin + 9 * cell + 2 {
+                raster.pixels[along * size + line] = INK;
+                raster.pixels[line * size + along] = INK;
+            }
+        }
+    }
+    for (i, value) in grid.squares.iter().enumerate() {
+        if *value == 0 {
+            continue;
+        }
+        let color = if puzzle.squares[i] == *value {
+            INK
+        } else {
+            FILLED
+        };
+        let x0 = margin + (i % 9) * cell + (cell - GLYPH_WIDTH * scale) / 2 + 1;
+        let y0 = margin + (i / 9) * cell + (cell - GLYPH_HEIGHT * scale) / 2 + 1;
+        for y in 0..GLYPH_HEIGHT * scale {
+            for x in 0..GLYPH_WIDTH * scale {
+                if font::pixel(*value, x / scale, y / scale) {
+                    raster.pixels[(y0 + y) * size + x0 + x] = color;
+                }
+            }
+        }
+    }
+    raster
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, RasterOptions, BACKGROUND, FILLED, HIGHLIGHT, INK};
+    use crate::{corpus, format::parse_line, sudoku::Coordinates};
+
+    #[test]
+    fn test_render() {
+        let puzzle = parse_line(corpus::EASY.puzzle, 1).unwrap();
+        let mut grid = puzzle.clone();
+        grid.set_unchecked(0, 0, 4);
+        let options = RasterOptions::default();
+        let raster = render(&grid, &puzzle, Some(&Coordinates::from((0, 1))), &options);
+        assert_eq!(9 * 40 + 2 * 10 + 2, raster.width);
+        assert_eq!(raster.width * raster.height, raster.pixels.len());
+
+        let count = |x0: usize, y0: usize, color: u8| {
+            (y0..y0 + 36)
+                .flat_map(|y| (x0..x0 + 36).map(move |x| (x, y)))
+                .filter(|(x, y)| raster.pixels[y * raster.width + x] == color)
+                .count()
+        };
+        // r1c1 holds a filled 4, r1c2 is highlighted and r1c3 holds a given 3.
+        assert!(count(12, 12, FILLED) > 0);
+        assert_eq!(0, count(12, 12, HIGHLIGHT));
+        assert!(count(52, 12, HIGHLIGHT) > 0);
+        assert!(count(92, 12, INK) > 0);
+        assert_eq!(BACKGROUND, raster.pixels[0]);
+    }
+}
@@ -0,0 +1,320 @@
+//! Recordings of solves, by a player or by the solver, to review them move by move or share
+//! them as text.
+
+use crate::{
+    constraint::CandidateSet,
+    format::{parse_line, write_line, FormatError},
+    notation::parse_cell,
+    observer::SolverObserver,
+    solver::{Solver, SolverError},
+    sudoku::{Coordinates, Sudoku},
+};
+use std::{
+    fmt::Display,
+    sync::mpsc::{self, Sender},
+    time::{Duration, Instant},
+};
+
+/// Who made a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Actor {
+    Player,
+    Solver,
+}
+
+/// One square set or cleared during a solve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayMove {
+    /// Time since the start of the solve, to the millisecond.
+    pub elapsed: Duration,
+    /// The square.
+    pub cell: Coordinates,
+    /// The value set, 0 if the square was cleared.
+    pub value: u8,
+    /// Who made the move.
+    pub actor: Actor,
+}
+
+/// A puzzle and the moves made on it, in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    puzzle: Sudoku,
+    moves: Vec<ReplayMove>,
+}
+
+/// Records the placements, guesses and backtracks of a solver as moves.
+#[derive(Debug)]
+pub struct ReplayRecorder {
+    started: Instant,
+    sender: Sender<ReplayMove>,
+}
+
+impl Actor {
+    /// Returns the name of the actor as written in replays.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Actor::Player => "player",
+            Actor::Solver => "solver",
+        }
+    }
+
+    /// Returns the actor with the given name, see [`Actor::name`].
+    pub(crate) fn from_name(name: &str) -> Option<Actor> {
+        match name {
+            "player" => Some(Actor::Player),
+            "solver" => Some(Actor::Solver),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ReplayMove {
+    /// Writes the move as `<milliseconds> r<row>c<column> <value> <actor>`, e.g.
+    /// `1500 r4c6 7 player`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.elapsed.as_millis(),
+            self.cell,
+            self.value,
+            self.actor.name()
+        )
+    }
+}
+
+impl ReplayRecorder {
+    /// Creates a recorder that starts its clock now and sends the moves to the given channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - Where to send the moves.
+    pub fn new(sender: Sender<ReplayMove>) -> Self {
+        ReplayRecorder {
+            started: Instant::now(),
+            sender,
+        }
+    }
+
+    /// Sends a move made now by the solver.
+    fn send(&self, cell: &Coordinates, value: u8) {
+        let _ = self.sender.send(ReplayMove {
+            elapsed: self.started.elapsed(),
+            cell: cell.clone(),
+            value,
+            actor: Actor::Solver,
+        });
+    }
+}
+
+impl SolverObserver for ReplayRecorder {
+    fn on_placement(&mut self, cell: &Coordinates, value: u8) {
+        self.send(cell, value);
+    }
+
+    fn on_guess(&mut self, cell: &Coordinates, value: u8, _candidates: &CandidateSet) {
+        self.send(cell, value);
+    }
+
+    fn on_backtrack(&mut self, cell: &Coordinates, _value: u8) {
+        self.send(cell, 0);
+    }
+}
+
+impl Replay {
+    /// Creates a replay of the puzzle without moves.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The puzzle as it was given.
+    pub fn new(puzzle: Sudoku) -> Self {
+        Replay {
+            puzzle,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Solves the puzzle and records every move of the solver, including bad guesses and the
+    /// backtracks that take them back.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The puzzle to solve.
+    pub fn record_solve(puzzle: Sudoku) -> Result<Self, SolverError> {
+        let (sender, receiver) = mpsc::channel();
+        let mut solver = Solver::new(puzzle.clone());
+        solver.add_observer(Box::new(ReplayRecorder::new(sender)));
+        solver.solve()?;
+        drop(solver);
+        Ok(Replay {
+            puzzle,
+            moves: receiver.into_iter().collect(),
+        })
+    }
+
+    /// Get the puzzle as it was given.
+    pub fn puzzle(&self) -> &Sudoku {
+        &self.puzzle
+    }
+
+    /// Get the moves in the order they were made.
+    pub fn moves(&self) -> &[ReplayMove] {
+        &self.moves
+    }
+
+    /// Adds a move to the end of the replay.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - Time since the start of the solve.
+    /// * `cell` - The square.
+    /// * `value` - The value set, 0 if the square was cleared.
+    /// * `actor` - Who made the move.
+    pub fn record(&mut self, elapsed: Duration, cell: &Coordinates, value: u8, actor: Actor) {
+        self.moves.push(ReplayMove {
+            elapsed,
+            cell: cell.clone(),
+            value,
+            actor,
+        });
+    }
+
+    /// Returns the grid after the given number of moves, the puzzle itself for 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `moves` - Number of moves to make; more than there are makes all of them.
+    pub fn board_at(&self, moves: usize) -> Sudoku {
+        let mut retval = self.puzzle.clone();
+        for step in self.moves.iter().take(moves) {
+            retval.set_unchecked(step.cell.row, step.cell.column, step.value);
+        }
+        retval
+    }
+
+    /// Writes the replay as text: a `puzzle <81 characters>` line followed by one line per
+    /// move, see [`ReplayMove`]'s `Display`.
+    pub fn to_text(&self) -> String {
+        let mut retval = format!("puzzle {}\n", write_line(&self.puzzle));
+        for step in &self.moves {
+            retval.push_str(&format!("{}\n", step));
+        }
+        retval
+    }
+
+    /// Reads a replay written by [`Replay::to_text`].
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The replay.
+    pub fn from_text(text: &str) -> Result<Self, FormatError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        let puzzle = match lines.next() {
+            Some((line_number, line)) => match line.strip_prefix("puzzle ") {
+                Some(puzzle) => parse_line(puzzle.trim(), line_number)?,
+                None => return Err(FormatError::MissingColumn("puzzle".to_string())),
+            },
+            None => return Err(FormatError::MissingColumn("puzzle".to_string())),
+        };
+        let mut retval = Replay::new(puzzle);
+        for (line_number, line) in lines {
+            retval.moves.push(parse_move(line, line_number)?);
+        }
+        Ok(retval)
+    }
+}
+
+/// Parses a move written by [`ReplayMove`]'s `Display`.
+///
+/// # Arguments
+///
+/// * `line` - The move.
+/// * `line_number` - Line number of the move, for errors.
+fn parse_move(line: &str, line_number: usize) -> Result<ReplayMove, FormatError> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() != 4 {
+        return Err(FormatError::BadLineLength(line_number));
+    }
+    let bad_number = |field: &str| FormatError::BadNumber(line_number, field.to_string());
+    let millis: u64 = fields[0].parse().map_err(|_| bad_number(fields[0]))?;
+    let cell = parse_cell(fields[1]).ok_or_else(|| bad_number(fields[1]))?;
+    let value = match fields[2].parse::<u8>() {
+        Ok(value) if value <= 9 => value,
+        Ok(value) => return Err(FormatError::BadValue(value)),
+        Err(_) => return Err(bad_number(fields[2])),
+    };
+    let actor = Actor::from_name(fields[3])
+        .ok_or_else(|| FormatError::UnknownSection(fields[3].to_string()))?;
+    Ok(ReplayMove {
+        elapsed: Duration::from_millis(millis),
+        cell,
+        value,
+        actor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Actor, Replay};
+    use crate::{
+        corpus,
+        format::{parse_line, FormatError},
+        sudoku::Coordinates,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_record_and_text() {
+        let puzzle = parse_line(corpus::EASY.puzzle, 1).unwrap();
+        let mut replay = Replay::new(puzzle.clone());
+        replay.record(
+            Duration::from_millis(1500),
+            &Coordinates::from((0, 0)),
+            5,
+            Actor::Player,
+        );
+        replay.record(
+            Duration::from_secs(3),
+            &Coordinates::from((0, 0)),
+            0,
+            Actor::Player,
+        );
+        replay.record(
+            Duration::from_secs(4),
+            &Coordinates::from((0, 0)),
+            4,
+            Actor::Solver,
+        );
+        assert_eq!(puzzle, replay.board_at(0));
+        assert_eq!(5, replay.board_at(1).squares[0]);
+        assert_eq!(puzzle, replay.board_at(2));
+        assert_eq!(4, replay.board_at(10).squares[0]);
+
+        let text = replay.to_text();
+        assert!(text.ends_with("\n1500 r1c1 5 player\n3000 r1c1 0 player\n4000 r1c1 4 solver\n"));
+        assert_eq!(replay, Replay::from_text(&text).unwrap());
+        assert_eq!(
+            Err(FormatError::BadNumber(2, "r0c1".to_string())),
+            Replay::from_text(&format!("puzzle {}\n1 r0c1 5 player", corpus::EASY.puzzle))
+        );
+    }
+
+    #[test]
+    fn test_record_solve() {
+        let puzzle = parse_line(corpus::EASY.puzzle, 1).unwrap();
+        let replay = Replay::record_solve(puzzle).unwrap();
+        assert_eq!(81 - 32, replay.moves().len());
+        assert!(replay
+            .moves()
+            .iter()
+            .all(|step| step.actor == Actor::Solver));
+        assert_eq!(
+            parse_line(corpus::EASY.solution, 1).unwrap(),
+            replay.board_at(replay.moves().len())
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use crate::{
+    constraint::Constraint,
+    sudoku::{Coordinates, Grid, House, Sudoku},
+};
+use std::{collections::HashSet, fmt::Write};
+
+/// A boolean formula in conjunctive normal form. Variables are numbered from 1 and literals
+/// are written the DIMACS way: `v` for a variable and `-v` for its negation.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cnf {
+    /// Number of variables.
+    pub variables: usize,
+    /// The clauses, each satisfied when any of its literals is true.
+    pub clauses: Vec<Vec<i32>>,
+}
+
+/// Returns the variable that is true when the square contains the value.
+///
+/// # Arguments
+///
+/// * `index` - Index of the square ([0,81)).
+/// * `value` - The value ([1,9]).
+pub fn variable(index: usize, value: u8) -> i32 {
+    (index * 9 + value as usize) as i32
+}
+
+/// Encodes the sudoku: every square has exactly one value, squares that share a constraint
+/// have different values, and the givens keep their values. Rows, columns and blocks that
+/// the constraints make all-different also get a clause per value requiring it somewhere,
+/// which is redundant but lets unit propagation find hidden singles.
+///
+/// # Arguments
+///
+/// * `sudoku` - The sudoku to encode.
+/// * `constraints` - The constraints whose peers must differ.
+pub fn encode(sudoku: &Sudoku, constraints: &[Box<dyn Constraint>]) -> Cnf {
+    let mut cnf = Cnf {
+        variables: 9 * 9 * 9,
+        clauses: Vec::new(),
+    };
+    let peers: Vec<HashSet<usize>> = (0..9 * 9)
+        .map(|i| {
+            constraints
+                .iter()
+                .flat_map(|constraint| constraint.peers(&Coordinates::from((i / 9, i % 9))))
+                .map(|peer| peer.row * 9 + peer.column)
+                .filter(|peer| *peer != i)
+                .collect()
+        })
+        .collect();
+
+    for (i, square_peers) in peers.iter().enumerate() {
+        cnf.clauses
+            .push((1..=9).map(|value| variable(i, value)).collect());
+        for value in 1..=9 {
+            for other in value + 1..=9 {
+                cnf.clauses
+                    .push(vec![-variable(i, value), -variable(i, other)]);
+            }
+        }
+        let mut later: Vec<usize> = square_peers
+            .iter()
+            .copied()
+            .filter(|peer| *peer > i)
+            .collect();
+        later.sort_unstable();
+        for peer in later {
+            for value in 1..=9 {
+                cnf.clauses
+                    .push(vec![-variable(i, value), -variable(peer, value)]);
+            }
+        }
+        if sudoku.squares[i] != 0 {
+            cnf.clauses.push(vec![variable(i, sudoku.squares[i])]);
+        }
+    }
+
+    for house in houses() {
+        let all_different = house.iter().enumerate().all(|(j, square)| {
+            house[j + 1..]
+                .iter()
+                .all(|other| peers[*square].contains(other))
+        });
+        if all_different {
+            for value in 1..=9 {
+                cnf.clauses.push(
+                    house
+                        .iter()
+                        .map(|square| variable(*square, value))
+                        .collect(),
+                );
+            }
+        }
+    }
+    cnf
+}
+
+/// Reads the grid back from a satisfying assignment of an encoded sudoku.
+///
+/// # Arguments
+///
+/// * `model` - Value of every variable, indexed by variable number.
+pub fn decode(model: &[bool]) -> Grid {
+    let mut grid = [0; 9 * 9];
+    for (i, square) in grid.iter_mut().enumerate() {
+        *square = (1..=9)
+            .find(|value| model[variable(i, *value) as usize])
+            .unwrap_or(0);
+    }
+    grid
+}
+
+/// Indices of the squares of every row, column and block.
+fn houses() -> Vec<Vec<usize>> {
+    House::all()
+        .map(|house| {
+            house
+                .cells()
+                .map(|cell| cell.row * 9 + cell.column)
+                .collect()
+        })
+        .collect()
+}
+
+/// Index into the watch lists for a literal.
+fn literal_index(literal: i32) -> usize {
+    literal.unsigned_abs() as usize * 2 + (literal < 0) as usize
+}
+
+/// State of the DPLL search.
+struct Dpll {
+    clauses: Vec<Vec<i32>>,
+    /// Clauses watching each literal, visited when the literal becomes false. The first two
+    /// literals of every clause are the watched ones.
+    watches: Vec<Vec<usize>>,
+    assignment: Vec<Option<bool>>,
+    /// Literals made true, in order.
+    trail: Vec<i32>,
+    /// Number of literals of the trail that have been propagated.
+    propagated: usize,
+    /// Decisions made so far, with the trail length before each and whether it was flipped.
+    decisions: Vec<(i32, usize, bool)>,
+}
+
+impl Dpll {
+    /// Returns the value of a literal under the current assignment.
+    fn value(&self, literal: i32) -> Option<bool> {
+        self.assignment[literal.unsigned_abs() as usize].map(|value| value == (literal > 0))
+    }
+
+    /// Makes a literal true. Returns false if it was already false.
+    fn enqueue(&mut self, literal: i32) -> bool {
+        match self.value(literal) {
+            Some(value) => value,
+            None => {
+                self.assignment[literal.unsigned_abs() as usize] = Some(literal > 0);
+                self.trail.push(literal);
+                true
+            }
+        }
+    }
+
+    /// Unit propagation with two watched literals. Returns false on a conflict.
+    fn propagate(&mut self) -> bool {
+        while self.propagated < self.trail.len() {
+            let falsified = -self.trail[self.propagated];
+            self.propagated += 1;
+            let watching = std::mem::take(&mut self.watches[literal_index(falsified)]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = false;
+            for (n, clause) in watching.iter().enumerate() {
+                if conflict {
+                    kept.extend_from_slice(&watching[n..]);
+                    break;
+                }
+                let literals = &mut self.clauses[*clause];
+                if literals[0] == falsified {
+                    literals.swap(0, 1);
+                }
+                let other = literals[0];
+                if self.value(other) == Some(true) {
+                    kept.push(*clause);
+                    continue;
+                }
+                let literals = &self.clauses[*clause];
+                let replacement =
+                    (2..literals.len()).find(|k| self.value(literals[*k]) != Some(false));
+                match replacement {
+                    Some(k) => {
+                        let literals = &mut self.clauses[*clause];
+                        literals.swap(1, k);
+                        let watched = literals[1];
+                        self.watches[literal_index(watched)].push(*clause);
+                    }
+                    None => {
+                        kept.push(*clause);
+                        if !self.enqueue(other) {
+                            conflict = true;
+                        }
+                    }
+                }
+            }
+            self.watches[literal_index(falsified)] = kept;
+            if conflict {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Undoes the latest decision that has not been flipped yet and makes the opposite choice.
+    /// Returns false when every decision has been tried both ways.
+    fn backtrack(&mut self) -> bool {
+        while let Some((literal, trail_length, flipped)) = self.decisions.pop() {
+            for undone in self.trail.drain(trail_length..) {
+                self.assignment[undone.unsigned_abs() as usize] = None;
+            }
+            self.propagated = trail_length;
+            if !flipped {
+                self.decisions.push((-literal, trail_length, true));
+                self.enqueue(-literal);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Picks the first open literal of the unsatisfied clause with the fewest open literals.
+    fn choose(&self) -> Option<i32> {
+        let mut best: Option<(usize, i32)> = None;
+        for literals in &self.clauses {
+            if literals
+                .iter()
+                .any(|literal| self.value(*literal) == Some(true))
+            {
+                continue;
+            }
+            let open: Vec<i32> = literals
+                .iter()
+                .copied()
+                .filter(|literal| self.value(*literal).is_none())
+                .collect();
+            if best.is_none_or(|(count, _)| open.len() < count) {
+                best = Some((open.len(), open[0]));
+                if open.len() == 2 {
+                    break;
+                }
+            }
+        }
+        best.map(|(_, literal)| literal)
+    }
+}
+
+impl Cnf {
+    /// Writes the formula in the DIMACS format read by SAT solvers.
+    pub fn to_dimacs(&self) -> String {
+        let mut retval = format!("p cnf {} {}\n", self.variables, self.clauses.len());
+        for clause in &self.clauses {
+            for literal in clause {
+                let _ = write!(retval, "{} ", literal);
+            }
+            retval.push_str("0\n");
+        }
+        retval
+    }
+
+    /// Searches for a satisfying assignment with the DPLL algorithm. Returns the value of every
+    /// variable, indexed by variable number (index 0 is unused), or `None` if the formula is
+    /// unsatisfiable.
+    pub fn solve(&self) -> Option<Vec<bool>> {
+        let mut dpll = Dpll {
+            clauses: Vec::new(),
+            watches: vec![Vec::new(); (self.variables + 1) * 2],
+            assignment: vec![None; self.variables + 1],
+            trail: Vec::new(),
+            propagated: 0,
+            decisions: Vec::new(),
+        };
+        for clause in &self.clauses {
+            match clause.len() {
+                0 => return None,
+                1 => {
+                    if !dpll.enqueue(clause[0]) {
+                        return None;
+                    }
+                }
+                _ => {
+                    dpll.watches[literal_index(clause[0])].push(dpll.clauses.len());
+                    dpll.watches[literal_index(clause[1])].push(dpll.clauses.len());
+                    dpll.clauses.push(clause.clone());
+                }
+            }
+        }
+
+        loop {
+            if !dpll.propagate() {
+                if !dpll.backtrack() {
+                    return None;
+                }
+                continue;
+            }
+            match dpll.choose() {
+                Some(literal) => {
+                    dpll.decisions.push((literal, dpll.trail.len(), false));
+                    dpll.enqueue(literal);
+                }
+                None => {
+                    return Some(
+                        dpll.assignment
+                            .iter()
+                            .map(|value| value.unwrap_or(false))
+                            .collect(),
+                    )
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, Cnf};
+    use crate::{constraint, format::parse_line, solver::Solver, sudoku::Sudoku};
+
+    #[test]
+    fn test_solve_cnf() {
+        let cnf = Cnf {
+            variables: 3,
+            clauses: vec![vec![1, 2], vec![-1, 3], vec![-3, -2], vec![-1, -2]],
+        };
+        let model = cnf.solve().unwrap();
+        assert!(cnf.clauses.iter().all(|clause| clause
+            .iter()
+            .any(|literal| model[literal.unsigned_abs() as usize] == (*literal > 0))));
+
+        let cnf = Cnf {
+            variables: 2,
+            clauses: vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]],
+        };
+        assert_eq!(None, cnf.solve());
+        assert!(cnf.to_dimacs().starts_with("p cnf 2 4\n1 2 0\n"));
+    }
+
+    #[test]
+    fn test_encode_and_decode() {
+        let puzzle = parse_line(
+            "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+            1,
+        )
+        .unwrap();
+        let cnf = encode(&puzzle, &constraint::standard());
+        let grid = decode(&cnf.solve().unwrap());
+        assert!((0..81)
+            .all(|i| grid[i] != 0 && (puzzle.squares[i] == 0 || puzzle.squares[i] == grid[i])));
+        assert!(Solver::new(Sudoku::new_from_state(grid))
+            .find_conflicts()
+            .is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{
+    format::{parse_line, write_line},
+    observer::SolveStep,
+    solver::SolverBuilder,
+};
+use std::{
+    io,
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc,
+    thread,
+};
+use tungstenite::{Message, WebSocket};
+
+/// Runs a WebSocket server that solves puzzles and streams every step of the solve, so that
+/// web front-ends can animate the solution while it is found.
+///
+/// Clients send puzzles as text messages of 81 characters with '.' or '0' for blanks. For
+/// every puzzle the server sends each [`SolveStep`] as a JSON object (see
+/// [`SolveStep::to_json`]) followed by either `{"type":"solved","solution":"..."}` or
+/// `{"type":"error","message":"..."}`. Every connection is served on its own thread.
+///
+/// # Arguments
+///
+/// * `address` - Address to listen on, e.g. "127.0.0.1:8080".
+pub fn serve<A: ToSocketAddrs>(address: A) -> io::Result<()> {
+    serve_listener(TcpListener::bind(address)?)
+}
+
+/// Accepts connections from an already bound listener. See [`serve`].
+fn serve_listener(listener: TcpListener) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        thread::spawn(move || {
+            if let Ok(mut socket) = tungstenite::accept(stream) {
+                let _ = handle(&mut socket);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Serves one client until it closes the connection.
+fn handle(socket: &mut WebSocket<TcpStream>) -> Result<(), Box<tungstenite::Error>> {
+    loop {
+        let text = match socket.read()? {
+            Message::Text(text) => text,
+            Message::Close(_) => return Ok(()),
+            _ => continue,
+        };
+        let puzzle = match parse_line(text.trim(), 1) {
+            Ok(puzzle) => puzzle,
+            Err(err) => {
+                socket.send(error_message(&err.to_string()))?;
+                continue;
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel::<SolveStep>();
+        let solving = thread::spawn(move || {
+            let mut solver = SolverBuilder::new()
+                .observer(Box::new(sender))
+                .build(puzzle);
+            solver.solve().map(|_| solver.sudoku().clone())
+        });
+        for step in receiver {
+            socket.send(Message::text(step.to_json()))?;
+        }
+        let reply = match solving.join() {
+            Ok(Ok(solution)) => Message::text(format!(
+                "{{\"type\":\"solved\",\"solution\":\"{}\"}}",
+                write_line(&solution)
+            )),
+            Ok(Err(err)) => error_message(&err.to_string()),
+            Err(_) => error_message("solver panicked"),
+        };
+        socket.send(reply)?;
+    }
+}
+
+/// Builds the JSON message reporting an error to the client.
+fn error_message(message: &str) -> Message {
+    let escaped = message.replace('\\', "\\\\").replace('"', "\\\"");
+    Message::text(format!(
+        "{{\"type\":\"error\",\"message\":\"{}\"}}",
+        escaped
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::serve_listener;
+    use std::{net::TcpListener, thread};
+    use tungstenite::Message;
+
+    #[test]
+    fn test_stream_steps() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve_listener(listener));
+
+        let (mut socket, _) = tungstenite::connect(format!("ws://{}", address)).unwrap();
+        socket
+            .send(Message::text(
+                "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+            ))
+            .unwrap();
+
+        let mut placements = 0;
+        let last = loop {
+            let text = socket.read().unwrap().into_text().unwrap();
+            if text.starts_with("{\"type\":\"placement\"") {
+                placements += 1;
+            } else if !text.starts_with("{\"type\":\"elimination\"") {
+                break text;
+            }
+        };
+        assert_eq!(81 - 32, placements);
+        assert_eq!(
+            "{\"type\":\"solved\",\"solution\":\"483921657967345821251876493548132976729564138136798245372689514814253769695417382\"}",
+            last
+        );
+
+        socket.send(Message::text("123")).unwrap();
+        let text = socket.read().unwrap().into_text().unwrap();
+        assert!(text.starts_with("{\"type\":\"error\""));
+    }
+}
@@ -1,9 +1,41 @@
 use crate::{
-    check, coords,
-    sudoku::{Coordinates, Grid, Sudoku, SudokuError},
+    check,
+    constraint::{self, CandidateGrid, CandidateSet, Constraint},
+    coords,
+    metadata::PuzzleMetadata,
+    notation::Move,
+    observer::{EliminationCause, SolverObserver},
+    sat,
+    sudoku::{houses_of, Coordinates, Grid, House, Sudoku, SudokuError},
+    transform::Transform,
 };
-use rand::Rng;
-use std::{collections::HashSet, fmt::Display};
+use chrono::{DateTime, Datelike, NaiveDate};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::{
+    cmp::Reverse,
+    collections::{HashSet, VecDeque},
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+mod annealing;
+mod dlx;
+mod state;
+
+pub use state::SolverState;
+
+/// Number of moves the annealing backend tries before giving up.
+const ANNEALING_MOVES: u32 = 2_000_000;
+
+/// Number of forced placements [`Strategy::ForcingChain`] follows by default, see
+/// [`SolverBuilder::max_chain_length`].
+const MAX_CHAIN_LENGTH: usize = 20;
 
 /// Shortcut for generating a HashSet with all nine possible values.
 macro_rules! all_possible {
@@ -15,55 +47,375 @@ macro_rules! all_possible {
 /// Type alias for squares that directly effect the possibilities for a given square.
 type Neighbors = HashSet<Coordinates>;
 
-/// Type alias for possible values for a given square.
-type Possibilities = HashSet<u8>;
+/// Type alias for values ruled out by a strategy, each with its square.
+type Eliminations = Vec<(Coordinates, u8)>;
 
-/// Solver class containing the sudoku to solve and a snapshot of the last secure state (before any guesses have been made).
+/// Solver class containing the sudoku to solve, the constraints it must satisfy
+/// and the restore points of the search.
 #[derive(Debug)]
 pub struct Solver {
     sudoku: Sudoku,
-    last_secure_state: Grid,
-    last_secure_state_set: bool,
+    constraints: Vec<Box<dyn Constraint>>,
+    /// Restore points of the backtracking search, oldest first: the grid the solver was created
+    /// with, then the secure state reached before the first guess. Recovery only unsets squares
+    /// that are empty in the newest one.
+    restore_points: Vec<Snapshot>,
+    stats: SolveStats,
+    rng: ChaCha8Rng,
+    observers: Vec<Box<dyn SolverObserver>>,
+    backend: SolverBackend,
+    config: SolverConfig,
+    strategies: Vec<Strategy>,
+    max_chain_length: usize,
+    max_guesses: Option<u32>,
+    /// [`Sudoku::zobrist`] hashes of the states the backtracking backend has proven to have no
+    /// solution, if the transposition table is enabled.
+    dead_states: Option<HashSet<u64>>,
+    rating_weights: RatingWeights,
+}
+
+/// Tuning of the guessing done by the backtracking backend.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SolverConfig {
+    /// How to choose the square to guess.
+    pub cell_selection: CellSelection,
+    /// How to choose the value to guess for the square.
+    pub value_order: ValueOrder,
+}
+
+/// Strategies for choosing the square to guess.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CellSelection {
+    /// The square with the fewest possible values, the first one in reading order on ties.
+    #[default]
+    MinimumRemainingValues,
+    /// The square with the most empty peers, i.e. the one constraining the most other squares.
+    /// Ties are broken by the fewest possible values, then by reading order.
+    Degree,
+    /// The first empty square in reading order.
+    FirstEmpty,
+    /// A random empty square.
+    Random,
+}
+
+/// Strategies for choosing the value to guess among the possible values of a square.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ValueOrder {
+    /// A random possible value.
+    #[default]
+    Random,
+    /// The smallest possible value.
+    Ascending,
+    /// The value that rules out the fewest possibilities of the empty peers, the smallest
+    /// one on ties.
+    LeastConstraining,
+}
+
+/// Logical strategies the solver can use to fill squares without guessing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Set squares that have only one possible value.
+    NakedSingle,
+    /// Set squares that are the only place for a value in their row, column or block.
+    HiddenSingle,
+    /// Rule out a value for the rest of a row, column or block when another house only has
+    /// places for it where the two overlap (pointing and claiming).
+    LockedCandidates,
+    /// Rule out values that would leave four squares of two rows, two columns and two blocks
+    /// with the same two possible values, which could be swapped for a second solution (types
+    /// 1 and 2). Only sound for puzzles with a unique solution, so not enabled by default.
+    UniqueRectangle,
+    /// When every empty square but one has two possible values and that one has three, set it
+    /// to the value it shares with two other squares of each of its houses (BUG+1): anything
+    /// else would leave a grid whose two-way choices allow a second solution. Only sound for
+    /// puzzles with a unique solution, so not enabled by default.
+    BivalueUniversalGrave,
+    /// Rule out values seen by two almost locked sets, groups of squares in a house with one
+    /// possible value more than squares, that share a value only one of them can hold (ALS-XZ).
+    /// Looks through many groups of squares, so not enabled by default.
+    AlsXz,
+    /// Try a possible value, follow the naked and hidden singles it forces and rule the value
+    /// out if they lead to a contradiction (Nishio). The costliest strategy, so not enabled by
+    /// default; [`SolverBuilder::max_chain_length`] bounds the work per value tried.
+    ForcingChain,
+}
+
+/// The algorithms [`Solver::solve`] can use.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SolverBackend {
+    /// Fill in singles, guess randomly when stuck and reset squares after bad guesses.
+    #[default]
+    Backtracking,
+    /// Exact cover search with dancing links. Fastest, but reports no reasoning.
+    Dlx,
+    /// Logical strategies only; fails with [`SolverError::Stuck`] where they run out.
+    Logic,
+    /// Logical strategies as far as they get, then exact cover search for the rest.
+    Hybrid,
+    /// Encode the sudoku as a boolean formula and solve it with the built-in SAT solver.
+    Sat,
+    /// Simulated annealing: fill the blocks and swap squares until no constraint is broken.
+    /// A baseline for benchmarks; fails with [`SolverError::Stuck`] if it gives up.
+    Annealing,
+    /// Logical strategies as far as they get, then every possible value of the square with the
+    /// fewest is searched on a thread of its own, and the first solution found wins. Cuts the
+    /// wall-clock time on puzzles where a bad first guess takes long to refute.
+    Parallel,
+}
+
+/// Builder for a [`Solver`] with non-default options. Options added later get a method
+/// here instead of a new parameter for [`Solver::new`].
+#[derive(Debug, Default)]
+pub struct SolverBuilder {
+    backend: SolverBackend,
+    config: SolverConfig,
+    seed: Option<u64>,
+    strategies: Option<Vec<Strategy>>,
+    max_chain_length: Option<usize>,
+    max_guesses: Option<u32>,
+    transposition_table: bool,
+    constraints: Option<Vec<Box<dyn Constraint>>>,
+    observers: Vec<Box<dyn SolverObserver>>,
+    rating_weights: RatingWeights,
+}
+
+/// Progress of sudoku generation, passed to the progress callback of
+/// [`Solver::generate_with_progress`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GenerationProgress {
+    /// Number of clues removed from the solved grid so far.
+    pub clues_removed: u32,
+    /// Number of uniqueness checks performed so far.
+    pub uniqueness_checks: u32,
+    /// Number of squares considered for removal so far (at most 81).
+    pub attempts: u32,
+}
+
+/// Requirements and limits for [`Solver::generate_with_options`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerationOptions {
+    /// Probability for each square to get reset, or with `unique` the percentage of the squares
+    /// to reset.
+    pub difficulty: u8,
+    /// Only remove squares while the solution stays unique.
+    pub unique: bool,
+    /// Remove squares in pairs mirrored through the center, so the clues are symmetric.
+    pub symmetric: bool,
+    /// Accept only puzzles with at most this many clues.
+    pub max_clues: Option<u8>,
+    /// Accept only puzzles that the default logical strategies (see
+    /// [`SolverBuilder::strategies`]) cannot solve without guessing.
+    pub requires_guessing: bool,
+    /// Number of puzzles to generate before giving up.
+    pub max_attempts: u32,
+    /// Time to spend before giving up, checked between attempts.
+    pub time_limit: Option<Duration>,
+    /// Make every solution by randomly [transforming](crate::transform::Transform) this grid
+    /// instead of solving an empty one, which is much faster. A partially filled grid is
+    /// solved first. The solutions are then all equivalent, e.g. to
+    /// [`SEED_GRID`](crate::transform::SEED_GRID), so use a different seed grid for variety.
+    pub seed_grid: Option<Grid>,
+}
+
+/// How hard a puzzle is, with the numbers the score is built from so that callers can
+/// build their own scales. See [`Solver::difficulty_report`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DifficultyReport {
+    /// Number of set squares.
+    pub clues: u8,
+    /// Name of the hardest technique needed, `None` if the puzzle is already solved.
+    pub hardest_technique: Option<&'static str>,
+    /// Names of every technique needed, see [`SolveStats::techniques`].
+    pub techniques: Vec<&'static str>,
+    /// Number of guesses a reproducible search needs, see [`Solver::rate_remaining`].
+    pub guesses: u32,
+    /// Average number of possible values of the empty squares at the start.
+    pub branching_factor: f64,
+    /// Overall difficulty: 0 for a solved grid, 1 for puzzles solvable with singles, plus
+    /// log2(1 + guesses) and (branching factor - 1) / 8, unless other [`RatingWeights`] are
+    /// given.
+    pub score: f64,
+}
+
+/// Scales a [`DifficultyReport`] can be expressed on, see [`DifficultyReport::rating`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RatingScale {
+    /// The score of this crate, see [`DifficultyReport::score`].
+    #[default]
+    Score,
+    /// An approximation of the Sudoku Explainer scale from 1.0 to 11.9, see
+    /// [`DifficultyReport::se_rating`].
+    SudokuExplainer,
+}
+
+/// Weights of the parts of [`DifficultyReport::score`]: the score of an unsolved puzzle is
+/// `base + guesses * log2(1 + guesses) + branching * (branching factor - 1)`. The defaults
+/// give the built-in scale; [`calibrate`](crate::calibration::calibrate) fits them to the solve
+/// times of real players.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RatingWeights {
+    /// Score of a puzzle solvable with singles and with one possible value per empty square.
+    pub base: f64,
+    /// Weight of the logarithm of the guesses.
+    pub guesses: f64,
+    /// Weight of the possible values per empty square beyond the first.
+    pub branching: f64,
+}
+
+/// A copy of the grid of a [`Solver`] to return to with [`Solver::restore`].
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    sudoku: Sudoku,
+}
+
+impl Snapshot {
+    /// Get the grid as it was when the snapshot was taken.
+    pub fn sudoku(&self) -> &Sudoku {
+        &self.sudoku
+    }
+}
+
+/// Counters describing the work done by the solver.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SolveStats {
+    /// Number of squares set because they had only one possible value.
+    pub singles: u32,
+    /// Number of squares set because they were the only place for a value in a house.
+    pub hidden_singles: u32,
+    /// Number of times a value was ruled out for part of a house because another house only
+    /// has places for it in their overlap.
+    pub locked_candidates: u32,
+    /// Number of times values were ruled out to avoid a unique rectangle.
+    pub unique_rectangles: u32,
+    /// Number of squares set to avoid a bivalue universal grave.
+    pub bivalue_universal_graves: u32,
+    /// Number of ALS-XZ findings ruling out values.
+    pub als_xz: u32,
+    /// Number of values ruled out because forcing chains led to a contradiction.
+    pub forcing_chains: u32,
+    /// Number of squares set by guessing between multiple possible values.
+    pub guesses: u32,
+    /// Number of uncertain squares reset after a bad guess.
+    pub backtracks: u32,
+    /// Number of times the set squares were propagated to a fixed point while solving.
+    pub propagations: u32,
 }
 
 /// Error type for exceptions during solving.
 #[derive(Debug)]
 pub enum SolverError {
-    NoPossibilities,
+    /// A square expected to have possible values had none.
+    NoPossibilities(FailureContext),
     SudokuError(SudokuError),
+    /// The sudoku has no solution.
+    Unsolvable {
+        /// The contradiction proving it, e.g. "digit 5 has no place in row 3".
+        reason: String,
+        /// Where the contradiction was found.
+        context: Box<FailureContext>,
+    },
+    /// The sudoku has more than one solution.
+    MultipleSolutions,
+    /// The solver gave up before finishing the sudoku: the logical strategies ran out, the
+    /// guess limit was reached, or annealing did not find a solution in time.
+    Stuck(FailureContext),
+    /// No generated puzzle met the requirements within the attempt or time limit.
+    GenerationFailed {
+        /// Number of puzzles generated and rejected.
+        attempts: u32,
+    },
 }
 
-impl From<Coordinates> for Neighbors {
-    fn from(coords: Coordinates) -> Self {
-        let mut retval = Neighbors::new();
+/// The part of the solve an error happened in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolvePhase {
+    /// Filling in squares with the logical strategies.
+    Propagation,
+    /// Choosing a square and a value to guess.
+    Guessing,
+    /// Resetting squares after a bad guess.
+    Recovery,
+    /// An exhaustive search: exact cover, SAT, annealing or counting solutions.
+    Search,
+}
 
-        for i in 0..9 {
-            // Row
-            retval.insert(Coordinates {
-                row: coords.row,
-                column: i,
-            });
-            // Column
-            retval.insert(Coordinates {
-                row: i,
-                column: coords.column,
-            });
-        }
-        // Block
-        let block_row = coords.row / 3;
-        let block_column = coords.column / 3;
-        for row_in_b in 0..3 {
-            for column_in_b in 0..3 {
-                retval.insert(Coordinates {
-                    row: block_row * 3 + row_in_b,
-                    column: block_column * 3 + column_in_b,
-                });
-            }
+/// Where the solver was and what the grid looked like when it failed, for diagnosing
+/// failed solves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailureContext {
+    /// The part of the solve that failed.
+    pub phase: SolvePhase,
+    /// The square being worked on, if the failure concerns one.
+    pub cell: Option<Coordinates>,
+    /// The grid at the time of the failure.
+    pub grid: Grid,
+}
+
+/// Best-effort result of [`Solver::solve_partial`].
+#[derive(Debug, Clone)]
+pub struct PartialSolution {
+    /// The most filled grid reached.
+    pub sudoku: Sudoku,
+    /// Possible values of every square of that grid; empty for the set squares.
+    pub candidates: CandidateGrid,
+    /// Whether the grid is completely solved.
+    pub solved: bool,
+}
+
+/// Result of comparing a player's attempt against the solution of the puzzle.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Mistakes {
+    /// Squares containing a value that differs from the solution.
+    pub wrong: Vec<Coordinates>,
+    /// Squares that are still empty.
+    pub empty: Vec<Coordinates>,
+}
+
+impl Default for GenerationOptions {
+    /// Difficulty 50 without further requirements, trying up to 100 puzzles.
+    fn default() -> Self {
+        GenerationOptions {
+            difficulty: 50,
+            unique: false,
+            symmetric: false,
+            max_clues: None,
+            requires_guessing: false,
+            max_attempts: 100,
+            time_limit: None,
+            seed_grid: None,
         }
+    }
+}
+
+impl SolveStats {
+    /// Name of the hardest technique the solve needed, or `None` if nothing had to be filled in.
+    pub fn hardest_technique(&self) -> Option<&'static str> {
+        self.techniques().last().copied()
+    }
 
-        retval.remove(&coords);
+    /// Names of every technique the solve needed, in the order of
+    /// [`TECHNIQUES`](crate::technique::TECHNIQUES).
+    pub fn techniques(&self) -> Vec<&'static str> {
+        [
+            ("Naked Single", self.singles),
+            ("Hidden Single", self.hidden_singles),
+            ("Locked Candidates", self.locked_candidates),
+            ("Unique Rectangle", self.unique_rectangles),
+            ("Bivalue Universal Grave", self.bivalue_universal_graves),
+            ("ALS-XZ", self.als_xz),
+            ("Forcing Chain", self.forcing_chains),
+            ("Guess", self.guesses),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(technique, _)| technique)
+        .collect()
+    }
+}
 
-        retval
+impl From<Coordinates> for Neighbors {
+    fn from(coords: Coordinates) -> Self {
+        coords.peers().collect()
     }
 }
 
@@ -73,195 +425,3497 @@ impl From<(usize, usize)> for Coordinates {
     }
 }
 
-impl From<SudokuError> for SolverError {
-    fn from(err: SudokuError) -> Self {
-        SolverError::SudokuError(err)
+impl DifficultyReport {
+    /// Approximates the rating Sudoku Explainer would give the puzzle, so that it can be
+    /// compared with ratings published elsewhere: the highest rating on the SE scale of the
+    /// techniques needed, where guesses rate 2.6 plus 0.8 per doubling of the guesses, up to
+    /// 11.9. Solved grids rate 0. As the solver knows fewer techniques than Sudoku Explainer,
+    /// puzzles needing guesses here can rate higher than they would there.
+    pub fn se_rating(&self) -> f64 {
+        self.techniques
+            .iter()
+            .map(|technique| match *technique {
+                "Guess" => (2.6 + 0.8 * (1.0 + self.guesses as f64).log2()).min(11.9),
+                technique => se_technique_rating(technique),
+            })
+            .fold(0.0, f64::max)
+    }
+
+    /// Returns the rating on the given scale.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The scale to rate on.
+    pub fn rating(&self, scale: RatingScale) -> f64 {
+        match scale {
+            RatingScale::Score => self.score,
+            RatingScale::SudokuExplainer => self.se_rating(),
+        }
     }
 }
 
-impl Solver {
-    /// Create a new solver with the given sudoku.
-    pub fn new(sudoku: Sudoku) -> Self {
-        let last_secure_state = sudoku.squares.clone();
-        Solver {
-            sudoku,
-            last_secure_state,
-            last_secure_state_set: false,
+/// Returns the rating of a technique on the Sudoku Explainer scale.
+///
+/// # Arguments
+///
+/// * `technique` - Name of the technique, as in [`SolveStats::hardest_technique`].
+fn se_technique_rating(technique: &str) -> f64 {
+    match technique {
+        "Hidden Single" => 1.5,
+        "Naked Single" => 2.3,
+        "Locked Candidates" => 2.6,
+        "Unique Rectangle" => 4.5,
+        "Bivalue Universal Grave" => 5.6,
+        "ALS-XZ" => 7.0,
+        "Forcing Chain" => 7.5,
+        _ => 2.6,
+    }
+}
+
+impl Default for RatingWeights {
+    fn default() -> Self {
+        RatingWeights {
+            base: 1.0,
+            guesses: 1.0,
+            branching: 1.0 / 8.0,
         }
     }
+}
 
-    /// Get possible values for given coordinates.
+impl RatingWeights {
+    /// Returns the score of an unsolved puzzle with the given numbers.
     ///
     /// # Arguments
     ///
-    /// * `row` - Row index for the square to check.
-    /// * `column` - Column index for the square to check.
-    pub fn get_possible(&self, row: usize, column: usize) -> Result<Possibilities, SudokuError> {
-        check!(coords row, column);
+    /// * `guesses` - Number of guesses the search needs.
+    /// * `branching_factor` - Average number of possible values of the empty squares.
+    pub fn score(&self, guesses: u32, branching_factor: f64) -> f64 {
+        self.base
+            + self.guesses * (1.0 + guesses as f64).log2()
+            + self.branching * (branching_factor - 1.0)
+    }
+}
 
-        let mut retval = all_possible!();
+impl SolverBuilder {
+    /// Create a builder with the default options.
+    pub fn new() -> Self {
+        SolverBuilder::default()
+    }
 
-        let row_values = self.sudoku.get_row(row)?;
-        let column_values = self.sudoku.get_column(column)?;
-        let block_values = self.sudoku.get_block(row / 3, column / 3)?;
+    /// Choose the algorithm used by [`Solver::solve`].
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The algorithm to use.
+    pub fn backend(mut self, backend: SolverBackend) -> Self {
+        self.backend = backend;
+        self
+    }
 
-        for i in 0..9 {
-            retval.remove(&row_values[i]);
-            retval.remove(&column_values[i]);
-            retval.remove(&block_values[i]);
-        }
+    /// Tune the guessing of the backtracking backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration to use.
+    pub fn config(mut self, config: SolverConfig) -> Self {
+        self.config = config;
+        self
+    }
 
-        Ok(retval)
+    /// Choose how the backtracking backend picks the square to guess.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell_selection` - The strategy to use.
+    pub fn cell_selection(mut self, cell_selection: CellSelection) -> Self {
+        self.config.cell_selection = cell_selection;
+        self
+    }
+
+    /// Choose how the backtracking backend picks the value to guess.
+    ///
+    /// # Arguments
+    ///
+    /// * `value_order` - The strategy to use.
+    pub fn value_order(mut self, value_order: ValueOrder) -> Self {
+        self.config.value_order = value_order;
+        self
     }
 
-    /// Take a snapshot of the current state and store as the last secure state.
-    fn set_last_secure_state(&mut self) {
-        self.last_secure_state = self.sudoku.squares.clone();
-        self.last_secure_state_set = true;
+    /// Seed the random number generator used for guessing, making the solve reproducible.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
     }
 
-    /// One step of the solver algorithm. First set any squares with only one possible value,
-    /// then guess all squares have multiple possibilities. If a square has no possible values (after a bad guess),
-    /// unset neighboring uncertain squares (squares that are not set in the last secure state)
-    /// until some value can be inserted.
-    fn step(&mut self) -> Result<(), SolverError> {
-        let mut changed = false;
-        let mut certain = true;
-        let mut lowest_possibilities = all_possible!();
-        let mut lowest_possible_coords = Coordinates::from((0, 0));
-
-        for row in 0usize..9 {
-            for column in 0usize..9 {
-                if !self.sudoku.is_set(row, column)? {
-                    let mut possibilities = self.get_possible(row, column)?;
-
-                    if possibilities.is_empty() {
-                        certain = false;
-                        while possibilities.is_empty() {
-                            let neighbors = Neighbors::from(Coordinates { row, column });
-                            let mut possible_resets = neighbors.clone();
-                            for possible_reset in neighbors {
-                                if self.last_secure_state
-                                    [coords!(possible_reset.row, possible_reset.column)]
-                                    != 0
-                                {
-                                    possible_resets.remove(&possible_reset);
-                                }
-                            }
-
-                            let mut rng = rand::thread_rng();
-                            let len = possible_resets.len();
-                            let reset = &possible_resets.into_iter().collect::<Vec<Coordinates>>()
-                                [rng.gen_range(0..len)];
-
-                            self.sudoku.set(reset.row, reset.column, 0)?;
-
-                            possibilities = self.get_possible(row, column)?;
-                        }
-                    }
+    /// Choose the logical strategies the solver may use. All of them but
+    /// [`Strategy::UniqueRectangle`] and [`Strategy::BivalueUniversalGrave`], which rely on the
+    /// puzzle having a unique solution, and the costly [`Strategy::AlsXz`] and
+    /// [`Strategy::ForcingChain`] are enabled by default; squares the enabled strategies cannot
+    /// fill are guessed.
+    ///
+    /// # Arguments
+    ///
+    /// * `strategies` - The enabled strategies.
+    pub fn strategies(mut self, strategies: &[Strategy]) -> Self {
+        self.strategies = Some(strategies.to_vec());
+        self
+    }
 
-                    if possibilities.len() <= lowest_possibilities.len() {
-                        certain = false;
-                        lowest_possibilities = possibilities.clone();
-                        lowest_possible_coords.row = row;
-                        lowest_possible_coords.column = column;
-                    }
+    /// Limit how many forced placements [`Strategy::ForcingChain`] follows after trying a value
+    /// before giving up on finding a contradiction. Longer chains rule out more values, but
+    /// every value tried may cost that many placements; 20 by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_chain_length` - The limit.
+    pub fn max_chain_length(mut self, max_chain_length: usize) -> Self {
+        self.max_chain_length = Some(max_chain_length);
+        self
+    }
+
+    /// Make the backtracking backend give up with [`SolverError::Stuck`] once it has made
+    /// this many guesses and backtracks combined.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_guesses` - The limit.
+    pub fn max_guesses(mut self, max_guesses: u32) -> Self {
+        self.max_guesses = Some(max_guesses);
+        self
+    }
+
+    /// Make the backtracking backend remember the states it has proven to have no solution, by
+    /// their [`Sudoku::zobrist`] hash, and never guess its way back into one. Random guessing
+    /// and recovery can reach the same dead state more than once; the table spares repeating
+    /// that work at the cost of eight bytes per dead state. Off by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to keep the table.
+    pub fn transposition_table(mut self, enabled: bool) -> Self {
+        self.transposition_table = enabled;
+        self
+    }
+
+    /// Replace the standard constraints.
+    ///
+    /// # Arguments
+    ///
+    /// * `constraints` - Every constraint the solution must satisfy, including the standard ones.
+    pub fn constraints(mut self, constraints: Vec<Box<dyn Constraint>>) -> Self {
+        self.constraints = Some(constraints);
+        self
+    }
+
+    /// Register an observer, see [`Solver::add_observer`].
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - The observer to notify.
+    pub fn observer(mut self, observer: Box<dyn SolverObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Weigh the parts of the difficulty score differently, e.g. with weights fitted by
+    /// [`calibrate`](crate::calibration::calibrate).
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - The weights for [`Solver::difficulty_report`].
+    pub fn rating_weights(mut self, weights: RatingWeights) -> Self {
+        self.rating_weights = weights;
+        self
+    }
+
+    /// Create the solver for the given sudoku.
+    ///
+    /// # Arguments
+    ///
+    /// * `sudoku` - The sudoku to solve.
+    pub fn build(self, sudoku: Sudoku) -> Solver {
+        let mut solver = match self.constraints {
+            Some(constraints) => Solver::with_constraints(sudoku, constraints),
+            None => Solver::new(sudoku),
+        };
+        solver.backend = self.backend;
+        solver.config = self.config;
+        if let Some(strategies) = self.strategies {
+            solver.strategies = strategies;
+        }
+        if let Some(max_chain_length) = self.max_chain_length {
+            solver.max_chain_length = max_chain_length;
+        }
+        solver.max_guesses = self.max_guesses;
+        if self.transposition_table {
+            solver.dead_states = Some(HashSet::new());
+        }
+        solver.observers = self.observers;
+        solver.rating_weights = self.rating_weights;
+        if let Some(seed) = self.seed {
+            solver.set_seed(seed);
+        }
+        solver
+    }
+}
+
+/// Get possible values for given coordinates in any grid under the given constraints.
+///
+/// # Arguments
+///
+/// * `constraints` - Every constraint the grid must satisfy.
+/// * `grid` - The grid to check.
+/// * `cell` - Coordinates of the square to check. Must be within bounds.
+fn candidates_with(
+    constraints: &[Box<dyn Constraint>],
+    grid: &Grid,
+    cell: &Coordinates,
+) -> CandidateSet {
+    let mut retval = all_possible!();
+
+    for constraint in constraints {
+        for value in constraint.eliminate(grid, cell) {
+            retval.remove(&value);
+        }
+    }
+
+    retval
+}
+
+/// Find a value that only one empty square of a house can hold, checking the houses in the
+/// order of [`House::all`] and the values in ascending order. Returns the square, the value and
+/// the house.
+///
+/// # Arguments
+///
+/// * `grid` - The grid to check.
+/// * `candidates` - Possible values of every square of the grid.
+pub(crate) fn hidden_single(
+    grid: &Grid,
+    candidates: &CandidateGrid,
+) -> Option<(Coordinates, u8, House)> {
+    House::all().find_map(|house| {
+        (1..=9).find_map(|value| {
+            let mut cells = house.cells().filter(|cell| {
+                let i = coords!(cell.row, cell.column);
+                grid[i] == 0 && candidates[i].contains(&value)
+            });
+            match (cells.next(), cells.next()) {
+                (Some(cell), None) => Some((cell, value, house)),
+                _ => None,
+            }
+        })
+    })
+}
 
-                    if possibilities.len() == 1 {
-                        changed = true;
-                        self.sudoku.set(
-                            row,
-                            column,
-                            possibilities
-                                .into_iter()
-                                .next()
-                                .ok_or(SolverError::NoPossibilities)?,
-                        )?;
+/// Find a value whose possible squares in one house all lie in a second house, so that it can
+/// be ruled out for the other squares of the second house: a block pointing at a row or column,
+/// or a row or column claiming a block. Houses are checked in the order of [`House::all`] and
+/// values in ascending order, and only findings that rule something out are returned. Returns
+/// the value, the house holding it, the house it is ruled out in and the squares it is ruled
+/// out for, in reading order.
+///
+/// # Arguments
+///
+/// * `grid` - The grid to check.
+/// * `candidates` - Possible values of every square of the grid.
+pub(crate) fn locked_candidates(
+    grid: &Grid,
+    candidates: &CandidateGrid,
+) -> Option<(u8, House, House, Vec<Coordinates>)> {
+    House::all().find_map(|base| {
+        (1..=9).find_map(|value| {
+            let cells: Vec<Coordinates> = base
+                .cells()
+                .filter(|cell| {
+                    let i = coords!(cell.row, cell.column);
+                    grid[i] == 0 && candidates[i].contains(&value)
+                })
+                .collect();
+            if cells.len() < 2 {
+                return None;
+            }
+            let cover = houses_of(&cells[0]).into_iter().find(|house| {
+                matches!(base, House::Block(_)) != matches!(house, House::Block(_))
+                    && cells.iter().all(|cell| houses_of(cell).contains(house))
+            })?;
+            let eliminations: Vec<Coordinates> = cover
+                .cells()
+                .filter(|cell| {
+                    let i = coords!(cell.row, cell.column);
+                    grid[i] == 0
+                        && candidates[i].contains(&value)
+                        && !houses_of(cell).contains(&base)
+                })
+                .collect();
+            (!eliminations.is_empty()).then_some((value, base, cover, eliminations))
+        })
+    })
+}
+
+/// Find four empty squares in two rows, two columns and two blocks that share two possible
+/// values, where ruling out other values would leave the two values free to swap places and the
+/// puzzle with two solutions. In type 1, three of the squares can only be the two values, so
+/// the fourth cannot be either. In type 2, two squares can only be the two values and the other
+/// two, which share a row or column, have the same third value, so one of them must be it and
+/// it is ruled out for every square that sees both. Rectangles are checked row pair by row pair
+/// and only findings that rule something out are returned. Returns the corners in reading
+/// order, the two values and the squares with the value ruled out for them.
+///
+/// # Arguments
+///
+/// * `grid` - The grid to check.
+/// * `candidates` - Possible values of every square of the grid.
+pub(crate) fn unique_rectangle(
+    grid: &Grid,
+    candidates: &CandidateGrid,
+) -> Option<([Coordinates; 4], [u8; 2], Eliminations)> {
+    for (top, bottom, left, right) in (0..9).flat_map(|top| {
+        (top + 1..9).flat_map(move |bottom| {
+            (0..9).flat_map(move |left| (left + 1..9).map(move |right| (top, bottom, left, right)))
+        })
+    }) {
+        if (top / 3 == bottom / 3) == (left / 3 == right / 3) {
+            continue;
+        }
+        let corners = [
+            Coordinates::from((top, left)),
+            Coordinates::from((top, right)),
+            Coordinates::from((bottom, left)),
+            Coordinates::from((bottom, right)),
+        ];
+        let indices = corners
+            .clone()
+            .map(|corner| coords!(corner.row, corner.column));
+        if indices.iter().any(|i| grid[*i] != 0) {
+            continue;
+        }
+        let mut shared: Vec<u8> = (1..=9)
+            .filter(|value| indices.iter().all(|i| candidates[*i].contains(value)))
+            .collect();
+        shared.sort_unstable();
+        for (n, a) in shared.iter().enumerate() {
+            for b in &shared[n + 1..] {
+                let pair = CandidateSet::from([*a, *b]);
+                let roof: Vec<usize> = (0..4)
+                    .filter(|corner| candidates[indices[*corner]] != pair)
+                    .collect();
+                let eliminations: Eliminations = match roof[..] {
+                    [corner] => vec![(corners[corner].clone(), *a), (corners[corner].clone(), *b)],
+                    [first, second] => {
+                        let extra: Vec<u8> = candidates[indices[first]]
+                            .difference(&pair)
+                            .copied()
+                            .collect();
+                        let (first_cell, second_cell) = (&corners[first], &corners[second]);
+                        if extra.len() != 1
+                            || candidates[indices[second]] != candidates[indices[first]]
+                            || (first_cell.row != second_cell.row
+                                && first_cell.column != second_cell.column)
+                        {
+                            continue;
+                        }
+                        let mut seeing: Vec<Coordinates> = first_cell
+                            .peers()
+                            .filter(|cell| {
+                                let i = coords!(cell.row, cell.column);
+                                cell != second_cell
+                                    && second_cell.peers().any(|peer| peer == *cell)
+                                    && grid[i] == 0
+                                    && candidates[i].contains(&extra[0])
+                            })
+                            .collect();
+                        seeing.sort_by_key(|cell| (cell.row, cell.column));
+                        seeing.into_iter().map(|cell| (cell, extra[0])).collect()
                     }
+                    _ => continue,
+                };
+                if !eliminations.is_empty() {
+                    return Some((corners, [*a, *b], eliminations));
                 }
             }
         }
+    }
+    None
+}
 
-        if !changed {
-            let lowest_vec: Vec<u8> = lowest_possibilities.into_iter().collect();
-            let mut rng = rand::thread_rng();
-            let lowest = lowest_vec[rng.gen_range(0..lowest_vec.len())];
-            self.sudoku.set(
-                lowest_possible_coords.row,
-                lowest_possible_coords.column,
-                lowest,
-            )?;
-        } else if !self.last_secure_state_set & certain {
-            self.set_last_secure_state();
+/// Find the way out of a bivalue universal grave: if every empty square has two possible values
+/// but one, which has three, and every possible value is in either none or two squares of each
+/// row, column and block, except for one value of the three that is in three squares of each
+/// house of that square, the square must be that value. Were it any other value, every square
+/// would be left with two possible values, each twice in every house, and such a grid has either
+/// no solution or several.
+///
+/// # Arguments
+///
+/// * `grid` - The grid to check.
+/// * `candidates` - Possible values of every square of the grid.
+pub(crate) fn bivalue_universal_grave(
+    grid: &Grid,
+    candidates: &CandidateGrid,
+) -> Option<(Coordinates, u8)> {
+    let mut triple = None;
+    for i in (0..9 * 9).filter(|i| grid[*i] == 0) {
+        match candidates[i].len() {
+            2 => {}
+            3 if triple.is_none() => triple = Some(i),
+            _ => return None,
         }
+    }
+    let cell = triple.map(|i| Coordinates::from((i / 9, i % 9)))?;
+    let holding = |house: &House, value: u8| {
+        house
+            .cells()
+            .filter(|other| {
+                let i = coords!(other.row, other.column);
+                grid[i] == 0 && candidates[i].contains(&value)
+            })
+            .count()
+    };
+    let houses = houses_of(&cell);
+    let mut values: Vec<u8> = candidates[coords!(cell.row, cell.column)]
+        .iter()
+        .copied()
+        .filter(|value| houses.iter().all(|house| holding(house, *value) == 3))
+        .collect();
+    let value = match values.len() {
+        1 => values.pop()?,
+        _ => return None,
+    };
+    let grave = House::all().all(|house| {
+        (1..=9).all(|other| match holding(&house, other) {
+            0 | 2 => !(other == value && houses.contains(&house)),
+            3 => other == value && houses.contains(&house),
+            _ => false,
+        })
+    });
+    grave.then_some((cell, value))
+}
 
-        Ok(())
+/// Find every almost locked set of the grid: empty squares of one house with exactly one possible
+/// value more than there are squares, a single square with two possible values included. Houses
+/// are checked in the order of [`House::all`], and a set found in two houses is returned once.
+/// Returns the squares of each set, in reading order, and their possible values.
+///
+/// # Arguments
+///
+/// * `grid` - The grid to check.
+/// * `candidates` - Possible values of every square of the grid.
+fn almost_locked_sets(
+    grid: &Grid,
+    candidates: &CandidateGrid,
+) -> Vec<(Vec<Coordinates>, CandidateSet)> {
+    let mut retval: Vec<(Vec<Coordinates>, CandidateSet)> = Vec::new();
+    for house in House::all() {
+        let empty: Vec<Coordinates> = house
+            .cells()
+            .filter(|cell| grid[coords!(cell.row, cell.column)] == 0)
+            .collect();
+        for subset in 1..1u32 << empty.len() {
+            let mut cells: Vec<Coordinates> = (0..empty.len())
+                .filter(|n| subset & (1 << n) != 0)
+                .map(|n| empty[n].clone())
+                .collect();
+            let values: CandidateSet = cells
+                .iter()
+                .flat_map(|cell| candidates[coords!(cell.row, cell.column)].iter().copied())
+                .collect();
+            cells.sort_by_key(|cell| (cell.row, cell.column));
+            if values.len() == cells.len() + 1 && !retval.iter().any(|(other, _)| *other == cells) {
+                retval.push((cells, values));
+            }
+        }
     }
+    retval
+}
 
-    /// Run the algorithm until all squares are set.
-    pub fn solve(&mut self) -> Result<(), SolverError> {
-        while self.sudoku.set_count < 9 * 9 {
-            self.step()?;
+/// Find two almost locked sets without shared squares and a value they both hold, the
+/// restricted common, whose squares in one set all see its squares in the other. The value
+/// cannot be in both sets, so one of them is locked to its other values, and another value the
+/// sets share is in one of them: it is ruled out for every square seeing all its squares in both
+/// sets. Sets are checked in the order of [`almost_locked_sets`] and values in ascending order,
+/// and only findings that rule something out are returned. Returns the two sets, the restricted
+/// common and the squares with the values ruled out for them.
+///
+/// # Arguments
+///
+/// * `grid` - The grid to check.
+/// * `candidates` - Possible values of every square of the grid.
+pub(crate) fn als_xz(
+    grid: &Grid,
+    candidates: &CandidateGrid,
+) -> Option<([Vec<Coordinates>; 2], u8, Eliminations)> {
+    let sets = almost_locked_sets(grid, candidates);
+    let sees = |a: &Coordinates, b: &Coordinates| {
+        houses_of(a)
+            .iter()
+            .any(|house| houses_of(b).contains(house))
+    };
+    for (n, (first, first_values)) in sets.iter().enumerate() {
+        for (second, second_values) in &sets[n + 1..] {
+            if first.iter().any(|cell| second.contains(cell)) {
+                continue;
+            }
+            let mut shared: Vec<u8> = first_values.intersection(second_values).copied().collect();
+            if shared.len() < 2 {
+                continue;
+            }
+            shared.sort_unstable();
+            let holding = |value: u8| -> Vec<&Coordinates> {
+                first
+                    .iter()
+                    .chain(second)
+                    .filter(|cell| candidates[coords!(cell.row, cell.column)].contains(&value))
+                    .collect()
+            };
+            for restricted_common in &shared {
+                let restricted = first
+                    .iter()
+                    .filter(|a| candidates[coords!(a.row, a.column)].contains(restricted_common))
+                    .all(|a| {
+                        second
+                            .iter()
+                            .filter(|b| {
+                                candidates[coords!(b.row, b.column)].contains(restricted_common)
+                            })
+                            .all(|b| sees(a, b))
+                    });
+                if !restricted {
+                    continue;
+                }
+                let eliminations: Eliminations = shared
+                    .iter()
+                    .filter(|value| *value != restricted_common)
+                    .flat_map(|value| {
+                        let squares = holding(*value);
+                        (0..9 * 9)
+                            .map(|i| Coordinates::from((i / 9, i % 9)))
+                            .filter(move |cell| {
+                                let i = coords!(cell.row, cell.column);
+                                grid[i] == 0
+                                    && candidates[i].contains(value)
+                                    && !squares.contains(&cell)
+                                    && squares.iter().all(|square| sees(cell, square))
+                            })
+                            .map(move |cell| (cell, *value))
+                    })
+                    .collect();
+                if !eliminations.is_empty() {
+                    return Some((
+                        [first.clone(), second.clone()],
+                        *restricted_common,
+                        eliminations,
+                    ));
+                }
+            }
         }
-        Ok(())
     }
+    None
+}
 
-    /// Generate a new sudoku. Generates a random solution by solving an
-    /// empty sudoku and then removes random values based on the difficulty.
-    /// 
-    /// # Arguments
-    /// 
-    /// * `difficulty` - Probability for each square to get reset.
-    pub fn generate(difficulty: u8) -> Result<Sudoku, SolverError> {
-        let sudoku = Sudoku::new_empty();
-        let mut solver = Solver::new(sudoku);
-        solver.solve()?;
-        let mut rng = rand::thread_rng();
-        for i in 0..9 * 9 {
-            if rng.gen_range(0..100) < difficulty {
-                solver.sudoku.squares[i] = 0;
+/// Depth-first search for a solution of the grid, always branching on the empty square with
+/// the fewest possible values. Returns whether a solution was found, leaving it in the grid;
+/// gives up as soon as `stop` is set.
+///
+/// # Arguments
+///
+/// * `constraints` - Every constraint the solution must satisfy.
+/// * `grid` - The grid to fill. Restored before returning when no solution is found.
+/// * `stop` - Set by another thread once the search is no longer needed.
+fn first_solution_in(
+    constraints: &[Box<dyn Constraint>],
+    grid: &mut Grid,
+    stop: &AtomicBool,
+) -> bool {
+    if stop.load(Ordering::Relaxed) {
+        return false;
+    }
+    let mut branch: Option<(usize, CandidateSet)> = None;
+    for i in 0..9 * 9 {
+        if grid[i] != 0 {
+            continue;
+        }
+        let possibilities = candidates_with(constraints, grid, &Coordinates::from((i / 9, i % 9)));
+        if possibilities.is_empty() {
+            return false;
+        }
+        if branch
+            .as_ref()
+            .is_none_or(|(_, fewest)| possibilities.len() < fewest.len())
+        {
+            let single = possibilities.len() == 1;
+            branch = Some((i, possibilities));
+            if single {
+                break;
             }
         }
+    }
 
-        Ok(Sudoku::new_from_state(solver.sudoku.squares))
+    let Some((i, possibilities)) = branch else {
+        return true;
+    };
+    for value in possibilities {
+        grid[i] = value;
+        if first_solution_in(constraints, grid, stop) {
+            return true;
+        }
     }
+    grid[i] = 0;
+    false
 }
 
-impl Display for Solver {
+impl Display for SolvePhase {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.sudoku.fmt(f)
+        match self {
+            SolvePhase::Propagation => write!(f, "propagation"),
+            SolvePhase::Guessing => write!(f, "guessing"),
+            SolvePhase::Recovery => write!(f, "recovery"),
+            SolvePhase::Search => write!(f, "search"),
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Neighbors;
-    use crate::{
-        solver::Solver,
-        sudoku::{Coordinates, Sudoku},
-    };
-    use std::collections::HashSet;
+/// Writes e.g. `during recovery at r1c5 in 8.3......`, with the grid on one line.
+impl Display for FailureContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "during {}", self.phase)?;
+        if let Some(cell) = &self.cell {
+            write!(f, " at {}", cell)?;
+        }
+        write!(f, " in {}", Sudoku::new_from_state(self.grid))
+    }
+}
 
-    #[test]
-    fn test_possible() {
-        let mut solver = Solver::new(Sudoku::new_empty());
-        assert_eq!(all_possible!(), solver.get_possible(1, 1).unwrap());
-        solver.sudoku.set(0, 1, 1).unwrap();
-        assert_eq!(
-            HashSet::from([2, 3, 4, 5, 6, 7, 8, 9]),
-            solver.get_possible(1, 1).unwrap()
-        );
+impl Display for SolverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolverError::NoPossibilities(context) => {
+                write!(f, "no possible values {}", context)
+            }
+            SolverError::SudokuError(err) => write!(f, "{:?}", err),
+            SolverError::Unsolvable { reason, context } => {
+                write!(f, "unsolvable: {} ({})", reason, context)
+            }
+            SolverError::MultipleSolutions => write!(f, "more than one solution"),
+            SolverError::Stuck(context) => write!(f, "gave up {}", context),
+            SolverError::GenerationFailed { attempts } => {
+                write!(f, "no puzzle met the requirements in {} attempts", attempts)
+            }
+        }
     }
+}
 
-    #[test]
-    fn test_possible_resets() {
-        let resets = Neighbors::from(Coordinates::from((1, 2)));
-        println!("{:#?}", resets);
+impl From<SudokuError> for SolverError {
+    fn from(err: SudokuError) -> Self {
+        SolverError::SudokuError(err)
     }
+}
 
-    #[test]
-    fn test_generate() {
-        let sudoku = Solver::generate(50).unwrap();
-        println!("{}", sudoku);
+impl Solver {
+    /// Create a new solver with the given sudoku and the standard row, column and block constraints.
+    pub fn new(sudoku: Sudoku) -> Self {
+        Solver::with_constraints(sudoku, constraint::standard())
+    }
+
+    /// Create a new solver with the given sudoku and a custom set of constraints.
+    ///
+    /// # Arguments
+    ///
+    /// * `sudoku` - The sudoku to solve.
+    /// * `constraints` - Every constraint the solution must satisfy, including the standard ones.
+    pub fn with_constraints(sudoku: Sudoku, constraints: Vec<Box<dyn Constraint>>) -> Self {
+        sudoku.debug_check_count();
+        let restore_points = vec![Snapshot {
+            sudoku: sudoku.clone(),
+        }];
+        Solver {
+            sudoku,
+            constraints,
+            restore_points,
+            stats: SolveStats::default(),
+            rng: ChaCha8Rng::from_entropy(),
+            observers: Vec::new(),
+            backend: SolverBackend::default(),
+            config: SolverConfig::default(),
+            strategies: vec![
+                Strategy::NakedSingle,
+                Strategy::HiddenSingle,
+                Strategy::LockedCandidates,
+            ],
+            max_chain_length: MAX_CHAIN_LENGTH,
+            max_guesses: None,
+            dead_states: None,
+            rating_weights: RatingWeights::default(),
+        }
+    }
+
+    /// Register an observer that gets called on every placement, elimination, guess and backtrack.
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - The observer to notify.
+    pub fn add_observer(&mut self, observer: Box<dyn SolverObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Reseed the random number generator used for guessing, making the solve reproducible.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The new seed.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+    }
+
+    /// Get the sudoku in its current state.
+    pub fn sudoku(&self) -> &Sudoku {
+        &self.sudoku
+    }
+
+    /// Get the counters of the work done so far.
+    pub fn stats(&self) -> &SolveStats {
+        &self.stats
+    }
+
+    /// Get the logical strategies the solver may use, see [`SolverBuilder::strategies`].
+    pub fn strategies(&self) -> &[Strategy] {
+        &self.strategies
+    }
+
+    /// Add a constraint on top of the ones the solver already has.
+    ///
+    /// # Arguments
+    ///
+    /// * `constraint` - The additional rule the solution must satisfy.
+    pub fn add_constraint(&mut self, constraint: Box<dyn Constraint>) {
+        self.constraints.push(constraint);
+    }
+
+    /// Get possible values for given coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - Row index for the square to check.
+    /// * `column` - Column index for the square to check.
+    pub fn get_possible(&self, row: usize, column: usize) -> Result<CandidateSet, SudokuError> {
+        check!(coords row, column);
+
+        Ok(self.candidates_in(&self.sudoku.squares, &Coordinates { row, column }))
+    }
+
+    /// Describe the current state of the solver for an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - The part of the solve that failed.
+    /// * `cell` - The square being worked on, if any.
+    fn failure(&self, phase: SolvePhase, cell: Option<&Coordinates>) -> FailureContext {
+        FailureContext {
+            phase,
+            cell: cell.cloned(),
+            grid: self.sudoku.squares,
+        }
+    }
+
+    /// Build the error for a sudoku proven to have no solution, explaining the contradiction
+    /// in the current grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - The part of the solve that found the contradiction.
+    /// * `cell` - The square left without possible values, if any.
+    fn unsolvable(&self, phase: SolvePhase, cell: Option<&Coordinates>) -> SolverError {
+        SolverError::Unsolvable {
+            reason: self.explain_contradiction(&self.sudoku.squares, cell),
+            context: Box::new(self.failure(phase, cell)),
+        }
+    }
+
+    /// Describe why a grid cannot be completed: two set squares that conflict, an empty square
+    /// without possible values (the given one first) or a value with no place left in a row,
+    /// column or block. Falls back to a generic reason when the contradiction lies deeper.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid` - The grid to explain.
+    /// * `cell` - A square known to have no possible values, if any.
+    fn explain_contradiction(&self, grid: &Grid, cell: Option<&Coordinates>) -> String {
+        for i in 0..9 * 9 {
+            if grid[i] == 0 {
+                continue;
+            }
+            let square = Coordinates::from((i / 9, i % 9));
+            let mut peers: Vec<Coordinates> = self.peers(&square).into_iter().collect();
+            peers.sort_by_key(|peer| (peer.row, peer.column));
+            if let Some(peer) = peers.into_iter().find(|peer| {
+                coords!(peer.row, peer.column) > i
+                    && grid[coords!(peer.row, peer.column)] == grid[i]
+            }) {
+                return format!("{} and {} both contain {}", square, peer, grid[i]);
+            }
+        }
+
+        let candidates = self.all_candidates_in(grid);
+        let empty = cell
+            .cloned()
+            .into_iter()
+            .chain((0..9 * 9).map(|i| Coordinates::from((i / 9, i % 9))))
+            .find(|square| {
+                let i = coords!(square.row, square.column);
+                grid[i] == 0 && candidates[i].is_empty()
+            });
+        if let Some(square) = empty {
+            return format!("{} has no possible value", square);
+        }
+
+        for house in House::all() {
+            for value in 1..=9 {
+                let placed = house
+                    .cells()
+                    .map(|square| coords!(square.row, square.column))
+                    .any(|i| grid[i] == value || candidates[i].contains(&value));
+                if !placed {
+                    return format!("digit {} has no place in {}", value, house);
+                }
+            }
+        }
+        String::from("no way to fill the empty squares satisfies every constraint")
+    }
+
+    /// Get possible values for given coordinates in any grid, using the constraints of this solver.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid` - The grid to check.
+    /// * `cell` - Coordinates of the square to check. Must be within bounds.
+    fn candidates_in(&self, grid: &Grid, cell: &Coordinates) -> CandidateSet {
+        candidates_with(&self.constraints, grid, cell)
+    }
+
+    /// Get the possible values of every square in one pass over the grid. Set squares get an
+    /// empty set.
+    pub fn all_candidates(&self) -> CandidateGrid {
+        self.all_candidates_in(&self.sudoku.squares)
+    }
+
+    /// Get the empty squares with their possible values, the ones with the fewest possible
+    /// values first and in reading order on ties.
+    pub fn empty_cells(&self) -> impl Iterator<Item = (Coordinates, CandidateSet)> {
+        let mut retval: Vec<(Coordinates, CandidateSet)> = self
+            .all_candidates()
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| self.sudoku.squares[*i] == 0)
+            .map(|(i, possibilities)| (Coordinates::from((i / 9, i % 9)), possibilities))
+            .collect();
+        retval.sort_by_key(|(_, possibilities)| possibilities.len());
+        retval.into_iter()
+    }
+
+    /// Get the possible values of every square of any grid, using the constraints of this
+    /// solver. Set squares get an empty set.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid` - The grid to check.
+    fn all_candidates_in(&self, grid: &Grid) -> CandidateGrid {
+        let mut retval: CandidateGrid = std::array::from_fn(|i| {
+            if grid[i] == 0 {
+                all_possible!()
+            } else {
+                CandidateSet::new()
+            }
+        });
+        for constraint in &self.constraints {
+            constraint.eliminate_all(grid, &mut retval);
+        }
+        retval
+    }
+
+    /// Find every pair of set squares that share a constraint but contain the same value.
+    /// Each pair is reported once, with the first square before the second in reading order.
+    pub fn find_conflicts(&self) -> Vec<(Coordinates, Coordinates)> {
+        let mut retval = Vec::new();
+        for i in 0..9 * 9 {
+            let value = self.sudoku.squares[i];
+            if value == 0 {
+                continue;
+            }
+            let cell = Coordinates::from((i / 9, i % 9));
+            let mut conflicting: Vec<Coordinates> = self
+                .peers(&cell)
+                .into_iter()
+                .filter(|peer| {
+                    coords!(peer.row, peer.column) > i
+                        && self.sudoku.squares[coords!(peer.row, peer.column)] == value
+                })
+                .collect();
+            conflicting.sort_by_key(|peer| (peer.row, peer.column));
+            for peer in conflicting {
+                retval.push((cell.clone(), peer));
+            }
+        }
+        retval
+    }
+
+    /// Count the solutions of the sudoku with an exhaustive search, stopping at the given limit.
+    /// A limit of 2 is enough to tell whether the solution is unique. Returns 0 if the set
+    /// squares already break a constraint.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Stop searching after this many solutions have been found.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.search(limit).0
+    }
+
+    /// Get the solution of the sudoku with an exhaustive search, failing if there is
+    /// no solution or more than one.
+    pub fn unique_solution(&self) -> Result<Sudoku, SolverError> {
+        match self.search(2) {
+            (1, Some(solution)) => Ok(Sudoku::new_from_state(solution)),
+            (0, _) => Err(self.unsolvable(SolvePhase::Search, None)),
+            _ => Err(SolverError::MultipleSolutions),
+        }
+    }
+
+    /// Compare a player's attempt against the unique solution of the puzzle, listing the
+    /// squares that are wrong separately from the ones that are merely empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The puzzle the attempt was started from.
+    /// * `attempt` - The player's partially or completely filled grid.
+    pub fn find_mistakes(puzzle: &Sudoku, attempt: &Sudoku) -> Result<Mistakes, SolverError> {
+        let solution = Solver::new(puzzle.clone()).unique_solution()?;
+        let mut retval = Mistakes::default();
+        for (i, value) in attempt.squares.iter().enumerate() {
+            let cell = Coordinates::from((i / 9, i % 9));
+            if *value == 0 {
+                retval.empty.push(cell);
+            } else if *value != solution.squares[i] {
+                retval.wrong.push(cell);
+            }
+        }
+        Ok(retval)
+    }
+
+    /// Check whether every given of the puzzle is needed for a unique solution, that is, the
+    /// puzzle has a unique solution and taking out any one given leaves it with several.
+    /// Puzzles without a unique solution are not minimal.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The puzzle to check.
+    pub fn is_minimal(puzzle: &Sudoku) -> bool {
+        Solver::new(puzzle.clone()).count_solutions(2) == 1
+            && (0..9 * 9)
+                .filter(|i| puzzle.squares[*i] != 0)
+                .all(|i| !Solver::is_redundant(puzzle, i))
+    }
+
+    /// Find the givens of the puzzle that can each be taken out on its own with the solution
+    /// staying unique, in reading order. Taking out one of them may make others necessary, so
+    /// they cannot all be taken out at once. Fails like [`Solver::unique_solution`] if the
+    /// puzzle itself does not have a unique solution.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The puzzle to check.
+    pub fn redundant_clues(puzzle: &Sudoku) -> Result<Vec<Coordinates>, SolverError> {
+        Solver::new(puzzle.clone()).unique_solution()?;
+        Ok((0..9 * 9)
+            .filter(|i| puzzle.squares[*i] != 0 && Solver::is_redundant(puzzle, *i))
+            .map(|i| Coordinates::from((i / 9, i % 9)))
+            .collect())
+    }
+
+    /// Check whether the puzzle, assumed to have a unique solution, keeps it without the given
+    /// at the index.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The puzzle to check.
+    /// * `i` - Index of a set square of the puzzle.
+    fn is_redundant(puzzle: &Sudoku, i: usize) -> bool {
+        let mut reduced = puzzle.clone();
+        reduced.set_unchecked(i / 9, i % 9, 0);
+        Solver::new(reduced).count_solutions(2) == 1
+    }
+
+    /// Rate how hard the rest of a partially completed grid is by solving it from that
+    /// position, so a trainer can tell whether the hard part is already behind the player.
+    /// The squares are filled with singles as long as possible and the search only guesses
+    /// when they run out, trying values in ascending order, so the rating is reproducible.
+    /// Fails with [`SolverError::Unsolvable`] if the position cannot be completed.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The grid as the player has filled it so far.
+    pub fn rate_remaining(&self, position: &Sudoku) -> Result<SolveStats, SolverError> {
+        let mut grid = position.squares;
+        let mut stats = SolveStats::default();
+        if self.is_consistent(&mut grid) && self.rate_in(&mut grid, &mut stats) {
+            Ok(stats)
+        } else {
+            Err(SolverError::Unsolvable {
+                reason: self.explain_contradiction(&position.squares, None),
+                context: Box::new(FailureContext {
+                    phase: SolvePhase::Search,
+                    cell: None,
+                    grid: position.squares,
+                }),
+            })
+        }
+    }
+
+    /// Rate the sudoku from its current state: clues, hardest technique, guesses, branching
+    /// factor and an overall score combining them.
+    pub fn difficulty_report(&self) -> Result<DifficultyReport, SolverError> {
+        let stats = self.rate_remaining(&self.sudoku)?;
+        let candidates = self.all_candidates();
+        let empty = 9 * 9 - self.sudoku.set_count as usize;
+        let branching_factor = if empty == 0 {
+            0.0
+        } else {
+            candidates
+                .iter()
+                .map(|possible| possible.len())
+                .sum::<usize>() as f64
+                / empty as f64
+        };
+        let score = if empty == 0 {
+            0.0
+        } else {
+            self.rating_weights.score(stats.guesses, branching_factor)
+        };
+        Ok(DifficultyReport {
+            clues: self.sudoku.set_count,
+            hardest_technique: stats.hardest_technique(),
+            techniques: stats.techniques(),
+            guesses: stats.guesses,
+            branching_factor,
+            score,
+        })
+    }
+
+    /// Fill the grid with naked and then hidden singles, ruling out locked candidates and unique
+    /// rectangles when there are none, then looking for a bivalue universal grave, ALS-XZ and
+    /// forcing chains, and guess on the square with the fewest possible values when nothing else is
+    /// left. The work is counted in `stats`. Returns whether a solution was found.
+    fn rate_in(&self, grid: &mut Grid, stats: &mut SolveStats) -> bool {
+        let mut eliminated: CandidateGrid = std::array::from_fn(|_| CandidateSet::new());
+        loop {
+            let mut branch: Option<(usize, CandidateSet)> = None;
+            for i in 0..9 * 9 {
+                if grid[i] != 0 {
+                    continue;
+                }
+                let mut possibilities =
+                    self.candidates_in(grid, &Coordinates::from((i / 9, i % 9)));
+                possibilities.retain(|value| !eliminated[i].contains(value));
+                if possibilities.is_empty() {
+                    return false;
+                }
+                if branch
+                    .as_ref()
+                    .is_none_or(|(_, fewest)| possibilities.len() < fewest.len())
+                {
+                    let single = possibilities.len() == 1;
+                    branch = Some((i, possibilities));
+                    if single {
+                        break;
+                    }
+                }
+            }
+
+            let (i, possibilities) = match branch {
+                Some(branch) => branch,
+                None => return true,
+            };
+            if possibilities.len() == 1 {
+                grid[i] = *possibilities.iter().next().unwrap();
+                stats.singles += 1;
+                continue;
+            }
+            let mut candidates = self.all_candidates_in(grid);
+            for (possibilities, ruled_out) in candidates.iter_mut().zip(&eliminated) {
+                possibilities.retain(|value| !ruled_out.contains(value));
+            }
+            if self.strategies.contains(&Strategy::HiddenSingle) {
+                if let Some((cell, value, _)) = hidden_single(grid, &candidates) {
+                    grid[coords!(cell.row, cell.column)] = value;
+                    stats.hidden_singles += 1;
+                    continue;
+                }
+            }
+            if self.strategies.contains(&Strategy::LockedCandidates) {
+                if let Some((value, _, _, cells)) = locked_candidates(grid, &candidates) {
+                    stats.locked_candidates += 1;
+                    for cell in cells {
+                        eliminated[coords!(cell.row, cell.column)].insert(value);
+                    }
+                    continue;
+                }
+            }
+            if self.strategies.contains(&Strategy::UniqueRectangle) {
+                if let Some((_, _, eliminations)) = unique_rectangle(grid, &candidates) {
+                    stats.unique_rectangles += 1;
+                    for (cell, value) in eliminations {
+                        eliminated[coords!(cell.row, cell.column)].insert(value);
+                    }
+                    continue;
+                }
+            }
+            if self.strategies.contains(&Strategy::BivalueUniversalGrave) {
+                if let Some((cell, value)) = bivalue_universal_grave(grid, &candidates) {
+                    grid[coords!(cell.row, cell.column)] = value;
+                    stats.bivalue_universal_graves += 1;
+                    continue;
+                }
+            }
+            if self.strategies.contains(&Strategy::AlsXz) {
+                if let Some((_, _, eliminations)) = als_xz(grid, &candidates) {
+                    stats.als_xz += 1;
+                    for (cell, value) in eliminations {
+                        eliminated[coords!(cell.row, cell.column)].insert(value);
+                    }
+                    continue;
+                }
+            }
+            if self.strategies.contains(&Strategy::ForcingChain) {
+                if let Some((cell, value, _)) = self.find_forcing_chain(grid, &candidates) {
+                    stats.forcing_chains += 1;
+                    eliminated[coords!(cell.row, cell.column)].insert(value);
+                    continue;
+                }
+            }
+            let mut values: Vec<u8> = possibilities.into_iter().collect();
+            values.sort_unstable();
+            for value in values {
+                let mut attempt = *grid;
+                attempt[i] = value;
+                stats.guesses += 1;
+                if self.rate_in(&mut attempt, stats) {
+                    *grid = attempt;
+                    return true;
+                }
+                stats.backtracks += 1;
+            }
+            return false;
+        }
+    }
+
+    /// Run the exhaustive search, returning the number of solutions found (up to the limit)
+    /// and the first solution.
+    fn search(&self, limit: usize) -> (usize, Option<Grid>) {
+        let mut grid = self.sudoku.squares;
+        if !self.is_consistent(&mut grid) {
+            return (0, None);
+        }
+        let mut first = None;
+        let count = self.count_in(&mut grid, limit, &mut first);
+        (count, first)
+    }
+
+    /// Check that every set square of the grid is allowed by the constraints.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid` - The grid to check. Modified during the check, but restored before returning.
+    fn is_consistent(&self, grid: &mut Grid) -> bool {
+        (0..9 * 9).all(|i| {
+            let value = grid[i];
+            if value == 0 {
+                return true;
+            }
+            grid[i] = 0;
+            let possible = self
+                .candidates_in(grid, &Coordinates::from((i / 9, i % 9)))
+                .contains(&value);
+            grid[i] = value;
+            possible
+        })
+    }
+
+    /// Depth-first search counting the solutions of the grid, always branching on the empty
+    /// square with the fewest possible values. The first solution found is stored in `first`.
+    fn count_in(&self, grid: &mut Grid, limit: usize, first: &mut Option<Grid>) -> usize {
+        let mut branch: Option<(usize, CandidateSet)> = None;
+        for i in 0..9 * 9 {
+            if grid[i] != 0 {
+                continue;
+            }
+            let possibilities = self.candidates_in(grid, &Coordinates::from((i / 9, i % 9)));
+            if possibilities.is_empty() {
+                return 0;
+            }
+            if branch
+                .as_ref()
+                .is_none_or(|(_, fewest)| possibilities.len() < fewest.len())
+            {
+                let single = possibilities.len() == 1;
+                branch = Some((i, possibilities));
+                if single {
+                    break;
+                }
+            }
+        }
+
+        let (i, possibilities) = match branch {
+            Some(branch) => branch,
+            None => {
+                first.get_or_insert(*grid);
+                return 1;
+            }
+        };
+        let mut count = 0;
+        for value in possibilities {
+            grid[i] = value;
+            count += self.count_in(grid, limit - count, first);
+            if count >= limit {
+                break;
+            }
+        }
+        grid[i] = 0;
+        count
+    }
+
+    /// Get every square that shares a constraint with the given coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square to check.
+    fn peers(&self, cell: &Coordinates) -> Neighbors {
+        self.constraints
+            .iter()
+            .flat_map(|constraint| constraint.peers(cell))
+            .collect()
+    }
+
+    /// Set a square and notify the observers about the values it rules out for its empty peers.
+    /// The caller notifies the observers about the placement itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square to set.
+    /// * `value` - The value to set ([1,9]).
+    fn place(&mut self, cell: &Coordinates, value: u8) -> Result<(), SolverError> {
+        if !self.observers.is_empty() {
+            let mut eliminated = Vec::new();
+            for peer in self.peers(cell) {
+                if self.sudoku.get_unchecked(peer.row, peer.column) == 0
+                    && self.get_possible(peer.row, peer.column)?.contains(&value)
+                {
+                    eliminated.push(peer);
+                }
+            }
+            eliminated.sort_by_key(|peer| (peer.row, peer.column));
+            let cause = EliminationCause::Placement(cell.clone());
+            for peer in eliminated {
+                for observer in &mut self.observers {
+                    observer.on_elimination(&peer, value, &cause);
+                }
+            }
+        }
+        self.sudoku.set_unchecked(cell.row, cell.column, value);
+        Ok(())
+    }
+
+    /// Take a snapshot of the grid to return to later with [`Solver::restore`].
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            sudoku: self.sudoku.clone(),
+        }
+    }
+
+    /// Return the grid to a snapshot, e.g. to try several continuations from the same state.
+    /// The counters keep counting and observers are not notified.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - The snapshot to return to.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.sudoku = snapshot.sudoku.clone();
+    }
+
+    /// The newest restore point of the search, whose set squares are never unset by recovery.
+    fn secure_state(&self) -> &Grid {
+        let newest = self
+            .restore_points
+            .last()
+            .expect("the solver always has its initial restore point");
+        &newest.sudoku.squares
+    }
+
+    /// Set a square that has only one possible value and notify the observers.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square to set.
+    /// * `value` - Its only possible value.
+    fn place_single(&mut self, cell: &Coordinates, value: u8) -> Result<(), SolverError> {
+        self.place(cell, value)?;
+        self.stats.singles += 1;
+        for observer in &mut self.observers {
+            observer.on_placement(cell, value);
+        }
+        Ok(())
+    }
+
+    /// Set the only square of a house that can hold a value and notify the observers.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - Coordinates of the square to set.
+    /// * `value` - The value no other square of the house can hold.
+    /// * `house` - The house.
+    fn place_hidden_single(
+        &mut self,
+        cell: &Coordinates,
+        value: u8,
+        house: House,
+    ) -> Result<(), SolverError> {
+        self.place(cell, value)?;
+        self.stats.hidden_singles += 1;
+        for observer in &mut self.observers {
+            observer.on_hidden_single(cell, value, house);
+        }
+        Ok(())
+    }
+
+    /// Propagate the set squares to a fixed point. Every empty square with a single possible
+    /// value is set and queued; for every square taken from the queue, its empty peers are
+    /// checked again, so chains of singles are followed to the end in one go. Once no naked
+    /// single is left, a hidden single is set and queued in the same way, and failing that,
+    /// locked candidates and unique rectangles are ruled out until they leave a single, with a
+    /// bivalue universal grave, ALS-XZ and forcing chains as the last resorts. The values ruled out are
+    /// only remembered until the propagation ends, as squares set later may be unset again.
+    /// Returns the first empty square found without possible values, if any.
+    fn propagate(&mut self) -> Result<Option<Coordinates>, SolverError> {
+        self.stats.propagations += 1;
+        let mut queue = VecDeque::new();
+        let check = |solver: &mut Solver,
+                     cell: Coordinates,
+                     queue: &mut VecDeque<Coordinates>|
+         -> Result<bool, SolverError> {
+            let possibilities = solver.get_possible(cell.row, cell.column)?;
+            match possibilities.len() {
+                0 => return Ok(false),
+                1 if solver.strategies.contains(&Strategy::NakedSingle) => {
+                    let value = possibilities.into_iter().next().ok_or_else(|| {
+                        SolverError::NoPossibilities(
+                            solver.failure(SolvePhase::Propagation, Some(&cell)),
+                        )
+                    })?;
+                    solver.place_single(&cell, value)?;
+                    queue.push_back(cell);
+                }
+                _ => {}
+            }
+            Ok(true)
+        };
+
+        for i in 0..9 * 9 {
+            let cell = Coordinates::from((i / 9, i % 9));
+            if self.sudoku.squares[i] == 0 && !check(self, cell.clone(), &mut queue)? {
+                return Ok(Some(cell));
+            }
+        }
+        let mut eliminated: CandidateGrid = std::array::from_fn(|_| CandidateSet::new());
+        loop {
+            while let Some(cell) = queue.pop_front() {
+                let mut peers: Vec<Coordinates> = self.peers(&cell).into_iter().collect();
+                peers.sort_by_key(|peer| (peer.row, peer.column));
+                for peer in peers {
+                    if self.sudoku.squares[coords!(peer.row, peer.column)] == 0
+                        && !check(self, peer.clone(), &mut queue)?
+                    {
+                        return Ok(Some(peer));
+                    }
+                }
+            }
+            if self
+                .strategies
+                .iter()
+                .all(|strategy| *strategy == Strategy::NakedSingle)
+            {
+                return Ok(None);
+            }
+            let mut candidates = self.all_candidates();
+            for (possibilities, ruled_out) in candidates.iter_mut().zip(&eliminated) {
+                possibilities.retain(|value| !ruled_out.contains(value));
+            }
+            let empty = |i: &usize| self.sudoku.squares[*i] == 0;
+            if let Some(i) = (0..9 * 9).filter(empty).find(|i| candidates[*i].is_empty()) {
+                return Ok(Some(Coordinates::from((i / 9, i % 9))));
+            }
+            if self.strategies.contains(&Strategy::NakedSingle) {
+                if let Some(i) = (0..9 * 9).filter(empty).find(|i| candidates[*i].len() == 1) {
+                    let cell = Coordinates::from((i / 9, i % 9));
+                    let value = *candidates[i].iter().next().expect("one candidate");
+                    self.place_single(&cell, value)?;
+                    queue.push_back(cell);
+                    continue;
+                }
+            }
+            if self.strategies.contains(&Strategy::HiddenSingle) {
+                if let Some((cell, value, house)) = hidden_single(&self.sudoku.squares, &candidates)
+                {
+                    self.place_hidden_single(&cell, value, house)?;
+                    queue.push_back(cell);
+                    continue;
+                }
+            }
+            if self.strategies.contains(&Strategy::LockedCandidates) {
+                if let Some((value, base, cover, cells)) =
+                    locked_candidates(&self.sudoku.squares, &candidates)
+                {
+                    self.stats.locked_candidates += 1;
+                    let eliminations: Eliminations =
+                        cells.into_iter().map(|cell| (cell, value)).collect();
+                    let cause = EliminationCause::LockedCandidates { base, cover };
+                    self.rule_out(&mut eliminated, &eliminations, &cause);
+                    continue;
+                }
+            }
+            if self.strategies.contains(&Strategy::UniqueRectangle) {
+                if let Some((corners, values, eliminations)) =
+                    unique_rectangle(&self.sudoku.squares, &candidates)
+                {
+                    self.stats.unique_rectangles += 1;
+                    let cause = EliminationCause::UniqueRectangle { corners, values };
+                    self.rule_out(&mut eliminated, &eliminations, &cause);
+                    continue;
+                }
+            }
+            if self.strategies.contains(&Strategy::BivalueUniversalGrave) {
+                if let Some((cell, value)) =
+                    bivalue_universal_grave(&self.sudoku.squares, &candidates)
+                {
+                    self.place(&cell, value)?;
+                    self.stats.bivalue_universal_graves += 1;
+                    for observer in &mut self.observers {
+                        observer.on_bivalue_universal_grave(&cell, value);
+                    }
+                    queue.push_back(cell);
+                    continue;
+                }
+            }
+            if self.strategies.contains(&Strategy::AlsXz) {
+                if let Some((sets, restricted_common, eliminations)) =
+                    als_xz(&self.sudoku.squares, &candidates)
+                {
+                    self.stats.als_xz += 1;
+                    let cause = EliminationCause::AlsXz {
+                        sets,
+                        restricted_common,
+                    };
+                    self.rule_out(&mut eliminated, &eliminations, &cause);
+                    continue;
+                }
+            }
+            if self.strategies.contains(&Strategy::ForcingChain) {
+                if let Some((cell, value, chain)) =
+                    self.find_forcing_chain(&self.sudoku.squares, &candidates)
+                {
+                    self.stats.forcing_chains += 1;
+                    let cause = EliminationCause::ForcingChain(chain);
+                    self.rule_out(&mut eliminated, &[(cell, value)], &cause);
+                    continue;
+                }
+            }
+            return Ok(None);
+        }
+    }
+
+    /// Try every possible value of the empty squares, the squares with the fewest possible
+    /// values first, until one leads to a contradiction, see [`Solver::forcing_chain`]. Returns
+    /// the square, the value and the placements it forces.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid` - The grid to check.
+    /// * `candidates` - Possible values of every square of the grid.
+    fn find_forcing_chain(
+        &self,
+        grid: &Grid,
+        candidates: &CandidateGrid,
+    ) -> Option<(Coordinates, u8, Vec<Move>)> {
+        let mut empty: Vec<usize> = (0..9 * 9).filter(|i| grid[*i] == 0).collect();
+        empty.sort_by_key(|i| candidates[*i].len());
+        empty.into_iter().find_map(|i| {
+            let cell = Coordinates::from((i / 9, i % 9));
+            let mut values: Vec<u8> = candidates[i].iter().copied().collect();
+            values.sort_unstable();
+            values.into_iter().find_map(|value| {
+                self.forcing_chain(grid, candidates, &cell, value)
+                    .map(|chain| (cell.clone(), value, chain))
+            })
+        })
+    }
+
+    /// Set a value in a copy of the grid and follow the naked and hidden singles it forces, at
+    /// most [`SolverBuilder::max_chain_length`] of them. Returns the forced placements if they
+    /// lead to a contradiction: an empty square without possible values, or a value without a
+    /// place in a row, column or block.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid` - The grid to start from.
+    /// * `candidates` - Possible values of every square of the grid.
+    /// * `cell` - The square to try the value in.
+    /// * `value` - The value to try.
+    fn forcing_chain(
+        &self,
+        grid: &Grid,
+        candidates: &CandidateGrid,
+        cell: &Coordinates,
+        value: u8,
+    ) -> Option<Vec<Move>> {
+        let mut grid = *grid;
+        let mut candidates = candidates.clone();
+        let mut chain = Vec::new();
+        let (mut cell, mut value) = (cell.clone(), value);
+        loop {
+            let i = coords!(cell.row, cell.column);
+            grid[i] = value;
+            candidates[i].clear();
+            for peer in self.peers(&cell) {
+                candidates[coords!(peer.row, peer.column)].remove(&value);
+            }
+            let contradiction = (0..9 * 9).any(|i| grid[i] == 0 && candidates[i].is_empty())
+                || House::all().any(|house| {
+                    (1..=9).any(|value| {
+                        house.cells().all(|cell| {
+                            let i = coords!(cell.row, cell.column);
+                            grid[i] != value && !candidates[i].contains(&value)
+                        })
+                    })
+                });
+            if contradiction {
+                return Some(chain);
+            }
+            if chain.len() >= self.max_chain_length {
+                return None;
+            }
+            (cell, value) = (0..9 * 9)
+                .find(|i| grid[*i] == 0 && candidates[*i].len() == 1)
+                .map(|i| {
+                    let value = *candidates[i].iter().next().expect("one candidate");
+                    (Coordinates::from((i / 9, i % 9)), value)
+                })
+                .or_else(|| {
+                    hidden_single(&grid, &candidates).map(|(cell, value, _)| (cell, value))
+                })?;
+            chain.push(Move::Place {
+                cell: cell.clone(),
+                value,
+            });
+        }
+    }
+
+    /// Notify the observers about values a strategy rules out and remember them for the rest of
+    /// the propagation.
+    ///
+    /// # Arguments
+    ///
+    /// * `eliminated` - The values ruled out so far in the propagation, for every square.
+    /// * `eliminations` - The squares and the values ruled out for them.
+    /// * `cause` - The reasoning that rules them out.
+    fn rule_out(
+        &mut self,
+        eliminated: &mut CandidateGrid,
+        eliminations: &[(Coordinates, u8)],
+        cause: &EliminationCause,
+    ) {
+        for (cell, value) in eliminations {
+            for observer in &mut self.observers {
+                observer.on_elimination(cell, *value, cause);
+            }
+            eliminated[coords!(cell.row, cell.column)].insert(*value);
+        }
+    }
+
+    /// Recover from a bad guess: unset random uncertain peers (set squares that are not set in
+    /// the last secure state) of the given square until it has a possible value again. If only
+    /// one value is possible, the square is set right away, before propagation can force the
+    /// unset peers back to their old values.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - An empty square without possible values.
+    fn recover(&mut self, cell: &Coordinates) -> Result<(), SolverError> {
+        while self.get_possible(cell.row, cell.column)?.is_empty() {
+            let mut possible_resets: Vec<Coordinates> = self
+                .peers(cell)
+                .into_iter()
+                .filter(|peer| {
+                    let i = coords!(peer.row, peer.column);
+                    self.secure_state()[i] == 0 && self.sudoku.squares[i] != 0
+                })
+                .collect();
+            if possible_resets.is_empty() {
+                return Err(self.unsolvable(SolvePhase::Recovery, Some(cell)));
+            }
+            possible_resets.sort_by_key(|reset| (reset.row, reset.column));
+            let reset = &possible_resets[self.rng.gen_range(0..possible_resets.len())];
+            let old_value = self.sudoku.get_unchecked(reset.row, reset.column);
+
+            self.sudoku.set_unchecked(reset.row, reset.column, 0);
+            self.stats.backtracks += 1;
+            for observer in &mut self.observers {
+                observer.on_backtrack(reset, old_value);
+            }
+        }
+        let possibilities = self.get_possible(cell.row, cell.column)?;
+        if possibilities.len() == 1 {
+            let value = possibilities.into_iter().next().ok_or_else(|| {
+                SolverError::NoPossibilities(self.failure(SolvePhase::Recovery, Some(cell)))
+            })?;
+            self.place_single(cell, value)?;
+        }
+        Ok(())
+    }
+
+    /// Unset a random uncertain square (a set square that is not set in the last secure state),
+    /// for when the current state is known to have no solution without any square being left
+    /// without possible values.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - The square that was to be guessed, used in the error.
+    fn backtrack_anywhere(&mut self, cell: &Coordinates) -> Result<(), SolverError> {
+        let uncertain: Vec<usize> = (0..9 * 9)
+            .filter(|i| self.secure_state()[*i] == 0 && self.sudoku.squares[*i] != 0)
+            .collect();
+        if uncertain.is_empty() {
+            return Err(self.unsolvable(SolvePhase::Guessing, Some(cell)));
+        }
+        let i = uncertain[self.rng.gen_range(0..uncertain.len())];
+        let reset = Coordinates::from((i / 9, i % 9));
+        let old_value = self.sudoku.get_unchecked(reset.row, reset.column);
+
+        self.sudoku.set_unchecked(reset.row, reset.column, 0);
+        self.stats.backtracks += 1;
+        for observer in &mut self.observers {
+            observer.on_backtrack(&reset, old_value);
+        }
+        Ok(())
+    }
+
+    /// Choose the empty square to guess according to the configured [`CellSelection`].
+    /// Returns the square with its possible values.
+    fn select_cell(&mut self) -> Result<(Coordinates, CandidateSet), SolverError> {
+        let empty: Vec<usize> = (0..9 * 9)
+            .filter(|i| self.sudoku.squares[*i] == 0)
+            .collect();
+        let i = match self.config.cell_selection {
+            CellSelection::FirstEmpty => empty.first().copied(),
+            CellSelection::Random => empty.choose(&mut self.rng).copied(),
+            CellSelection::MinimumRemainingValues => {
+                return self.empty_cells().next().ok_or_else(|| {
+                    SolverError::NoPossibilities(self.failure(SolvePhase::Guessing, None))
+                });
+            }
+            CellSelection::Degree => empty.iter().copied().min_by_key(|i| {
+                let cell = Coordinates::from((i / 9, i % 9));
+                let empty_peers = self
+                    .peers(&cell)
+                    .into_iter()
+                    .filter(|peer| self.sudoku.squares[coords!(peer.row, peer.column)] == 0)
+                    .count();
+                let possibilities = self.candidates_in(&self.sudoku.squares, &cell).len();
+                (Reverse(empty_peers), possibilities)
+            }),
+        };
+        let i = i.ok_or_else(|| {
+            SolverError::NoPossibilities(self.failure(SolvePhase::Guessing, None))
+        })?;
+        let cell = Coordinates::from((i / 9, i % 9));
+        let possibilities = self.candidates_in(&self.sudoku.squares, &cell);
+        Ok((cell, possibilities))
+    }
+
+    /// Choose the value to guess according to the configured [`ValueOrder`].
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - The square to guess.
+    /// * `candidates` - Its possible values in ascending order.
+    fn select_value(&mut self, cell: &Coordinates, candidates: &[u8]) -> Result<u8, SolverError> {
+        let value = match self.config.value_order {
+            ValueOrder::Random => candidates.choose(&mut self.rng).copied(),
+            ValueOrder::Ascending => candidates.first().copied(),
+            ValueOrder::LeastConstraining => {
+                let peer_candidates: Vec<CandidateSet> = self
+                    .peers(cell)
+                    .into_iter()
+                    .filter(|peer| self.sudoku.squares[coords!(peer.row, peer.column)] == 0)
+                    .map(|peer| self.candidates_in(&self.sudoku.squares, &peer))
+                    .collect();
+                candidates.iter().copied().min_by_key(|value| {
+                    peer_candidates
+                        .iter()
+                        .filter(|possible| possible.contains(value))
+                        .count()
+                })
+            }
+        };
+        value.ok_or_else(|| {
+            SolverError::NoPossibilities(self.failure(SolvePhase::Guessing, Some(cell)))
+        })
+    }
+
+    /// One step of the solver algorithm. First propagate the set squares to a fixed point,
+    /// then guess a value for the square chosen by the configured [`CellSelection`]. If a square is left
+    /// without possible values (after a bad guess), recover by unsetting uncertain peers.
+    /// The state reached before the first guess is stored as the last secure state.
+    ///
+    /// With the transposition table enabled, states that propagate into a contradiction are
+    /// remembered, and guesses leading back into them are skipped. A state where every guess
+    /// is skipped is remembered in turn and left by unsetting a random uncertain square.
+    fn step(&mut self) -> Result<(), SolverError> {
+        let before = self.sudoku.zobrist();
+        if let Some(cell) = self.propagate()? {
+            if let Some(dead_states) = &mut self.dead_states {
+                dead_states.insert(before);
+            }
+            return self.recover(&cell);
+        }
+        if self.sudoku.set_count == 9 * 9 {
+            return Ok(());
+        }
+        if self.restore_points.len() == 1 && self.stats.guesses == 0 {
+            self.restore_points.push(self.snapshot());
+        }
+
+        let (cell, possibilities) = self.select_cell()?;
+
+        let mut candidates: Vec<u8> = possibilities.into_iter().collect();
+        candidates.sort_unstable();
+        let value = match &self.dead_states {
+            Some(dead_states) => {
+                let live: Vec<u8> = candidates
+                    .iter()
+                    .copied()
+                    .filter(|value| {
+                        !dead_states.contains(&self.sudoku.zobrist_with(
+                            cell.row,
+                            cell.column,
+                            *value,
+                        ))
+                    })
+                    .collect();
+                if live.is_empty() {
+                    let hash = self.sudoku.zobrist();
+                    if let Some(dead_states) = &mut self.dead_states {
+                        dead_states.insert(hash);
+                    }
+                    return self.backtrack_anywhere(&cell);
+                }
+                self.select_value(&cell, &live)?
+            }
+            None => self.select_value(&cell, &candidates)?,
+        };
+        self.place(&cell, value)?;
+        self.stats.guesses += 1;
+        let candidates: CandidateSet = candidates.into_iter().collect();
+        for observer in &mut self.observers {
+            observer.on_guess(&cell, value, &candidates);
+        }
+        Ok(())
+    }
+
+    /// Fill the empty squares with an exact cover search over the standard rules. Every value
+    /// the search tries is also checked against the constraints of the solver, so additional
+    /// constraints are respected, but they do not guide the search and mostly empty variant
+    /// grids can take long. Squares filled by the search are counted as guesses.
+    fn solve_dlx(&mut self) -> Result<(), SolverError> {
+        // Columns: every square is filled, and every row, column and block has every value.
+        let mut dlx = dlx::Dlx::new(4 * 9 * 9);
+        for i in 0..9 * 9 {
+            let (row, column) = (i / 9, i % 9);
+            let block = row / 3 * 3 + column / 3;
+            let values = match self.sudoku.squares[i] {
+                0 => self.candidates_in(&self.sudoku.squares, &Coordinates::from((row, column))),
+                value => HashSet::from([value]),
+            };
+            for value in values {
+                let v = value as usize - 1;
+                dlx.add_row(
+                    i * 9 + v,
+                    &[
+                        i,
+                        81 + row * 9 + v,
+                        162 + column * 9 + v,
+                        243 + block * 9 + v,
+                    ],
+                );
+            }
+        }
+
+        let found = dlx.solve(|rows| {
+            let mut grid = self.sudoku.squares;
+            for id in rows {
+                grid[id / 9] = (id % 9) as u8 + 1;
+            }
+            let i = rows[rows.len() - 1] / 9;
+            let value = grid[i];
+            grid[i] = 0;
+            self.candidates_in(&grid, &Coordinates::from((i / 9, i % 9)))
+                .contains(&value)
+        });
+        let rows = found.ok_or_else(|| self.unsolvable(SolvePhase::Search, None))?;
+        let mut grid = self.sudoku.squares;
+        for id in rows {
+            grid[id / 9] = (id % 9) as u8 + 1;
+        }
+        self.fill_from_search(&grid)
+    }
+
+    /// Fill the empty squares from a solution found by an exhaustive search. The search does
+    /// not tell how it settled on the values, so every square filled counts as a guess and is
+    /// reported to the observers as one, out of the values it could have before the search.
+    ///
+    /// # Arguments
+    ///
+    /// * `solution` - The solution found.
+    fn fill_from_search(&mut self, solution: &Grid) -> Result<(), SolverError> {
+        let candidates = self.all_candidates();
+        for (i, value) in solution.iter().copied().enumerate() {
+            if self.sudoku.squares[i] == 0 {
+                let cell = Coordinates::from((i / 9, i % 9));
+                self.place(&cell, value)?;
+                self.stats.guesses += 1;
+                for observer in &mut self.observers {
+                    observer.on_guess(&cell, value, &candidates[i]);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill the empty squares from a satisfying assignment of the encoded sudoku. Constraints are
+    /// encoded as their peers having different values; solutions that still break a constraint
+    /// are excluded with an extra clause and the formula is solved again. Squares filled by the
+    /// search are counted as guesses.
+    fn solve_sat(&mut self) -> Result<(), SolverError> {
+        let mut cnf = sat::encode(&self.sudoku, &self.constraints);
+        let grid = loop {
+            let model = cnf
+                .solve()
+                .ok_or_else(|| self.unsolvable(SolvePhase::Search, None))?;
+            let mut grid = sat::decode(&model);
+            if self.is_consistent(&mut grid) {
+                break grid;
+            }
+            cnf.clauses.push(
+                (0..9 * 9)
+                    .filter(|i| self.sudoku.squares[*i] == 0)
+                    .map(|i| -sat::variable(i, grid[i]))
+                    .collect(),
+            );
+        };
+
+        self.fill_from_search(&grid)
+    }
+
+    /// Sorted indices of the peers of every square, as used by [`annealing::anneal`].
+    fn peer_indices(&self) -> Vec<Vec<usize>> {
+        (0..9 * 9)
+            .map(|i| {
+                let mut peers: Vec<usize> = self
+                    .peers(&Coordinates::from((i / 9, i % 9)))
+                    .into_iter()
+                    .map(|peer| coords!(peer.row, peer.column))
+                    .filter(|peer| *peer != i)
+                    .collect();
+                peers.sort_unstable();
+                peers
+            })
+            .collect()
+    }
+
+    /// Fill the empty squares with simulated annealing. Squares filled this way are counted as
+    /// guesses.
+    fn solve_annealing(&mut self) -> Result<(), SolverError> {
+        let mut grid = self.sudoku.squares;
+        if !self.is_consistent(&mut grid) {
+            return Err(self.unsolvable(SolvePhase::Search, None));
+        }
+        let peers = self.peer_indices();
+        let mut grid = annealing::anneal(&grid, &peers, &mut self.rng, ANNEALING_MOVES)
+            .ok_or_else(|| SolverError::Stuck(self.failure(SolvePhase::Search, None)))?;
+        if !self.is_consistent(&mut grid) {
+            return Err(SolverError::Stuck(self.failure(SolvePhase::Search, None)));
+        }
+
+        self.fill_from_search(&grid)
+    }
+
+    /// Propagate the set squares, then search every possible value of the square with the
+    /// fewest on its own thread. The first thread to find a solution stops the others. Squares
+    /// filled by the search are counted as guesses.
+    fn solve_parallel(&mut self) -> Result<(), SolverError> {
+        if let Some(cell) = self.propagate()? {
+            return Err(self.unsolvable(SolvePhase::Propagation, Some(&cell)));
+        }
+        if self.sudoku.set_count == 9 * 9 {
+            return Ok(());
+        }
+        let mut grid = self.sudoku.squares;
+        if !self.is_consistent(&mut grid) {
+            return Err(self.unsolvable(SolvePhase::Search, None));
+        }
+        let (cell, possibilities) = self
+            .empty_cells()
+            .next()
+            .ok_or_else(|| SolverError::NoPossibilities(self.failure(SolvePhase::Search, None)))?;
+
+        let constraints = &self.constraints;
+        let stop = AtomicBool::new(false);
+        let winner = OnceLock::new();
+        thread::scope(|scope| {
+            for value in possibilities {
+                let (stop, winner) = (&stop, &winner);
+                scope.spawn(move || {
+                    let mut grid = grid;
+                    grid[coords!(cell.row, cell.column)] = value;
+                    if first_solution_in(constraints, &mut grid, stop) {
+                        let _ = winner.set(grid);
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                });
+            }
+        });
+        let grid = winner
+            .into_inner()
+            .ok_or_else(|| self.unsolvable(SolvePhase::Search, None))?;
+
+        self.fill_from_search(&grid)
+    }
+
+    /// Solve the sudoku with the backend the solver was built with.
+    pub fn solve(&mut self) -> Result<(), SolverError> {
+        // The backends rely on set_count to tell when the grid is complete.
+        self.sudoku.debug_check_count();
+        match self.backend {
+            SolverBackend::Backtracking => {
+                let limit = self
+                    .max_guesses
+                    .map(|max_guesses| self.stats.guesses + self.stats.backtracks + max_guesses);
+                let exhausted = |solver: &Solver| {
+                    limit.is_some_and(|limit| {
+                        solver.stats.guesses + solver.stats.backtracks >= limit
+                    })
+                };
+                while self.sudoku.set_count < 9 * 9 {
+                    if exhausted(self) {
+                        // Fill in what the strategies can before giving up.
+                        if self.propagate()?.is_none() && self.sudoku.set_count == 9 * 9 {
+                            break;
+                        }
+                        return Err(SolverError::Stuck(self.failure(SolvePhase::Guessing, None)));
+                    }
+                    self.step()?;
+                }
+                Ok(())
+            }
+            SolverBackend::Dlx => self.solve_dlx(),
+            SolverBackend::Sat => self.solve_sat(),
+            SolverBackend::Annealing => self.solve_annealing(),
+            SolverBackend::Parallel => self.solve_parallel(),
+            SolverBackend::Logic => {
+                if let Some(cell) = self.propagate()? {
+                    return Err(self.unsolvable(SolvePhase::Propagation, Some(&cell)));
+                }
+                if self.sudoku.set_count < 9 * 9 {
+                    return Err(SolverError::Stuck(
+                        self.failure(SolvePhase::Propagation, None),
+                    ));
+                }
+                Ok(())
+            }
+            SolverBackend::Hybrid => {
+                if let Some(cell) = self.propagate()? {
+                    return Err(self.unsolvable(SolvePhase::Propagation, Some(&cell)));
+                }
+                if self.sudoku.set_count < 9 * 9 {
+                    self.solve_dlx()?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Solve with the backtracking algorithm, but give up once the number of guesses and
+    /// backtracks reaches the budget. Returns the most filled grid reached along the way,
+    /// which is not necessarily the current state of the solver, with its possible values.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_guesses` - Budget for guesses and backtracks combined.
+    pub fn solve_partial(&mut self, max_guesses: u32) -> Result<PartialSolution, SolverError> {
+        let budget = self.stats.guesses + self.stats.backtracks + max_guesses;
+        let mut best = self.sudoku.clone();
+        loop {
+            if let Some(cell) = self.propagate()? {
+                if self.stats.guesses + self.stats.backtracks >= budget {
+                    break;
+                }
+                self.recover(&cell)?;
+                continue;
+            }
+            if self.sudoku.set_count > best.set_count {
+                best = self.sudoku.clone();
+            }
+            if self.sudoku.set_count == 9 * 9
+                || self.stats.guesses + self.stats.backtracks >= budget
+            {
+                break;
+            }
+            self.step()?;
+        }
+
+        let candidates = self.all_candidates_in(&best.squares);
+        Ok(PartialSolution {
+            solved: best.set_count == 9 * 9,
+            sudoku: best,
+            candidates,
+        })
+    }
+
+    /// Apply the logical strategies until they run out, without guessing, and return the grid
+    /// reached with its possible values. A primitive for hint engines and difficulty ratings:
+    /// `solved` tells whether the strategies alone complete the puzzle. Fails if they find a
+    /// contradiction.
+    pub fn solve_logical(&mut self) -> Result<PartialSolution, SolverError> {
+        if let Some(cell) = self.propagate()? {
+            return Err(self.unsolvable(SolvePhase::Propagation, Some(&cell)));
+        }
+        Ok(PartialSolution {
+            solved: self.sudoku.set_count == 9 * 9,
+            candidates: self.all_candidates(),
+            sudoku: self.sudoku.clone(),
+        })
+    }
+
+    /// Generate a new sudoku. Generates a random solution by solving an
+    /// empty sudoku and then removes random values based on the difficulty.
+    /// See [`Solver::generate_with_options`] for puzzles with further requirements.
+    ///
+    /// # Arguments
+    ///
+    /// * `difficulty` - Probability for each square to get reset.
+    pub fn generate(difficulty: u8) -> Result<Sudoku, SolverError> {
+        Solver::generate_seeded(difficulty, rand::thread_rng().gen())
+    }
+
+    /// Generate a new sudoku like [`Solver::generate`], but deterministically:
+    /// the same seed and difficulty always produce the same sudoku.
+    ///
+    /// # Arguments
+    ///
+    /// * `difficulty` - Probability for each square to get reset.
+    /// * `seed` - Seed for the random number generator.
+    pub fn generate_seeded(difficulty: u8, seed: u64) -> Result<Sudoku, SolverError> {
+        Solver::generate_with_progress(difficulty, false, seed, |_| {})
+    }
+
+    /// Generate a new sudoku with exactly one solution. Squares are removed in random order
+    /// as long as the solution stays unique, until the number of blanks matches the difficulty
+    /// or no more squares can be removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `difficulty` - Percentage of the squares to reset.
+    pub fn generate_unique(difficulty: u8) -> Result<Sudoku, SolverError> {
+        Solver::generate_with_progress(difficulty, true, rand::thread_rng().gen(), |_| {})
+    }
+
+    /// Generate a new sudoku, reporting the progress after every square considered for removal.
+    ///
+    /// # Arguments
+    ///
+    /// * `difficulty` - Probability for each square to get reset, or with `unique` the
+    ///   percentage of the squares to reset.
+    /// * `unique` - Only remove squares while the solution stays unique.
+    /// * `seed` - Seed for the random number generator.
+    /// * `on_progress` - Called with the progress so far after every square considered.
+    pub fn generate_with_progress<F: FnMut(&GenerationProgress)>(
+        difficulty: u8,
+        unique: bool,
+        seed: u64,
+        mut on_progress: F,
+    ) -> Result<Sudoku, SolverError> {
+        let options = GenerationOptions {
+            difficulty,
+            unique,
+            ..GenerationOptions::default()
+        };
+        Solver::generate_attempt(&options, seed, &mut on_progress)
+    }
+
+    /// Generate sudokus until one meets the requirements of the options, failing with
+    /// [`SolverError::GenerationFailed`] when the attempt or time limit is reached first.
+    /// Attempt `n` uses the seed plus `n`, so the result is reproducible without a time limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The requirements and limits.
+    /// * `seed` - Seed for the random number generator of the first attempt.
+    pub fn generate_with_options(
+        options: &GenerationOptions,
+        seed: u64,
+    ) -> Result<Sudoku, SolverError> {
+        let start = Instant::now();
+        for attempt in 0..options.max_attempts {
+            if options
+                .time_limit
+                .is_some_and(|limit| start.elapsed() >= limit)
+            {
+                return Err(SolverError::GenerationFailed { attempts: attempt });
+            }
+            let sudoku =
+                Solver::generate_attempt(options, seed.wrapping_add(attempt as u64), &mut |_| {})?;
+            if options
+                .max_clues
+                .is_some_and(|max_clues| sudoku.set_count > max_clues)
+            {
+                continue;
+            }
+            if options.requires_guessing && !Solver::requires_guessing(&sudoku)? {
+                continue;
+            }
+            return Ok(sudoku);
+        }
+        Err(SolverError::GenerationFailed {
+            attempts: options.max_attempts,
+        })
+    }
+
+    /// Check whether the default logical strategies leave squares of the puzzle to guess, see
+    /// [`GenerationOptions::requires_guessing`].
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The puzzle to check.
+    fn requires_guessing(puzzle: &Sudoku) -> Result<bool, SolverError> {
+        Ok(Solver::new(puzzle.clone()).rate_remaining(puzzle)?.guesses > 0)
+    }
+
+    /// Generate a sudoku like [`Solver::generate_with_options`], together with metadata
+    /// describing it: the seed in hexadecimal as the id, this crate as the source, today's date
+    /// (UTC) as the creation date, the [`DifficultyReport`] score as the rating, and tags for the
+    /// uniqueness and symmetry requested, for unique puzzles that are also minimal (see
+    /// [`Solver::is_minimal`]) and for the hardest technique needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The requirements and limits.
+    /// * `seed` - Seed for the random number generator of the first attempt.
+    pub fn generate_with_metadata(
+        options: &GenerationOptions,
+        seed: u64,
+    ) -> Result<(Sudoku, PuzzleMetadata), SolverError> {
+        let sudoku = Solver::generate_with_options(options, seed)?;
+        let report = Solver::new(sudoku.clone()).difficulty_report()?;
+        let mut tags = Vec::new();
+        if options.unique {
+            tags.push("unique".to_string());
+        }
+        if options.symmetric {
+            tags.push("symmetric".to_string());
+        }
+        if options.unique && Solver::is_minimal(&sudoku) {
+            tags.push("minimal".to_string());
+        }
+        tags.extend(report.hardest_technique.map(String::from));
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|elapsed| DateTime::from_timestamp(elapsed.as_secs() as i64, 0))
+            .map(|now| now.date_naive());
+        let metadata = PuzzleMetadata {
+            id: Some(format!("{:016x}", seed)),
+            source: Some(env!("CARGO_PKG_NAME").to_string()),
+            created,
+            rating: Some(report.score as f32),
+            tags,
+            ..PuzzleMetadata::default()
+        };
+        Ok((sudoku, metadata))
+    }
+
+    /// Generate one sudoku by solving an empty grid, or transforming the seed grid, and removing
+    /// squares as the options say. Only `difficulty`, `unique`, `symmetric` and `seed_grid` are
+    /// used; the caller checks the rest.
+    fn generate_attempt<F: FnMut(&GenerationProgress)>(
+        options: &GenerationOptions,
+        seed: u64,
+        on_progress: &mut F,
+    ) -> Result<Sudoku, SolverError> {
+        let difficulty = options.difficulty;
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let sudoku = match options.seed_grid {
+            Some(grid) => Transform::random(&mut rng).apply(&Sudoku::try_new_from_state(grid)?),
+            None => Sudoku::new_empty(),
+        };
+        let mut solver = Solver::new(sudoku);
+        solver.rng = rng;
+        solver.solve()?;
+
+        // With symmetry every square is removed together with its mirror image.
+        let squares_of = |i: usize| {
+            if options.symmetric && i != 9 * 9 - 1 - i {
+                vec![i, 9 * 9 - 1 - i]
+            } else {
+                vec![i]
+            }
+        };
+        let candidates = if options.symmetric {
+            9 * 9 / 2 + 1
+        } else {
+            9 * 9
+        };
+
+        let mut progress = GenerationProgress::default();
+        if options.unique {
+            let target = (9 * 9 * difficulty.min(100) as u32) / 100;
+            let mut order: Vec<usize> = (0..candidates).collect();
+            order.shuffle(&mut solver.rng);
+            for i in order {
+                if progress.clues_removed >= target {
+                    break;
+                }
+                progress.attempts += 1;
+                let squares = squares_of(i);
+                let values: Vec<u8> = squares.iter().map(|j| solver.sudoku.squares[*j]).collect();
+                for j in &squares {
+                    solver.sudoku.set_unchecked(j / 9, j % 9, 0);
+                }
+                progress.uniqueness_checks += 1;
+                if solver.count_solutions(2) == 1 {
+                    progress.clues_removed += squares.len() as u32;
+                } else {
+                    for (j, value) in squares.iter().zip(values) {
+                        solver.sudoku.set_unchecked(j / 9, j % 9, value);
+                    }
+                }
+                on_progress(&progress);
+            }
+        } else {
+            for i in 0..candidates {
+                progress.attempts += 1;
+                if solver.rng.gen_range(0..100) < difficulty {
+                    for j in squares_of(i) {
+                        solver.sudoku.set_unchecked(j / 9, j % 9, 0);
+                        progress.clues_removed += 1;
+                    }
+                }
+                on_progress(&progress);
+            }
+        }
+
+        Ok(Sudoku::new_from_state(solver.sudoku.squares))
+    }
+
+    /// Generate the "puzzle of the day". The seed is derived from the date and the difficulty,
+    /// so every caller gets the identical sudoku for the same day.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The day to generate the puzzle for.
+    /// * `difficulty` - Probability for each square to get reset.
+    pub fn daily(date: NaiveDate, difficulty: u8) -> Result<Sudoku, SolverError> {
+        let seed = (date.num_days_from_ce() as u64) << 8 | difficulty as u64;
+        Solver::generate_seeded(difficulty, seed)
+    }
+}
+
+impl Display for Solver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.sudoku.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Neighbors;
+    use crate::{
+        constraint::{self, CandidateGrid, CandidateSet, Constraint},
+        coords,
+        observer::{EliminationCause, SolveStep, SolverObserver},
+        solver::{
+            CellSelection, DifficultyReport, GenerationOptions, GenerationProgress, Mistakes,
+            PartialSolution, RatingScale, SolvePhase, SolveStats, Solver, SolverBackend,
+            SolverBuilder, SolverConfig, SolverError, Strategy, ValueOrder,
+        },
+        sudoku::{Coordinates, Grid, House, Sudoku},
+        transform::{canonical, SEED_GRID},
+    };
+    use chrono::NaiveDate;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::{cell::RefCell, collections::HashSet, rc::Rc, time::Duration};
+
+    /// Test observer counting placements, eliminations, guesses and backtracks.
+    #[derive(Debug, Default)]
+    struct CountingObserver {
+        counts: Rc<RefCell<[u32; 4]>>,
+    }
+
+    impl SolverObserver for CountingObserver {
+        fn on_placement(&mut self, _cell: &Coordinates, _value: u8) {
+            self.counts.borrow_mut()[0] += 1;
+        }
+
+        fn on_elimination(&mut self, _cell: &Coordinates, _value: u8, _cause: &EliminationCause) {
+            self.counts.borrow_mut()[1] += 1;
+        }
+
+        fn on_guess(&mut self, _cell: &Coordinates, _value: u8, candidates: &CandidateSet) {
+            assert!(candidates.len() > 1);
+            self.counts.borrow_mut()[2] += 1;
+        }
+
+        fn on_backtrack(&mut self, _cell: &Coordinates, _value: u8) {
+            self.counts.borrow_mut()[3] += 1;
+        }
+    }
+
+    /// Test variant: values on the main diagonal must be unique.
+    #[derive(Debug)]
+    struct DiagonalConstraint;
+
+    impl Constraint for DiagonalConstraint {
+        fn eliminate(&self, grid: &Grid, cell: &Coordinates) -> CandidateSet {
+            self.peers(cell)
+                .into_iter()
+                .map(|peer| grid[coords!(peer.row, peer.column)])
+                .filter(|value| *value != 0)
+                .collect()
+        }
+
+        fn peers(&self, cell: &Coordinates) -> Vec<Coordinates> {
+            if cell.row != cell.column {
+                return Vec::new();
+            }
+            (0..9)
+                .filter(|i| *i != cell.row)
+                .map(|i| Coordinates::from((i, i)))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_possible() {
+        let mut solver = Solver::new(Sudoku::new_empty());
+        assert_eq!(all_possible!(), solver.get_possible(1, 1).unwrap());
+        solver.sudoku.set(0, 1, 1).unwrap();
+        assert_eq!(
+            HashSet::from([2, 3, 4, 5, 6, 7, 8, 9]),
+            solver.get_possible(1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_possible_resets() {
+        let resets = Neighbors::from(Coordinates::from((1, 2)));
+        println!("{:#?}", resets);
+        assert_eq!(20, resets.len());
+    }
+
+    #[test]
+    fn test_custom_constraint() {
+        let mut constraints = constraint::standard();
+        constraints.push(Box::new(DiagonalConstraint));
+        let mut solver = Solver::with_constraints(Sudoku::new_empty(), constraints);
+        solver.sudoku.set(0, 0, 1).unwrap();
+        assert!(!solver.get_possible(4, 4).unwrap().contains(&1));
+        assert!(solver.get_possible(4, 5).unwrap().contains(&1));
+
+        solver.solve().unwrap();
+        let diagonal: HashSet<u8> = (0..9)
+            .map(|i| solver.sudoku.squares[coords!(i, i)])
+            .collect();
+        assert_eq!(9, diagonal.len());
+    }
+
+    #[test]
+    fn test_observer() {
+        let observer = CountingObserver::default();
+        let counts = observer.counts.clone();
+        let mut solver = Solver::new(Sudoku::new_empty());
+        solver.add_observer(Box::new(observer));
+        solver.solve().unwrap();
+
+        let counts = counts.borrow();
+        assert_eq!(
+            solver.stats().singles + solver.stats().hidden_singles,
+            counts[0]
+        );
+        assert!(counts[1] > 0);
+        assert_eq!(solver.stats().guesses, counts[2]);
+        assert_eq!(solver.stats().backtracks, counts[3]);
+    }
+
+    #[test]
+    fn test_generate() {
+        let sudoku = Solver::generate(50).unwrap();
+        println!("{}", sudoku);
+        for i in 0..9 * 9 {
+            assert_eq!(
+                sudoku.squares[i] != 0,
+                sudoku.is_given(i / 9, i % 9).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let mut solver = Solver::new(Sudoku::new_empty());
+        assert!(solver.find_conflicts().is_empty());
+        solver.sudoku.set(0, 0, 5).unwrap();
+        solver.sudoku.set(0, 8, 5).unwrap();
+        solver.sudoku.set(1, 1, 5).unwrap();
+        solver.sudoku.set(8, 8, 4).unwrap();
+        assert_eq!(
+            vec![
+                (Coordinates::from((0, 0)), Coordinates::from((0, 8))),
+                (Coordinates::from((0, 0)), Coordinates::from((1, 1))),
+            ],
+            solver.find_conflicts()
+        );
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let solver = Solver::new(Sudoku::new_from_state(
+            Solver::generate_seeded(0, 1).unwrap().squares,
+        ));
+        assert_eq!(1, solver.count_solutions(2));
+
+        let mut solver = Solver::new(Sudoku::new_empty());
+        assert_eq!(2, solver.count_solutions(2));
+        solver.sudoku.set(0, 0, 1).unwrap();
+        solver.sudoku.set(0, 1, 1).unwrap();
+        assert_eq!(0, solver.count_solutions(2));
+    }
+
+    #[test]
+    fn test_search_observer() {
+        let puzzle = crate::format::parse_line(crate::corpus::EASY.puzzle, 1).unwrap();
+        let candidates = Solver::new(puzzle.clone()).all_candidates();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut solver = SolverBuilder::new()
+            .backend(SolverBackend::Dlx)
+            .build(puzzle.clone());
+        solver.add_observer(Box::new(sender));
+        solver.solve().unwrap();
+        drop(solver);
+
+        // Eliminations follow from the guesses; every square filled must be reported as a guess.
+        let steps: Vec<SolveStep> = receiver
+            .into_iter()
+            .filter(|step| !matches!(step, SolveStep::Elimination { .. }))
+            .collect();
+        assert_eq!(81 - puzzle.set_count as usize, steps.len());
+        for step in steps {
+            match step {
+                SolveStep::Guess {
+                    cell,
+                    value,
+                    candidates: possible,
+                } => {
+                    let i = coords!(cell.row, cell.column);
+                    assert_eq!(0, puzzle.squares[i]);
+                    assert!(possible.contains(&value));
+                    let mut expected: Vec<u8> = candidates[i].iter().copied().collect();
+                    expected.sort_unstable();
+                    assert_eq!(expected, possible);
+                }
+                step => panic!("unexpected step {:?}", step),
+            }
+        }
+    }
+
+    #[test]
+    fn test_backends() {
+        let puzzle = crate::format::parse_line(
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+            1,
+        )
+        .unwrap();
+        let solution = Solver::new(puzzle.clone()).unique_solution().unwrap();
+        for backend in [
+            SolverBackend::Backtracking,
+            SolverBackend::Dlx,
+            SolverBackend::Logic,
+            SolverBackend::Hybrid,
+            SolverBackend::Sat,
+            SolverBackend::Annealing,
+            SolverBackend::Parallel,
+        ] {
+            let mut solver = SolverBuilder::new()
+                .backend(backend)
+                .seed(1)
+                .build(puzzle.clone());
+            solver.solve().unwrap();
+            assert_eq!(solution.squares, solver.sudoku().squares);
+        }
+
+        let mut solver = SolverBuilder::new()
+            .backend(SolverBackend::Logic)
+            .build(Sudoku::new_empty());
+        assert!(matches!(solver.solve(), Err(SolverError::Stuck(_))));
+
+        let mut solver = SolverBuilder::new()
+            .backend(SolverBackend::Hybrid)
+            .build(Sudoku::new_empty());
+        solver.solve().unwrap();
+        assert_eq!(81, solver.sudoku().set_count);
+        assert!(solver.find_conflicts().is_empty());
+        assert_eq!(0, solver.stats().singles);
+        assert_eq!(81, solver.stats().guesses);
+
+        let mut solver = SolverBuilder::new()
+            .backend(SolverBackend::Annealing)
+            .seed(3)
+            .build(Sudoku::new_empty());
+        solver.solve().unwrap();
+        assert_eq!(81, solver.sudoku().set_count);
+        assert!(solver.find_conflicts().is_empty());
+
+        let mut solver = Solver::new(Sudoku::new_empty());
+        solver.sudoku.set(0, 0, 1).unwrap();
+        solver.sudoku.set(0, 1, 1).unwrap();
+        solver.backend = SolverBackend::Dlx;
+        assert!(matches!(
+            solver.solve(),
+            Err(SolverError::Unsolvable { .. })
+        ));
+        solver.backend = SolverBackend::Sat;
+        assert!(matches!(
+            solver.solve(),
+            Err(SolverError::Unsolvable { .. })
+        ));
+        solver.backend = SolverBackend::Parallel;
+        assert!(matches!(
+            solver.solve(),
+            Err(SolverError::Unsolvable { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parallel() {
+        for known in [crate::corpus::MINIMAL, crate::corpus::INKALA] {
+            let puzzle = crate::format::parse_line(known.puzzle, 1).unwrap();
+            let mut dlx = SolverBuilder::new()
+                .backend(SolverBackend::Dlx)
+                .build(puzzle.clone());
+            dlx.solve().unwrap();
+            let mut solver = SolverBuilder::new()
+                .backend(SolverBackend::Parallel)
+                .build(puzzle);
+            solver.solve().unwrap();
+            assert_eq!(dlx.sudoku().squares, solver.sudoku().squares);
+            assert_eq!(known.solution, crate::format::write_line(solver.sudoku()));
+        }
+
+        // Propagation does not notice that 1 has no place in the first row, so every thread
+        // searches in vain.
+        let invalid = crate::corpus::INVALID
+            .into_iter()
+            .find(|invalid| invalid.problem == crate::corpus::Problem::NoSolution)
+            .unwrap();
+        let mut solver = SolverBuilder::new()
+            .backend(SolverBackend::Parallel)
+            .build(crate::format::parse_line(invalid.puzzle, 1).unwrap());
+        assert!(matches!(
+            solver.solve(),
+            Err(SolverError::Unsolvable { context, .. }) if context.phase == SolvePhase::Search
+        ));
+    }
+
+    #[test]
+    fn test_annealing() {
+        for (known, seed) in [(crate::corpus::MEDIUM, 0), (crate::corpus::INKALA, 2)] {
+            let puzzle = crate::format::parse_line(known.puzzle, 1).unwrap();
+            let mut solver = SolverBuilder::new()
+                .backend(SolverBackend::Annealing)
+                .seed(seed)
+                .build(puzzle.clone());
+            match solver.solve() {
+                Ok(()) => {
+                    assert!(solver.find_conflicts().is_empty());
+                    assert_eq!(known.solution, crate::format::write_line(solver.sudoku()));
+                }
+                // Out of moves; the givens are left as they were.
+                Err(SolverError::Stuck(_)) => {
+                    assert_eq!(puzzle.squares, solver.sudoku().squares)
+                }
+                Err(err) => panic!("unexpected error {:?}", err),
+            }
+        }
+
+        // A thousand moves are not enough for the hardest puzzle, and the search stops there.
+        let puzzle = crate::format::parse_line(crate::corpus::INKALA.puzzle, 1).unwrap();
+        let solver = Solver::new(puzzle.clone());
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        assert_eq!(
+            None,
+            super::annealing::anneal(&puzzle.squares, &solver.peer_indices(), &mut rng, 1000)
+        );
+    }
+
+    #[test]
+    fn test_propagate() {
+        let puzzle = crate::format::parse_line(
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+            1,
+        )
+        .unwrap();
+        let mut solver = Solver::new(puzzle.clone());
+        assert_eq!(None, solver.propagate().unwrap());
+        assert_eq!(81, solver.sudoku.set_count);
+        assert_eq!(81 - puzzle.set_count as u32, solver.stats.singles);
+        assert_eq!(1, solver.stats.propagations);
+
+        let mut sudoku = Sudoku::new_empty();
+        for column in 0..8 {
+            sudoku.set(0, column, column as u8 + 1).unwrap();
+        }
+        sudoku.set(1, 8, 9).unwrap();
+        let mut solver = Solver::new(sudoku);
+        assert_eq!(Some(Coordinates::from((0, 8))), solver.propagate().unwrap());
+        assert!(matches!(
+            solver.solve(),
+            Err(SolverError::Unsolvable { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cell_selection() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set(0, 0, 1).unwrap();
+        for (selection, expected) in [
+            (CellSelection::MinimumRemainingValues, (0, 1)),
+            (CellSelection::Degree, (1, 3)),
+            (CellSelection::FirstEmpty, (0, 1)),
+        ] {
+            let mut solver = SolverBuilder::new()
+                .config(SolverConfig {
+                    cell_selection: selection,
+                    ..Default::default()
+                })
+                .build(sudoku.clone());
+            assert_eq!(Coordinates::from(expected), solver.select_cell().unwrap().0);
+        }
+
+        let mut solver = SolverBuilder::new()
+            .config(SolverConfig {
+                cell_selection: CellSelection::Random,
+                ..Default::default()
+            })
+            .seed(2)
+            .build(sudoku);
+        solver.solve().unwrap();
+        assert!(solver.find_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_transposition_table() {
+        let puzzle = crate::format::parse_line(crate::corpus::MEDIUM.puzzle, 1).unwrap();
+        let mut remembered = 0;
+        for seed in 0..8 {
+            let mut solver = SolverBuilder::new()
+                .seed(seed)
+                .strategies(&[Strategy::NakedSingle])
+                .transposition_table(true)
+                .build(puzzle.clone());
+            solver.solve().unwrap();
+            assert_eq!(
+                crate::corpus::MEDIUM.solution,
+                crate::format::write_line(solver.sudoku())
+            );
+            remembered += solver.dead_states.as_ref().unwrap().len();
+        }
+        assert!(remembered > 0);
+
+        // A dead state with no uncertain square to unset proves the sudoku unsolvable.
+        let mut solver = SolverBuilder::new()
+            .strategies(&[Strategy::NakedSingle])
+            .transposition_table(true)
+            .build(puzzle);
+        assert!(solver.propagate().unwrap().is_none());
+        solver.restore_points.push(solver.snapshot());
+        let (cell, possibilities) = solver.select_cell().unwrap();
+        let dead: HashSet<u64> = possibilities
+            .iter()
+            .map(|value| solver.sudoku.zobrist_with(cell.row, cell.column, *value))
+            .collect();
+        solver.dead_states = Some(dead);
+        assert!(matches!(solver.step(), Err(SolverError::Unsolvable { .. })));
+        let propagated = solver.sudoku.zobrist();
+        assert!(solver.dead_states.unwrap().contains(&propagated));
+    }
+
+    #[test]
+    fn test_value_order() {
+        // Row 1 holds 1-6, so (1, 6) can be 7, 8 or 9. Every empty peer can still be 7, while
+        // 8 is ruled out in row 5 and 9 in row 2.
+        let mut sudoku = Sudoku::new_empty();
+        for column in 0..6 {
+            sudoku.set(1, column, column as u8 + 1).unwrap();
+        }
+        sudoku.set(2, 0, 9).unwrap();
+        sudoku.set(5, 0, 8).unwrap();
+        for (order, expected) in [
+            (ValueOrder::Ascending, 7),
+            (ValueOrder::LeastConstraining, 9),
+        ] {
+            let mut solver = SolverBuilder::new()
+                .config(SolverConfig {
+                    value_order: order,
+                    ..Default::default()
+                })
+                .build(sudoku.clone());
+            let cell = Coordinates::from((1, 6));
+            assert_eq!(HashSet::from([7, 8, 9]), solver.get_possible(1, 6).unwrap());
+            assert_eq!(expected, solver.select_value(&cell, &[7, 8, 9]).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let puzzle = crate::format::parse_line(crate::corpus::MEDIUM.puzzle, 1).unwrap();
+        let mut solver = SolverBuilder::new()
+            .seed(2)
+            .strategies(&[Strategy::NakedSingle])
+            .build(puzzle.clone());
+        let start = solver.snapshot();
+        solver.solve().unwrap();
+        let solved = solver.snapshot();
+        assert_eq!(2, solver.restore_points.len());
+        assert_eq!(81, solved.sudoku().set_count);
+
+        solver.restore(&start);
+        assert_eq!(puzzle.squares, solver.sudoku().squares);
+        assert_eq!(puzzle.set_count, solver.sudoku().set_count);
+        solver.restore(&solved);
+        assert_eq!(
+            crate::corpus::MEDIUM.solution,
+            crate::format::write_line(solver.sudoku())
+        );
+    }
+
+    #[test]
+    fn test_solve_partial() {
+        let puzzle = crate::format::parse_line(
+            "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+            1,
+        )
+        .unwrap();
+        let mut solver = SolverBuilder::new().seed(5).build(puzzle.clone());
+        let partial = solver.solve_partial(3).unwrap();
+        assert!(!partial.solved);
+        assert!(partial.sudoku.set_count >= puzzle.set_count);
+        assert!(solver.stats().guesses + solver.stats().backtracks <= 3);
+        for i in 0..81 {
+            assert_eq!(
+                partial.sudoku.squares[i] != 0,
+                partial.candidates[i].is_empty()
+            );
+        }
+
+        let mut solver = Solver::new(Sudoku::new_empty());
+        let partial: PartialSolution = solver.solve_partial(1000).unwrap();
+        assert!(partial.solved);
+        assert_eq!(solver.sudoku().squares, partial.sudoku.squares);
+    }
+
+    #[test]
+    fn test_solve_logical() {
+        let puzzle = crate::format::parse_line(crate::corpus::EASY.puzzle, 1).unwrap();
+        let partial = Solver::new(puzzle).solve_logical().unwrap();
+        assert!(partial.solved);
+        assert_eq!(
+            crate::format::parse_line(crate::corpus::EASY.solution, 1).unwrap(),
+            partial.sudoku
+        );
+
+        let puzzle = crate::format::parse_line(crate::corpus::MEDIUM.puzzle, 1).unwrap();
+        let mut solver = SolverBuilder::new()
+            .strategies(&[Strategy::NakedSingle])
+            .build(puzzle.clone());
+        let partial = solver.solve_logical().unwrap();
+        assert!(!partial.solved);
+        assert!(partial.sudoku.set_count >= puzzle.set_count);
+        assert_eq!(0, solver.stats().guesses);
+        assert!(partial
+            .candidates
+            .iter()
+            .zip(partial.sudoku.squares)
+            .all(|(candidates, value)| (value == 0) == (candidates.len() > 1)));
+    }
+
+    #[test]
+    fn test_hidden_single() {
+        // The 5s in rows 1 and 2 and columns 1 and 2 leave only r3c3 for a 5 in block 1.
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set(0, 5, 5).unwrap();
+        sudoku.set(1, 7, 5).unwrap();
+        sudoku.set(3, 0, 5).unwrap();
+        sudoku.set(6, 1, 5).unwrap();
+        let solver = Solver::new(sudoku.clone());
+        let (cell, value, _) =
+            super::hidden_single(&sudoku.squares, &solver.all_candidates()).unwrap();
+        assert_eq!((Coordinates::from((2, 2)), 5), (cell, value));
+        assert!(solver.get_possible(2, 2).unwrap().len() > 1);
+
+        let puzzle = crate::format::parse_line(crate::corpus::MEDIUM.puzzle, 1).unwrap();
+        let mut solver = Solver::new(puzzle);
+        assert!(solver.solve_logical().unwrap().solved);
+        assert!(solver.stats().hidden_singles > 0);
+        assert_eq!(0, solver.stats().guesses);
+    }
+
+    #[test]
+    fn test_locked_candidates() {
+        // Block 1 only has room left in row 1, so its 5 rules out 5 for the rest of row 1.
+        let mut sudoku = Sudoku::new_empty();
+        for (i, value) in [1, 2, 3, 4, 6, 7].into_iter().enumerate() {
+            sudoku.set(1 + i / 3, i % 3, value).unwrap();
+        }
+        let solver = Solver::new(sudoku.clone());
+        let (value, base, cover, cells) =
+            super::locked_candidates(&sudoku.squares, &solver.all_candidates()).unwrap();
+        assert_eq!((5, House::Block(0), House::Row(0)), (value, base, cover));
+        assert_eq!(
+            (3..9)
+                .map(|column| Coordinates::from((0, column)))
+                .collect::<Vec<_>>(),
+            cells
+        );
+
+        let puzzle = crate::format::parse_line(crate::corpus::MINIMAL.puzzle, 1).unwrap();
+        let mut solver = Solver::new(puzzle.clone());
+        assert!(solver.solve_logical().unwrap().solved);
+        assert!(solver.stats().locked_candidates > 0);
+        assert_eq!(
+            crate::corpus::MINIMAL.solution,
+            crate::format::write_line(solver.sudoku())
+        );
+        let mut solver = SolverBuilder::new()
+            .strategies(&[Strategy::NakedSingle, Strategy::HiddenSingle])
+            .build(puzzle);
+        assert!(!solver.solve_logical().unwrap().solved);
+    }
+
+    #[test]
+    fn test_unique_rectangle() {
+        let grid: Grid = [0; 9 * 9];
+        let corners = [(0, 0), (0, 3), (1, 0), (1, 3)].map(Coordinates::from);
+        let mut candidates: CandidateGrid = std::array::from_fn(|_| (1..=9).collect());
+        for corner in &corners[..3] {
+            candidates[coords!(corner.row, corner.column)] = CandidateSet::from([2, 7]);
+        }
+        candidates[coords!(1, 3)] = CandidateSet::from([2, 5, 7]);
+        assert_eq!(
+            Some((
+                corners.clone(),
+                [2, 7],
+                vec![(corners[3].clone(), 2), (corners[3].clone(), 7)]
+            )),
+            super::unique_rectangle(&grid, &candidates)
+        );
+
+        // With r2c1 and r2c4 both 2, 5 or 7, one of them is 5.
+        candidates[coords!(1, 0)] = CandidateSet::from([2, 5, 7]);
+        let (_, values, eliminations) = super::unique_rectangle(&grid, &candidates).unwrap();
+        assert_eq!([2, 7], values);
+        assert_eq!(
+            [1, 2, 4, 5, 6, 7, 8]
+                .map(|column| (Coordinates::from((1, column)), 5))
+                .to_vec(),
+            eliminations
+        );
+
+        candidates[coords!(1, 0)] = CandidateSet::from([2, 6, 7]);
+        assert_eq!(None, super::unique_rectangle(&grid, &candidates));
+        assert!(!Solver::new(Sudoku::new_empty())
+            .strategies
+            .contains(&Strategy::UniqueRectangle));
+    }
+
+    #[test]
+    fn test_als_xz() {
+        // r1c1 is 1 or 2, and r5c1 and r5c2 hold two of 1, 2 and 3. Only one of them can be the
+        // 1 of column 1, so the other holds a 2, which r1c2 sees in both.
+        let mut grid: Grid = [1; 9 * 9];
+        let mut candidates: CandidateGrid = std::array::from_fn(|_| CandidateSet::new());
+        for (row, column, possibilities) in [
+            (0, 0, [1, 2].as_slice()),
+            (0, 1, &[2, 4]),
+            (4, 0, &[1, 3]),
+            (4, 1, &[2, 3]),
+        ] {
+            grid[coords!(row, column)] = 0;
+            candidates[coords!(row, column)] = possibilities.iter().copied().collect();
+        }
+        assert_eq!(
+            Some((
+                [
+                    vec![Coordinates::from((0, 0))],
+                    vec![Coordinates::from((4, 0)), Coordinates::from((4, 1))]
+                ],
+                1,
+                vec![(Coordinates::from((0, 1)), 2)]
+            )),
+            super::als_xz(&grid, &candidates)
+        );
+
+        // The 2s of the sets do not see each other, so there is no second finding.
+        candidates[coords!(0, 1)].remove(&2);
+        assert_eq!(None, super::als_xz(&grid, &candidates));
+    }
+
+    #[test]
+    fn test_bivalue_universal_grave() {
+        // The squares of rows 1-3 in columns 1, 4 and 7 miss a different one of 1, 2 and 3 in
+        // every row and column, so each of them is twice in every house, but r1c1 has all three.
+        let mut grid: Grid = [1; 9 * 9];
+        let mut candidates: CandidateGrid = std::array::from_fn(|_| CandidateSet::new());
+        let missing = [[1, 2, 3], [2, 3, 1], [3, 1, 2]];
+        for (row, missing) in missing.iter().enumerate() {
+            for (column, missing) in missing.iter().enumerate() {
+                let i = coords!(row, column * 3);
+                grid[i] = 0;
+                candidates[i] = (1..=3).filter(|value| value != missing).collect();
+            }
+        }
+        candidates[0].insert(1);
+        assert_eq!(
+            Some((Coordinates::from((0, 0)), 1)),
+            super::bivalue_universal_grave(&grid, &candidates)
+        );
+
+        candidates[0].insert(4);
+        assert_eq!(None, super::bivalue_universal_grave(&grid, &candidates));
+
+        // Only r1c1, r1c4 and r1c7 are empty, and r1c4 has three possible values, but the
+        // columns and blocks hold each value once.
+        let mut grid: Grid = [1; 9 * 9];
+        let mut candidates: CandidateGrid = std::array::from_fn(|_| CandidateSet::new());
+        for (column, possibilities) in [(0, [1, 2].as_slice()), (3, &[1, 2, 3]), (6, &[1, 3])] {
+            grid[column] = 0;
+            candidates[column] = possibilities.iter().copied().collect();
+        }
+        assert_eq!(None, super::bivalue_universal_grave(&grid, &candidates));
+        assert!(!Solver::new(Sudoku::new_empty())
+            .strategies()
+            .contains(&Strategy::BivalueUniversalGrave));
+    }
+
+    #[test]
+    fn test_forcing_chain() {
+        let puzzle = crate::format::parse_line(crate::corpus::MINIMAL.puzzle, 1).unwrap();
+        let chain_lengths = |max_chain_length: usize| {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let mut solver = SolverBuilder::new()
+                .strategies(&[Strategy::NakedSingle, Strategy::ForcingChain])
+                .max_chain_length(max_chain_length)
+                .build(puzzle.clone());
+            solver.add_observer(Box::new(sender));
+            assert!(solver.solve_logical().unwrap().solved);
+            assert_eq!(0, solver.stats().guesses);
+            drop(solver);
+            receiver
+                .into_iter()
+                .filter_map(|step| match step {
+                    SolveStep::Elimination {
+                        cause: EliminationCause::ForcingChain(chain),
+                        ..
+                    } => Some(chain.len()),
+                    _ => None,
+                })
+                .collect::<Vec<usize>>()
+        };
+        let long = chain_lengths(20);
+        assert!(long.iter().any(|length| *length > 2));
+        let short = chain_lengths(2);
+        assert!(!short.is_empty());
+        assert!(short.iter().all(|length| *length <= 2));
+
+        // Inkala's puzzle needs chains within chains.
+        let puzzle = crate::format::parse_line(crate::corpus::INKALA.puzzle, 1).unwrap();
+        let mut solver = SolverBuilder::new()
+            .strategies(&[Strategy::NakedSingle, Strategy::ForcingChain])
+            .build(puzzle);
+        assert!(!solver.solve_logical().unwrap().solved);
+        assert_eq!(0, solver.stats().forcing_chains);
+    }
+
+    #[test]
+    fn test_rate_remaining() {
+        let puzzle = crate::format::parse_line(
+            "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+            1,
+        )
+        .unwrap();
+        let solver = Solver::new(puzzle.clone());
+        let solution = crate::format::parse_line(
+            "812753649943682175675491283154237896369845721287169534521974368438526917796318452",
+            1,
+        )
+        .unwrap();
+        let mut position = puzzle.clone();
+        position.squares[..18].copy_from_slice(&solution.squares[..18]);
+        let stats = solver.rate_remaining(&position).unwrap();
+        assert!(stats.guesses > 0);
+        assert_eq!(stats, solver.rate_remaining(&position).unwrap());
+
+        position.squares[..54].copy_from_slice(&solution.squares[..54]);
+        let stats = solver.rate_remaining(&position).unwrap();
+        assert_eq!(0, stats.guesses);
+        assert_eq!(Some("Naked Single"), stats.hardest_technique());
+        assert_eq!(
+            SolveStats::default(),
+            solver.rate_remaining(&solution).unwrap()
+        );
+
+        position.squares[1] = position.squares[0];
+        assert!(matches!(
+            solver.rate_remaining(&position),
+            Err(SolverError::Unsolvable { .. })
+        ));
+    }
+
+    #[test]
+    fn test_all_candidates() {
+        let puzzle = crate::format::parse_line(
+            "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+            1,
+        )
+        .unwrap();
+        let mut constraints = constraint::standard();
+        constraints.push(Box::new(DiagonalConstraint));
+        let solver = Solver::with_constraints(puzzle, constraints);
+        let candidates = solver.all_candidates();
+        for (i, square) in candidates.iter().enumerate() {
+            if solver.sudoku().squares[i] == 0 {
+                assert_eq!(solver.get_possible(i / 9, i % 9).unwrap(), *square);
+            } else {
+                assert!(square.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_cells() {
+        let puzzle = crate::format::parse_line(
+            "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+            1,
+        )
+        .unwrap();
+        let solver = Solver::new(puzzle.clone());
+        let empty: Vec<(Coordinates, CandidateSet)> = solver.empty_cells().collect();
+        assert_eq!(81 - puzzle.set_count as usize, empty.len());
+        assert!(empty
+            .windows(2)
+            .all(|pair| pair[0].1.len() <= pair[1].1.len()));
+        for (cell, possibilities) in empty {
+            assert_eq!(
+                solver.get_possible(cell.row, cell.column).unwrap(),
+                possibilities
+            );
+        }
+    }
+
+    #[test]
+    fn test_failure_context() {
+        let mut sudoku = Sudoku::new_empty();
+        for column in 0..8 {
+            sudoku.set(0, column, column as u8 + 1).unwrap();
+        }
+        sudoku.set(1, 8, 9).unwrap();
+        let cell = Coordinates::from((0, 8));
+
+        let mut solver = Solver::new(sudoku.clone());
+        match solver.solve() {
+            Err(SolverError::Unsolvable { reason, context }) => {
+                assert_eq!("r1c9 has no possible value", reason);
+                assert_eq!(SolvePhase::Recovery, context.phase);
+                assert_eq!(Some(cell.clone()), context.cell);
+                assert_eq!(sudoku.squares, context.grid);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let mut solver = SolverBuilder::new()
+            .backend(SolverBackend::Logic)
+            .build(sudoku);
+        let err = solver.solve().unwrap_err();
+        assert!(matches!(
+            &err,
+            SolverError::Unsolvable { context, .. } if context.phase == SolvePhase::Propagation
+        ));
+        assert!(err.to_string().starts_with(
+            "unsolvable: r1c9 has no possible value (during propagation at r1c9 in 12345678."
+        ));
+    }
+
+    #[test]
+    fn test_unsolvable_reason() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set(0, 0, 2).unwrap();
+        sudoku.set(0, 1, 3).unwrap();
+        sudoku.set(1, 8, 1).unwrap();
+        sudoku.set(2, 4, 1).unwrap();
+        sudoku.set(3, 2, 1).unwrap();
+        match Solver::new(sudoku.clone()).unique_solution() {
+            Err(SolverError::Unsolvable { reason, .. }) => {
+                assert_eq!("digit 1 has no place in row 1", reason)
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        sudoku.set(8, 0, 2).unwrap();
+        match Solver::new(sudoku).unique_solution() {
+            Err(SolverError::Unsolvable { reason, .. }) => {
+                assert_eq!("r1c1 and r9c1 both contain 2", reason)
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_generate_with_options() {
+        let options = GenerationOptions {
+            difficulty: 60,
+            symmetric: true,
+            max_clues: Some(40),
+            ..GenerationOptions::default()
+        };
+        let sudoku = Solver::generate_with_options(&options, 3).unwrap();
+        assert!(sudoku.set_count <= 40);
+        for i in 0..81 {
+            assert_eq!(sudoku.squares[i] == 0, sudoku.squares[80 - i] == 0);
+        }
+        assert_eq!(
+            sudoku.squares,
+            Solver::generate_with_options(&options, 3).unwrap().squares
+        );
+
+        let options = GenerationOptions {
+            difficulty: 0,
+            max_clues: Some(10),
+            max_attempts: 3,
+            ..GenerationOptions::default()
+        };
+        assert!(matches!(
+            Solver::generate_with_options(&options, 3),
+            Err(SolverError::GenerationFailed { attempts: 3 })
+        ));
+
+        let options = GenerationOptions {
+            time_limit: Some(Duration::ZERO),
+            ..options
+        };
+        assert!(matches!(
+            Solver::generate_with_options(&options, 3),
+            Err(SolverError::GenerationFailed { attempts: 0 })
+        ));
+
+        let options = GenerationOptions {
+            difficulty: 50,
+            seed_grid: Some(SEED_GRID),
+            ..GenerationOptions::default()
+        };
+        let sudoku = Solver::generate_with_options(&options, 8).unwrap();
+        let solution = Solver::new(sudoku.clone()).unique_solution().unwrap();
+        let seed = Sudoku::new_from_state(SEED_GRID);
+        assert_eq!(canonical(&seed).squares, canonical(&solution).squares);
+        assert_eq!(
+            sudoku.squares,
+            Solver::generate_with_options(&options, 8).unwrap().squares
+        );
+        assert_ne!(
+            sudoku.squares,
+            Solver::generate_with_options(&options, 9).unwrap().squares
+        );
+
+        let options = GenerationOptions {
+            unique: true,
+            symmetric: true,
+            ..options
+        };
+        let (sudoku, metadata) = Solver::generate_with_metadata(&options, 10).unwrap();
+        let report = Solver::new(sudoku).difficulty_report().unwrap();
+        assert_eq!(Some("000000000000000a".to_string()), metadata.id);
+        assert_eq!(Some(report.score as f32), metadata.rating);
+        assert_eq!("unique", metadata.tags[0]);
+        assert_eq!("symmetric", metadata.tags[1]);
+        assert!(metadata.created.is_some());
+    }
+
+    #[test]
+    fn test_requires_guessing() {
+        // Singles alone need guesses on this puzzle, but locked candidates finish it.
+        let puzzle = crate::format::parse_line(crate::corpus::MINIMAL.puzzle, 1).unwrap();
+        let singles = SolverBuilder::new()
+            .strategies(&[Strategy::NakedSingle, Strategy::HiddenSingle])
+            .build(puzzle.clone());
+        assert!(singles.rate_remaining(&puzzle).unwrap().guesses > 0);
+        assert!(!Solver::requires_guessing(&puzzle).unwrap());
+
+        let puzzle = crate::format::parse_line(crate::corpus::INKALA.puzzle, 1).unwrap();
+        assert!(Solver::requires_guessing(&puzzle).unwrap());
+    }
+
+    #[test]
+    fn test_difficulty_report() {
+        let easy = crate::format::parse_line(
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+            1,
+        )
+        .unwrap();
+        let report = Solver::new(easy.clone()).difficulty_report().unwrap();
+        assert_eq!(easy.set_count, report.clues);
+        assert_eq!(Some("Naked Single"), report.hardest_technique);
+        assert_eq!(0, report.guesses);
+        assert!(report.branching_factor > 1.0);
+
+        let hard = crate::format::parse_line(
+            "812753649943682175.7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+            1,
+        )
+        .unwrap();
+        let hard_report = Solver::new(hard).difficulty_report().unwrap();
+        assert_eq!(Some("Guess"), hard_report.hardest_technique);
+        assert!(hard_report.score > report.score);
+        assert_eq!(2.3, report.rating(RatingScale::SudokuExplainer));
+        let se = hard_report.se_rating();
+        assert!(se > 2.6 && se <= 11.9, "{}", se);
+
+        let mut solver = Solver::new(easy);
+        solver.solve().unwrap();
+        let solved = solver.difficulty_report().unwrap();
+        assert_eq!(None, solved.hardest_technique);
+        assert_eq!(0.0, solved.score);
+        assert_eq!(0.0, solved.se_rating());
+    }
+
+    #[test]
+    fn test_se_rating() {
+        let puzzle = crate::format::parse_line(crate::corpus::EASY.puzzle, 1).unwrap();
+        let report = Solver::new(puzzle).difficulty_report().unwrap();
+        assert_eq!(vec!["Naked Single"], report.techniques);
+        assert_eq!(2.3, report.se_rating());
+
+        // Hidden singles rate below naked singles on the SE scale.
+        let puzzle = crate::format::parse_line(crate::corpus::MEDIUM.puzzle, 1).unwrap();
+        let report = Solver::new(puzzle).difficulty_report().unwrap();
+        assert_eq!(Some("Hidden Single"), report.hardest_technique);
+        assert_eq!(vec!["Naked Single", "Hidden Single"], report.techniques);
+        assert_eq!(2.3, report.se_rating());
+
+        let report = DifficultyReport {
+            clues: 17,
+            hardest_technique: Some("Guess"),
+            techniques: vec!["Naked Single", "Locked Candidates", "Guess"],
+            guesses: 3,
+            branching_factor: 3.0,
+            score: 3.25,
+        };
+        assert!((report.se_rating() - 4.2).abs() < 1e-9);
+        let report = DifficultyReport {
+            techniques: vec!["Forcing Chain", "Guess"],
+            guesses: 1,
+            ..report
+        };
+        assert_eq!(7.5, report.se_rating());
+        let report = DifficultyReport {
+            guesses: 1 << 20,
+            ..report
+        };
+        assert_eq!(11.9, report.se_rating());
+    }
+
+    #[test]
+    fn test_builder() {
+        let puzzle = crate::format::parse_line(
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+            1,
+        )
+        .unwrap();
+        let mut solver = SolverBuilder::new()
+            .backend(SolverBackend::Logic)
+            .strategies(&[])
+            .build(puzzle.clone());
+        assert!(matches!(solver.solve(), Err(SolverError::Stuck(_))));
+        assert_eq!(puzzle.squares, solver.sudoku().squares);
+
+        let mut solver = SolverBuilder::new()
+            .strategies(&[Strategy::NakedSingle])
+            .max_guesses(0)
+            .build(puzzle.clone());
+        solver.solve().unwrap();
+        assert_eq!(0, solver.stats().guesses);
+
+        let mut solver = SolverBuilder::new()
+            .max_guesses(2)
+            .build(Sudoku::new_empty());
+        assert!(matches!(solver.solve(), Err(SolverError::Stuck(_))));
+        assert_eq!(2, solver.stats().guesses + solver.stats().backtracks);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut solver = SolverBuilder::new()
+            .constraints(constraint::standard())
+            .cell_selection(CellSelection::FirstEmpty)
+            .value_order(ValueOrder::Ascending)
+            .observer(Box::new(sender))
+            .build(puzzle.clone());
+        solver.solve().unwrap();
+        drop(solver);
+        let placements = receiver
+            .iter()
+            .filter(|step| matches!(step, crate::observer::SolveStep::Placement { .. }))
+            .count();
+        assert_eq!(81 - puzzle.set_count as usize, placements);
+    }
+
+    #[test]
+    fn test_dlx_custom_constraint() {
+        let mut constraints = constraint::standard();
+        constraints.push(Box::new(DiagonalConstraint));
+        let mut solver = Solver::with_constraints(Sudoku::new_empty(), constraints);
+        solver.set_seed(4);
+        solver.solve().unwrap();
+        let mut squares = solver.sudoku.squares;
+        for i in (0..9 * 9).step_by(2) {
+            squares[i] = 0;
+        }
+
+        let mut constraints = constraint::standard();
+        constraints.push(Box::new(DiagonalConstraint));
+        let mut solver = Solver::with_constraints(Sudoku::new_from_state(squares), constraints);
+        solver.backend = SolverBackend::Dlx;
+        solver.solve().unwrap();
+        let diagonal: HashSet<u8> = (0..9)
+            .map(|i| solver.sudoku.squares[coords!(i, i)])
+            .collect();
+        assert_eq!(9, diagonal.len());
+        assert!(solver.find_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_find_mistakes() {
+        let puzzle = Solver::generate_with_progress(50, true, 3, |_| {}).unwrap();
+        let solution = Solver::new(puzzle.clone()).unique_solution().unwrap();
+        assert_eq!(81, solution.set_count);
+
+        let mut attempt = puzzle.clone();
+        let empty = (0..81).find(|i| puzzle.squares[*i] == 0).unwrap();
+        let wrong_value = solution.squares[empty] % 9 + 1;
+        attempt.set(empty / 9, empty % 9, wrong_value).unwrap();
+
+        let mistakes = Solver::find_mistakes(&puzzle, &attempt).unwrap();
+        assert_eq!(
+            vec![Coordinates::from((empty / 9, empty % 9))],
+            mistakes.wrong
+        );
+        assert_eq!(81 - puzzle.set_count as usize - 1, mistakes.empty.len());
+
+        let mistakes = Solver::find_mistakes(&puzzle, &solution).unwrap();
+        assert_eq!(Mistakes::default(), mistakes);
+
+        assert!(matches!(
+            Solver::find_mistakes(&Sudoku::new_empty(), &attempt),
+            Err(SolverError::MultipleSolutions)
+        ));
+    }
+
+    #[test]
+    fn test_is_minimal() {
+        let puzzle = crate::format::parse_line(crate::corpus::EASY.puzzle, 1).unwrap();
+        assert!(!Solver::is_minimal(&puzzle));
+        assert!(!Solver::is_minimal(&Sudoku::new_empty()));
+
+        // Taking out givens in reading order while the solution stays unique leaves only
+        // necessary ones: a given needed once is needed with fewer givens as well.
+        let mut minimal = puzzle;
+        for i in 0..81 {
+            let mut reduced = minimal.clone();
+            reduced.set_unchecked(i / 9, i % 9, 0);
+            if Solver::new(reduced.clone()).count_solutions(2) == 1 {
+                minimal = reduced;
+            }
+        }
+        assert!(Solver::is_minimal(&minimal));
+    }
+
+    #[test]
+    fn test_redundant_clues() {
+        let puzzle = crate::format::parse_line(crate::corpus::EASY.puzzle, 1).unwrap();
+        let redundant = Solver::redundant_clues(&puzzle).unwrap();
+        assert!(!redundant.is_empty());
+        assert!(redundant.len() < puzzle.set_count as usize);
+        for cell in &redundant {
+            assert_ne!(0, puzzle.get_unchecked(cell.row, cell.column));
+            let mut reduced = puzzle.clone();
+            reduced.set_unchecked(cell.row, cell.column, 0);
+            assert_eq!(1, Solver::new(reduced).count_solutions(2));
+        }
+
+        // With fewer givens, each remaining one is at most as easy to spare.
+        let mut reduced = puzzle;
+        reduced.set_unchecked(redundant[0].row, redundant[0].column, 0);
+        let still_redundant = Solver::redundant_clues(&reduced).unwrap();
+        assert!(still_redundant.len() < redundant.len());
+        assert!(still_redundant.iter().all(|cell| redundant.contains(cell)));
+        assert!(matches!(
+            Solver::redundant_clues(&Sudoku::new_empty()),
+            Err(SolverError::MultipleSolutions)
+        ));
+    }
+
+    #[test]
+    fn test_generate_unique() {
+        let mut last = GenerationProgress::default();
+        let sudoku = Solver::generate_with_progress(50, true, 7, |progress| {
+            assert!(progress.attempts > last.attempts);
+            last = progress.clone();
+        })
+        .unwrap();
+        assert_eq!(1, Solver::new(sudoku.clone()).count_solutions(2));
+        assert_eq!(81 - last.clues_removed as u8, sudoku.set_count);
+        assert_eq!(last.attempts, last.uniqueness_checks);
+        assert!(last.clues_removed <= 40);
+    }
+
+    #[test]
+    fn test_generate_seeded() {
+        let first = Solver::generate_seeded(50, 42).unwrap();
+        let second = Solver::generate_seeded(50, 42).unwrap();
+        assert_eq!(first.squares, second.squares);
+    }
+
+    #[test]
+    fn test_daily() {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let today = Solver::daily(date, 60).unwrap();
+        assert_eq!(today.squares, Solver::daily(date, 60).unwrap().squares);
+        assert_ne!(
+            today.squares,
+            Solver::daily(date.succ_opt().unwrap(), 60).unwrap().squares
+        );
     }
 }
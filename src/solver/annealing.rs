@@ -0,0 +1,100 @@
+use crate::sudoku::Grid;
+use rand::{seq::SliceRandom, Rng};
+use rand_chacha::ChaCha8Rng;
+
+/// Temperature the search starts from, and returns to when it gets stuck.
+const START_TEMPERATURE: f64 = 0.6;
+
+/// Factor the temperature is multiplied with after every move.
+const COOLING: f64 = 0.9999;
+
+/// Number of moves without a new best grid after which the temperature is raised again.
+const REHEAT_AFTER: u32 = 20_000;
+
+/// Search for a solution with simulated annealing. Every block is filled with its missing
+/// values, after which two open squares of a block are swapped at a time, keeping swaps that
+/// lower the number of conflicting pairs and sometimes accepting worse ones while the
+/// temperature is high. Returns `None` if the blocks cannot be filled or no solution is
+/// found within the given number of moves.
+///
+/// # Arguments
+///
+/// * `grid` - The givens.
+/// * `peers` - Indices of the squares that must differ from each square.
+/// * `rng` - Random number generator for filling the blocks and choosing moves.
+/// * `max_moves` - Number of moves to try before giving up.
+pub(crate) fn anneal(
+    grid: &Grid,
+    peers: &[Vec<usize>],
+    rng: &mut ChaCha8Rng,
+    max_moves: u32,
+) -> Option<Grid> {
+    let mut current = *grid;
+    let mut open_squares = Vec::new();
+    for block in 0..9 {
+        let squares: Vec<usize> = (0..9)
+            .map(|j| (block / 3 * 3 + j / 3) * 9 + block % 3 * 3 + j % 3)
+            .collect();
+        let mut missing: Vec<u8> = (1..=9)
+            .filter(|value| !squares.iter().any(|i| grid[*i] == *value))
+            .collect();
+        let open: Vec<usize> = squares.into_iter().filter(|i| grid[*i] == 0).collect();
+        if missing.len() != open.len() {
+            return None;
+        }
+        missing.shuffle(rng);
+        for (i, value) in open.iter().zip(missing) {
+            current[*i] = value;
+        }
+        if open.len() >= 2 {
+            open_squares.push(open);
+        }
+    }
+
+    let conflicts_of = |grid: &Grid, i: usize| {
+        peers[i]
+            .iter()
+            .filter(|peer| grid[**peer] == grid[i])
+            .count()
+    };
+    let mut cost: usize = (0..9 * 9).map(|i| conflicts_of(&current, i)).sum::<usize>() / 2;
+    let mut best = cost;
+    let mut temperature = START_TEMPERATURE;
+    let mut since_best = 0;
+
+    for _ in 0..max_moves {
+        if cost == 0 {
+            return Some(current);
+        }
+        let open = open_squares.choose(rng)?;
+        let first = open[rng.gen_range(0..open.len())];
+        let second = open[rng.gen_range(0..open.len())];
+        if first == second {
+            continue;
+        }
+
+        let before = conflicts_of(&current, first) + conflicts_of(&current, second);
+        current.swap(first, second);
+        let after = conflicts_of(&current, first) + conflicts_of(&current, second);
+        let delta = after as f64 - before as f64;
+        if delta <= 0.0 || rng.gen::<f64>() < (-delta / temperature).exp() {
+            cost = cost + after - before;
+        } else {
+            current.swap(first, second);
+        }
+
+        temperature *= COOLING;
+        if cost < best {
+            best = cost;
+            since_best = 0;
+        } else {
+            since_best += 1;
+            if since_best >= REHEAT_AFTER {
+                temperature = START_TEMPERATURE;
+                since_best = 0;
+                best = cost;
+            }
+        }
+    }
+    (cost == 0).then_some(current)
+}
@@ -0,0 +1,201 @@
+/// An exact cover problem solved with Knuth's Algorithm X using dancing links.
+///
+/// Node 0 is the root, nodes 1..=columns are the column headers and the rest are the
+/// cells of the rows, linked in circular lists both horizontally and vertically.
+#[derive(Debug)]
+pub(crate) struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// Column header of every node.
+    column: Vec<usize>,
+    /// Id of the row every node belongs to.
+    row: Vec<usize>,
+    /// Number of nodes in every column, indexed by header node.
+    size: Vec<usize>,
+}
+
+impl Dlx {
+    /// Create a problem with the given number of columns and no rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - Number of columns that must be covered exactly once.
+    pub(crate) fn new(columns: usize) -> Self {
+        let count = columns + 1;
+        Dlx {
+            left: (0..count).map(|i| (i + count - 1) % count).collect(),
+            right: (0..count).map(|i| (i + 1) % count).collect(),
+            up: (0..count).collect(),
+            down: (0..count).collect(),
+            column: (0..count).collect(),
+            row: vec![usize::MAX; count],
+            size: vec![0; count],
+        }
+    }
+
+    /// Add a row covering the given columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Identifier of the row, reported back in solutions.
+    /// * `columns` - Indices ([0, columns)) of the columns the row covers.
+    pub(crate) fn add_row(&mut self, id: usize, columns: &[usize]) {
+        let first = self.left.len();
+        for (j, column) in columns.iter().enumerate() {
+            let header = column + 1;
+            let node = first + j;
+            self.left.push(if j == 0 {
+                first + columns.len() - 1
+            } else {
+                node - 1
+            });
+            self.right.push(if j + 1 == columns.len() {
+                first
+            } else {
+                node + 1
+            });
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            let above = self.up[header];
+            self.down[above] = node;
+            self.up[header] = node;
+            self.column.push(header);
+            self.row.push(id);
+            self.size[header] += 1;
+        }
+    }
+
+    /// Search for an exact cover and return the ids of its rows. Whenever a row is chosen,
+    /// `allowed` is called with the ids of the rows chosen so far, the new one last, and
+    /// can reject it to prune the search.
+    ///
+    /// # Arguments
+    ///
+    /// * `allowed` - Decides whether the last chosen row may be combined with the others.
+    pub(crate) fn solve<F: FnMut(&[usize]) -> bool>(
+        &mut self,
+        mut allowed: F,
+    ) -> Option<Vec<usize>> {
+        let mut rows = Vec::new();
+        if self.search(&mut rows, &mut allowed) {
+            Some(rows)
+        } else {
+            None
+        }
+    }
+
+    /// Recursive part of [`Dlx::solve`], always branching on the column with the fewest rows.
+    fn search<F: FnMut(&[usize]) -> bool>(
+        &mut self,
+        rows: &mut Vec<usize>,
+        allowed: &mut F,
+    ) -> bool {
+        if self.right[0] == 0 {
+            return true;
+        }
+
+        let mut header = self.right[0];
+        let mut candidate = header;
+        while candidate != 0 {
+            if self.size[candidate] < self.size[header] {
+                header = candidate;
+            }
+            candidate = self.right[candidate];
+        }
+        if self.size[header] == 0 {
+            return false;
+        }
+
+        self.cover(header);
+        let mut node = self.down[header];
+        while node != header {
+            rows.push(self.row[node]);
+            if !allowed(rows) {
+                rows.pop();
+                node = self.down[node];
+                continue;
+            }
+            let mut other = self.right[node];
+            while other != node {
+                self.cover(self.column[other]);
+                other = self.right[other];
+            }
+
+            let found = self.search(rows, allowed);
+
+            let mut other = self.left[node];
+            while other != node {
+                self.uncover(self.column[other]);
+                other = self.left[other];
+            }
+            if found {
+                // Leave the links restored so the problem can be searched again.
+                self.uncover(header);
+                return true;
+            }
+            rows.pop();
+            node = self.down[node];
+        }
+        self.uncover(header);
+        false
+    }
+
+    /// Remove a column and every row covering it from the lists.
+    fn cover(&mut self, header: usize) {
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+        let mut node = self.down[header];
+        while node != header {
+            let mut other = self.right[node];
+            while other != node {
+                self.down[self.up[other]] = self.down[other];
+                self.up[self.down[other]] = self.up[other];
+                self.size[self.column[other]] -= 1;
+                other = self.right[other];
+            }
+            node = self.down[node];
+        }
+    }
+
+    /// Undo [`Dlx::cover`], relinking in the exact reverse order.
+    fn uncover(&mut self, header: usize) {
+        let mut node = self.up[header];
+        while node != header {
+            let mut other = self.left[node];
+            while other != node {
+                self.size[self.column[other]] += 1;
+                self.down[self.up[other]] = other;
+                self.up[self.down[other]] = other;
+                other = self.left[other];
+            }
+            node = self.up[node];
+        }
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dlx;
+
+    #[test]
+    fn test_exact_cover() {
+        // Knuth's example: rows 0, 3 and 4 cover the seven columns exactly once.
+        let mut dlx = Dlx::new(7);
+        dlx.add_row(0, &[2, 4, 5]);
+        dlx.add_row(1, &[0, 3, 6]);
+        dlx.add_row(2, &[1, 2, 5]);
+        dlx.add_row(3, &[0, 3]);
+        dlx.add_row(4, &[1, 6]);
+        dlx.add_row(5, &[3, 4, 6]);
+
+        let mut rows = dlx.solve(|_| true).unwrap();
+        rows.sort();
+        assert_eq!(vec![0, 3, 4], rows);
+
+        assert_eq!(None, dlx.solve(|rows| !rows.contains(&3)));
+    }
+}
@@ -0,0 +1,269 @@
+use super::{Snapshot, SolveStats, Solver};
+use crate::{
+    format::{parse_line, write_line, FormatError},
+    sudoku::Sudoku,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::{fmt::Display, str::FromStr};
+
+/// Everything the backtracking search needs to continue a solve later: the grid, the last
+/// secure state that bad guesses are undone back to, the counters and the random number
+/// generator. The possible values of the squares follow from the grid and the constraints, so
+/// resume with a solver built with the same constraints, backend and configuration.
+///
+/// The text form, written by `Display` and read by `FromStr`, has one `key value` line per
+/// field:
+///
+/// ```text
+/// grid 2...8.3...6..7..84.3.5..2.9...1.54.8.........4.27.6...3.1..7.4.72..4..6...4.1...3
+/// secure 2...8.3...6..7..84.3.5..2.9...1.54.8.........4.27.6...3.1..7.4.72..4..6...4.1...3
+/// stats 0 0 0 0 0 0 0 0 0 0
+/// rng c80c403bd0206c0937a63b6ff24f251e3cbb2f1bb384565a9aa066150d1b203f 0 0
+/// ```
+///
+/// `stats` lists the singles, guesses, backtracks, propagations, hidden singles, locked
+/// candidates, unique rectangles, bivalue universal graves, forcing chains and ALS-XZ
+/// findings, the last seven of which may be left out, and `rng` the seed in hexadecimal, the
+/// stream and the word position of the generator. `secure` is left out until the solver has
+/// stored a secure state.
+#[derive(Debug, Clone)]
+pub struct SolverState {
+    /// The grid with every square set so far.
+    pub sudoku: Sudoku,
+    /// The state reached before the first guess, if the search has got that far.
+    pub secure_state: Option<Sudoku>,
+    /// The work done so far.
+    pub stats: SolveStats,
+    rng: ChaCha8Rng,
+}
+
+impl Solver {
+    /// Take the state of the search, e.g. to write it to disk after
+    /// [`SolverBuilder::max_guesses`](super::SolverBuilder::max_guesses) stopped a long solve.
+    pub fn state(&self) -> SolverState {
+        SolverState {
+            sudoku: self.sudoku.clone(),
+            secure_state: (self.restore_points.len() > 1)
+                .then(|| Sudoku::new_from_state(*self.secure_state())),
+            stats: self.stats.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+
+    /// Continue from a state taken with [`Solver::state`]. The sudoku the solver was created
+    /// with is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state to continue from.
+    pub fn set_state(&mut self, state: SolverState) {
+        self.sudoku = state.sudoku;
+        self.restore_points = vec![self.snapshot()];
+        self.restore_points
+            .extend(state.secure_state.map(|sudoku| Snapshot { sudoku }));
+        self.stats = state.stats;
+        self.rng = state.rng;
+    }
+}
+
+impl Display for SolverState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "grid {}", write_line(&self.sudoku))?;
+        if let Some(secure_state) = &self.secure_state {
+            writeln!(f, "secure {}", write_line(secure_state))?;
+        }
+        writeln!(
+            f,
+            "stats {} {} {} {} {} {} {} {} {} {}",
+            self.stats.singles,
+            self.stats.guesses,
+            self.stats.backtracks,
+            self.stats.propagations,
+            self.stats.hidden_singles,
+            self.stats.locked_candidates,
+            self.stats.unique_rectangles,
+            self.stats.bivalue_universal_graves,
+            self.stats.forcing_chains,
+            self.stats.als_xz
+        )?;
+        write!(f, "rng ")?;
+        for byte in self.rng.get_seed() {
+            write!(f, "{:02x}", byte)?;
+        }
+        writeln!(f, " {} {}", self.rng.get_stream(), self.rng.get_word_pos())
+    }
+}
+
+/// Parses the numbers of a line, failing on the first field that is not one.
+///
+/// # Arguments
+///
+/// * `fields` - The fields after the key.
+/// * `line_number` - Line number used in error messages.
+fn parse_numbers<T: FromStr>(fields: &[&str], line_number: usize) -> Result<Vec<T>, FormatError> {
+    fields
+        .iter()
+        .map(|field| {
+            field
+                .parse()
+                .map_err(|_| FormatError::BadNumber(line_number, field.to_string()))
+        })
+        .collect()
+}
+
+/// Parses a ChaCha seed written as 64 hexadecimal digits.
+///
+/// # Arguments
+///
+/// * `field` - The hexadecimal text.
+/// * `line_number` - Line number used in error messages.
+fn parse_seed(field: &str, line_number: usize) -> Result<[u8; 32], FormatError> {
+    let bad_number = || FormatError::BadNumber(line_number, field.to_string());
+    if field.len() != 64 || !field.is_ascii() {
+        return Err(bad_number());
+    }
+    let mut seed = [0u8; 32];
+    for (i, byte) in seed.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&field[i * 2..i * 2 + 2], 16).map_err(|_| bad_number())?;
+    }
+    Ok(seed)
+}
+
+impl FromStr for SolverState {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sudoku = None;
+        let mut secure_state = None;
+        let mut stats = None;
+        let mut rng = None;
+        for (i, line) in s.lines().enumerate() {
+            let line_number = i + 1;
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let Some((key, values)) = fields.split_first() else {
+                continue;
+            };
+            match (*key, values) {
+                ("grid", [grid]) => sudoku = Some(parse_line(grid, line_number)?),
+                ("secure", [grid]) => secure_state = Some(parse_line(grid, line_number)?),
+                ("stats", numbers) if (3..=10).contains(&numbers.len()) => {
+                    let counts: Vec<u32> = parse_numbers(numbers, line_number)?;
+                    stats = Some(SolveStats {
+                        singles: counts[0],
+                        guesses: counts[1],
+                        backtracks: counts[2],
+                        propagations: counts.get(3).copied().unwrap_or(0),
+                        hidden_singles: counts.get(4).copied().unwrap_or(0),
+                        locked_candidates: counts.get(5).copied().unwrap_or(0),
+                        unique_rectangles: counts.get(6).copied().unwrap_or(0),
+                        bivalue_universal_graves: counts.get(7).copied().unwrap_or(0),
+                        forcing_chains: counts.get(8).copied().unwrap_or(0),
+                        als_xz: counts.get(9).copied().unwrap_or(0),
+                    });
+                }
+                ("rng", [seed, stream, word_pos]) => {
+                    let mut generator = ChaCha8Rng::from_seed(parse_seed(seed, line_number)?);
+                    generator.set_stream(parse_numbers(&[stream], line_number)?[0]);
+                    generator.set_word_pos(parse_numbers(&[word_pos], line_number)?[0]);
+                    rng = Some(generator);
+                }
+                ("grid" | "secure" | "stats" | "rng", _) => {
+                    return Err(FormatError::BadLineLength(line_number))
+                }
+                _ => return Err(FormatError::UnknownSection(key.to_string())),
+            }
+        }
+        let missing = |key: &str| FormatError::MissingColumn(key.to_string());
+        Ok(SolverState {
+            sudoku: sudoku.ok_or_else(|| missing("grid"))?,
+            secure_state,
+            stats: stats.ok_or_else(|| missing("stats"))?,
+            rng: rng.ok_or_else(|| missing("rng"))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SolverState;
+    use crate::{
+        corpus,
+        format::{parse_line, FormatError},
+        solver::{SolveStats, Solver, SolverBuilder, SolverError, Strategy},
+    };
+
+    #[test]
+    fn test_resume() {
+        // Hidden singles alone solve the puzzle.
+        let naked_singles = [Strategy::NakedSingle];
+        let puzzle = parse_line(corpus::MEDIUM.puzzle, 1).unwrap();
+        let mut uninterrupted = SolverBuilder::new()
+            .seed(4)
+            .strategies(&naked_singles)
+            .build(puzzle.clone());
+        uninterrupted.solve().unwrap();
+
+        let mut interrupted = SolverBuilder::new()
+            .seed(4)
+            .strategies(&naked_singles)
+            .max_guesses(1)
+            .build(puzzle.clone());
+        assert!(matches!(interrupted.solve(), Err(SolverError::Stuck(_))));
+        let text = interrupted.state().to_string();
+        let state: SolverState = text.parse().unwrap();
+        assert!(state.secure_state.is_some());
+        assert_eq!(text, state.to_string());
+
+        let mut resumed = SolverBuilder::new()
+            .seed(9)
+            .strategies(&naked_singles)
+            .build(puzzle);
+        resumed.set_state(state);
+        resumed.solve().unwrap();
+        assert_eq!(uninterrupted.sudoku().squares, resumed.sudoku().squares);
+        // Resuming propagates the saved grid once more, which is all that differs.
+        let without_propagations = |solver: &Solver| SolveStats {
+            propagations: 0,
+            ..solver.stats().clone()
+        };
+        assert_eq!(
+            without_propagations(&uninterrupted),
+            without_propagations(&resumed)
+        );
+        assert_eq!(
+            corpus::MEDIUM.solution,
+            crate::format::write_line(resumed.sudoku())
+        );
+
+        let fresh = Solver::new(parse_line(corpus::MEDIUM.puzzle, 1).unwrap()).state();
+        assert!(fresh
+            .to_string()
+            .parse::<SolverState>()
+            .unwrap()
+            .secure_state
+            .is_none());
+    }
+
+    #[test]
+    fn test_bad_state() {
+        assert_eq!(
+            FormatError::MissingColumn("rng".to_string()),
+            format!("grid {}\nstats 0 0 0\n", ".".repeat(81))
+                .parse::<SolverState>()
+                .unwrap_err()
+        );
+        assert_eq!(
+            FormatError::BadNumber(1, "x".to_string()),
+            "stats 0 x 0".parse::<SolverState>().unwrap_err()
+        );
+        assert_eq!(
+            FormatError::BadLineLength(1),
+            "stats 0 0".parse::<SolverState>().unwrap_err()
+        );
+        assert_eq!(
+            FormatError::UnknownSection("guesses".to_string()),
+            "guesses 3".parse::<SolverState>().unwrap_err()
+        );
+    }
+}
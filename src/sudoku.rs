@@ -1,4 +1,8 @@
-use std::fmt::Display;
+use crate::format::{self, pretty, sdk::SdkPuzzle, ss, FormatError};
+use std::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
 
 /// Type alias for the 9*9 sudoku grid.
 pub type Grid = [u8; 9 * 9];
@@ -12,6 +16,90 @@ pub type Column = [u8; 9];
 /// Type alias for the array of nine block values.
 pub type Block = [u8; 9];
 
+/// Indices of the 20 squares sharing a row, column or block with each square, in reading order.
+pub static PEER_TABLE: [[u8; 20]; 9 * 9] = peer_table();
+
+/// Indices of the nine squares of each house in reading order, the houses ordered like
+/// [`House::all`]: rows, then columns, then blocks. See [`House::index`].
+pub static HOUSE_TABLE: [[u8; 9]; 27] = house_table();
+
+/// Builds [`PEER_TABLE`] at compile time.
+const fn peer_table() -> [[u8; 20]; 9 * 9] {
+    let mut table = [[0u8; 20]; 9 * 9];
+    let mut i = 0;
+    while i < 9 * 9 {
+        let mut count = 0;
+        let mut j = 0;
+        while j < 9 * 9 {
+            let same_row = i / 9 == j / 9;
+            let same_column = i % 9 == j % 9;
+            let same_block = i / 27 == j / 27 && i % 9 / 3 == j % 9 / 3;
+            if j != i && (same_row || same_column || same_block) {
+                table[i][count] = j as u8;
+                count += 1;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    table
+}
+
+/// Random keys for [`Sudoku::zobrist`], one for each value [1,9] of each square.
+static ZOBRIST_TABLE: [[u64; 9]; 9 * 9] = zobrist_table();
+
+/// Mixes the bits of a number with the SplitMix64 finalizer.
+const fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Builds [`ZOBRIST_TABLE`] at compile time.
+const fn zobrist_table() -> [[u64; 9]; 9 * 9] {
+    let mut table = [[0u64; 9]; 9 * 9];
+    let mut i = 0;
+    while i < 9 * 9 * 9 {
+        table[i / 9][i % 9] = splitmix64(i as u64);
+        i += 1;
+    }
+    table
+}
+
+/// Returns the key of a value in a square for [`Sudoku::zobrist`]. Empty squares have the key 0,
+/// so that the empty grid hashes to 0.
+///
+/// # Arguments
+///
+/// * `i` - Index of the square.
+/// * `value` - The value in the square.
+const fn zobrist_key(i: usize, value: u8) -> u64 {
+    match value {
+        0 => 0,
+        1..=9 => ZOBRIST_TABLE[i][value as usize - 1],
+        // Out of range values only come from unchecked constructors; hash them all the same.
+        _ => splitmix64(((i as u64) << 8) | value as u64),
+    }
+}
+
+/// Builds [`HOUSE_TABLE`] at compile time.
+const fn house_table() -> [[u8; 9]; 27] {
+    let mut table = [[0u8; 9]; 27];
+    let mut house = 0;
+    while house < 9 {
+        let mut i = 0;
+        while i < 9 {
+            table[house][i] = (house * 9 + i) as u8;
+            table[9 + house][i] = (i * 9 + house) as u8;
+            table[18 + house][i] = ((house / 3 * 3 + i / 3) * 9 + house % 3 * 3 + i % 3) as u8;
+            i += 1;
+        }
+        house += 1;
+    }
+    table
+}
+
 /// Converts row, column pairs into one dimensional array index.
 #[macro_export]
 macro_rules! coords {
@@ -45,13 +133,46 @@ macro_rules! check {
     };
 }
 
+mod index;
+pub use index::{CellIndex, ColIndex, RowIndex};
+
 /// Main game struct. Contains the 9*9 array of squares and a counter for how many squares are currently set.
-#[derive(Debug)]
+///
+/// Sudokus are equal when their squares are, and hash by their [`Sudoku::zobrist`] hash.
+#[derive(Debug, Clone)]
 pub struct Sudoku {
     /// The 9*9 grid of squares, each containing either an empty value (0) or a number (1-9).
     pub squares: Grid,
     /// The count of non-zero numbers in the sudoku.
     pub set_count: u8,
+    /// Zobrist hash of the squares, kept up to date like `set_count`.
+    hash: u64,
+    /// One bit per square in reading order, set for the givens of the puzzle.
+    givens: u128,
+}
+
+/// One of the 27 rows, columns and blocks, which must each contain every value once.
+/// Blocks are numbered [0,9) in reading order.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum House {
+    Row(usize),
+    Column(usize),
+    Block(usize),
+}
+
+/// Options for rendering a sudoku as text with [`Sudoku::format_with`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayOptions {
+    /// Character written for empty squares.
+    pub blank: char,
+    /// Whether to separate the blocks with lines.
+    pub separators: bool,
+    /// Whether to draw a frame around the grid, joining the separators to it.
+    pub border: bool,
+    /// Whether to label the columns and rows with their 1-based numbers.
+    pub coordinates: bool,
+    /// Squares marked with a '*' after their value.
+    pub highlight: Vec<Coordinates>,
 }
 
 /// Simple x,y coordinate pair.
@@ -62,6 +183,7 @@ pub struct Coordinates {
 }
 
 /// Error type for bad indices or values.
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, PartialEq)]
 pub enum SudokuError {
     /// Row index >= 9.
@@ -72,89 +194,429 @@ pub enum SudokuError {
     BadCoordinates(usize, usize),
     /// Value > 9.
     BadValue(u8),
+    /// Two squares in the same row, column or block contain the same value.
+    Conflict(Coordinates, Coordinates),
+    /// Block index >= 9.
+    BadBlock(usize),
+    /// The square holds a given of the puzzle, which only [`Sudoku::set_given`] may change.
+    Given(Coordinates),
+    /// Number of values given for a grid != 81.
+    BadLength(usize),
+}
+
+/// Writes the house with its 1-based number, e.g. `row 3` or `block 9`.
+impl Display for House {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            House::Row(row) => write!(f, "row {}", row + 1),
+            House::Column(column) => write!(f, "column {}", column + 1),
+            House::Block(block) => write!(f, "block {}", block + 1),
+        }
+    }
 }
 
+/// Writes the coordinates in the common 1-based `r1c1` notation.
+impl Display for Coordinates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "r{}c{}", self.row + 1, self.column + 1)
+    }
+}
+
+impl Default for DisplayOptions {
+    /// Blanks as spaces and blocks separated by lines, without a frame.
+    fn default() -> Self {
+        DisplayOptions {
+            blank: ' ',
+            separators: true,
+            border: false,
+            coordinates: false,
+            highlight: Vec::new(),
+        }
+    }
+}
+
+/// `{}` writes the grid on one line with '.' for blanks, the form
+/// [`parse_line`](crate::format::parse_line) reads. `{:#}` draws the framed board.
 impl Display for Sudoku {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (i, value) in self.squares.iter().enumerate() {
-            if i != 0 {
-                if i % 27 == 0 {
-                    write!(f, "\n----------------------\n")?;
-                } else if i % 9 == 0 {
-                    write!(f, "\n")?;
-                } else if i % 3 == 0 {
-                    write!(f, "| ")?;
+        if f.alternate() {
+            let options = DisplayOptions {
+                blank: '.',
+                border: true,
+                ..DisplayOptions::default()
+            };
+            write!(f, "{}", self.format_with(&options))
+        } else {
+            for value in self.squares {
+                match value {
+                    0 => write!(f, ".")?,
+                    _ => write!(f, "{}", value)?,
                 }
             }
-            if *value != 0 {
-                write!(f, "{} ", *value)?;
-            } else {
-                write!(f, "  ")?;
-            }
+            Ok(())
         }
-        Ok(())
+    }
+}
+
+impl Coordinates {
+    /// Returns the 20 squares sharing a row, column or block with this one, in reading order.
+    /// Panics if the coordinates are out of bounds.
+    pub fn peers(&self) -> impl Iterator<Item = Coordinates> {
+        PEER_TABLE[self.row * 9 + self.column]
+            .iter()
+            .map(|i| Coordinates {
+                row: *i as usize / 9,
+                column: *i as usize % 9,
+            })
+    }
+}
+
+impl House {
+    /// Returns every row, then every column, then every block.
+    pub fn all() -> impl Iterator<Item = House> {
+        (0..9)
+            .map(House::Row)
+            .chain((0..9).map(House::Column))
+            .chain((0..9).map(House::Block))
+    }
+
+    /// Returns the position of the house in [`House::all`] and [`HOUSE_TABLE`].
+    pub const fn index(self) -> usize {
+        match self {
+            House::Row(row) => row,
+            House::Column(column) => 9 + column,
+            House::Block(block) => 18 + block,
+        }
+    }
+
+    /// Returns the coordinates of the nine squares of the house in reading order.
+    pub fn cells(self) -> impl Iterator<Item = Coordinates> {
+        HOUSE_TABLE[self.index()].iter().map(|i| Coordinates {
+            row: *i as usize / 9,
+            column: *i as usize % 9,
+        })
+    }
+}
+
+/// Returns the row, column and block containing the square.
+///
+/// # Arguments
+///
+/// * `cell` - Coordinates of the square.
+pub const fn houses_of(cell: &Coordinates) -> [House; 3] {
+    [
+        House::Row(cell.row),
+        House::Column(cell.column),
+        House::Block(cell.row / 3 * 3 + cell.column / 3),
+    ]
+}
+
+impl PartialEq for Sudoku {
+    fn eq(&self, other: &Self) -> bool {
+        self.squares == other.squares
+    }
+}
+
+impl Eq for Sudoku {}
+
+impl Hash for Sudoku {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+/// Builds a sudoku from 81 values in row-major order, validated like
+/// [`Sudoku::try_new_from_state`].
+impl TryFrom<&[u8]> for Sudoku {
+    type Error = SudokuError;
+
+    fn try_from(values: &[u8]) -> Result<Self, Self::Error> {
+        let state: Grid = values
+            .try_into()
+            .map_err(|_| SudokuError::BadLength(values.len()))?;
+        Sudoku::try_new_from_state(state)
+    }
+}
+
+/// Builds a sudoku from 81 values in row-major order, validated like
+/// [`Sudoku::try_new_from_state`].
+impl TryFrom<Vec<u8>> for Sudoku {
+    type Error = SudokuError;
+
+    fn try_from(values: Vec<u8>) -> Result<Self, Self::Error> {
+        Sudoku::try_from(values.as_slice())
+    }
+}
+
+/// Splits the grid into its nine rows, see [`Sudoku::to_rows`].
+impl From<Sudoku> for [Row; 9] {
+    fn from(sudoku: Sudoku) -> Self {
+        sudoku.to_rows()
     }
 }
 
 impl Sudoku {
-    /// Produces a new sudoku from a given 9*9 array of values.
-    /// 
+    /// Produces a new sudoku from a given 9*9 array of values. The set squares become its
+    /// givens, as for every other constructor and parser, see [`Sudoku::set_given`].
+    ///
     /// # Arguments
-    /// 
+    ///
     /// * `state` - A 9*9 array of values in [0,9].
-    pub fn new_from_state(state: Grid) -> Self {
+    pub const fn new_from_state(state: Grid) -> Self {
         let mut retval = Sudoku::new_empty();
-        for (i, value) in state.into_iter().enumerate() {
-            retval.squares[i] = value;
-            if value != 0 {
+        let mut i = 0;
+        while i < 9 * 9 {
+            retval.squares[i] = state[i];
+            if state[i] != 0 {
                 retval.set_count += 1;
+                retval.givens |= 1 << i;
+            }
+            retval.hash ^= zobrist_key(i, state[i]);
+            i += 1;
+        }
+        retval
+    }
+
+    /// Produces a new sudoku from a given 9*9 array of values, checking that every value is in
+    /// [0,9] and that no two squares in the same row, column or block have the same value.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - A 9*9 array of values in [0,9].
+    pub fn try_new_from_state(state: Grid) -> Result<Self, SudokuError> {
+        for value in state {
+            check!(value value);
+        }
+        for i in 0..9 * 9 {
+            if state[i] == 0 {
+                continue;
+            }
+            let cell = Coordinates {
+                row: i / 9,
+                column: i % 9,
+            };
+            let conflict = cell.peers().find(|peer| {
+                peer.row * 9 + peer.column > i && state[peer.row * 9 + peer.column] == state[i]
+            });
+            if let Some(peer) = conflict {
+                return Err(SudokuError::Conflict(cell, peer));
             }
         }
+        Ok(Sudoku::new_from_state(state))
+    }
+
+    /// Produces a new sudoku from its nine rows, so that puzzles written in source code can be
+    /// laid out like the grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The rows from top to bottom, with values in [0,9].
+    pub const fn from_rows(rows: [Row; 9]) -> Self {
+        let mut state = [0u8; 9 * 9];
+        let mut i = 0;
+        while i < 9 * 9 {
+            state[i] = rows[i / 9][i % 9];
+            i += 1;
+        }
+        Sudoku::new_from_state(state)
+    }
+
+    /// Returns the grid as nine rows from top to bottom, the layout most code outside the crate
+    /// expects.
+    pub fn to_rows(&self) -> [Row; 9] {
+        let mut retval = [[0u8; 9]; 9];
+        for (row, values) in retval.iter_mut().zip(self.squares.chunks_exact(9)) {
+            row.copy_from_slice(values);
+        }
         retval
     }
 
+    /// Produces a new sudoku from rows, e.g. as parsed from lines of text, validated like
+    /// [`Sudoku::try_new_from_state`]. Fails with [`SudokuError::BadLength`] giving the number
+    /// of values read if there are not exactly nine rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The rows from top to bottom, with values in [0,9].
+    pub fn try_from_rows<I: IntoIterator<Item = Row>>(rows: I) -> Result<Self, SudokuError> {
+        let values: Vec<u8> = rows.into_iter().take(10).flatten().collect();
+        Sudoku::try_from(values)
+    }
+
+    /// Parses a puzzle in whichever text format it is written: an 81 character line, a `.sdk`
+    /// file, a `.ss` layout or nine plain rows, or the grid drawn by [`Sudoku::format_with`].
+    /// Blanks may be '.' or '0' in every format. Errors are reported as the `.ss` parser
+    /// sees them when no format matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The pasted text.
+    pub fn parse_any(input: &str) -> Result<Self, FormatError> {
+        let trimmed = input.trim();
+        if !trimmed.contains('\n') {
+            return format::parse_line(trimmed, 1);
+        }
+        if trimmed
+            .lines()
+            .any(|line| line.trim_start().starts_with(['[', '#']))
+        {
+            return trimmed.parse::<SdkPuzzle>().map(|puzzle| puzzle.givens);
+        }
+        ss::parse(input).or_else(|err| pretty::parse(input).map_err(|_| err))
+    }
+
+    /// Recomputes `set_count` and the [`Sudoku::zobrist`] hash from the squares. Needed after
+    /// modifying `squares` directly, which bypasses the bookkeeping done by [`Sudoku::set`].
+    pub fn recount(&mut self) {
+        *self = Sudoku {
+            givens: self.givens,
+            ..Sudoku::new_from_state(self.squares)
+        };
+    }
+
+    /// Panics in debug builds if `set_count` or the hash does not match the squares.
+    pub(crate) fn debug_check_count(&self) {
+        debug_assert_eq!(
+            self.squares.iter().filter(|value| **value != 0).count(),
+            self.set_count as usize,
+            "set_count out of sync with the squares, call Sudoku::recount after modifying them"
+        );
+        debug_assert_eq!(
+            Sudoku::new_from_state(self.squares).hash,
+            self.hash,
+            "hash out of sync with the squares, call Sudoku::recount after modifying them"
+        );
+    }
+
+    /// Returns the Zobrist hash of the squares: the XOR of a fixed random key for every value
+    /// in every square. It is updated in constant time by [`Sudoku::set`], so it is cheap to keep
+    /// for every state of a search, and is stable across runs and platforms.
+    pub fn zobrist(&self) -> u64 {
+        self.hash
+    }
+
+    /// Returns the [`Sudoku::zobrist`] hash the sudoku would have after setting a square, e.g.
+    /// to look up a move in a table of known states before making it.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - Row coordinate of the square ([0,9)).
+    /// * `column` - Column coordinate of the square ([0,9)).
+    /// * `value` - The value to set ([0,9]).
+    pub fn zobrist_with(&self, row: usize, column: usize, value: u8) -> u64 {
+        let i = coords!(row, column);
+        self.hash ^ zobrist_key(i, self.squares[i]) ^ zobrist_key(i, value)
+    }
+
     /// Produces a new empty sudoku (every square = 0).
-    pub fn new_empty() -> Self {
+    pub const fn new_empty() -> Self {
         Sudoku {
             squares: [0; 9 * 9],
             set_count: 0,
+            hash: 0,
+            givens: 0,
+        }
+    }
+
+    /// Renders the sudoku as text, one row per line, with every square followed by a space.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - How to render blanks, separators, coordinates and highlighted squares.
+    pub fn format_with(&self, options: &DisplayOptions) -> String {
+        let margin = if options.coordinates { "  " } else { "" };
+        let rule = match (options.border, options.separators) {
+            (true, true) => "+-------+-------+-------+",
+            (true, false) => "+-------------------+",
+            (false, _) => "----------------------",
+        };
+        let mut retval = String::new();
+        if options.coordinates {
+            retval.push_str(margin);
+            if options.border {
+                retval.push_str("  ");
+            }
+            for column in 0..9 {
+                if column != 0 && column % 3 == 0 && options.separators {
+                    retval.push_str("  ");
+                }
+                retval.push_str(&format!("{} ", column + 1));
+            }
+            retval.push('\n');
+        }
+        if options.border {
+            retval.push_str(margin);
+            retval.push_str(rule);
+            retval.push('\n');
+        }
+        for row in 0..9 {
+            if row != 0 {
+                retval.push('\n');
+                if row % 3 == 0 && options.separators {
+                    retval.push_str(margin);
+                    retval.push_str(rule);
+                    retval.push('\n');
+                }
+            }
+            if options.coordinates {
+                retval.push_str(&format!("{} ", row + 1));
+            }
+            if options.border {
+                retval.push_str("| ");
+            }
+            for column in 0..9 {
+                if column != 0 && column % 3 == 0 && options.separators {
+                    retval.push_str("| ");
+                }
+                let value = self.squares[coords!(row, column)];
+                retval.push(match value {
+                    0 => options.blank,
+                    _ => (b'0' + value) as char,
+                });
+                retval.push(
+                    if options.highlight.contains(&Coordinates { row, column }) {
+                        '*'
+                    } else {
+                        ' '
+                    },
+                );
+            }
+            if options.border {
+                retval.push('|');
+            }
         }
+        if options.border {
+            retval.push('\n');
+            retval.push_str(margin);
+            retval.push_str(rule);
+        }
+        retval
     }
 
-    /// Returns an array containing all the values in the given row.
-    /// 
+    /// Returns an array containing all the values in the given row. See [`Sudoku::row`] for a
+    /// version taking a checked [`RowIndex`].
+    ///
     /// # Arguments
-    /// 
+    ///
     /// * `row` - Index of the row to retrieve ([0,9)).
     pub fn get_row(&self, row: usize) -> Result<Row, SudokuError> {
-        check!(row row);
-
-        let mut retval = [0u8; 9];
-        for (i, value) in self.squares[row * 9..row * 9 + 9].iter().enumerate() {
-            retval[i] = *value;
-        }
-        Ok(retval)
+        Ok(self.row(RowIndex::new(row)?))
     }
-    
-    /// Returns an array containing all the values in the given column.
-    /// 
+
+    /// Returns an array containing all the values in the given column. See [`Sudoku::column`]
+    /// for a version taking a checked [`ColIndex`].
+    ///
     /// # Arguments
-    /// 
+    ///
     /// * `column` - Index of the column to retrieve ([0,9)).
     pub fn get_column(&self, column: usize) -> Result<Column, SudokuError> {
-        check!(column column);
-
-        let mut retval = [0u8; 9];
-        for (i, value) in self.squares[column..].iter().step_by(9).enumerate() {
-            retval[i] = *value;
-        }
-        Ok(retval)
+        Ok(self.column(ColIndex::new(column)?))
     }
 
     /// Returns an array containing all the values in the given block.
-    /// 
+    ///
     /// # Arguments
-    /// 
+    ///
     /// * `block_row` - The row of the block to retrieve ([0,3)).
     /// * `block_column` - The column of the block to retrieve ([0,3)).
     pub fn get_block(&self, block_row: usize, block_column: usize) -> Result<Block, SudokuError> {
@@ -162,24 +624,133 @@ impl Sudoku {
             return Err(SudokuError::BadCoordinates(block_row, block_column));
         }
         let mut retval = [0u8; 9];
-        for i in 0..9 {
-            retval[i] = self.squares[coords!((block_row * 3 + i / 3), (block_column * 3 + i % 3))]
+        for (i, value) in retval.iter_mut().enumerate() {
+            *value = self.squares[coords!((block_row * 3 + i / 3), (block_column * 3 + i % 3))]
         }
         Ok(retval)
     }
 
+    /// Returns an array containing all the values in the given block.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - Index of the block to retrieve in reading order ([0,9)).
+    pub fn get_block_by_index(&self, block: usize) -> Result<Block, SudokuError> {
+        if block >= 9 {
+            return Err(SudokuError::BadBlock(block));
+        }
+        self.get_block(block / 3, block % 3)
+    }
+
+    /// Returns an array containing all the values in the given row, column or block.
+    ///
+    /// # Arguments
+    ///
+    /// * `house` - The house to retrieve.
+    pub fn get_house(&self, house: House) -> Result<[u8; 9], SudokuError> {
+        match house {
+            House::Row(row) => self.get_row(row),
+            House::Column(column) => self.get_column(column),
+            House::Block(block) => self.get_block_by_index(block),
+        }
+    }
+
     /// Insert a value to the sudoku. Increments or decrements the set count based on the result.
-    /// 
+    /// Givens cannot be changed, see [`Sudoku::set_given`]; every square set when the sudoku was
+    /// built, loaded or generated is one.
+    ///
     /// # Arguments
-    /// 
+    ///
     /// * `row` - Row coordinate for the new value.
     /// * `column` - Column coordinate for the new value.
     /// * `value` - The value to insert ([0,9]).
     pub fn set(&mut self, row: usize, column: usize, value: u8) -> Result<(), SudokuError> {
         check!(coords row, column);
         check!(value value);
+        if self.givens & (1 << (coords!(row, column))) != 0 {
+            return Err(SudokuError::Given(Coordinates { row, column }));
+        }
+
+        self.set_unchecked(row, column, value);
+
+        Ok(())
+    }
+
+    /// Insert a value like [`Sudoku::set`] and mark the square as a given, or clear a given
+    /// with 0. Givens are locked against [`Sudoku::set`], through which games enter the player's
+    /// values, so that they cannot overwrite the puzzle.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - Row coordinate for the given.
+    /// * `column` - Column coordinate for the given.
+    /// * `value` - The given ([0,9]), 0 to make the square an empty non-given one.
+    pub fn set_given(&mut self, row: usize, column: usize, value: u8) -> Result<(), SudokuError> {
+        check!(coords row, column);
+        check!(value value);
 
-        if self.squares[coords!(row, column)] == 0 {
+        let bit = 1 << (coords!(row, column));
+        if value == 0 {
+            self.givens &= !bit;
+        } else {
+            self.givens |= bit;
+        }
+        self.set_unchecked(row, column, value);
+
+        Ok(())
+    }
+
+    /// Checks if the square at the given coordinates is a given, see [`Sudoku::set_given`].
+    ///
+    /// # Arguments
+    /// * `row` - Row coordinate for the square to check.
+    /// * `column` - Column coordinate for the square to check.
+    pub fn is_given(&self, row: usize, column: usize) -> Result<bool, SudokuError> {
+        check!(coords row, column);
+        Ok(self.givens & (1 << (coords!(row, column))) != 0)
+    }
+
+    /// Marks every set square as a given and every empty one as not, as done when a sudoku is
+    /// built, e.g. after modifying `squares` directly.
+    pub fn lock_givens(&mut self) {
+        self.givens = Sudoku::new_from_state(self.squares).givens;
+    }
+
+    /// Marks the squares set in the puzzle as the givens, e.g. for a game in progress whose
+    /// other squares hold the player's values.
+    ///
+    /// # Arguments
+    ///
+    /// * `puzzle` - The puzzle the sudoku was played from.
+    pub fn lock_givens_of(&mut self, puzzle: &Sudoku) {
+        self.givens = Sudoku::new_from_state(puzzle.squares).givens;
+    }
+
+    /// Unmarks every given, so that [`Sudoku::set`] can change any square.
+    pub fn unlock_givens(&mut self) {
+        self.givens = 0;
+    }
+
+    /// Insert a value like [`Sudoku::set`] without checking the coordinates, the value, conflicts
+    /// or givens, for the solver's inner loops where they are known to be valid. Bad coordinates
+    /// panic and bad values are caught only by debug assertions.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - Row coordinate for the new value ([0,9)).
+    /// * `column` - Column coordinate for the new value ([0,9)).
+    /// * `value` - The value to insert ([0,9]).
+    pub(crate) fn set_unchecked(&mut self, row: usize, column: usize, value: u8) {
+        debug_assert!(
+            row < 9 && column < 9,
+            "bad coordinates ({}, {})",
+            row,
+            column
+        );
+        debug_assert!(value <= 9, "bad value {}", value);
+
+        let i = coords!(row, column);
+        if self.squares[i] == 0 {
             if value != 0 {
                 self.set_count += 1;
             }
@@ -189,13 +760,29 @@ impl Sudoku {
             }
         }
 
-        self.squares[coords!(row, column)] = value;
+        self.hash ^= zobrist_key(i, self.squares[i]) ^ zobrist_key(i, value);
+        self.squares[i] = value;
+    }
 
-        Ok(())
+    /// Returns the value of a square, 0 if it is empty, without checking the coordinates. For
+    /// the solver's inner loops where they are known to be valid; bad coordinates panic.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - Row coordinate of the square ([0,9)).
+    /// * `column` - Column coordinate of the square ([0,9)).
+    pub fn get_unchecked(&self, row: usize, column: usize) -> u8 {
+        debug_assert!(
+            row < 9 && column < 9,
+            "bad coordinates ({}, {})",
+            row,
+            column
+        );
+        self.squares[coords!(row, column)]
     }
 
     /// Checks if the given coordinates contain a non-zero value.
-    /// 
+    ///
     /// # Arguments
     /// * `row` - Row coordinate for the square to check.
     /// * `column` - Column coordinate for the square to check.
@@ -203,11 +790,43 @@ impl Sudoku {
         check!(coords row, column);
         Ok(self.squares[coords!(row, column)] != 0)
     }
+
+    /// Find every pair of set squares that share a row, column or block but contain the same
+    /// value, e.g. for highlighting exactly the squares breaking the rules. Each pair is
+    /// reported once, with the first square before the second in reading order. See
+    /// [`Solver::find_conflicts`](crate::solver::Solver::find_conflicts) for puzzles with
+    /// extra constraints.
+    pub fn find_conflicts(&self) -> Vec<(Coordinates, Coordinates)> {
+        let mut retval = Vec::new();
+        for (i, value) in self.squares.iter().enumerate() {
+            if *value == 0 {
+                continue;
+            }
+            let cell = Coordinates {
+                row: i / 9,
+                column: i % 9,
+            };
+            for peer in cell.peers() {
+                if coords!(peer.row, peer.column) > i
+                    && self.get_unchecked(peer.row, peer.column) == *value
+                {
+                    retval.push((cell.clone(), peer));
+                }
+            }
+        }
+        retval
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::sudoku::{Sudoku, SudokuError};
+    use crate::{
+        format::{self, FormatError},
+        sudoku::{
+            houses_of, Coordinates, DisplayOptions, House, Sudoku, SudokuError, HOUSE_TABLE,
+            PEER_TABLE,
+        },
+    };
 
     macro_rules! test_sudoku {
         () => {
@@ -223,7 +842,63 @@ mod tests {
     #[test]
     fn test_fmt() {
         let sudoku = test_sudoku!();
-        println!("{}", sudoku);
+        println!("{:#}", sudoku);
+    }
+
+    #[test]
+    fn test_format_with() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set(0, 0, 5).unwrap();
+        sudoku.set(0, 4, 7).unwrap();
+
+        let text = sudoku.format_with(&DisplayOptions::default());
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(11, lines.len());
+        assert_eq!("5     |   7   |       ", lines[0]);
+        assert_eq!("----------------------", lines[3]);
+
+        let options = DisplayOptions {
+            blank: '.',
+            separators: false,
+            border: false,
+            coordinates: true,
+            highlight: vec![Coordinates { row: 0, column: 4 }],
+        };
+        let text = sudoku.format_with(&options);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(10, lines.len());
+        assert_eq!("  1 2 3 4 5 6 7 8 9 ", lines[0]);
+        assert_eq!("1 5 . . . 7*. . . . ", lines[1]);
+        assert_eq!("9 . . . . . . . . . ", lines[9]);
+    }
+
+    #[test]
+    fn test_fmt_alternate() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set(0, 0, 5).unwrap();
+        sudoku.set(8, 8, 9).unwrap();
+        let compact = sudoku.to_string();
+        assert_eq!(81, compact.len());
+        assert!(compact.starts_with("5.."));
+        assert_eq!(
+            sudoku.squares,
+            crate::format::parse_line(&compact, 1).unwrap().squares
+        );
+
+        let framed = format!("{:#}", sudoku);
+        let lines: Vec<&str> = framed.lines().collect();
+        assert_eq!(13, lines.len());
+        assert_eq!("+-------+-------+-------+", lines[0]);
+        assert_eq!("| 5 . . | . . . | . . . |", lines[1]);
+        assert_eq!("+-------+-------+-------+", lines[4]);
+        assert_eq!("| . . . | . . . | . . 9 |", lines[11]);
+        assert_eq!("+-------+-------+-------+", lines[12]);
+    }
+
+    #[test]
+    fn test_fmt_coordinates() {
+        assert_eq!("r1c1", Coordinates { row: 0, column: 0 }.to_string());
+        assert_eq!("r4c9", Coordinates { row: 3, column: 8 }.to_string());
     }
 
     #[test]
@@ -346,12 +1021,244 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_house() {
+        let sudoku = test_sudoku!();
+        assert_eq!(
+            sudoku.get_block(1, 2).unwrap(),
+            sudoku.get_block_by_index(5).unwrap()
+        );
+        assert_eq!(
+            SudokuError::BadBlock(9),
+            sudoku.get_block_by_index(9).unwrap_err()
+        );
+        assert_eq!(
+            sudoku.get_column(4).unwrap(),
+            sudoku.get_house(House::Column(4)).unwrap()
+        );
+
+        let houses: Vec<House> = House::all().collect();
+        assert_eq!(27, houses.len());
+        assert_eq!(House::Row(0), houses[0]);
+        assert_eq!(House::Block(8), houses[26]);
+        assert!(houses.iter().all(|house| sudoku.get_house(*house).is_ok()));
+    }
+
+    #[test]
+    fn test_house_cells() {
+        let sudoku = test_sudoku!();
+        for house in House::all() {
+            let values: Vec<u8> = house
+                .cells()
+                .map(|cell| sudoku.squares[cell.row * 9 + cell.column])
+                .collect();
+            assert_eq!(sudoku.get_house(house).unwrap().to_vec(), values);
+            assert!(house.cells().all(|cell| houses_of(&cell).contains(&house)));
+        }
+        assert_eq!(
+            [House::Row(4), House::Column(7), House::Block(5)],
+            houses_of(&Coordinates { row: 4, column: 7 })
+        );
+    }
+
+    #[test]
+    fn test_const() {
+        static EMPTY: Sudoku = Sudoku::new_empty();
+        const CORNERS: Sudoku = Sudoku::from_rows([
+            [1, 0, 0, 0, 0, 0, 0, 0, 2],
+            [0; 9],
+            [0; 9],
+            [0; 9],
+            [0; 9],
+            [0; 9],
+            [0; 9],
+            [0; 9],
+            [3, 0, 0, 0, 0, 0, 0, 0, 4],
+        ]);
+        const HOUSES: [House; 3] = houses_of(&Coordinates { row: 8, column: 0 });
+        assert_eq!(0, EMPTY.set_count);
+        assert_eq!(4, CORNERS.set_count);
+        assert_eq!(4, CORNERS.squares[80]);
+        assert_eq!([House::Row(8), House::Column(0), House::Block(6)], HOUSES);
+        for (i, house) in House::all().enumerate() {
+            assert_eq!(i, house.index());
+            assert!(HOUSE_TABLE[i]
+                .iter()
+                .all(|cell| PEER_TABLE[HOUSE_TABLE[i][0] as usize].contains(cell)
+                    || *cell == HOUSE_TABLE[i][0]));
+        }
+    }
+
+    #[test]
+    fn test_peers() {
+        let cell = Coordinates { row: 4, column: 7 };
+        let peers: Vec<Coordinates> = cell.peers().collect();
+        assert_eq!(20, peers.len());
+        assert!(!peers.contains(&cell));
+        assert_eq!(Coordinates { row: 0, column: 7 }, peers[0]);
+        assert!(peers.contains(&Coordinates { row: 3, column: 6 }));
+        assert!(peers.contains(&Coordinates { row: 4, column: 0 }));
+        assert!(!peers.contains(&Coordinates { row: 3, column: 5 }));
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let mut sudoku = Sudoku::new_empty();
+        assert!(sudoku.find_conflicts().is_empty());
+        sudoku.set(0, 0, 5).unwrap();
+        sudoku.set(1, 1, 5).unwrap();
+        sudoku.set(0, 8, 5).unwrap();
+        sudoku.set(8, 8, 3).unwrap();
+        assert_eq!(
+            vec![
+                (
+                    Coordinates { row: 0, column: 0 },
+                    Coordinates { row: 0, column: 8 }
+                ),
+                (
+                    Coordinates { row: 0, column: 0 },
+                    Coordinates { row: 1, column: 1 }
+                ),
+            ],
+            sudoku.find_conflicts()
+        );
+    }
+
     #[test]
     fn test_bad_row() {
         let sudoku = test_sudoku!();
         assert_eq!(SudokuError::BadRow(10), sudoku.get_row(10).unwrap_err());
     }
 
+    #[test]
+    fn test_try_new_from_state() {
+        assert_eq!(
+            SudokuError::BadValue(10),
+            Sudoku::try_new_from_state(test_sudoku!().squares).unwrap_err()
+        );
+
+        let mut state = [0; 9 * 9];
+        state[0] = 5;
+        state[20] = 5;
+        assert_eq!(
+            SudokuError::Conflict(
+                Coordinates { row: 0, column: 0 },
+                Coordinates { row: 2, column: 2 }
+            ),
+            Sudoku::try_new_from_state(state).unwrap_err()
+        );
+
+        state[20] = 6;
+        let sudoku = Sudoku::try_new_from_state(state).unwrap();
+        assert_eq!(2, sudoku.set_count);
+    }
+
+    #[test]
+    fn test_try_from() {
+        let mut values = vec![0u8; 9 * 9];
+        values[40] = 7;
+        let sudoku = Sudoku::try_from(values.as_slice()).unwrap();
+        assert_eq!(7, sudoku.squares[40]);
+        assert_eq!(1, sudoku.set_count);
+
+        values[41] = 12;
+        assert_eq!(
+            SudokuError::BadValue(12),
+            Sudoku::try_from(values.clone()).unwrap_err()
+        );
+        assert_eq!(
+            SudokuError::BadLength(80),
+            Sudoku::try_from(&values[1..]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_from_rows() {
+        let rows = [
+            [0, 0, 3, 0, 2, 0, 6, 0, 0],
+            [9, 0, 0, 3, 0, 5, 0, 0, 1],
+            [0, 0, 1, 8, 0, 6, 4, 0, 0],
+            [0, 0, 8, 1, 0, 2, 9, 0, 0],
+            [7, 0, 0, 0, 0, 0, 0, 0, 8],
+            [0, 0, 6, 7, 0, 8, 2, 0, 0],
+            [0, 0, 2, 6, 0, 9, 5, 0, 0],
+            [8, 0, 0, 2, 0, 3, 0, 0, 9],
+            [0, 0, 5, 0, 1, 0, 3, 0, 0],
+        ];
+        let sudoku = Sudoku::from_rows(rows);
+        assert_eq!(crate::corpus::EASY.puzzle, format::write_line(&sudoku));
+        assert_eq!(32, sudoku.set_count);
+        assert_eq!(rows, sudoku.to_rows());
+        assert_eq!(rows, <[[u8; 9]; 9]>::from(sudoku.clone()));
+        assert_eq!(
+            sudoku.squares,
+            Sudoku::try_from_rows(rows.iter().copied()).unwrap().squares
+        );
+
+        assert_eq!(
+            SudokuError::BadLength(72),
+            Sudoku::try_from_rows(rows.into_iter().skip(1)).unwrap_err()
+        );
+        assert_eq!(
+            SudokuError::BadLength(90),
+            Sudoku::try_from_rows(std::iter::repeat([0; 9])).unwrap_err()
+        );
+        let mut conflicting = rows;
+        conflicting[1][1] = 3;
+        assert!(matches!(
+            Sudoku::try_from_rows(conflicting),
+            Err(SudokuError::Conflict(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_recount() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.squares[3] = 4;
+        sudoku.squares[70] = 9;
+        assert_eq!(0, sudoku.set_count);
+        sudoku.recount();
+        assert_eq!(2, sudoku.set_count);
+        sudoku.debug_check_count();
+    }
+
+    #[test]
+    fn test_zobrist() {
+        let puzzle = format::parse_line(crate::corpus::EASY.puzzle, 1).unwrap();
+        let mut sudoku = Sudoku::new_empty();
+        assert_eq!(0, sudoku.zobrist());
+        for (i, value) in puzzle.squares.iter().enumerate() {
+            sudoku.set(i / 9, i % 9, *value).unwrap();
+        }
+        assert_eq!(puzzle.zobrist(), sudoku.zobrist());
+        assert_eq!(puzzle, sudoku);
+
+        let predicted = sudoku.zobrist_with(0, 0, 5);
+        sudoku.set(0, 0, 5).unwrap();
+        assert_eq!(predicted, sudoku.zobrist());
+        assert_ne!(puzzle.zobrist(), sudoku.zobrist());
+        sudoku.set(0, 0, 0).unwrap();
+        assert_eq!(puzzle.zobrist(), sudoku.zobrist());
+
+        let mut changed = puzzle.clone();
+        changed.squares[0] = 5;
+        changed.recount();
+        assert_eq!(33, changed.set_count);
+        assert_ne!(puzzle.zobrist(), changed.zobrist());
+        let unique: std::collections::HashSet<Sudoku> =
+            [puzzle, sudoku, changed].into_iter().collect();
+        assert_eq!(2, unique.len());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "set_count out of sync")]
+    fn test_debug_check_count() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.squares[3] = 4;
+        sudoku.debug_check_count();
+    }
+
     #[test]
     fn test_set() {
         let mut sudoku = Sudoku::new_empty();
@@ -372,4 +1279,93 @@ mod tests {
         assert!(!sudoku.is_set(2, 3).unwrap());
         assert_eq!(0, sudoku.set_count);
     }
+
+    #[test]
+    fn test_givens() {
+        let mut sudoku: Sudoku = format!("1{}", ".".repeat(80)).parse().unwrap();
+        assert!(sudoku.is_given(0, 0).unwrap());
+        assert!(!sudoku.is_given(0, 1).unwrap());
+        let r1c1 = Coordinates { row: 0, column: 0 };
+        assert_eq!(Err(SudokuError::Given(r1c1.clone())), sudoku.set(0, 0, 2));
+        assert_eq!(Err(SudokuError::Given(r1c1)), sudoku.set(0, 0, 0));
+        sudoku.set(0, 1, 2).unwrap();
+
+        sudoku.set_given(0, 0, 3).unwrap();
+        assert_eq!(3, sudoku.get_unchecked(0, 0));
+        sudoku.set_given(0, 0, 0).unwrap();
+        sudoku.set(0, 0, 4).unwrap();
+        sudoku.lock_givens();
+        sudoku.recount();
+        assert!(sudoku.set(0, 1, 0).is_err());
+        sudoku.unlock_givens();
+        sudoku.set(0, 1, 0).unwrap();
+        assert_eq!(
+            Err(SudokuError::BadCoordinates(9, 0)),
+            sudoku.is_given(9, 0)
+        );
+
+        let mut state = [0; 9 * 9];
+        state[4] = 7;
+        for sudoku in [
+            Sudoku::new_from_state(state),
+            Sudoku::try_from(state.to_vec()).unwrap(),
+        ] {
+            assert!(sudoku.is_given(0, 4).unwrap());
+            assert!(!sudoku.is_given(0, 3).unwrap());
+        }
+        let mut board = Sudoku::new_from_state(state);
+        board.set_unchecked(0, 3, 5);
+        board.lock_givens_of(&Sudoku::new_from_state(state));
+        assert!(board.set(0, 3, 6).is_ok());
+        assert!(board.set(0, 4, 6).is_err());
+    }
+
+    #[test]
+    fn test_set_unchecked() {
+        let mut sudoku = Sudoku::new_empty();
+
+        sudoku.set_unchecked(8, 8, 4);
+        assert_eq!(4, sudoku.get_unchecked(8, 8));
+        assert_eq!(1, sudoku.set_count);
+
+        sudoku.set_unchecked(8, 8, 0);
+        assert_eq!(0, sudoku.get_unchecked(8, 8));
+        assert_eq!(0, sudoku.set_count);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_get_unchecked_out_of_bounds() {
+        Sudoku::new_empty().get_unchecked(9, 0);
+    }
+
+    #[test]
+    fn test_parse_any() {
+        let line =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+        let sudoku = format::parse_line(line, 1).unwrap();
+        let rows: String = line
+            .as_bytes()
+            .chunks(9)
+            .map(|row| String::from_utf8_lossy(row).to_string() + "\n")
+            .collect();
+
+        for input in [
+            format!("  {}\n", line),
+            line.replace('.', "0"),
+            rows.clone(),
+            format!("#Aanonymous\n[Puzzle]\n{}", rows),
+            format::ss::write(&sudoku),
+            format!("{:#}", sudoku),
+            sudoku.format_with(&DisplayOptions::default()),
+        ] {
+            assert_eq!(sudoku.squares, Sudoku::parse_any(&input).unwrap().squares);
+        }
+
+        assert_eq!(
+            Err(FormatError::BadLineLength(1)),
+            Sudoku::parse_any("123").map(|sudoku| sudoku.squares)
+        );
+        assert!(Sudoku::parse_any("12\n34").is_err());
+    }
 }
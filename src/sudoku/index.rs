@@ -0,0 +1,194 @@
+use super::{Column, Coordinates, Row, Sudoku, SudokuError};
+
+/// Index of a row, checked to be in [0,9) when created.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct RowIndex(u8);
+
+/// Index of a column, checked to be in [0,9) when created.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct ColIndex(u8);
+
+/// Index of a square in reading order, checked to be in [0,81) when created.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct CellIndex(u8);
+
+impl RowIndex {
+    /// Checks the index of a row.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The row index ([0,9)).
+    pub const fn new(row: usize) -> Result<Self, SudokuError> {
+        check!(row row);
+        Ok(RowIndex(row as u8))
+    }
+
+    /// Returns the index.
+    pub const fn get(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Returns the nine rows from top to bottom.
+    pub fn all() -> impl Iterator<Item = RowIndex> {
+        (0..9).map(RowIndex)
+    }
+}
+
+impl ColIndex {
+    /// Checks the index of a column.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The column index ([0,9)).
+    pub const fn new(column: usize) -> Result<Self, SudokuError> {
+        check!(column column);
+        Ok(ColIndex(column as u8))
+    }
+
+    /// Returns the index.
+    pub const fn get(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Returns the nine columns from left to right.
+    pub fn all() -> impl Iterator<Item = ColIndex> {
+        (0..9).map(ColIndex)
+    }
+}
+
+impl CellIndex {
+    /// Checks the index of a square. Indices past the grid fail as the coordinates they would
+    /// have.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the square in reading order ([0,81)).
+    pub const fn new(index: usize) -> Result<Self, SudokuError> {
+        check!(coords index / 9, index % 9);
+        Ok(CellIndex(index as u8))
+    }
+
+    /// Returns the square at the crossing of a row and a column.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The row of the square.
+    /// * `column` - The column of the square.
+    pub const fn at(row: RowIndex, column: ColIndex) -> Self {
+        CellIndex(row.0 * 9 + column.0)
+    }
+
+    /// Returns the index.
+    pub const fn get(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Returns the row of the square.
+    pub const fn row(self) -> RowIndex {
+        RowIndex(self.0 / 9)
+    }
+
+    /// Returns the column of the square.
+    pub const fn column(self) -> ColIndex {
+        ColIndex(self.0 % 9)
+    }
+
+    /// Returns the 81 squares in reading order.
+    pub fn all() -> impl Iterator<Item = CellIndex> {
+        (0..9 * 9).map(CellIndex)
+    }
+}
+
+impl TryFrom<&Coordinates> for CellIndex {
+    type Error = SudokuError;
+
+    fn try_from(cell: &Coordinates) -> Result<Self, Self::Error> {
+        check!(coords cell.row, cell.column);
+        Ok(CellIndex((cell.row * 9 + cell.column) as u8))
+    }
+}
+
+impl From<CellIndex> for Coordinates {
+    fn from(cell: CellIndex) -> Self {
+        Coordinates {
+            row: cell.row().get(),
+            column: cell.column().get(),
+        }
+    }
+}
+
+impl Sudoku {
+    /// Returns the values of a row. Unlike [`Sudoku::get_row`] this cannot fail, as the index
+    /// was checked when it was created.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The row to retrieve.
+    pub fn row(&self, row: RowIndex) -> Row {
+        let start = row.get() * 9;
+        self.squares[start..start + 9]
+            .try_into()
+            .expect("a row is nine squares")
+    }
+
+    /// Returns the values of a column. Unlike [`Sudoku::get_column`] this cannot fail, as the
+    /// index was checked when it was created.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The column to retrieve.
+    pub fn column(&self, column: ColIndex) -> Column {
+        let mut retval = [0u8; 9];
+        for (i, value) in self.squares[column.get()..].iter().step_by(9).enumerate() {
+            retval[i] = *value;
+        }
+        retval
+    }
+
+    /// Returns the value of a square, 0 if it is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - The square to read.
+    pub const fn cell(&self, cell: CellIndex) -> u8 {
+        self.squares[cell.get()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CellIndex, ColIndex, RowIndex};
+    use crate::sudoku::{Coordinates, Sudoku, SudokuError};
+
+    #[test]
+    fn test_indices() {
+        assert_eq!(SudokuError::BadRow(9), RowIndex::new(9).unwrap_err());
+        assert_eq!(SudokuError::BadColumn(12), ColIndex::new(12).unwrap_err());
+        assert_eq!(
+            SudokuError::BadCoordinates(9, 0),
+            CellIndex::new(81).unwrap_err()
+        );
+
+        let cell = CellIndex::at(RowIndex::new(4).unwrap(), ColIndex::new(7).unwrap());
+        assert_eq!(43, cell.get());
+        assert_eq!(4, cell.row().get());
+        assert_eq!(7, cell.column().get());
+        let coordinates = Coordinates { row: 4, column: 7 };
+        assert_eq!(Ok(cell), CellIndex::try_from(&coordinates));
+        assert_eq!(coordinates, Coordinates::from(cell));
+        assert_eq!(81, CellIndex::all().count());
+
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set(4, 7, 6).unwrap();
+        assert_eq!(6, sudoku.cell(cell));
+        for row in RowIndex::all() {
+            assert_eq!(sudoku.get_row(row.get()).unwrap(), sudoku.row(row));
+        }
+        for column in ColIndex::all() {
+            assert_eq!(
+                sudoku.get_column(column.get()).unwrap(),
+                sudoku.column(column)
+            );
+        }
+    }
+}
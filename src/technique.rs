@@ -0,0 +1,105 @@
+//! Names of the solving techniques in the nomenclature of HoDoKu and SudokuWiki, so that
+//! output of this crate can link to their documentation and to trainers built around them.
+
+/// A technique the solver reports, with its names elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Technique {
+    /// Name used by this crate, as in
+    /// [`SolveStats::hardest_technique`](crate::solver::SolveStats::hardest_technique).
+    pub name: &'static str,
+    /// Name HoDoKu gives the technique.
+    pub hodoku_name: &'static str,
+    /// Short code of the technique in HoDoKu's settings and command line, e.g. `ns`.
+    pub hodoku_code: &'static str,
+    /// Page of SudokuWiki explaining the technique.
+    pub sudokuwiki_page: &'static str,
+}
+
+/// Every technique the solver reports, from the simplest to the hardest as ranked by
+/// [`SolveStats::hardest_technique`](crate::solver::SolveStats::hardest_technique). Sudoku
+/// Explainer rates hidden singles below naked singles, so
+/// [`DifficultyReport::se_rating`](crate::solver::DifficultyReport::se_rating) rates every
+/// technique used rather than the last one.
+pub const TECHNIQUES: [Technique; 8] = [
+    Technique {
+        name: "Naked Single",
+        hodoku_name: "Naked Single",
+        hodoku_code: "ns",
+        sudokuwiki_page: "Getting_Started",
+    },
+    Technique {
+        name: "Hidden Single",
+        hodoku_name: "Hidden Single",
+        hodoku_code: "hs",
+        sudokuwiki_page: "Getting_Started",
+    },
+    Technique {
+        name: "Locked Candidates",
+        hodoku_name: "Locked Candidates",
+        hodoku_code: "lc",
+        sudokuwiki_page: "Intersection_Removal",
+    },
+    Technique {
+        name: "Unique Rectangle",
+        hodoku_name: "Uniqueness Test 1",
+        hodoku_code: "u1",
+        sudokuwiki_page: "Unique_Rectangles",
+    },
+    Technique {
+        name: "Bivalue Universal Grave",
+        hodoku_name: "Bivalue Universal Grave + 1",
+        hodoku_code: "bug1",
+        sudokuwiki_page: "BUG",
+    },
+    Technique {
+        name: "ALS-XZ",
+        hodoku_name: "Almost Locked Set XZ-Rule",
+        hodoku_code: "axz",
+        sudokuwiki_page: "Almost_Locked_Sets",
+    },
+    Technique {
+        name: "Forcing Chain",
+        hodoku_name: "Forcing Chain Contradiction",
+        hodoku_code: "fcc",
+        sudokuwiki_page: "Nishio_Forcing_Chains",
+    },
+    Technique {
+        name: "Guess",
+        hodoku_name: "Brute Force",
+        hodoku_code: "bf",
+        sudokuwiki_page: "Bowmans_Bingo",
+    },
+];
+
+impl Technique {
+    /// Finds a technique by the name this crate gives it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name, e.g. `Naked Single`.
+    pub fn find(name: &str) -> Option<&'static Technique> {
+        TECHNIQUES.iter().find(|technique| technique.name == name)
+    }
+
+    /// Returns the address of the SudokuWiki page on the technique.
+    pub fn sudokuwiki_url(&self) -> String {
+        format!("https://www.sudokuwiki.org/{}", self.sudokuwiki_page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Technique;
+
+    #[test]
+    fn test_find() {
+        let technique = Technique::find("Guess").unwrap();
+        assert_eq!("Brute Force", technique.hodoku_name);
+        assert_eq!("bf", technique.hodoku_code);
+        assert_eq!(
+            "https://www.sudokuwiki.org/Getting_Started",
+            Technique::find("Naked Single").unwrap().sudokuwiki_url()
+        );
+        assert_eq!(None, Technique::find("naked single"));
+    }
+}
@@ -0,0 +1,313 @@
+use crate::sudoku::{Grid, Sudoku};
+use rand::{seq::SliceRandom, Rng};
+
+/// A solved grid to generate from with [`Transform`]s: row `r` is the first row shifted left
+/// by `3 * (r % 3) + r / 3` squares.
+pub const SEED_GRID: Grid = {
+    let mut grid = [0; 9 * 9];
+    let mut i = 0;
+    while i < 9 * 9 {
+        let (row, column) = (i / 9, i % 9);
+        grid[i] = ((column + 3 * (row % 3) + row / 3) % 9 + 1) as u8;
+        i += 1;
+    }
+    grid
+};
+
+/// A transformation that keeps a grid valid: optionally transposing it, reordering its rows
+/// and columns without splitting bands or stacks, and relabeling its digits. Reflections and
+/// rotations are combinations of these.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transform {
+    /// Whether to swap rows and columns first.
+    pub transpose: bool,
+    /// Row `i` of the result is row `rows[i]` of the (transposed) grid.
+    pub rows: [usize; 9],
+    /// Column `j` of the result is column `columns[j]` of the (transposed) grid.
+    pub columns: [usize; 9],
+    /// New value of every digit; `digits[0]` must be 0 to keep blanks blank.
+    pub digits: [u8; 10],
+}
+
+/// Every order of the nine rows (or columns) that keeps the bands (or stacks) together: the
+/// bands in any order, and the rows of every band in any order. 6 * 6^3 = 1296 in total.
+fn line_orders() -> Vec<[usize; 9]> {
+    const PERMUTATIONS: [[usize; 3]; 6] = [
+        [0, 1, 2],
+        [0, 2, 1],
+        [1, 0, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [2, 1, 0],
+    ];
+    let mut retval = Vec::with_capacity(1296);
+    for bands in PERMUTATIONS {
+        for first in PERMUTATIONS {
+            for second in PERMUTATIONS {
+                for third in PERMUTATIONS {
+                    let mut order = [0; 9];
+                    for (i, within) in [first, second, third].iter().enumerate() {
+                        for (j, line) in within.iter().enumerate() {
+                            order[i * 3 + j] = bands[i] * 3 + line;
+                        }
+                    }
+                    retval.push(order);
+                }
+            }
+        }
+    }
+    retval
+}
+
+/// Returns a random band-preserving order of nine lines.
+fn random_line_order<R: Rng + ?Sized>(rng: &mut R) -> [usize; 9] {
+    let mut bands = [0, 1, 2];
+    bands.shuffle(rng);
+    let mut order = [0; 9];
+    for (i, band) in bands.iter().enumerate() {
+        let mut lines = [0, 1, 2];
+        lines.shuffle(rng);
+        for (j, line) in lines.iter().enumerate() {
+            order[i * 3 + j] = band * 3 + line;
+        }
+    }
+    order
+}
+
+impl Transform {
+    /// Returns the transformation that changes nothing.
+    pub fn identity() -> Self {
+        Transform {
+            transpose: false,
+            rows: std::array::from_fn(|i| i),
+            columns: std::array::from_fn(|i| i),
+            digits: std::array::from_fn(|i| i as u8),
+        }
+    }
+
+    /// Returns a transformation chosen uniformly from all of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Random number generator to choose with.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut digits: [u8; 10] = std::array::from_fn(|i| i as u8);
+        digits[1..].shuffle(rng);
+        Transform {
+            transpose: rng.gen(),
+            rows: random_line_order(rng),
+            columns: random_line_order(rng),
+            digits,
+        }
+    }
+
+    /// Applies the transformation to a grid. Valid grids stay valid, and puzzles keep their
+    /// number of solutions and difficulty.
+    ///
+    /// # Arguments
+    ///
+    /// * `sudoku` - The grid to transform.
+    pub fn apply(&self, sudoku: &Sudoku) -> Sudoku {
+        let mut grid: Grid = [0; 9 * 9];
+        for (i, square) in grid.iter_mut().enumerate() {
+            let (row, column) = (self.rows[i / 9], self.columns[i % 9]);
+            let value = if self.transpose {
+                sudoku.squares[column * 9 + row]
+            } else {
+                sudoku.squares[row * 9 + column]
+            };
+            *square = self.digits[value as usize];
+        }
+        Sudoku::new_from_state(grid)
+    }
+}
+
+/// Returns the canonical form of the sudoku: of all the grids it can be turned into by
+/// transposing, reordering bands, stacks and the lines within them, and relabeling the
+/// digits, the smallest one in reading order with blanks before digits. Two puzzles are
+/// essentially the same exactly when their canonical forms are equal.
+///
+/// Digits are relabeled in order of first appearance, so only the geometric transformations
+/// are searched: every column order, and for each the rows one at a time, abandoning a row
+/// order as soon as its first rows compare larger than the best grid so far.
+///
+/// # Arguments
+///
+/// * `sudoku` - The sudoku to canonicalize.
+pub fn canonical(sudoku: &Sudoku) -> Sudoku {
+    // Every transformation ties on the empty grid, the slowest case for the search.
+    if sudoku.set_count == 0 {
+        return Sudoku::new_empty();
+    }
+    let mut search = CanonicalSearch {
+        grid: &sudoku.squares,
+        transpose: false,
+        columns: [0; 9],
+        best: [u8::MAX; 9 * 9],
+    };
+    for transpose in [false, true] {
+        search.transpose = transpose;
+        for columns in line_orders() {
+            search.columns = columns;
+            search.place_row(0, [false; 9], [0; 3], [0; 10], 1);
+        }
+    }
+    Sudoku::new_from_state(search.best)
+}
+
+/// State of the branch and bound search of [`canonical`].
+struct CanonicalSearch<'a> {
+    grid: &'a Grid,
+    /// Whether the rows are read from the columns of the grid.
+    transpose: bool,
+    /// Order of the columns being tried.
+    columns: [usize; 9],
+    /// The smallest grid found so far. Rows below a newly improved row are reset to the
+    /// maximum until the search fills them again.
+    best: Grid,
+}
+
+impl CanonicalSearch<'_> {
+    /// Tries every row allowed at the given position of the row order, given the rows and bands
+    /// placed above it, and continues with the ones that keep the grid as small as the best.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Index of the row to place ([0,9]); 9 means every row is placed.
+    /// * `used` - Which rows of the grid have been placed.
+    /// * `bands` - The band placed at each band position so far.
+    /// * `labels` - New label of every digit seen so far, 0 for unseen.
+    /// * `next` - The label of the next new digit.
+    fn place_row(
+        &mut self,
+        position: usize,
+        used: [bool; 9],
+        mut bands: [usize; 3],
+        labels: [u8; 10],
+        next: u8,
+    ) {
+        if position == 9 {
+            return;
+        }
+        for row in 0..9 {
+            if used[row] {
+                continue;
+            }
+            if position.is_multiple_of(3) {
+                if bands[..position / 3].contains(&(row / 3)) {
+                    continue;
+                }
+                bands[position / 3] = row / 3;
+            } else if row / 3 != bands[position / 3] {
+                continue;
+            }
+
+            let mut labels = labels;
+            let mut next = next;
+            let mut values = [0u8; 9];
+            for (j, value) in values.iter_mut().enumerate() {
+                let square = if self.transpose {
+                    self.grid[self.columns[j] * 9 + row]
+                } else {
+                    self.grid[row * 9 + self.columns[j]]
+                };
+                if square != 0 {
+                    if labels[square as usize] == 0 {
+                        labels[square as usize] = next;
+                        next += 1;
+                    }
+                    *value = labels[square as usize];
+                }
+            }
+            let best_row = &mut self.best[position * 9..position * 9 + 9];
+            match values.as_slice().cmp(best_row) {
+                std::cmp::Ordering::Greater => continue,
+                std::cmp::Ordering::Less => {
+                    best_row.copy_from_slice(&values);
+                    self.best[position * 9 + 9..].fill(u8::MAX);
+                }
+                std::cmp::Ordering::Equal => {}
+            }
+            let mut used = used;
+            used[row] = true;
+            self.place_row(position + 1, used, bands, labels, next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical, line_orders, Transform, SEED_GRID};
+    use crate::{format::parse_line, solver::Solver, sudoku::Sudoku};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_line_orders() {
+        let orders = line_orders();
+        assert_eq!(1296, orders.len());
+        assert_eq!(1296, orders.iter().collect::<HashSet<_>>().len());
+        assert!(orders
+            .iter()
+            .all(|order| (0..9).all(|i| order[i] / 3 == order[i / 3 * 3] / 3)));
+    }
+
+    #[test]
+    fn test_canonical() {
+        let puzzle = parse_line(
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+            1,
+        )
+        .unwrap();
+        // Transpose, swap the first two bands and relabel every digit d as 10 - d.
+        let mut squares = [0; 81];
+        for row in 0..9 {
+            for column in 0..9 {
+                let source = if column < 6 { (column + 3) % 6 } else { column };
+                let value = puzzle.squares[source * 9 + row];
+                squares[row * 9 + column] = if value == 0 { 0 } else { 10 - value };
+            }
+        }
+        let transformed = Sudoku::new_from_state(squares);
+        assert_ne!(puzzle.squares, transformed.squares);
+
+        let form = canonical(&puzzle);
+        assert_eq!(form.squares, canonical(&transformed).squares);
+        assert_eq!(form.squares, canonical(&form).squares);
+        assert_eq!(puzzle.set_count, form.set_count);
+
+        let mut different = puzzle.clone();
+        different.set(0, 0, 4).unwrap();
+        assert_ne!(form.squares, canonical(&different).squares);
+    }
+
+    #[test]
+    fn test_transform() {
+        let seed = Sudoku::try_new_from_state(SEED_GRID).unwrap();
+        assert_eq!(81, seed.set_count);
+        assert_eq!(seed.squares, Transform::identity().apply(&seed).squares);
+
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let transform = Transform::random(&mut rng);
+        assert_ne!(Transform::identity(), transform);
+        let grid = transform.apply(&seed);
+        assert!(Sudoku::try_new_from_state(grid.squares).is_ok());
+        assert_eq!(canonical(&seed).squares, canonical(&grid).squares);
+
+        let puzzle = parse_line(
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+            1,
+        )
+        .unwrap();
+        let transformed = transform.apply(&puzzle);
+        assert_eq!(puzzle.set_count, transformed.set_count);
+        let solution = Solver::new(transformed).unique_solution().unwrap();
+        assert_eq!(
+            transform
+                .apply(&Solver::new(puzzle).unique_solution().unwrap())
+                .squares,
+            solution.squares
+        );
+    }
+}